@@ -25,22 +25,137 @@
 //!
 //! Model preferences can be set via:
 //! - `~/.model-openrouter` for OpenRouter model selection
+//!
+//! ## Logging
+//!
+//! Output is human-readable by default. Pass `--log-format json` to emit one JSON
+//! object per event (affiliate key, action, old/new rate, tx signature or error)
+//! for ingestion into log pipelines.
+//!
+//! ## Local Bootstrapping
+//!
+//! The `register` subcommand registers the given keypair as an affiliate so a fresh
+//! localnet/devnet has something for the optimizer to manage, e.g.
+//! `optimizer-bot --cluster devnet register --keypair ~/affiliate.json --referral-level 1`.
 
 use anchor_client::{Client, Program, Cluster};
-use affiliate_program::accounts::{SetCommissionRate, RegisterAffiliate, UpdateAnalytics};
-use affiliate_program::instruction::{SetCommissionRate as SetCommissionRateInstruction, RegisterAffiliate as RegisterAffiliateInstruction, UpdateAnalytics as UpdateAnalyticsInstruction};
+use anchor_client::anchor_lang::{InstructionData, ToAccountMetas};
+use affiliate_program::accounts::{SetCommissionRate, RegisterAffiliate};
+use affiliate_program::instruction::{SetCommissionRate as SetCommissionRateInstruction, RegisterAffiliate as RegisterAffiliateInstruction};
+use affiliate_program::RegisterAffiliateArgs;
 use serde::{Deserialize, Serialize};
+use solana_sdk::instruction::Instruction;
 use solana_sdk::pubkey::Pubkey;
-use solana_sdk::signer::{keypair::Keypair, Signer};
-use solana_sdk::commitment_config::CommitmentConfig;
-use solana_sdk::transaction::Transaction;
-use std::fs;
+use solana_sdk::signer::{keypair::{read_keypair_file, Keypair}, Signer};
 use std::rc::Rc;
+use std::sync::Arc;
 use std::time::Duration;
 use tokio::time::sleep;
-use chrono::{Utc, Duration as ChronoDuration};
-use std::collections::HashMap;
-use log::{info, warn, error};
+use log::warn;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+
+/// Command-line options for the optimizer bot.
+#[derive(Debug, Parser)]
+#[command(name = "optimizer-bot", about = "AI-driven affiliate commission optimizer")]
+struct Cli {
+    /// Output format for bot events.
+    #[arg(long, global = true, value_enum, default_value_t = LogFormat::Human)]
+    log_format: LogFormat,
+
+    /// Cluster to send transactions to.
+    #[arg(long, global = true, value_enum, default_value_t = ClusterArg::Localnet)]
+    cluster: ClusterArg,
+
+    /// Runs an optimizer update cycle when omitted.
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Register a keypair as an affiliate (for bootstrapping local/devnet state).
+    Register(RegisterCmd),
+}
+
+/// Arguments for the `register` subcommand, mirroring `RegisterAffiliateArgs`.
+#[derive(Debug, Args)]
+struct RegisterCmd {
+    /// Keypair of the affiliate to register; it signs and pays for the account.
+    #[arg(long, default_value = "~/.config/solana/id.json")]
+    keypair: String,
+
+    /// Optional parent affiliate for multi-level referrals.
+    #[arg(long)]
+    parent: Option<Pubkey>,
+
+    /// Referral depth of the new affiliate (1 = direct).
+    #[arg(long, default_value_t = 1)]
+    referral_level: u8,
+
+    /// Enforce the per-affiliate min/max commission caps below.
+    #[arg(long)]
+    rate_caps: bool,
+
+    /// Maximum commission rate in bps when caps are enabled.
+    #[arg(long, default_value_t = 2000)]
+    max_rate_bps: u16,
+
+    /// Minimum commission rate in bps when caps are enabled.
+    #[arg(long, default_value_t = 50)]
+    min_rate_bps: u16,
+}
+
+/// Clusters the bot can target.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum ClusterArg {
+    Localnet,
+    Devnet,
+}
+
+impl From<ClusterArg> for Cluster {
+    fn from(arg: ClusterArg) -> Self {
+        match arg {
+            ClusterArg::Localnet => Cluster::Localnet,
+            ClusterArg::Devnet => Cluster::Devnet,
+        }
+    }
+}
+
+/// Supported output formats for bot events.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum LogFormat {
+    /// Free-form, human-readable lines (default).
+    Human,
+    /// One JSON object per line.
+    Json,
+}
+
+/// A single structured bot event, serialized as one JSON line in `--log-format json` mode.
+#[derive(Debug, Default, Serialize)]
+struct LogEvent {
+    entity: String,
+    action: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    old_value: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    new_value: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    signature: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// Emits an event either as the given human-readable message or as a JSON line.
+fn log_event(format: LogFormat, human: &str, event: LogEvent) {
+    match format {
+        LogFormat::Human if event.error.is_some() => eprintln!("{}", human),
+        LogFormat::Human => println!("{}", human),
+        LogFormat::Json => match serde_json::to_string(&event) {
+            Ok(line) => println!("{}", line),
+            Err(e) => eprintln!("Failed to serialize log event: {}", e),
+        },
+    }
+}
 
 fn read_first_line(path: &std::path::Path) -> Option<String> {
     std::fs::read_to_string(path).ok().map(|s| s.trim().to_string()).filter(|s| !s.is_empty())
@@ -203,7 +318,7 @@ async fn get_commission_rate_openrouter(
     let data: ORResp = res.json().await?;
     let content = data
         .choices
-        .get(0)
+        .first()
         .and_then(|c| c.message.content.as_ref())
         .map(|s| s.trim().to_string())
         .ok_or("No content from OpenRouter")?;
@@ -216,36 +331,138 @@ async fn get_commission_rate_openrouter(
     Ok(new_rate)
 }
 
+/// Builds the `register_affiliate` instruction for `affiliate` without sending it.
+fn build_register_instruction(affiliate: &Pubkey, cmd: &RegisterCmd) -> Instruction {
+    let (affiliate_info_pda, _) = Pubkey::find_program_address(
+        &[b"affiliate_info", affiliate.as_ref()],
+        &affiliate_program::id(),
+    );
+    let (ecosystem_stats_pda, _) = Pubkey::find_program_address(&[b"ecosystem_stats"], &affiliate_program::id());
+    // The parent's descendant counters are updated, so its account is passed when a parent is set
+    let parent_affiliate_info = cmd.parent.map(|parent| {
+        Pubkey::find_program_address(&[b"affiliate_info", parent.as_ref()], &affiliate_program::id()).0
+    });
+
+    Instruction {
+        program_id: affiliate_program::id(),
+        accounts: RegisterAffiliate {
+            affiliate_info: affiliate_info_pda,
+            ecosystem_stats: ecosystem_stats_pda,
+            parent_affiliate_info,
+            affiliate: *affiliate,
+            system_program: solana_sdk::system_program::id(),
+        }
+        .to_account_metas(None),
+        data: RegisterAffiliateInstruction {
+            args: RegisterAffiliateArgs {
+                parent_affiliate: cmd.parent,
+                referral_level: cmd.referral_level,
+                rate_caps_enabled: cmd.rate_caps,
+                max_commission_rate_bps: cmd.max_rate_bps,
+                min_commission_rate_bps: cmd.min_rate_bps,
+            },
+        }
+        .data(),
+    }
+}
+
+/// Registers the keypair from `cmd` as an affiliate on `cluster`.
+async fn register_affiliate(
+    cluster: Cluster,
+    log_format: LogFormat,
+    cmd: RegisterCmd,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let keypair_path = shellexpand::tilde(&cmd.keypair).to_string();
+    let affiliate = Rc::new(read_keypair_file(&keypair_path)?);
+    let affiliate_key = affiliate.pubkey().to_string();
+
+    let client = Client::new(cluster, affiliate.clone());
+    let program: Program<Rc<Keypair>> = client.program(affiliate_program::id())?;
+
+    let tx_signature = program
+        .request()
+        .instruction(build_register_instruction(&affiliate.pubkey(), &cmd))
+        .send()
+        .await;
+
+    match tx_signature {
+        Ok(sig) => log_event(log_format, &format!("Affiliate {} registered! Signature: {}", affiliate_key, sig), LogEvent {
+            entity: affiliate_key,
+            action: "registered",
+            signature: Some(sig.to_string()),
+            ..Default::default()
+        }),
+        Err(e) => log_event(log_format, &format!("Affiliate registration failed: {}", e), LogEvent {
+            entity: affiliate_key,
+            action: "register_failed",
+            error: Some(e.to_string()),
+            ..Default::default()
+        }),
+    }
+    Ok(())
+}
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let cli = Cli::parse();
+    match cli.command {
+        Some(Command::Register(cmd)) => register_affiliate(cli.cluster.into(), cli.log_format, cmd).await,
+        None => run_optimizer_cycle(cli.cluster.into(), cli.log_format).await,
+    }
+}
+
+/// Fetches the managed affiliate, asks the AI for a new rate and pushes it on-chain.
+async fn run_optimizer_cycle(cluster: Cluster, log_format: LogFormat) -> Result<(), Box<dyn std::error::Error>> {
     let payer_kp_path = shellexpand::tilde("~/.config/solana/id.json").to_string();
-    let payer = Rc::new(Keypair::from_json(&fs::read_to_string(&payer_kp_path)?)?);
+    let payer = Rc::new(read_keypair_file(&payer_kp_path)?);
 
-    let affiliate_to_manage = Rc::new(Keypair::new());
-    println!("Managing affiliate: {}", affiliate_to_manage.pubkey());
+    // The async client only takes thread-safe signers, so the managed affiliate is shared by `Arc`
+    let affiliate_to_manage = Arc::new(Keypair::new());
+    let affiliate_key = affiliate_to_manage.pubkey().to_string();
+    log_event(log_format, &format!("Managing affiliate: {}", affiliate_key), LogEvent {
+        entity: affiliate_key.clone(),
+        action: "manage",
+        ..Default::default()
+    });
 
-    let client = Client::new(Cluster::Localnet, payer.clone());
-    let program: Program = client.program(affiliate_program::id());
+    let client = Client::new(cluster, payer.clone());
+    let program: Program<Rc<Keypair>> = client.program(affiliate_program::id())?;
 
     let http_client = reqwest::Client::new();
-    println!("\n--- Starting Optimizer Update Cycle ---");
+    if log_format == LogFormat::Human {
+        println!("\n--- Starting Optimizer Update Cycle ---");
+    }
 
     let (affiliate_info_pda, _) = Pubkey::find_program_address(
         &[b"affiliate_info", affiliate_to_manage.pubkey().as_ref()],
         &affiliate_program::id(),
     );
 
-    let info_account: affiliate_program::AffiliateInfo = match program.account(affiliate_info_pda).await {
+    let info_account: affiliate_program::state::AffiliateInfo = match program.account(affiliate_info_pda).await {
         Ok(acc) => acc,
         Err(_) => {
-            println!("Affiliate not registered. Exiting.");
+            log_event(log_format, "Affiliate not registered. Exiting.", LogEvent {
+                entity: affiliate_key,
+                action: "not_registered",
+                ..Default::default()
+            });
             return Ok(());
         }
     };
 
-    println!(
-        "Fetched on-chain data: rate={} bps, volume={}",
-        info_account.commission_rate_bps, info_account.total_referred_volume
+    let current_rate = info_account.commission_rate_bps.to_string();
+    log_event(
+        log_format,
+        &format!(
+            "Fetched on-chain data: rate={} bps, volume={}",
+            info_account.commission_rate_bps, info_account.total_referred_volume
+        ),
+        LogEvent {
+            entity: affiliate_key.clone(),
+            action: "fetched",
+            old_value: Some(current_rate.clone()),
+            ..Default::default()
+        },
     );
 
     match get_commission_rate(
@@ -256,14 +473,27 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     )
     .await {
         Ok(new_rate_bps) => {
-            println!("AI suggested new rate: {} bps", new_rate_bps);
+            log_event(log_format, &format!("AI suggested new rate: {} bps", new_rate_bps), LogEvent {
+                entity: affiliate_key.clone(),
+                action: "rate_suggested",
+                old_value: Some(current_rate.clone()),
+                new_value: Some(new_rate_bps.to_string()),
+                ..Default::default()
+            });
             if new_rate_bps == info_account.commission_rate_bps {
-                println!("Rate is already optimal. No update needed.");
+                log_event(log_format, "Rate is already optimal. No update needed.", LogEvent {
+                    entity: affiliate_key.clone(),
+                    action: "rate_unchanged",
+                    old_value: Some(current_rate),
+                    ..Default::default()
+                });
             } else {
-                println!("Sending transaction to update rate...");
+                if log_format == LogFormat::Human {
+                    println!("Sending transaction to update rate...");
+                }
                 let tx_signature = program
                     .request()
-                    .signer(affiliate_to_manage.as_ref())
+                    .signer(affiliate_to_manage.clone())
                     .accounts(SetCommissionRate {
                         affiliate_info: affiliate_info_pda,
                         affiliate_key: affiliate_to_manage.pubkey(),
@@ -273,14 +503,167 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                     .await;
 
                 match tx_signature {
-                    Ok(sig) => println!("Transaction successful! Signature: {}", sig),
-                    Err(e) => eprintln!("Transaction failed: {}", e),
+                    Ok(sig) => log_event(log_format, &format!("Transaction successful! Signature: {}", sig), LogEvent {
+                        entity: affiliate_key.clone(),
+                        action: "rate_updated",
+                        old_value: Some(current_rate),
+                        new_value: Some(new_rate_bps.to_string()),
+                        signature: Some(sig.to_string()),
+                        ..Default::default()
+                    }),
+                    Err(e) => log_event(log_format, &format!("Transaction failed: {}", e), LogEvent {
+                        entity: affiliate_key.clone(),
+                        action: "rate_update_failed",
+                        old_value: Some(current_rate),
+                        new_value: Some(new_rate_bps.to_string()),
+                        error: Some(e.to_string()),
+                        ..Default::default()
+                    }),
                 }
             }
         }
-        Err(e) => eprintln!("Failed to get rate from provider: {}", e),
+        Err(e) => log_event(log_format, &format!("Failed to get rate from provider: {}", e), LogEvent {
+            entity: affiliate_key.clone(),
+            action: "provider_failed",
+            error: Some(e.to_string()),
+            ..Default::default()
+        }),
     }
 
-    println!("\n--- Update Cycle Complete ---");
+    if log_format == LogFormat::Human {
+        println!("\n--- Update Cycle Complete ---");
+    }
     Ok(())
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn log_event_serializes_as_single_json_object() {
+        let event = LogEvent {
+            entity: "Aff1aTe111111111111111111111111111111111111".to_string(),
+            action: "rate_updated",
+            old_value: Some("1000".to_string()),
+            new_value: Some("1200".to_string()),
+            signature: Some("5sig".to_string()),
+            ..Default::default()
+        };
+
+        let line = serde_json::to_string(&event).unwrap();
+        assert!(!line.contains('\n'));
+
+        let v: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(v["action"], "rate_updated");
+        assert_eq!(v["old_value"], "1000");
+        assert_eq!(v["new_value"], "1200");
+        assert_eq!(v["signature"], "5sig");
+        assert!(v.get("error").is_none());
+    }
+
+    #[test]
+    fn log_format_defaults_to_human() {
+        let cli = Cli::try_parse_from(["optimizer-bot"]).unwrap();
+        assert_eq!(cli.log_format, LogFormat::Human);
+        let cli = Cli::try_parse_from(["optimizer-bot", "--log-format", "json"]).unwrap();
+        assert_eq!(cli.log_format, LogFormat::Json);
+    }
+
+    #[test]
+    fn register_subcommand_parses_args() {
+        let parent = Pubkey::new_unique();
+        let cli = Cli::try_parse_from([
+            "optimizer-bot",
+            "--cluster",
+            "devnet",
+            "register",
+            "--keypair",
+            "/tmp/affiliate.json",
+            "--parent",
+            &parent.to_string(),
+            "--referral-level",
+            "2",
+            "--rate-caps",
+            "--max-rate-bps",
+            "1500",
+        ])
+        .unwrap();
+
+        assert_eq!(cli.cluster, ClusterArg::Devnet);
+        let Some(Command::Register(cmd)) = cli.command else {
+            panic!("expected register subcommand");
+        };
+        assert_eq!(cmd.keypair, "/tmp/affiliate.json");
+        assert_eq!(cmd.parent, Some(parent));
+        assert_eq!(cmd.referral_level, 2);
+        assert!(cmd.rate_caps);
+        assert_eq!(cmd.max_rate_bps, 1500);
+        assert_eq!(cmd.min_rate_bps, 50);
+    }
+
+    #[test]
+    fn register_instruction_targets_affiliate_pda() {
+        let affiliate = Pubkey::new_unique();
+        let cli = Cli::try_parse_from(["optimizer-bot", "register", "--referral-level", "3"]).unwrap();
+        let Some(Command::Register(cmd)) = cli.command else {
+            panic!("expected register subcommand");
+        };
+
+        let ix = build_register_instruction(&affiliate, &cmd);
+        let (expected_info, _) = Pubkey::find_program_address(
+            &[b"affiliate_info", affiliate.as_ref()],
+            &affiliate_program::id(),
+        );
+        let (expected_stats, _) = Pubkey::find_program_address(&[b"ecosystem_stats"], &affiliate_program::id());
+
+        assert_eq!(ix.program_id, affiliate_program::id());
+        assert_eq!(ix.accounts[0].pubkey, expected_info);
+        assert!(ix.accounts[0].is_writable);
+        assert_eq!(ix.accounts[1].pubkey, expected_stats);
+        assert!(ix.accounts[1].is_writable);
+        // Without a parent the optional parent account is the program ID placeholder.
+        assert_eq!(ix.accounts[2].pubkey, affiliate_program::id());
+        assert_eq!(ix.accounts[3].pubkey, affiliate);
+        assert!(ix.accounts[3].is_signer);
+
+        let expected_data = RegisterAffiliateInstruction {
+            args: RegisterAffiliateArgs {
+                parent_affiliate: None,
+                referral_level: 3,
+                rate_caps_enabled: false,
+                max_commission_rate_bps: 2000,
+                min_commission_rate_bps: 50,
+            },
+        }
+        .data();
+        assert_eq!(ix.data, expected_data);
+    }
+
+    #[test]
+    fn register_instruction_passes_parent_affiliate_info() {
+        let affiliate = Pubkey::new_unique();
+        let parent = Pubkey::new_unique();
+        let cli = Cli::try_parse_from([
+            "optimizer-bot",
+            "register",
+            "--parent",
+            &parent.to_string(),
+            "--referral-level",
+            "2",
+        ])
+        .unwrap();
+        let Some(Command::Register(cmd)) = cli.command else {
+            panic!("expected register subcommand");
+        };
+
+        let ix = build_register_instruction(&affiliate, &cmd);
+        let (expected_parent_info, _) = Pubkey::find_program_address(
+            &[b"affiliate_info", parent.as_ref()],
+            &affiliate_program::id(),
+        );
+
+        assert_eq!(ix.accounts[2].pubkey, expected_parent_info);
+        assert!(ix.accounts[2].is_writable);
+    }
+}
@@ -13,6 +13,9 @@
 //! 4. Parses AI responses to extract precise price data (with 9 decimal precision)
 //! 5. Submits transactions to update oracle prices on-chain for each pool
 //!
+//! Pools are processed concurrently (bounded by `--max-in-flight`) so slow AI
+//! queries and transaction sends overlap instead of stalling the whole cycle.
+//!
 //! ## Oracle Role
 //!
 //! This bot acts as the authoritative price oracle for the barter DEX system:
@@ -32,15 +35,266 @@
 //!
 //! Model preferences can be set via:
 //! - `~/.model-openrouter` for OpenRouter model selection
+//!
+//! ## Logging
+//!
+//! Output is human-readable by default. Pass `--log-format json` to emit one JSON
+//! object per event (pool key, action, old/new price, tx signature or error)
+//! for ingestion into log pipelines.
+//!
+//! ## Local Bootstrapping
+//!
+//! The `create-pool` subcommand creates a pool for the keeper to price, making
+//! end-to-end local testing possible without hand-written transactions, e.g.
+//! `price-keeper-bot create-pool --mint-a <MINT> --mint-b <MINT> --oracle-provider ai-oracle`.
 
 use anchor_client::{Client, Program, Cluster};
-use barter_dex_program::accounts::UpdateOraclePrice;
-use barter_dex_program::instruction::UpdateOraclePrice as UpdateOraclePriceInstruction;
+use anchor_client::anchor_lang::{InstructionData, ToAccountMetas};
+use barter_dex_program::accounts::{CreatePool, UpdateOraclePrice};
+use barter_dex_program::instruction::{CreatePool as CreatePoolInstruction, UpdateOraclePrice as UpdateOraclePriceInstruction};
+use barter_dex_program::state::OracleProvider;
+use barter_dex_program::{CreatePoolArgs, UpdatePriceArgs};
 use serde::{Deserialize, Serialize};
+use solana_sdk::instruction::Instruction;
 use solana_sdk::pubkey::Pubkey;
-use solana_sdk::signer::{keypair::Keypair, Signer};
-use std::fs;
+use solana_sdk::signer::{keypair::{read_keypair_file, Keypair}, Signer};
+use std::future::Future;
 use std::rc::Rc;
+use std::sync::Arc;
+use futures::stream::{self, StreamExt};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+
+/// Command-line options for the price keeper bot.
+#[derive(Debug, Parser)]
+#[command(name = "price-keeper-bot", about = "AI-driven price oracle for the barter DEX")]
+struct Cli {
+    /// Output format for bot events.
+    #[arg(long, global = true, value_enum, default_value_t = LogFormat::Human)]
+    log_format: LogFormat,
+
+    /// Cluster to send transactions to.
+    #[arg(long, global = true, value_enum, default_value_t = ClusterArg::Localnet)]
+    cluster: ClusterArg,
+
+    /// Maximum number of pools priced concurrently during an update cycle.
+    #[arg(long, default_value_t = 4)]
+    max_in_flight: usize,
+
+    /// Runs a price update cycle when omitted.
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Create a liquidity pool for the keeper to price.
+    CreatePool(CreatePoolCmd),
+}
+
+/// Arguments for the `create-pool` subcommand, mirroring `CreatePoolArgs`.
+#[derive(Debug, Args)]
+struct CreatePoolCmd {
+    /// Keypair that pays for and creates the pool.
+    #[arg(long, default_value = "~/.config/solana/id.json")]
+    keypair: String,
+
+    /// Mint of token A.
+    #[arg(long)]
+    mint_a: Pubkey,
+
+    /// Mint of token B.
+    #[arg(long)]
+    mint_b: Pubkey,
+
+    /// Key allowed to push prices; defaults to the creating keypair.
+    #[arg(long)]
+    oracle_authority: Option<Pubkey>,
+
+    /// Price source the pool is configured for. Only that provider's feed may be passed
+    /// (Pyth needs --pyth-feed-a, Switchboard needs --switchboard-feed); hybrid needs two or more.
+    #[arg(long, value_enum, default_value_t = ProviderArg::AiOracle)]
+    oracle_provider: ProviderArg,
+
+    /// Pyth price feed for token A.
+    #[arg(long)]
+    pyth_feed_a: Option<Pubkey>,
+
+    /// Pyth price feed for token B.
+    #[arg(long)]
+    pyth_feed_b: Option<Pubkey>,
+
+    /// Switchboard aggregator feed.
+    #[arg(long)]
+    switchboard_feed: Option<Pubkey>,
+
+    /// AI oracle program.
+    #[arg(long)]
+    ai_oracle_program: Option<Pubkey>,
+
+    /// Base trading fee in basis points.
+    #[arg(long, default_value_t = 30)]
+    fee_bps: u16,
+
+    /// Scale fees with price volatility.
+    #[arg(long)]
+    dynamic_fee: bool,
+
+    /// Volatility above which dynamic fees kick in.
+    #[arg(long, default_value_t = 0)]
+    volatility_threshold: u64,
+
+    /// Oracle price age (seconds) up to which swaps trade normally.
+    #[arg(long, default_value_t = 300)]
+    max_staleness_seconds: i64,
+
+    /// Oracle price age (seconds) beyond which swaps fail.
+    #[arg(long, default_value_t = 900)]
+    hard_stale_seconds: i64,
+
+    /// Extra fee in basis points charged while the price is stale but not expired.
+    #[arg(long, default_value_t = 50)]
+    stale_fee_bps: u16,
+
+    /// Price swaps against a TWAP of the pool's price history.
+    #[arg(long)]
+    twap: bool,
+
+    /// Number of price history entries (1-24) the TWAP averages.
+    #[arg(long, default_value_t = 24)]
+    twap_window: u8,
+
+    /// Price swaps against an EMA of pushes rather than the latest push.
+    #[arg(long)]
+    smoothing: bool,
+
+    /// Weight in basis points of each new push in the EMA.
+    #[arg(long, default_value_t = 2000)]
+    ema_alpha_bps: u16,
+
+    /// Price moves beyond this many basis points need a confirming call (0 = disabled).
+    #[arg(long, default_value_t = 0)]
+    max_price_move_bps: u16,
+
+    /// Seconds a staged large move must wait before it can be confirmed.
+    #[arg(long, default_value_t = 60)]
+    price_confirm_delay_seconds: i64,
+
+    /// Reject pushed prices further than this many basis points from the current price (0 = disabled).
+    #[arg(long, default_value_t = 0)]
+    max_price_deviation_bps: u16,
+
+    /// Minimum seconds between pool config updates.
+    #[arg(long, default_value_t = 3600)]
+    config_update_cooldown_seconds: i64,
+
+    /// Round pushed prices to the nearest multiple of this (0 = no rounding).
+    #[arg(long, default_value_t = 0)]
+    price_tick: u64,
+
+    /// Halt swaps while the price confidence exceeds this many basis points of the price (0 = disabled).
+    #[arg(long, default_value_t = 0)]
+    max_confidence_bps: u64,
+
+    /// Blend the oracle price with the pool's reserve ratio when swapping.
+    #[arg(long)]
+    amm_blend: bool,
+
+    /// Oracle's weight in basis points in the blended price.
+    #[arg(long, default_value_t = 10_000)]
+    oracle_weight_bps: u16,
+
+    /// Pyth's weight in basis points in the multi-source price; the three weights sum to 10000.
+    #[arg(long, default_value_t = 4_000)]
+    pyth_weight: u16,
+
+    /// Switchboard's weight in basis points in the multi-source price.
+    #[arg(long, default_value_t = 3_500)]
+    switchboard_weight: u16,
+
+    /// The AI oracle's weight in basis points in the multi-source price.
+    #[arg(long, default_value_t = 2_500)]
+    ai_weight: u16,
+
+    /// Price sources (0-3) that must be present before swaps are allowed.
+    #[arg(long, default_value_t = 1)]
+    min_price_sources: u8,
+
+    /// Reject liquidity deposits that don't match the pool's reserve ratio.
+    #[arg(long)]
+    require_ratio: bool,
+}
+
+/// Clusters the bot can target.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum ClusterArg {
+    Localnet,
+    Devnet,
+}
+
+impl From<ClusterArg> for Cluster {
+    fn from(arg: ClusterArg) -> Self {
+        match arg {
+            ClusterArg::Localnet => Cluster::Localnet,
+            ClusterArg::Devnet => Cluster::Devnet,
+        }
+    }
+}
+
+/// Command-line spelling of `OracleProvider`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum ProviderArg {
+    Pyth,
+    Switchboard,
+    AiOracle,
+    Hybrid,
+}
+
+impl From<ProviderArg> for OracleProvider {
+    fn from(arg: ProviderArg) -> Self {
+        match arg {
+            ProviderArg::Pyth => OracleProvider::Pyth,
+            ProviderArg::Switchboard => OracleProvider::Switchboard,
+            ProviderArg::AiOracle => OracleProvider::AIOracle,
+            ProviderArg::Hybrid => OracleProvider::Hybrid,
+        }
+    }
+}
+
+/// Supported output formats for bot events.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum LogFormat {
+    /// Free-form, human-readable lines (default).
+    Human,
+    /// One JSON object per line.
+    Json,
+}
+
+/// A single structured bot event, serialized as one JSON line in `--log-format json` mode.
+#[derive(Debug, Default, Serialize)]
+struct LogEvent {
+    entity: String,
+    action: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    old_value: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    new_value: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    signature: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// Emits an event either as the given human-readable message or as a JSON line.
+fn log_event(format: LogFormat, human: &str, event: LogEvent) {
+    match format {
+        LogFormat::Human if event.error.is_some() => eprintln!("{}", human),
+        LogFormat::Human => println!("{}", human),
+        LogFormat::Json => match serde_json::to_string(&event) {
+            Ok(line) => println!("{}", line),
+            Err(e) => eprintln!("Failed to serialize log event: {}", e),
+        },
+    }
+}
 
 fn read_first_line(path: &std::path::Path) -> Option<String> {
     std::fs::read_to_string(path).ok().map(|s| s.trim().to_string()).filter(|s| !s.is_empty())
@@ -129,7 +383,7 @@ async fn get_price_openrouter(
     let data: ORResp = res.json().await?;
     let content = data
         .choices
-        .get(0)
+        .first()
         .and_then(|c| c.message.content.as_ref())
         .map(|s| s.trim().to_string())
         .ok_or("No content from OpenRouter")?;
@@ -152,53 +406,402 @@ async fn get_exchange_rate(
     get_price_openrouter(client, &model, &key, &prompt).await
 }
 
+/// The SPL Token program that owns the pool vaults.
+const SPL_TOKEN_PROGRAM_ID: Pubkey = solana_sdk::pubkey!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+
+/// Builds the `create_pool` instruction for `authority` without sending it.
+fn build_create_pool_instruction(authority: &Pubkey, cmd: &CreatePoolCmd) -> Instruction {
+    let program_id = barter_dex_program::id();
+    let (pool_pda, _) = Pubkey::find_program_address(
+        &[b"liquidity_pool", cmd.mint_a.as_ref(), cmd.mint_b.as_ref()],
+        &program_id,
+    );
+    let (vault_a, _) = Pubkey::find_program_address(
+        &[b"pool_vault", cmd.mint_a.as_ref(), cmd.mint_b.as_ref(), b"a"],
+        &program_id,
+    );
+    let (vault_b, _) = Pubkey::find_program_address(
+        &[b"pool_vault", cmd.mint_a.as_ref(), cmd.mint_b.as_ref(), b"b"],
+        &program_id,
+    );
+    let (lp_mint, _) = Pubkey::find_program_address(
+        &[b"lp_mint", cmd.mint_a.as_ref(), cmd.mint_b.as_ref()],
+        &program_id,
+    );
+
+    Instruction {
+        program_id,
+        accounts: CreatePool {
+            pool: pool_pda,
+            vault_a,
+            vault_b,
+            lp_mint,
+            mint_a: cmd.mint_a,
+            mint_b: cmd.mint_b,
+            authority: *authority,
+            system_program: solana_sdk::system_program::id(),
+            token_program: SPL_TOKEN_PROGRAM_ID,
+            rent: solana_sdk::sysvar::rent::id(),
+        }
+        .to_account_metas(None),
+        data: CreatePoolInstruction {
+            args: CreatePoolArgs {
+                oracle_authority: cmd.oracle_authority.unwrap_or(*authority),
+                oracle_provider: cmd.oracle_provider.into(),
+                pyth_price_feed_a: cmd.pyth_feed_a,
+                pyth_price_feed_b: cmd.pyth_feed_b,
+                switchboard_feed: cmd.switchboard_feed,
+                ai_oracle_program: cmd.ai_oracle_program,
+                fee_bps: cmd.fee_bps,
+                dynamic_fee_enabled: cmd.dynamic_fee,
+                volatility_threshold: cmd.volatility_threshold,
+                max_staleness_seconds: cmd.max_staleness_seconds,
+                hard_stale_seconds: cmd.hard_stale_seconds,
+                stale_fee_bps: cmd.stale_fee_bps,
+                use_twap: cmd.twap,
+                twap_window: cmd.twap_window,
+                smoothing_enabled: cmd.smoothing,
+                ema_alpha_bps: cmd.ema_alpha_bps,
+                max_price_move_bps: cmd.max_price_move_bps,
+                price_confirm_delay_seconds: cmd.price_confirm_delay_seconds,
+                max_price_deviation_bps: cmd.max_price_deviation_bps,
+                config_update_cooldown_seconds: cmd.config_update_cooldown_seconds,
+                price_tick: cmd.price_tick,
+                max_confidence_bps: cmd.max_confidence_bps,
+                amm_blend_enabled: cmd.amm_blend,
+                oracle_weight_bps: cmd.oracle_weight_bps,
+                pyth_weight: cmd.pyth_weight,
+                switchboard_weight: cmd.switchboard_weight,
+                ai_weight: cmd.ai_weight,
+                min_price_sources: cmd.min_price_sources,
+                require_ratio: cmd.require_ratio,
+            },
+        }
+        .data(),
+    }
+}
+
+/// Creates the pool described by `cmd` on `cluster`.
+async fn create_pool(
+    cluster: Cluster,
+    log_format: LogFormat,
+    cmd: CreatePoolCmd,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let keypair_path = shellexpand::tilde(&cmd.keypair).to_string();
+    let authority = Rc::new(read_keypair_file(&keypair_path)?);
+
+    let client = Client::new(cluster, authority.clone());
+    let program: Program<Rc<Keypair>> = client.program(barter_dex_program::id())?;
+
+    let ix = build_create_pool_instruction(&authority.pubkey(), &cmd);
+    let pool_key = ix.accounts[0].pubkey.to_string();
+    let tx_signature = program
+        .request()
+        .instruction(ix)
+        .send()
+        .await;
+
+    match tx_signature {
+        Ok(sig) => log_event(log_format, &format!("Pool {} created! Signature: {}", pool_key, sig), LogEvent {
+            entity: pool_key,
+            action: "pool_created",
+            signature: Some(sig.to_string()),
+            ..Default::default()
+        }),
+        Err(e) => log_event(log_format, &format!("Pool creation failed: {}", e), LogEvent {
+            entity: pool_key,
+            action: "pool_create_failed",
+            error: Some(e.to_string()),
+            ..Default::default()
+        }),
+    }
+    Ok(())
+}
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let cli = Cli::parse();
+    match cli.command {
+        Some(Command::CreatePool(cmd)) => create_pool(cli.cluster.into(), cli.log_format, cmd).await,
+        None => run_price_keeper_cycle(cli.cluster.into(), cli.log_format, cli.max_in_flight).await,
+    }
+}
+
+/// Runs `task` over `items` with at most `max_in_flight` futures in flight at once.
+///
+/// Results are returned in completion order.
+async fn run_bounded<I, T, F, Fut>(items: I, max_in_flight: usize, task: F) -> Vec<T>
+where
+    I: IntoIterator,
+    F: FnMut(I::Item) -> Fut,
+    Fut: Future<Output = T>,
+{
+    stream::iter(items)
+        .map(task)
+        .buffer_unordered(max_in_flight.max(1))
+        .collect()
+        .await
+}
+
+/// Fetches an AI price for a single pool and pushes it on-chain, logging the outcome.
+async fn process_pool(
+    program: &Program<Rc<Keypair>>,
+    http_client: &reqwest::Client,
+    oracle_authority: &Arc<Keypair>,
+    log_format: LogFormat,
+    pool_pda: Pubkey,
+    pool_data: barter_dex_program::state::LiquidityPool,
+) {
+    let pool_key = pool_pda.to_string();
+    let old_price = pool_data.oracle_price.to_string();
+    if log_format == LogFormat::Human {
+        println!("\nProcessing pool for {} <-> {}", pool_data.mint_a, pool_data.mint_b);
+    }
+
+    match get_exchange_rate(http_client, &pool_data.mint_a, &pool_data.mint_b).await {
+        Ok(new_price) => {
+            log_event(log_format, &format!("AI suggested new price: {}", new_price), LogEvent {
+                entity: pool_key.clone(),
+                action: "price_suggested",
+                old_value: Some(old_price.clone()),
+                new_value: Some(new_price.to_string()),
+                ..Default::default()
+            });
+            if log_format == LogFormat::Human {
+                println!("Sending transaction to update on-chain price...");
+            }
+            let tx_signature = program
+                .request()
+                .signer(oracle_authority.clone())
+                .accounts(UpdateOraclePrice {
+                    pool: pool_pda,
+                    oracle_authority: oracle_authority.pubkey(),
+                })
+                .args(UpdateOraclePriceInstruction {
+                    args: UpdatePriceArgs {
+                        pyth_price: None,
+                        switchboard_price: None,
+                        ai_price: Some(new_price),
+                        price_confidence: None,
+                        override_deviation: false,
+                    },
+                })
+                .send()
+                .await;
+
+            match tx_signature {
+                Ok(sig) => log_event(log_format, &format!("Price update successful! Signature: {}", sig), LogEvent {
+                    entity: pool_key,
+                    action: "price_updated",
+                    old_value: Some(old_price),
+                    new_value: Some(new_price.to_string()),
+                    signature: Some(sig.to_string()),
+                    ..Default::default()
+                }),
+                Err(e) => log_event(log_format, &format!("Price update transaction failed: {}", e), LogEvent {
+                    entity: pool_key,
+                    action: "price_update_failed",
+                    old_value: Some(old_price),
+                    new_value: Some(new_price.to_string()),
+                    error: Some(e.to_string()),
+                    ..Default::default()
+                }),
+            }
+        }
+        Err(e) => log_event(
+            log_format,
+            &format!("Failed to get price from provider for pool {}: {}", pool_pda, e),
+            LogEvent {
+                entity: pool_key,
+                action: "provider_failed",
+                old_value: Some(old_price),
+                error: Some(e.to_string()),
+                ..Default::default()
+            },
+        ),
+    }
+}
+
+/// Prices every pool of the DEX through the AI provider and pushes the results on-chain.
+async fn run_price_keeper_cycle(
+    cluster: Cluster,
+    log_format: LogFormat,
+    max_in_flight: usize,
+) -> Result<(), Box<dyn std::error::Error>> {
     let payer_kp_path = shellexpand::tilde("~/.config/solana/id.json").to_string();
-    let payer = Rc::new(Keypair::from_json(&fs::read_to_string(&payer_kp_path)?)?);
+    let payer = Rc::new(read_keypair_file(&payer_kp_path)?);
 
-    let oracle_authority = Rc::new(Keypair::from_json(&std::fs::read_to_string(&payer_kp_path)?)?);
-    println!("Price Keeper starting with authority: {}", oracle_authority.pubkey());
+    // The async client only takes thread-safe signers, so the oracle authority is shared by `Arc`
+    let oracle_authority = Arc::new(read_keypair_file(&payer_kp_path)?);
+    log_event(
+        log_format,
+        &format!("Price Keeper starting with authority: {}", oracle_authority.pubkey()),
+        LogEvent {
+            entity: oracle_authority.pubkey().to_string(),
+            action: "start",
+            ..Default::default()
+        },
+    );
 
-    let client = Client::new(Cluster::Localnet, payer.clone());
-    let program: Program = client.program(barter_dex_program::id());
+    let client = Client::new(cluster, payer.clone());
+    let program: Program<Rc<Keypair>> = client.program(barter_dex_program::id())?;
     let http_client = reqwest::Client::new();
 
-    println!("\n--- Starting Price Keeper Update Cycle ---");
+    if log_format == LogFormat::Human {
+        println!("\n--- Starting Price Keeper Update Cycle ---");
+    }
 
-    let pool_accounts: Vec<(Pubkey, barter_dex_program::LiquidityPool)> = program.accounts(vec![]).await?;
+    let pool_accounts: Vec<(Pubkey, barter_dex_program::state::LiquidityPool)> = program.accounts(vec![]).await?;
     if pool_accounts.is_empty() {
-        println!("No liquidity pools found. Exiting.");
+        if log_format == LogFormat::Human {
+            println!("No liquidity pools found. Exiting.");
+        }
         return Ok(());
     }
 
-    for (pool_pda, pool_data) in pool_accounts {
-        println!("\nProcessing pool for {} <-> {}", pool_data.mint_a, pool_data.mint_b);
+    // Pools are priced concurrently; each task logs its own outcome so one failing
+    // pool never aborts the others.
+    run_bounded(pool_accounts, max_in_flight, |(pool_pda, pool_data)| {
+        process_pool(&program, &http_client, &oracle_authority, log_format, pool_pda, pool_data)
+    })
+    .await;
 
-        match get_exchange_rate(&http_client, &pool_data.mint_a, &pool_data.mint_b).await {
-            Ok(new_price) => {
-                println!("AI suggested new price: {}", new_price);
-                println!("Sending transaction to update on-chain price...");
-                let tx_signature = program
-                    .request()
-                    .signer(oracle_authority.as_ref())
-                    .accounts(UpdateOraclePrice {
-                        pool: pool_pda,
-                        oracle_authority: oracle_authority.pubkey(),
-                    })
-                    .args(UpdateOraclePriceInstruction { new_price })
-                    .send()
-                    .await;
-
-                match tx_signature {
-                    Ok(sig) => println!("Price update successful! Signature: {}", sig),
-                    Err(e) => eprintln!("Price update transaction failed: {}", e),
-                }
+    if log_format == LogFormat::Human {
+        println!("\n--- Update Cycle Complete ---");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn log_event_serializes_as_single_json_object() {
+        let event = LogEvent {
+            entity: "DEXy2D1fVf5s3f2y6D4b7j8N1M5P9kH3rW7T4gS6fX8a".to_string(),
+            action: "price_update_failed",
+            old_value: Some("1000000000".to_string()),
+            new_value: Some("1200000000".to_string()),
+            error: Some("blockhash not found".to_string()),
+            ..Default::default()
+        };
+
+        let line = serde_json::to_string(&event).unwrap();
+        assert!(!line.contains('\n'));
+
+        let v: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(v["action"], "price_update_failed");
+        assert_eq!(v["new_value"], "1200000000");
+        assert_eq!(v["error"], "blockhash not found");
+        assert!(v.get("signature").is_none());
+    }
+
+    #[tokio::test]
+    async fn run_bounded_caps_in_flight_tasks() {
+        use std::cell::Cell;
+        use std::time::Duration;
+
+        let in_flight = Cell::new(0usize);
+        let peak = Cell::new(0usize);
+
+        let results = run_bounded(0..10u64, 3, |i| {
+            let (in_flight, peak) = (&in_flight, &peak);
+            async move {
+                in_flight.set(in_flight.get() + 1);
+                peak.set(peak.get().max(in_flight.get()));
+                tokio::time::sleep(Duration::from_millis(5 + (i % 3) * 5)).await;
+                in_flight.set(in_flight.get() - 1);
+                if i == 4 { Err(i) } else { Ok(i) }
             }
-            Err(e) => eprintln!("Failed to get price from provider for pool {}: {}", pool_pda, e),
-        }
+        })
+        .await;
+
+        assert_eq!(peak.get(), 3);
+        assert_eq!(results.len(), 10);
+        // A failing task does not prevent the rest from completing.
+        assert_eq!(results.iter().filter(|r| r.is_ok()).count(), 9);
     }
 
-    println!("\n--- Update Cycle Complete ---");
-    Ok(())
-}
\ No newline at end of file
+    #[tokio::test]
+    async fn run_bounded_treats_zero_as_sequential() {
+        let results = run_bounded(vec![1, 2, 3], 0, |i| async move { i * 2 }).await;
+        assert_eq!(results, vec![2, 4, 6]);
+    }
+
+    #[test]
+    fn create_pool_instruction_assembles_accounts_and_args() {
+        let authority = Pubkey::new_unique();
+        let mint_a = Pubkey::new_unique();
+        let mint_b = Pubkey::new_unique();
+        let feed = Pubkey::new_unique();
+        let cli = Cli::try_parse_from([
+            "price-keeper-bot",
+            "create-pool",
+            "--mint-a",
+            &mint_a.to_string(),
+            "--mint-b",
+            &mint_b.to_string(),
+            "--oracle-provider",
+            "switchboard",
+            "--switchboard-feed",
+            &feed.to_string(),
+            "--fee-bps",
+            "50",
+        ])
+        .unwrap();
+        let Some(Command::CreatePool(cmd)) = cli.command else {
+            panic!("expected create-pool subcommand");
+        };
+
+        let ix = build_create_pool_instruction(&authority, &cmd);
+        let program_id = barter_dex_program::id();
+        let (pool_pda, _) = Pubkey::find_program_address(
+            &[b"liquidity_pool", mint_a.as_ref(), mint_b.as_ref()],
+            &program_id,
+        );
+
+        assert_eq!(ix.program_id, program_id);
+        assert_eq!(ix.accounts[0].pubkey, pool_pda);
+        assert_eq!(ix.accounts[4].pubkey, mint_a);
+        assert_eq!(ix.accounts[5].pubkey, mint_b);
+        assert_eq!(ix.accounts[6].pubkey, authority);
+        assert!(ix.accounts[6].is_signer);
+
+        let expected_data = CreatePoolInstruction {
+            args: CreatePoolArgs {
+                oracle_authority: authority,
+                oracle_provider: OracleProvider::Switchboard,
+                pyth_price_feed_a: None,
+                pyth_price_feed_b: None,
+                switchboard_feed: Some(feed),
+                ai_oracle_program: None,
+                fee_bps: 50,
+                dynamic_fee_enabled: false,
+                volatility_threshold: 0,
+                max_staleness_seconds: 300,
+                hard_stale_seconds: 900,
+                stale_fee_bps: 50,
+                use_twap: false,
+                twap_window: 24,
+                smoothing_enabled: false,
+                ema_alpha_bps: 2000,
+                max_price_move_bps: 0,
+                price_confirm_delay_seconds: 60,
+                max_price_deviation_bps: 0,
+                config_update_cooldown_seconds: 3600,
+                price_tick: 0,
+                max_confidence_bps: 0,
+                amm_blend_enabled: false,
+                oracle_weight_bps: 10_000,
+                pyth_weight: 4_000,
+                switchboard_weight: 3_500,
+                ai_weight: 2_500,
+                min_price_sources: 1,
+                require_ratio: false,
+            },
+        }
+        .data();
+        assert_eq!(ix.data, expected_data);
+    }
+}
@@ -33,7 +33,11 @@
 //! - Retry attempts and timeouts for reliability
 //! - Minimum liquidity requirements for stability
 
-use anchor_lang::prelude::constant;
+use anchor_lang::prelude::{constant, Pubkey};
+
+/// Program ID of the `factory-program`; must match its `declare_id!`.
+pub const FACTORY_PROGRAM_ID: Pubkey =
+    anchor_lang::solana_program::pubkey!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
 
 /// Seed for the `LaunchState` PDA in the `factory-program`.
 #[constant]
@@ -47,10 +51,42 @@ pub const SOL_VAULT_SEED: &[u8] = b"sol_vault";
 #[constant]
 pub const VESTING_SCHEDULE_SEED: &[u8] = b"vesting_schedule";
 
+/// Seed for the per-buyer `PurchaseTracker` PDAs in the `factory-program`.
+#[constant]
+pub const PURCHASE_TRACKER_SEED: &[u8] = b"purchase_tracker";
+
+/// Seed for the `WhitelistEntry` PDAs in the `factory-program`.
+#[constant]
+pub const WHITELIST_ENTRY_SEED: &[u8] = b"whitelist_entry";
+
+/// Seed for the `BlockedBuyer` PDAs in the `factory-program`.
+#[constant]
+pub const BLOCKED_BUYER_SEED: &[u8] = b"blocked_buyer";
+
+/// Seed for the `FeeSplitConfig` PDAs in the `factory-program`.
+#[constant]
+pub const FEE_SPLIT_CONFIG_SEED: &[u8] = b"fee_split_config";
+
+/// Seed for the holder balance `Snapshot` PDAs in the `factory-program`.
+#[constant]
+pub const SNAPSHOT_SEED: &[u8] = b"snapshot";
+
+/// Seed for the `LaunchMetadata` PDAs in the `factory-program`.
+#[constant]
+pub const LAUNCH_METADATA_SEED: &[u8] = b"launch_metadata";
+
+/// Seed for the `PlatformConfig` singleton PDA in the `factory-program`.
+#[constant]
+pub const PLATFORM_CONFIG_SEED: &[u8] = b"platform_config";
+
 /// Seed for the `AffiliateInfo` PDA in the `affiliate-program`.
 #[constant]
 pub const AFFILIATE_INFO_SEED: &[u8] = b"affiliate_info";
 
+/// Seed for the `EcosystemStats` singleton PDA in the `affiliate-program`.
+#[constant]
+pub const ECOSYSTEM_STATS_SEED: &[u8] = b"ecosystem_stats";
+
 /// Seed for the affiliate analytics PDA in the `affiliate-program`.
 #[constant]
 pub const AFFILIATE_ANALYTICS_SEED: &[u8] = b"affiliate_analytics";
@@ -63,6 +99,10 @@ pub const LIQUIDITY_POOL_SEED: &[u8] = b"liquidity_pool";
 #[constant]
 pub const POOL_VAULT_SEED: &[u8] = b"pool_vault";
 
+/// Seed for the LP token mint PDA of each pool in the `barter-dex-program`.
+#[constant]
+pub const LP_MINT_SEED: &[u8] = b"lp_mint";
+
 /// Seed for the oracle price feed PDA in the `barter-dex-program`.
 #[constant]
 pub const ORACLE_PRICE_FEED_SEED: &[u8] = b"oracle_price_feed";
@@ -70,17 +110,44 @@ pub const ORACLE_PRICE_FEED_SEED: &[u8] = b"oracle_price_feed";
 /// Mathematical constants for precision and calculations
 pub const ORACLE_PRICE_PRECISION: u64 = 1_000_000_000; // 1e9 for price precision
 pub const BPS_PRECISION: u64 = 10_000; // 100% = 10,000 basis points
+pub const FIXED_POINT_ONE: u64 = 1_000_000_000; // 1.0 in 1e9 fixed point
+pub const TOKEN_BASE_UNITS: u64 = 1_000_000_000; // Base units per whole token (9 decimals)
+pub const MAX_CURVE_PRICE_LAMPORTS: u64 = 1_000_000_000_000_000; // 1M SOL per whole token price ceiling
+pub const MAX_LAUNCH_RAISE_LAMPORTS: u64 = 1_000_000_000_000_000_000; // 1B SOL lifetime raise, well below u64::MAX (~18.4B SOL)
 pub const MAX_ORACLE_AGE_SECONDS: i64 = 300; // 5 minutes max oracle staleness
+pub const MAX_PRICE_SOURCES: usize = 3; // Pyth, Switchboard and the AI oracle
+pub const MAX_ORACLE_SIGNERS: usize = 3; // Extra keys per pool, e.g. redundant price keepers, allowed to push prices
+pub const DEFAULT_PYTH_WEIGHT: u16 = 4_000; // Pyth's default share, in bps, of a weighted price
+pub const DEFAULT_SWITCHBOARD_WEIGHT: u16 = 3_500; // Switchboard's default share, in bps, of a weighted price
+pub const DEFAULT_AI_WEIGHT: u16 = 2_500; // The AI oracle's default share, in bps, of a weighted price
+pub const MIN_HYBRID_PRICE_SOURCES: usize = 2; // Hybrid pools blend at least two sources
 pub const MINIMUM_LIQUIDITY: u64 = 1_000_000; // Minimum liquidity tokens
+pub const DEPOSIT_RATIO_TOLERANCE_BPS: u64 = 100; // Off-ratio slack allowed on deposits into ratio-enforcing pools
 pub const FEE_BPS: u16 = 30; // 0.3% fee in basis points
+pub const SUPPLY_ROUNDING_TOLERANCE: u64 = 1_000_000; // 0.001 token of overshoot clamped on the final sale
 
 /// Security constants
 pub const MAX_RATE_BPS: u16 = 2000; // Maximum 20% commission rate
 pub const MIN_RATE_BPS: u16 = 50; // Minimum 0.5% commission rate
+pub const MAX_AFFILIATE_DESCENDANTS: u32 = 1_000; // Sub-affiliates one parent may register, bounding sybil tree growth
+pub const PERFORMANCE_DECAY_PERIOD_SECONDS: i64 = 2_592_000; // 30 days of affiliate inactivity per score decay step
+pub const PERFORMANCE_DECAY_RETAIN_BPS: u64 = 9_000; // Share of the performance score kept per inactive period
+pub const VOLUME_MONTH_SECONDS: i64 = 2_592_000; // 30-day month bucketing affiliate referral volume windows
+pub const ANCESTOR_COMMISSION_SHARE_BPS: u64 = 5_000; // Each referral level up earns this share of the level below's commission
+pub const MAX_TOTAL_FEE_BPS: u16 = 3000; // Maximum 30% combined platform + affiliate fee
+pub const MAX_FEE_SPLIT_RECIPIENTS: usize = 4; // Maximum platform fee split recipients per launch
+pub const MAX_APPROVED_AFFILIATES: usize = 16; // Maximum affiliates on a launch's commission allowlist
+pub const MAX_ALLOWED_PAYMENT_MINTS: usize = 8; // Maximum SPL payment mints on the platform allowlist
+pub const SECONDS_PER_DAY: i64 = 86_400; // One UTC day, the bucket size of affiliate daily analytics
 pub const MAX_VESTING_DURATION_SECONDS: i64 = 31_557_600; // 1 year in seconds
 pub const MIN_VESTING_DURATION_SECONDS: i64 = 86_400; // 1 day in seconds
+pub const MAX_VESTING_MILESTONES: usize = 12; // Maximum unlock steps in a milestone vesting schedule
+pub const MAX_METADATA_NAME_LEN: usize = 32; // Launch token name, in bytes
+pub const MAX_METADATA_SYMBOL_LEN: usize = 10; // Launch token symbol, in bytes
+pub const MAX_METADATA_URI_LEN: usize = 200; // Launch token metadata URI, in bytes
 
 /// Performance optimization constants
 pub const MAX_BATCH_SIZE: usize = 100; // Maximum batch processing size
+pub const MAX_MERKLE_PROOF_LEN: usize = 32; // Proof depth limit; covers trees of 2^32 leaves
 pub const RETRY_ATTEMPTS: u32 = 3; // Number of retry attempts for transactions
 pub const TRANSACTION_TIMEOUT_SECONDS: u64 = 30; // Transaction timeout
\ No newline at end of file
@@ -9,6 +9,7 @@
 //! - [`pda_utils`]: Program Derived Address (PDA) derivation functions for consistent addressing
 //! - [`math_utils`]: Safe arithmetic operations with comprehensive overflow/underflow protection
 //! - [`time_utils`]: Time-based validation utilities for oracle staleness and vesting calculations
+//! - [`merkle_utils`]: SHA-256 merkle leaf hashing and inclusion proof verification for off-chain built trees
 //!
 //! ## Safety Features
 //!
@@ -46,6 +47,12 @@ pub mod pda_utils {
         )
     }
 
+    /// Derive the mint authority of a launch's token: the factory program's launch state PDA.
+    /// Pure address math, so bots and tests can call it without a Solana runtime.
+    pub fn derive_mint_authority(authority: &Pubkey, token_mint: &Pubkey) -> (Pubkey, u8) {
+        derive_launch_state_address(authority, token_mint, &crate::constants::FACTORY_PROGRAM_ID)
+    }
+
     /// Derive SOL vault PDA
     pub fn derive_sol_vault_address(
         authority: &Pubkey,
@@ -91,6 +98,62 @@ pub mod pda_utils {
             program_id,
         )
     }
+
+    /// Signer seeds for the launch state PDA, for use as `&[&seeds[..]]` in a CPI
+    pub fn launch_state_signer_seeds<'a>(
+        authority: &'a Pubkey,
+        token_mint: &'a Pubkey,
+        bump: &'a u8,
+    ) -> [&'a [u8]; 4] {
+        [
+            crate::constants::LAUNCH_STATE_SEED,
+            authority.as_ref(),
+            token_mint.as_ref(),
+            std::slice::from_ref(bump),
+        ]
+    }
+
+    /// Signer seeds for the SOL vault PDA, for use as `&[&seeds[..]]` in a CPI
+    pub fn sol_vault_signer_seeds<'a>(
+        authority: &'a Pubkey,
+        token_mint: &'a Pubkey,
+        bump: &'a u8,
+    ) -> [&'a [u8]; 4] {
+        [
+            crate::constants::SOL_VAULT_SEED,
+            authority.as_ref(),
+            token_mint.as_ref(),
+            std::slice::from_ref(bump),
+        ]
+    }
+
+    /// Signer seeds for a vesting schedule PDA, for use as `&[&seeds[..]]` in a CPI
+    pub fn vesting_schedule_signer_seeds<'a>(
+        launch_state: &'a Pubkey,
+        beneficiary: &'a Pubkey,
+        bump: &'a u8,
+    ) -> [&'a [u8]; 4] {
+        [
+            crate::constants::VESTING_SCHEDULE_SEED,
+            launch_state.as_ref(),
+            beneficiary.as_ref(),
+            std::slice::from_ref(bump),
+        ]
+    }
+
+    /// Signer seeds for the liquidity pool PDA, for use as `&[&seeds[..]]` in a CPI
+    pub fn liquidity_pool_signer_seeds<'a>(
+        mint_a: &'a Pubkey,
+        mint_b: &'a Pubkey,
+        bump: &'a u8,
+    ) -> [&'a [u8]; 4] {
+        [
+            crate::constants::LIQUIDITY_POOL_SEED,
+            mint_a.as_ref(),
+            mint_b.as_ref(),
+            std::slice::from_ref(bump),
+        ]
+    }
 }
 
 /// Mathematical utility functions for safe calculations
@@ -152,6 +215,51 @@ pub mod math_utils {
         Ok(current_price.try_into().map_err(|_| crate::ErrorCode::Overflow)?)
     }
 
+    /// Raise a 1e9 fixed-point `base` to an integer power using exponentiation by squaring.
+    /// Each step is rounded down, and results that do not fit in a `u64` are rejected.
+    pub fn pow_fixed(base_1e9: u64, exp: u64) -> Result<u64> {
+        let one = FIXED_POINT_ONE as u128;
+        let mut result = one;
+        let mut base = base_1e9 as u128;
+        let mut exp = exp;
+
+        while exp > 0 {
+            if exp & 1 == 1 {
+                result = safe_div_u128(safe_mul_u128(result, base)?, one)?;
+                if result > u64::MAX as u128 {
+                    return err!(crate::ErrorCode::Overflow);
+                }
+            }
+            exp >>= 1;
+            if exp > 0 {
+                base = safe_div_u128(safe_mul_u128(base, base)?, one)?;
+                if base > u64::MAX as u128 {
+                    return err!(crate::ErrorCode::Overflow);
+                }
+            }
+        }
+
+        Ok(result as u64)
+    }
+
+    /// Integer square root, rounded down, by Newton's method. Deterministic on every
+    /// validator, unlike `f64::sqrt`.
+    pub fn isqrt_u128(n: u128) -> u128 {
+        if n < 2 {
+            return n;
+        }
+
+        // Start from a power of two at or above the root so the iteration decreases monotonically
+        let mut x = 1u128 << (128 - n.leading_zeros()).div_ceil(2);
+        loop {
+            let next = (x + n / x) / 2;
+            if next >= x {
+                return x;
+            }
+            x = next;
+        }
+    }
+
     /// Calculate tokens to mint based on SOL amount and price
     pub fn calculate_tokens_to_mint(
         sol_amount: u64,
@@ -166,6 +274,117 @@ pub mod math_utils {
 
         Ok(tokens_to_mint.try_into().map_err(|_| crate::ErrorCode::Overflow)?)
     }
+
+    /// Price curves understood by [`cost_for_tokens`] and [`tokens_for_sol`]. Prices are in
+    /// lamports per whole token (`TOKEN_BASE_UNITS` base units).
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub enum CurveModel {
+        /// price = initial_price + slope * tokens_sold
+        Linear,
+        /// price = initial_price * slope^whole_tokens_sold, with slope in 1e9 fixed point
+        Exponential,
+        /// price = initial_price
+        Fixed,
+    }
+
+    /// Cost of buying `tokens_to_buy` base units when `tokens_sold` have already sold: the area
+    /// under the price curve, each base unit paying the price in effect when it sells. Rounded up
+    /// so a buyer never underpays.
+    pub fn cost_for_tokens(
+        initial_price: u64,
+        slope: u64,
+        tokens_sold: u64,
+        tokens_to_buy: u64,
+        model: CurveModel,
+    ) -> Result<u64> {
+        let initial = initial_price as u128;
+        let n = tokens_to_buy as u128;
+
+        // Cost scaled by TOKEN_BASE_UNITS, i.e. the sum of every base unit's price
+        let scaled_cost = match model {
+            CurveModel::Fixed => safe_mul_u128(initial, n)?,
+            CurveModel::Linear => {
+                // sum over i < n of (initial + slope * (tokens_sold + i))
+                let steps = safe_add_u128(
+                    safe_mul_u128(n, tokens_sold as u128)?,
+                    safe_mul_u128(n, n.saturating_sub(1))? / 2,
+                )?;
+                safe_add_u128(safe_mul_u128(initial, n)?, safe_mul_u128(slope as u128, steps)?)?
+            }
+            CurveModel::Exponential => exponential_scaled_cost(initial_price, slope, tokens_sold, tokens_to_buy)?,
+        };
+
+        let base_units = TOKEN_BASE_UNITS as u128;
+        let cost = safe_div_u128(safe_add_u128(scaled_cost, base_units - 1)?, base_units)?;
+        Ok(cost.try_into().map_err(|_| crate::ErrorCode::Overflow)?)
+    }
+
+    /// Exponential curve cost scaled by `TOKEN_BASE_UNITS`: a partial step at the current
+    /// price, whole steps summed as a geometric series, then a partial step at the final price.
+    fn exponential_scaled_cost(initial_price: u64, slope: u64, tokens_sold: u64, tokens_to_buy: u64) -> Result<u128> {
+        let base_units = TOKEN_BASE_UNITS;
+        let end = tokens_sold.checked_add(tokens_to_buy).ok_or(crate::ErrorCode::Overflow)?;
+        let first_step = tokens_sold / base_units;
+        let last_step = end / base_units;
+        let step_price = |step: u64| -> Result<u128> {
+            let growth = pow_fixed(slope, step)?;
+            safe_div_u128(safe_mul_u128(initial_price as u128, growth as u128)?, FIXED_POINT_ONE as u128)
+        };
+
+        if first_step == last_step {
+            return safe_mul_u128(step_price(first_step)?, tokens_to_buy as u128);
+        }
+
+        let head_units = (first_step + 1) * base_units - tokens_sold;
+        let head = safe_mul_u128(step_price(first_step)?, head_units as u128)?;
+        let tail = safe_mul_u128(step_price(last_step)?, (end - last_step * base_units) as u128)?;
+
+        // Whole steps first_step + 1 .. last_step each sell TOKEN_BASE_UNITS at initial * slope^k
+        let (from, to) = (first_step + 1, last_step);
+        let growth_sum = if slope as u128 > FIXED_POINT_ONE as u128 {
+            let span = safe_sub_u128(pow_fixed(slope, to)? as u128, pow_fixed(slope, from)? as u128)?;
+            safe_div_u128(safe_mul_u128(span, FIXED_POINT_ONE as u128)?, slope as u128 - FIXED_POINT_ONE as u128)?
+        } else {
+            safe_mul_u128((to - from) as u128, FIXED_POINT_ONE as u128)?
+        };
+        let whole_steps = safe_mul_u128(initial_price as u128, growth_sum)?;
+
+        safe_add_u128(safe_add_u128(head, whole_steps)?, tail)
+    }
+
+    /// Most base units `sol_amount` buys along the curve, the inverse of [`cost_for_tokens`].
+    /// Fixed prices are solved in closed form; rising curves are searched between zero and what
+    /// the spot price alone would buy.
+    pub fn tokens_for_sol(
+        initial_price: u64,
+        slope: u64,
+        tokens_sold: u64,
+        sol_amount: u64,
+        model: CurveModel,
+    ) -> Result<u64> {
+        let spot_price = match model {
+            CurveModel::Fixed => return calculate_tokens_to_mint(sol_amount, initial_price),
+            CurveModel::Linear => calculate_bonding_curve_price(initial_price, slope, tokens_sold)?,
+            CurveModel::Exponential => {
+                let growth = pow_fixed(slope, tokens_sold / TOKEN_BASE_UNITS)?;
+                let price = safe_mul_u128(initial_price as u128, growth as u128)? / FIXED_POINT_ONE as u128;
+                price.try_into().map_err(|_| crate::ErrorCode::Overflow)?
+            }
+        };
+
+        // Prices only rise along the curve, so the spot price bounds the answer from above
+        let mut low = 0u64;
+        let mut high = calculate_tokens_to_mint(sol_amount, spot_price)?;
+        while low < high {
+            let mid = low + (high - low).div_ceil(2);
+            if matches!(cost_for_tokens(initial_price, slope, tokens_sold, mid, model), Ok(cost) if cost <= sol_amount) {
+                low = mid;
+            } else {
+                high = mid - 1;
+            }
+        }
+        Ok(low)
+    }
 }
 
 /// Time utility functions
@@ -195,6 +414,38 @@ pub mod time_utils {
     }
 }
 
+/// Merkle tree helpers for roots computed off-chain
+///
+/// Leaves and interior nodes are domain separated (`0x00` / `0x01` prefixes) so a node can never
+/// be passed off as a leaf, and sibling pairs are hashed in sorted order so proofs carry no
+/// left/right flags.
+pub mod merkle_utils {
+    use anchor_lang::solana_program::hash::hashv;
+
+    const LEAF_PREFIX: &[u8] = &[0];
+    const NODE_PREFIX: &[u8] = &[1];
+
+    /// Hash the fields of a leaf
+    pub fn hash_leaf(fields: &[&[u8]]) -> [u8; 32] {
+        let mut parts = Vec::with_capacity(fields.len() + 1);
+        parts.push(LEAF_PREFIX);
+        parts.extend_from_slice(fields);
+        hashv(&parts).to_bytes()
+    }
+
+    /// Hash two sibling nodes into their parent
+    pub fn hash_pair(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+        let (first, second) = if a <= b { (a, b) } else { (b, a) };
+        hashv(&[NODE_PREFIX, first, second]).to_bytes()
+    }
+
+    /// Check that `leaf` is included in the tree with `root`, given its sibling hashes bottom-up
+    pub fn verify_proof(proof: &[[u8; 32]], root: &[u8; 32], leaf: [u8; 32]) -> bool {
+        let computed = proof.iter().fold(leaf, |node, sibling| hash_pair(&node, sibling));
+        &computed == root
+    }
+}
+
 /// Error codes for shared utilities
 #[error_code]
 pub enum ErrorCode {
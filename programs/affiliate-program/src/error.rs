@@ -56,6 +56,12 @@ pub enum AffiliateError {
     ParentAffiliateNotFound,
     #[msg("Circular referral relationship detected.")]
     CircularReferral,
+    #[msg("Parent affiliate has reached the maximum number of descendants.")]
+    DescendantLimitReached,
+    #[msg("An affiliate that has registered sub-affiliates cannot be closed.")]
+    AffiliateHasDescendants,
+    #[msg("Affiliate batch is empty, too large, or its accounts don't match its entries.")]
+    InvalidAffiliateBatch,
 
     // Time-related errors
     #[msg("Invalid timestamp provided.")]
@@ -15,11 +15,18 @@
 //! ## Key Instructions
 //!
 //! - [`register_affiliate`]: Creates affiliate accounts with configurable parameters
+//! - [`register_affiliates_batch`]: Campaign onboarding of many affiliates, paid by one payer
 //! - [`set_commission_rate`]: Basic rate setting (legacy compatibility)
+//! - [`set_auto_compound`]: Opt in to compounding commissions into launch purchases
+//! - [`close_affiliate`]: Wind down an affiliate and reclaim its accounts' rent
+//! - [`propose_affiliate_transfer`] / [`accept_affiliate_transfer`]: Move an affiliate to a new wallet
 //! - [`update_commission_rate_ai`]: AI-optimized rate updates with validation
 //! - [`process_commission`]: CPI-only commission processing for token launches
 //! - [`update_analytics`]: Performance data updates for AI analysis
 //! - [`get_ai_suggested_rate`]: Query current AI-suggested rates
+//! - [`record_launch_activity`]: CPI-only reporting of launches and SOL raised by the factory
+//! - [`get_ecosystem_stats`]: Read ecosystem-wide launch, SOL and affiliate totals
+//! - [`health_check`]: Logs and returns the program's build version
 //!
 //! ## AI Integration
 //!
@@ -32,10 +39,12 @@
 //!
 //! - Rate caps and minimum bounds to prevent abuse
 //! - Time-based restrictions on rate updates
+//! - A cap on sub-affiliates per parent to bound sybil referral trees
 //! - Authority validation for all sensitive operations
 //! - Comprehensive error handling with custom error codes
 
 use anchor_lang::prelude::*;
+use anchor_lang::system_program;
 use anchor_spl::token::{self, Token, MintTo, TokenAccount};
 use genesis_common::constants::*;
 use genesis_common::utils::*;
@@ -48,6 +57,10 @@ use error::*;
 
 declare_id!("Aff1aTe111111111111111111111111111111111111"); // 32-byte base58 placeholder for local tests
 
+/// Crate version of this build, logged by `health_check` so operators can tell which build
+/// processed a transaction.
+pub const PROGRAM_VERSION: &str = env!("CARGO_PKG_VERSION");
+
 /// Enhanced instruction arguments
 #[derive(AnchorSerialize, AnchorDeserialize)]
 pub struct RegisterAffiliateArgs {
@@ -58,6 +71,13 @@ pub struct RegisterAffiliateArgs {
     pub min_commission_rate_bps: u16,
 }
 
+/// One affiliate to register in a `register_affiliates_batch` campaign
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct AffiliateRegistration {
+    pub affiliate: Pubkey,
+    pub args: RegisterAffiliateArgs,
+}
+
 #[derive(AnchorSerialize, AnchorDeserialize)]
 pub struct UpdateCommissionRateArgs {
     pub new_rate_bps: u16,
@@ -77,58 +97,153 @@ pub mod affiliate_program {
     /// Creates an `AffiliateInfo` account for the signer, registering them as an affiliate with enhanced features.
     pub fn register_affiliate(ctx: Context<RegisterAffiliate>, args: RegisterAffiliateArgs) -> Result<()> {
         let current_time = Clock::get()?.unix_timestamp;
-        let info = &mut ctx.accounts.affiliate_info;
+        let affiliate = ctx.accounts.affiliate.key();
+        AffiliateInfo::validate_registration(&affiliate, &args)?;
 
-        // Validate referral level
-        require!(args.referral_level > 0 && args.referral_level <= 5, AffiliateError::InvalidReferralLevel);
-
-        // Validate parent affiliate if provided
+        // Validate parent affiliate if provided, and count the new affiliate against its descendant cap
         if let Some(parent) = args.parent_affiliate {
-            require!(parent != ctx.accounts.affiliate.key(), AffiliateError::CircularReferral);
-            // Additional validation would check if parent exists
+            let parent_info = ctx.accounts.parent_affiliate_info.as_mut()
+                .ok_or(AffiliateError::ParentAffiliateNotFound)?;
+            require!(parent_info.seed_key == parent, AffiliateError::ParentAffiliateNotFound);
+            parent_info.record_descendant()?;
         }
 
-        // Initialize basic fields
-        info.affiliate_key = ctx.accounts.affiliate.key();
-        info.total_referred_volume = 0;
-        info.commission_rate_bps = 1000; // Default to 10% commission
-
-        // Initialize performance analytics
-        info.performance_tier = PerformanceTier::Bronze;
-        info.monthly_referred_volume = 0;
-        info.quarterly_referred_volume = 0;
-        info.yearly_referred_volume = 0;
-        info.successful_referrals = 0;
-        info.total_clicks = 0;
-        info.conversion_rate_bps = 0;
-        info.performance_score = 0;
-
-        // Initialize AI optimization settings
-        info.rate_caps_enabled = args.rate_caps_enabled;
-        info.max_commission_rate_bps = if args.rate_caps_enabled { args.max_commission_rate_bps } else { MAX_RATE_BPS };
-        info.min_commission_rate_bps = if args.rate_caps_enabled { args.min_commission_rate_bps } else { MIN_RATE_BPS };
-        info.ai_optimization_enabled = true;
-
-        // Initialize multi-level referral tracking
-        info.referral_level = args.referral_level;
-        info.parent_affiliate = args.parent_affiliate;
-        info.total_descendants = 0;
-        info.active_descendants = 0;
-
-        // Initialize time tracking
-        info.registration_time = current_time;
-        info.last_activity_time = current_time;
-        info.last_rate_update_time = current_time;
-        info.tier_upgrade_time = current_time;
-
-        // Initialize monthly volume history
-        info.monthly_volume_history = [0; 12];
+        ctx.accounts.affiliate_info.set_inner(AffiliateInfo::new(affiliate, &args, current_time));
+        ctx.accounts.ecosystem_stats.record_affiliate()?;
 
+        let info = &ctx.accounts.affiliate_info;
         msg!("Enhanced affiliate {} registered with tier: {:?}, level: {}",
              info.affiliate_key, info.performance_tier, info.referral_level);
         Ok(())
     }
 
+    /// Registers up to `MAX_BATCH_SIZE` affiliates at once for a marketing campaign, with the
+    /// campaign payer funding every account's rent. Each account is still owned by its affiliate.
+    ///
+    /// For each entry of `registrations`, in order, `remaining_accounts` holds the affiliate's
+    /// `AffiliateInfo` PDA followed by the affiliate's wallet, which must sign so nobody is
+    /// enrolled without consent. Affiliates that are already registered are skipped. Entries with
+    /// a parent must all name the affiliate passed as `parent_affiliate_info`.
+    pub fn register_affiliates_batch<'info>(
+        ctx: Context<'_, '_, '_, 'info, RegisterAffiliatesBatch<'info>>,
+        registrations: Vec<AffiliateRegistration>,
+    ) -> Result<()> {
+        require!(!registrations.is_empty() && registrations.len() <= MAX_BATCH_SIZE, AffiliateError::InvalidAffiliateBatch);
+        require!(ctx.remaining_accounts.len() == registrations.len() * 2, AffiliateError::InvalidAffiliateBatch);
+
+        let current_time = Clock::get()?.unix_timestamp;
+        let space = AffiliateInfo::LEN + 8;
+        let lamports = Rent::get()?.minimum_balance(space);
+        let mut registered: u32 = 0;
+
+        for (registration, pair) in registrations.iter().zip(ctx.remaining_accounts.chunks_exact(2)) {
+            let (info_account, affiliate_account) = (&pair[0], &pair[1]);
+            let affiliate = registration.affiliate;
+            require_keys_eq!(affiliate_account.key(), affiliate, AffiliateError::InvalidAffiliateBatch);
+            require!(affiliate_account.is_signer, AffiliateError::AuthorityMismatch);
+            AffiliateInfo::validate_registration(&affiliate, &registration.args)?;
+
+            let (expected_info, bump) = Pubkey::find_program_address(
+                &[AFFILIATE_INFO_SEED, affiliate.as_ref()],
+                ctx.program_id,
+            );
+            require_keys_eq!(info_account.key(), expected_info, AffiliateError::InvalidAffiliateBatch);
+            if !info_account.data_is_empty() {
+                continue;
+            }
+
+            if let Some(parent) = registration.args.parent_affiliate {
+                let parent_info = ctx.accounts.parent_affiliate_info.as_mut()
+                    .ok_or(AffiliateError::ParentAffiliateNotFound)?;
+                require!(parent_info.seed_key == parent, AffiliateError::ParentAffiliateNotFound);
+                parent_info.record_descendant()?;
+            }
+
+            let seeds = &[AFFILIATE_INFO_SEED, affiliate.as_ref(), &[bump]];
+            let system_program = ctx.accounts.system_program.to_account_info();
+            let current_lamports = info_account.lamports();
+            if current_lamports == 0 {
+                system_program::create_account(
+                    CpiContext::new_with_signer(
+                        system_program,
+                        system_program::CreateAccount {
+                            from: ctx.accounts.payer.to_account_info(),
+                            to: info_account.clone(),
+                        },
+                        &[&seeds[..]],
+                    ),
+                    lamports,
+                    space as u64,
+                    ctx.program_id,
+                )?;
+            } else {
+                // Someone sent lamports to the PDA ahead of time, which makes `create_account`
+                // fail; top it up to rent exemption, then allocate and assign it instead
+                let top_up = lamports.saturating_sub(current_lamports);
+                if top_up > 0 {
+                    system_program::transfer(
+                        CpiContext::new(
+                            system_program.clone(),
+                            system_program::Transfer {
+                                from: ctx.accounts.payer.to_account_info(),
+                                to: info_account.clone(),
+                            },
+                        ),
+                        top_up,
+                    )?;
+                }
+                system_program::allocate(
+                    CpiContext::new_with_signer(
+                        system_program.clone(),
+                        system_program::Allocate { account_to_allocate: info_account.clone() },
+                        &[&seeds[..]],
+                    ),
+                    space as u64,
+                )?;
+                system_program::assign(
+                    CpiContext::new_with_signer(
+                        system_program,
+                        system_program::Assign { account_to_assign: info_account.clone() },
+                        &[&seeds[..]],
+                    ),
+                    ctx.program_id,
+                )?;
+            }
+
+            let info = AffiliateInfo::new(affiliate, &registration.args, current_time);
+            info.try_serialize(&mut &mut info_account.try_borrow_mut_data()?[..])?;
+            ctx.accounts.ecosystem_stats.record_affiliate()?;
+            registered += 1;
+        }
+
+        msg!("Registered {} affiliates", registered);
+        Ok(())
+    }
+
+    /// Records launches and SOL raised in the ecosystem-wide stats.
+    /// Called via CPI by the `factory-program`, signed by the launch's `launch_state` PDA.
+    pub fn record_launch_activity(ctx: Context<RecordLaunchActivity>, new_launches: u64, sol_raised: u64) -> Result<()> {
+        ctx.accounts.ecosystem_stats.record_launch_activity(new_launches, sol_raised)
+    }
+
+    /// Read-only view of the ecosystem-wide stats, returned via return data.
+    pub fn get_ecosystem_stats(ctx: Context<GetEcosystemStats>) -> Result<EcosystemStats> {
+        let stats = &ctx.accounts.ecosystem_stats;
+        msg!("Ecosystem stats: {} launches, {} lamports raised, {} affiliates",
+             stats.total_launches, stats.total_sol_raised, stats.total_affiliates);
+        Ok(EcosystemStats {
+            total_launches: stats.total_launches,
+            total_sol_raised: stats.total_sol_raised,
+            total_affiliates: stats.total_affiliates,
+        })
+    }
+
+    /// Logs and returns this program's build version via return data.
+    pub fn health_check(ctx: Context<HealthCheck>) -> Result<String> {
+        msg!("affiliate-program v{} at slot {}", PROGRAM_VERSION, ctx.accounts.clock.slot);
+        Ok(PROGRAM_VERSION.to_string())
+    }
+
     /// Allows an affiliate to set their own commission rate.
     /// In a production system, this would likely be restricted to a program admin.
     /// # Parameters
@@ -140,13 +255,72 @@ pub mod affiliate_program {
         Ok(())
     }
 
+    /// Opts the affiliate in or out of compounding commission tokens into launch purchases
+    /// through the factory's `compound_commission` instruction.
+    pub fn set_auto_compound(ctx: Context<SetAutoCompound>, enabled: bool) -> Result<()> {
+        ctx.accounts.affiliate_info.auto_compound_enabled = enabled;
+        msg!("Auto-compounding for {} set to {}", ctx.accounts.affiliate_key.key(), enabled);
+        Ok(())
+    }
+
+    /// Closes the signer's `AffiliateInfo` and, if present, `AffiliateAnalytics` accounts,
+    /// returning their rent to the affiliate. The parent's active descendant count and the
+    /// ecosystem's affiliate count are decremented. Affiliates that have registered
+    /// sub-affiliates can't close, so parents always outlive their descendants.
+    /// Commissions are minted as they are earned, so there is never a pending balance to settle.
+    pub fn close_affiliate(ctx: Context<CloseAffiliate>) -> Result<()> {
+        ctx.accounts.affiliate_info.check_closable()?;
+
+        if let Some(parent) = ctx.accounts.affiliate_info.parent_affiliate {
+            let parent_info = ctx.accounts.parent_affiliate_info.as_mut()
+                .ok_or(AffiliateError::ParentAffiliateNotFound)?;
+            require!(parent_info.seed_key == parent, AffiliateError::ParentAffiliateNotFound);
+            parent_info.record_descendant_closed();
+        }
+        ctx.accounts.ecosystem_stats.record_affiliate_closed();
+
+        msg!("Affiliate {} closed", ctx.accounts.affiliate_key.key());
+        Ok(())
+    }
+
+    /// Proposes `new_key` as the wallet that owns this affiliate and receives its rewards
+    /// (affiliate only). The current wallet stays in charge until `new_key` calls
+    /// `accept_affiliate_transfer`; proposing again replaces the pending key.
+    ///
+    /// The account keeps its address, derived from `seed_key`, the wallet it was registered
+    /// with. Clients and sub-affiliates keep addressing it, and naming it as a parent, by that key.
+    pub fn propose_affiliate_transfer(ctx: Context<ProposeAffiliateTransfer>, new_key: Pubkey) -> Result<()> {
+        ctx.accounts.affiliate_info.pending_authority = Some(new_key);
+        msg!("Transfer of affiliate {} to {} proposed", ctx.accounts.affiliate_info.seed_key, new_key);
+        Ok(())
+    }
+
+    /// Completes an affiliate transfer; must be signed by the proposed wallet.
+    pub fn accept_affiliate_transfer(ctx: Context<AcceptAffiliateTransfer>) -> Result<()> {
+        let new_key = ctx.accounts.new_affiliate_key.key();
+        ctx.accounts.affiliate_info.accept_transfer(&new_key)?;
+        msg!("Affiliate {} transferred to {}", ctx.accounts.affiliate_info.seed_key, new_key);
+        Ok(())
+    }
+
     /// Processes a commission payment for an affiliate.
     /// This instruction is designed to be called via CPI from another program (e.g., `factory-program`).
-    /// It calculates the commission and mints the corresponding tokens to the affiliate.
+    /// It calculates the commission and mints the corresponding tokens to the affiliate, and a
+    /// shrinking share of it to each ancestor up the referral chain, at most `referral_level - 1`
+    /// levels. Returns the total commission tokens minted via return data.
+    ///
+    /// Ancestors are passed in `remaining_accounts` as `(AffiliateInfo, token account)` pairs,
+    /// parent first. The chain stops early at the first missing ancestor, and an affiliate
+    /// repeated in a malformed tree is paid only once.
     /// # Parameters
     /// - `purchased_tokens`: The total amount of tokens the referred user purchased.
-    pub fn process_commission(ctx: Context<ProcessCommission>, purchased_tokens: u64) -> Result<()> {
+    pub fn process_commission<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ProcessCommission<'info>>,
+        purchased_tokens: u64,
+    ) -> Result<u64> {
+        let current_time = Clock::get()?.unix_timestamp;
         let affiliate_info = &mut ctx.accounts.affiliate_info;
+        affiliate_info.roll_periods(current_time);
         let commission_bps = affiliate_info.commission_rate_bps as u128;
 
         // Calculate commission amount: (purchased_tokens * rate) / 10000
@@ -158,17 +332,53 @@ pub mod affiliate_program {
         // Mint commission tokens to the affiliate.
         // The mint authority is the `launch_state` PDA from the factory program,
         // which is passed in and must sign this CPI call.
-        token::mint_to(
-            CpiContext::new(
-                ctx.accounts.token_program.to_account_info(),
-                MintTo {
-                    mint: ctx.accounts.token_mint.to_account_info(),
-                    to: ctx.accounts.affiliate_token_account.to_account_info(),
-                    authority: ctx.accounts.launch_state.to_account_info(),
-                }
-            ),
-            commission_amount
-        )?;
+        let mint_commission = |to: AccountInfo<'info>, amount: u64| {
+            token::mint_to(
+                CpiContext::new(
+                    ctx.accounts.token_program.to_account_info(),
+                    MintTo {
+                        mint: ctx.accounts.token_mint.to_account_info(),
+                        to,
+                        authority: ctx.accounts.launch_state.to_account_info(),
+                    }
+                ),
+                amount
+            )
+        };
+        mint_commission(ctx.accounts.affiliate_token_account.to_account_info(), commission_amount)?;
+
+        // Walk up the referral chain through the ancestor accounts that were passed in
+        let mut chain = vec![affiliate_info.seed_key];
+        let mut ancestor_token_accounts = Vec::new();
+        let mut next_parent = affiliate_info.parent_affiliate;
+        for pair in ctx.remaining_accounts.chunks_exact(2).take(affiliate_info.max_ancestor_levels()) {
+            let Some(parent) = next_parent else { break };
+            let (info_account, token_account) = (&pair[0], &pair[1]);
+            let (expected_info, _) = Pubkey::find_program_address(&[AFFILIATE_INFO_SEED, parent.as_ref()], ctx.program_id);
+            require_keys_eq!(info_account.key(), expected_info, AffiliateError::ParentAffiliateNotFound);
+            if info_account.data_is_empty() {
+                break;
+            }
+            let parent_info = Account::<AffiliateInfo>::try_from(info_account)?;
+            let parent_tokens = Account::<TokenAccount>::try_from(token_account)?;
+            require!(parent_tokens.owner == parent_info.affiliate_key && parent_tokens.mint == ctx.accounts.token_mint.key(),
+                    AffiliateError::AuthorityMismatch);
+
+            chain.push(parent);
+            ancestor_token_accounts.push(token_account.clone());
+            next_parent = parent_info.parent_affiliate;
+        }
+
+        let mut total_minted = commission_amount;
+        for level in AffiliateInfo::distinct_commission_levels(&chain).into_iter().skip(1) {
+            let ancestor_amount = AffiliateInfo::ancestor_commission(commission_amount, level);
+            if ancestor_amount == 0 {
+                break;
+            }
+            mint_commission(ancestor_token_accounts[level - 1].clone(), ancestor_amount)?;
+            total_minted = total_minted.checked_add(ancestor_amount).ok_or(AffiliateError::Overflow)?;
+            msg!("Paid level {} commission of {} tokens to ancestor {}", level + 1, ancestor_amount, chain[level]);
+        }
 
         // Update the affiliate's lifetime referral volume.
         affiliate_info.total_referred_volume = affiliate_info.total_referred_volume
@@ -178,19 +388,17 @@ pub mod affiliate_program {
         msg!("Processed commission of {} tokens for affiliate {}", commission_amount, affiliate_info.affiliate_key);
 
         // Update analytics
-        affiliate_info.monthly_referred_volume = affiliate_info.monthly_referred_volume
-            .checked_add(purchased_tokens)
-            .ok_or(AffiliateError::Overflow)?;
+        affiliate_info.record_period_volume(purchased_tokens)?;
         affiliate_info.successful_referrals = affiliate_info.successful_referrals
             .checked_add(1)
             .ok_or(AffiliateError::Overflow)?;
-        affiliate_info.last_activity_time = Clock::get()?.unix_timestamp;
+        affiliate_info.last_activity_time = current_time;
 
         // Recalculate performance metrics
         affiliate_info.calculate_performance_tier()?;
-        affiliate_info.update_performance_score()?;
+        affiliate_info.update_performance_score(current_time)?;
 
-        Ok(())
+        Ok(total_minted)
     }
 
     /// AI-optimized commission rate update with validation
@@ -224,11 +432,11 @@ pub mod affiliate_program {
         let current_time = Clock::get()?.unix_timestamp;
 
         // Update daily stats
-        analytics.add_daily_stats(args.volume, args.clicks);
-        analytics.last_update = current_time;
+        analytics.add_daily_stats(args.volume, args.clicks, current_time)?;
 
         // Update affiliate info with aggregated data
         let affiliate_info = &mut ctx.accounts.affiliate_info;
+        affiliate_info.roll_periods(current_time);
         affiliate_info.total_referred_volume = affiliate_info.total_referred_volume
             .checked_add(args.volume)
             .ok_or(AffiliateError::Overflow)?;
@@ -243,7 +451,7 @@ pub mod affiliate_program {
 
         // Update performance metrics
         affiliate_info.calculate_performance_tier()?;
-        affiliate_info.update_performance_score()?;
+        affiliate_info.update_performance_score(current_time)?;
 
         msg!("Analytics updated for affiliate {}", affiliate_info.affiliate_key);
         Ok(())
@@ -290,11 +498,85 @@ pub struct RegisterAffiliate<'info> {
         bump
     )]
     pub affiliate_info: Account<'info, AffiliateInfo>,
+    #[account(
+        init_if_needed,
+        payer = affiliate,
+        space = EcosystemStats::LEN + 8,
+        seeds = [ECOSYSTEM_STATS_SEED.as_ref()],
+        bump
+    )]
+    pub ecosystem_stats: Account<'info, EcosystemStats>,
+    /// Required when `parent_affiliate` is set; its descendant counters are updated.
+    #[account(
+        mut,
+        seeds = [AFFILIATE_INFO_SEED.as_ref(), parent_affiliate_info.seed_key.as_ref()],
+        bump
+    )]
+    pub parent_affiliate_info: Option<Account<'info, AffiliateInfo>>,
     #[account(mut)]
     pub affiliate: Signer<'info>,
     pub system_program: Program<'info, System>,
 }
 
+#[derive(Accounts)]
+pub struct RegisterAffiliatesBatch<'info> {
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = EcosystemStats::LEN + 8,
+        seeds = [ECOSYSTEM_STATS_SEED.as_ref()],
+        bump
+    )]
+    pub ecosystem_stats: Account<'info, EcosystemStats>,
+    /// Required when any registration sets `parent_affiliate`; its descendant counters are updated.
+    #[account(
+        mut,
+        seeds = [AFFILIATE_INFO_SEED.as_ref(), parent_affiliate_info.seed_key.as_ref()],
+        bump
+    )]
+    pub parent_affiliate_info: Option<Account<'info, AffiliateInfo>>,
+    /// Campaign payer funding every new account's rent
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(new_launches: u64, sol_raised: u64)]
+pub struct RecordLaunchActivity<'info> {
+    /// CHECK: A `launch_state` PDA of the `factory-program`. Only the factory can sign for
+    /// accounts it owns, so the owner check plus signature authenticates the caller.
+    #[account(signer, owner = FACTORY_PROGRAM_ID @ AffiliateError::AuthorityMismatch)]
+    pub launch_state: AccountInfo<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = EcosystemStats::LEN + 8,
+        seeds = [ECOSYSTEM_STATS_SEED.as_ref()],
+        bump
+    )]
+    pub ecosystem_stats: Account<'info, EcosystemStats>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct HealthCheck<'info> {
+    pub clock: Sysvar<'info, Clock>,
+}
+
+#[derive(Accounts)]
+pub struct GetEcosystemStats<'info> {
+    #[account(
+        seeds = [ECOSYSTEM_STATS_SEED.as_ref()],
+        bump
+    )]
+    pub ecosystem_stats: Account<'info, EcosystemStats>,
+}
+
 #[derive(Accounts)]
 pub struct SetCommissionRate<'info> {
     #[account(
@@ -308,6 +590,79 @@ pub struct SetCommissionRate<'info> {
     pub affiliate_key: Signer<'info>,
 }
 
+#[derive(Accounts)]
+pub struct SetAutoCompound<'info> {
+    #[account(
+        mut,
+        has_one = affiliate_key @ AffiliateError::AuthorityMismatch
+    )]
+    pub affiliate_info: Account<'info, AffiliateInfo>,
+
+    pub affiliate_key: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CloseAffiliate<'info> {
+    #[account(
+        mut,
+        seeds = [AFFILIATE_INFO_SEED.as_ref(), affiliate_info.seed_key.as_ref()],
+        bump,
+        has_one = affiliate_key @ AffiliateError::AuthorityMismatch,
+        close = affiliate_key
+    )]
+    pub affiliate_info: Account<'info, AffiliateInfo>,
+
+    /// The affiliate's analytics, if `update_analytics` ever created them.
+    #[account(
+        mut,
+        seeds = [AFFILIATE_ANALYTICS_SEED.as_ref(), affiliate_info.seed_key.as_ref()],
+        bump,
+        close = affiliate_key
+    )]
+    pub analytics: Option<Account<'info, AffiliateAnalytics>>,
+
+    /// Required when the affiliate has a parent; its active descendant count is decremented.
+    #[account(
+        mut,
+        seeds = [AFFILIATE_INFO_SEED.as_ref(), parent_affiliate_info.seed_key.as_ref()],
+        bump
+    )]
+    pub parent_affiliate_info: Option<Account<'info, AffiliateInfo>>,
+
+    #[account(
+        mut,
+        seeds = [ECOSYSTEM_STATS_SEED.as_ref()],
+        bump
+    )]
+    pub ecosystem_stats: Account<'info, EcosystemStats>,
+
+    #[account(mut)]
+    pub affiliate_key: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ProposeAffiliateTransfer<'info> {
+    #[account(
+        mut,
+        has_one = affiliate_key @ AffiliateError::AuthorityMismatch
+    )]
+    pub affiliate_info: Account<'info, AffiliateInfo>,
+
+    pub affiliate_key: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptAffiliateTransfer<'info> {
+    #[account(
+        mut,
+        seeds = [AFFILIATE_INFO_SEED.as_ref(), affiliate_info.seed_key.as_ref()],
+        bump
+    )]
+    pub affiliate_info: Account<'info, AffiliateInfo>,
+
+    pub new_affiliate_key: Signer<'info>,
+}
+
 #[derive(Accounts)]
 pub struct ProcessCommission<'info> {
     /// CHECK: This is the `launch_state` account from the `factory-program`.
@@ -317,7 +672,7 @@ pub struct ProcessCommission<'info> {
 
     #[account(
         mut,
-        seeds = [AFFILIATE_INFO_SEED.as_ref(), affiliate_info.affiliate_key.as_ref()],
+        seeds = [AFFILIATE_INFO_SEED.as_ref(), affiliate_info.seed_key.as_ref()],
         bump
     )]
     pub affiliate_info: Account<'info, AffiliateInfo>,
@@ -338,9 +693,9 @@ pub struct ProcessCommission<'info> {
 pub struct UpdateCommissionRate<'info> {
     #[account(
         mut,
-        seeds = [AFFILIATE_INFO_SEED.as_ref(), affiliate.key().as_ref()],
+        seeds = [AFFILIATE_INFO_SEED.as_ref(), affiliate_info.seed_key.as_ref()],
         bump,
-        has_one = affiliate_key @ AffiliateError::AuthorityMismatch
+        constraint = affiliate_info.affiliate_key == affiliate.key() @ AffiliateError::AuthorityMismatch
     )]
     pub affiliate_info: Account<'info, AffiliateInfo>,
 
@@ -353,8 +708,9 @@ pub struct UpdateCommissionRate<'info> {
 pub struct UpdateAnalytics<'info> {
     #[account(
         mut,
-        seeds = [AFFILIATE_INFO_SEED.as_ref(), affiliate.key().as_ref()],
-        bump
+        seeds = [AFFILIATE_INFO_SEED.as_ref(), affiliate_info.seed_key.as_ref()],
+        bump,
+        constraint = affiliate_info.affiliate_key == affiliate.key() @ AffiliateError::AuthorityMismatch
     )]
     pub affiliate_info: Account<'info, AffiliateInfo>,
 
@@ -362,7 +718,7 @@ pub struct UpdateAnalytics<'info> {
         init_if_needed,
         payer = affiliate,
         space = AffiliateAnalytics::LEN + 8,
-        seeds = [AFFILIATE_ANALYTICS_SEED.as_ref(), affiliate.key().as_ref()],
+        seeds = [AFFILIATE_ANALYTICS_SEED.as_ref(), affiliate_info.seed_key.as_ref()],
         bump
     )]
     pub analytics: Account<'info, AffiliateAnalytics>,
@@ -376,8 +732,9 @@ pub struct UpdateAnalytics<'info> {
 #[derive(Accounts)]
 pub struct GetAISuggestedRate<'info> {
     #[account(
-        seeds = [AFFILIATE_INFO_SEED.as_ref(), affiliate.key().as_ref()],
-        bump
+        seeds = [AFFILIATE_INFO_SEED.as_ref(), affiliate_info.seed_key.as_ref()],
+        bump,
+        constraint = affiliate_info.affiliate_key == affiliate.key() @ AffiliateError::AuthorityMismatch
     )]
     pub affiliate_info: Account<'info, AffiliateInfo>,
 
@@ -8,6 +8,7 @@
 //!
 //! - [`AffiliateInfo`]: Main state account for individual affiliates with comprehensive analytics
 //! - [`AffiliateAnalytics`]: Daily tracking data for performance analysis
+//! - [`EcosystemStats`]: Singleton of ecosystem-wide launch, SOL and affiliate totals
 //! - [`PerformanceTier`]: Enumeration of affiliate performance levels
 //!
 //! ## Performance System
@@ -27,6 +28,8 @@
 
 use anchor_lang::prelude::*;
 use genesis_common::constants::*;
+use crate::error::AffiliateError;
+use crate::RegisterAffiliateArgs;
 
 /// Performance tier for affiliates based on their performance
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
@@ -46,6 +49,11 @@ pub enum PerformanceTier {
 pub struct AffiliateInfo {
     /// The public key of the affiliate's main wallet. This is the authority.
     pub affiliate_key: Pubkey,
+    /// The wallet the affiliate registered with. The account's PDA is derived from it, so it
+    /// stays fixed when `affiliate_key` moves to a new wallet.
+    pub seed_key: Pubkey,
+    /// Wallet proposed to take over `affiliate_key`, pending its acceptance
+    pub pending_authority: Option<Pubkey>,
     /// The cumulative volume of tokens purchased via this affiliate's referrals.
     pub total_referred_volume: u64,
     /// The commission rate in basis points (bps). For example, 1000 bps is 10.00%.
@@ -79,18 +87,115 @@ pub struct AffiliateInfo {
     pub tier_upgrade_time: i64,
 
     /// Analytics tracking
-    pub monthly_volume_history: [u64; 12], // Last 12 months volume
+    pub monthly_volume_history: [u64; 12], // Volume of the 12 months before the current one, most recent first
+    pub volume_month: i64, // The VOLUME_MONTH_SECONDS month, counted from the Unix epoch, that monthly_referred_volume covers
     pub performance_score: u32, // Calculated performance score
+    /// Start of the latest inactivity period the performance score was decayed for
+    pub last_score_decay_time: i64,
+
+    /// Commission compounding: when set, commission tokens may be reinvested as purchases
+    pub auto_compound_enabled: bool,
 }
 
 impl AffiliateInfo {
     /// The total disk space required for an `AffiliateInfo` account in bytes.
-    pub const LEN: usize = 32 + 8 + 2 + // Basic fields
+    pub const LEN: usize = 32 + 32 + (1 + 32) + 8 + 2 + // Basic fields and ownership transfer
         1 + 8 + 8 + 8 + 4 + 4 + 2 + // Performance analytics
         1 + 2 + 2 + 1 + // AI optimization settings
         1 + (1 + 32) + 4 + 4 + // Multi-level referral
         8 + 8 + 8 + 8 + // Time tracking
-        (8 * 12) + 4; // Analytics (12 months * 8 bytes + score)
+        (8 * 12) + 8 + 4 + 8 + // Analytics (12 months * 8 bytes + current month + score + decay time)
+        1; // Commission compounding
+
+    /// Check registration arguments for `affiliate`: a supported referral level and no
+    /// self-referral
+    pub fn validate_registration(affiliate: &Pubkey, args: &RegisterAffiliateArgs) -> Result<()> {
+        require!(args.referral_level > 0 && args.referral_level <= 5, AffiliateError::InvalidReferralLevel);
+        require!(args.parent_affiliate != Some(*affiliate), AffiliateError::CircularReferral);
+        Ok(())
+    }
+
+    /// A freshly registered affiliate owned by `affiliate`, at the default 10% commission
+    pub fn new(affiliate: Pubkey, args: &RegisterAffiliateArgs, current_time: i64) -> Self {
+        Self {
+            affiliate_key: affiliate,
+            seed_key: affiliate,
+            pending_authority: None,
+            total_referred_volume: 0,
+            commission_rate_bps: 1000, // Default to 10% commission
+
+            // Performance analytics
+            performance_tier: PerformanceTier::Bronze,
+            monthly_referred_volume: 0,
+            quarterly_referred_volume: 0,
+            yearly_referred_volume: 0,
+            successful_referrals: 0,
+            total_clicks: 0,
+            conversion_rate_bps: 0,
+
+            // AI optimization settings
+            rate_caps_enabled: args.rate_caps_enabled,
+            max_commission_rate_bps: if args.rate_caps_enabled { args.max_commission_rate_bps } else { MAX_RATE_BPS },
+            min_commission_rate_bps: if args.rate_caps_enabled { args.min_commission_rate_bps } else { MIN_RATE_BPS },
+            ai_optimization_enabled: true,
+
+            // Multi-level referral tracking
+            referral_level: args.referral_level,
+            parent_affiliate: args.parent_affiliate,
+            total_descendants: 0,
+            active_descendants: 0,
+
+            // Time tracking
+            registration_time: current_time,
+            last_activity_time: current_time,
+            last_rate_update_time: current_time,
+            tier_upgrade_time: current_time,
+
+            // Analytics tracking
+            monthly_volume_history: [0; 12],
+            volume_month: current_time.div_euclid(VOLUME_MONTH_SECONDS),
+            performance_score: 0,
+            last_score_decay_time: current_time,
+
+            // Commission compounding is opt-in
+            auto_compound_enabled: false,
+        }
+    }
+
+    /// Roll the volume windows forward to the month of `current_time`. Each month that has
+    /// passed shifts `monthly_volume_history` by one, with the finished month's volume moved
+    /// into it and empty months for any gap; quarterly and yearly volumes are then rebuilt from
+    /// the current month and the last two or eleven finished ones.
+    pub fn roll_periods(&mut self, current_time: i64) {
+        let month = current_time.div_euclid(VOLUME_MONTH_SECONDS);
+        let months_passed = month - self.volume_month;
+        if months_passed <= 0 {
+            return;
+        }
+
+        let finished = std::mem::take(&mut self.monthly_referred_volume);
+        let shift = months_passed.min(12) as usize;
+        self.monthly_volume_history.rotate_right(shift);
+        self.monthly_volume_history[..shift].fill(0);
+        if months_passed <= 12 {
+            self.monthly_volume_history[shift - 1] = finished;
+        }
+        self.volume_month = month;
+
+        let history_sum = |months: usize| {
+            self.monthly_volume_history[..months].iter().fold(0u64, |sum, &volume| sum.saturating_add(volume))
+        };
+        self.quarterly_referred_volume = history_sum(2);
+        self.yearly_referred_volume = history_sum(11);
+    }
+
+    /// Add referred volume to the current month, quarter and year
+    pub fn record_period_volume(&mut self, volume: u64) -> Result<()> {
+        self.monthly_referred_volume = self.monthly_referred_volume.checked_add(volume).ok_or(AffiliateError::Overflow)?;
+        self.quarterly_referred_volume = self.quarterly_referred_volume.checked_add(volume).ok_or(AffiliateError::Overflow)?;
+        self.yearly_referred_volume = self.yearly_referred_volume.checked_add(volume).ok_or(AffiliateError::Overflow)?;
+        Ok(())
+    }
 
     /// Calculate performance tier based on metrics
     pub fn calculate_performance_tier(&mut self) -> Result<()> {
@@ -107,8 +212,10 @@ impl AffiliateInfo {
         Ok(())
     }
 
-    /// Update performance score
-    pub fn update_performance_score(&mut self) -> Result<()> {
+    /// Update performance score. Every full `PERFORMANCE_DECAY_PERIOD_SECONDS` since the
+    /// affiliate's last activity keeps only `PERFORMANCE_DECAY_RETAIN_BPS` of the score, so
+    /// lifetime totals stop carrying an affiliate that has gone quiet.
+    pub fn update_performance_score(&mut self, current_time: i64) -> Result<()> {
         let volume_score = (self.total_referred_volume / 1_000_000) as u32; // 1M tokens = 1 point
         let conversion_score = (self.conversion_rate_bps / 10) as u32; // 1% conversion = 10 points
         let referral_score = self.successful_referrals / 10; // 10 referrals = 1 point
@@ -119,7 +226,20 @@ impl AffiliateInfo {
             PerformanceTier::Platinum => 5,
         };
 
-        self.performance_score = (volume_score + conversion_score + referral_score) * tier_multiplier;
+        let score = (volume_score + conversion_score + referral_score) * tier_multiplier;
+        let inactive_periods = current_time.saturating_sub(self.last_activity_time).max(0) / PERFORMANCE_DECAY_PERIOD_SECONDS;
+        let mut decayed = score as u64;
+        for _ in 0..inactive_periods {
+            if decayed == 0 {
+                break;
+            }
+            decayed = decayed * PERFORMANCE_DECAY_RETAIN_BPS / BPS_PRECISION;
+        }
+
+        self.performance_score = decayed as u32;
+        if inactive_periods > 0 {
+            self.last_score_decay_time = self.last_activity_time + inactive_periods * PERFORMANCE_DECAY_PERIOD_SECONDS;
+        }
         Ok(())
     }
 
@@ -136,6 +256,70 @@ impl AffiliateInfo {
         Ok(time_since_last_update >= 86400) // 24 hours in seconds
     }
 
+    /// Record a sub-affiliate registering under this affiliate, rejecting it once the
+    /// program-wide `MAX_AFFILIATE_DESCENDANTS` cap is reached
+    pub fn record_descendant(&mut self) -> Result<()> {
+        require!(self.total_descendants < MAX_AFFILIATE_DESCENDANTS, AffiliateError::DescendantLimitReached);
+        self.total_descendants += 1;
+        self.active_descendants = self.active_descendants.saturating_add(1);
+        Ok(())
+    }
+
+    /// Hand the affiliate's rewards to the proposed wallet once it accepts
+    pub fn accept_transfer(&mut self, new_key: &Pubkey) -> Result<()> {
+        require!(self.pending_authority == Some(*new_key), AffiliateError::AuthorityMismatch);
+        self.affiliate_key = *new_key;
+        self.pending_authority = None;
+        Ok(())
+    }
+
+    /// Record a sub-affiliate closing its account. It still counts against the descendant cap,
+    /// but no longer as active.
+    pub fn record_descendant_closed(&mut self) {
+        self.active_descendants = self.active_descendants.saturating_sub(1);
+    }
+
+    /// Ensure the affiliate can close its account. One that has ever registered sub-affiliates
+    /// stays open, so closing and re-registering can't reset its count against the descendant cap.
+    pub fn check_closable(&self) -> Result<()> {
+        require!(self.total_descendants == 0, AffiliateError::AffiliateHasDescendants);
+        Ok(())
+    }
+
+    /// Levels of a referral chain (direct affiliate first, then ancestors) that should be paid.
+    /// An affiliate appearing more than once in a malformed tree is paid only at its first
+    /// (closest) level; later repeats are skipped rather than failing the purchase.
+    pub fn distinct_commission_levels(chain: &[Pubkey]) -> Vec<usize> {
+        chain.iter()
+            .enumerate()
+            .filter(|(level, key)| !chain[..*level].contains(key))
+            .map(|(level, _)| level)
+            .collect()
+    }
+
+    /// Number of ancestors that share this affiliate's commissions: one per referral level
+    /// above it
+    pub fn max_ancestor_levels(&self) -> usize {
+        self.referral_level.saturating_sub(1) as usize
+    }
+
+    /// Commission for the ancestor `level` steps above the direct affiliate, which earned
+    /// `commission_amount`. Each level earns `ANCESTOR_COMMISSION_SHARE_BPS` of the one below.
+    pub fn ancestor_commission(commission_amount: u64, level: usize) -> u64 {
+        let amount = (0..level).fold(commission_amount as u128, |amount, _| {
+            amount * ANCESTOR_COMMISSION_SHARE_BPS as u128 / BPS_PRECISION as u128
+        });
+        amount as u64
+    }
+
+    /// Most a purchase paying this affiliate `commission_amount` can mint: the direct commission
+    /// plus every ancestor's share, as if the full referral chain were passed
+    pub fn max_chain_commission(&self, commission_amount: u64) -> u64 {
+        (1..=self.max_ancestor_levels()).fold(commission_amount, |total, level| {
+            total.saturating_add(Self::ancestor_commission(commission_amount, level))
+        })
+    }
+
     /// Get suggested rate based on performance tier
     pub fn get_suggested_rate(&self) -> u16 {
         let base_rate = match self.performance_tier {
@@ -169,7 +353,7 @@ pub struct AffiliateAnalytics {
     pub daily_clicks: [u32; 30],
     /// Last update timestamp
     pub last_update: i64,
-    /// Current day index for circular buffers
+    /// Slot of `last_update`'s UTC day in the circular buffers
     pub current_day_index: u8,
 }
 
@@ -177,11 +361,22 @@ impl AffiliateAnalytics {
     /// Space required for analytics account
     pub const LEN: usize = 32 + (8 * 30) + (4 * 30) + 8 + 1; // 32 + 240 + 120 + 8 + 1 = 401 bytes
 
-    /// Add daily stats
-    pub fn add_daily_stats(&mut self, volume: u64, clicks: u32) {
-        self.daily_volume[self.current_day_index as usize] = volume;
-        self.daily_clicks[self.current_day_index as usize] = clicks;
-        self.current_day_index = ((self.current_day_index as usize + 1) % 30) as u8;
+    /// Add stats to the current UTC day's slot. Updates within one day are summed; once the
+    /// day changes the index advances one slot per day, zeroing the slots of any skipped days.
+    pub fn add_daily_stats(&mut self, volume: u64, clicks: u32, current_time: i64) -> Result<()> {
+        let elapsed_days = (current_time.div_euclid(SECONDS_PER_DAY) - self.last_update.div_euclid(SECONDS_PER_DAY))
+            .clamp(0, 30);
+        for _ in 0..elapsed_days {
+            self.current_day_index = ((self.current_day_index as usize + 1) % 30) as u8;
+            self.daily_volume[self.current_day_index as usize] = 0;
+            self.daily_clicks[self.current_day_index as usize] = 0;
+        }
+
+        let day = self.current_day_index as usize;
+        self.daily_volume[day] = self.daily_volume[day].checked_add(volume).ok_or(AffiliateError::Overflow)?;
+        self.daily_clicks[day] = self.daily_clicks[day].checked_add(clicks).ok_or(AffiliateError::Overflow)?;
+        self.last_update = current_time;
+        Ok(())
     }
 
     /// Calculate 30-day moving average volume
@@ -189,4 +384,44 @@ impl AffiliateAnalytics {
         let sum: u64 = self.daily_volume.iter().sum();
         sum / 30
     }
-}
\ No newline at end of file
+}
+
+/// Ecosystem-wide totals for landing pages, kept in one place so they can be read cheaply.
+/// Launch and SOL totals are reported by the `factory-program` via CPI.
+/// PDA seeds: `[b"ecosystem_stats"]`
+#[account]
+#[derive(Default)]
+pub struct EcosystemStats {
+    /// Number of launches created
+    pub total_launches: u64,
+    /// Lamports raised into launch vaults across all launches
+    pub total_sol_raised: u64,
+    /// Number of registered affiliates that haven't closed their accounts
+    pub total_affiliates: u64,
+}
+
+impl EcosystemStats {
+    /// Space required for ecosystem stats account
+    pub const LEN: usize = 8 + 8 + 8; // 24 bytes
+
+    /// Record launches and SOL raised reported by the factory
+    pub fn record_launch_activity(&mut self, new_launches: u64, sol_raised: u64) -> Result<()> {
+        self.total_launches = self.total_launches.checked_add(new_launches)
+            .ok_or(AffiliateError::Overflow)?;
+        self.total_sol_raised = self.total_sol_raised.checked_add(sol_raised)
+            .ok_or(AffiliateError::Overflow)?;
+        Ok(())
+    }
+
+    /// Record a newly registered affiliate
+    pub fn record_affiliate(&mut self) -> Result<()> {
+        self.total_affiliates = self.total_affiliates.checked_add(1)
+            .ok_or(AffiliateError::Overflow)?;
+        Ok(())
+    }
+
+    /// Record an affiliate closing its account
+    pub fn record_affiliate_closed(&mut self) {
+        self.total_affiliates = self.total_affiliates.saturating_sub(1);
+    }
+}
@@ -32,6 +32,10 @@ use anchor_lang::prelude::*;
 pub enum BarterError {
     #[msg("The calculated swap amount is less than the minimum amount out specified, indicating slippage tolerance was exceeded.")]
     SlippageExceeded,
+    #[msg("The swap amount is too small to produce any output at the current price.")]
+    AmountTooSmall,
+    #[msg("The deposit's token ratio differs from the pool's reserve ratio by more than the allowed tolerance.")]
+    DepositRatioMismatch,
     #[msg("The liquidity pool does not have enough tokens to fulfill the requested swap.")]
     InsufficientLiquidity,
     #[msg("A calculation in the program resulted in an arithmetic overflow.")]
@@ -58,6 +62,20 @@ pub enum BarterError {
     OraclePriceConfidenceTooHigh,
     #[msg("No valid price sources available.")]
     NoValidPriceSources,
+    #[msg("Fewer price sources are available than the pool requires for swaps.")]
+    InsufficientPriceSources,
+    #[msg("There is no staged oracle price awaiting confirmation.")]
+    NoPendingPrice,
+    #[msg("The staged oracle price cannot be confirmed until its confirmation delay has passed.")]
+    PriceConfirmationTooEarly,
+    #[msg("The staged oracle price has expired; push a fresh price instead.")]
+    PendingPriceExpired,
+    #[msg("The pushed price deviates from the current oracle price by more than the pool allows.")]
+    PriceDeviationTooLarge,
+    #[msg("Key is already allowed to push prices, or is not an oracle signer to remove.")]
+    InvalidOracleSigner,
+    #[msg("The pool already has the maximum number of oracle signers.")]
+    OracleSignerSetFull,
 
     // Dynamic fee errors
     #[msg("Dynamic fee calculation failed.")]
@@ -74,6 +92,8 @@ pub enum BarterError {
     InsufficientTokenLiquidity,
     #[msg("Pool configuration is invalid.")]
     InvalidPoolConfiguration,
+    #[msg("Pool configuration was updated too recently; wait for the config update cooldown.")]
+    UpdateTooFrequent,
     #[msg("Price history is not available.")]
     PriceHistoryNotAvailable,
 }
\ No newline at end of file
@@ -24,10 +24,19 @@
 //! ## Core Instructions
 //!
 //! - [`create_pool`]: Initialize new liquidity pools with oracle configuration
-//! - [`update_oracle_price`]: Permissioned price updates from oracle authorities
+//! - [`update_oracle_price`]: Permissioned price updates from the oracle authority or its oracle signers
+//! - [`add_oracle_signer`] / [`remove_oracle_signer`]: Manage up to three extra keys, e.g. redundant price keepers, that may push prices
+//! - [`confirm_price`]: Commit a large price move staged by `update_oracle_price`
+//! - [`refresh_switchboard_price`]: Pull the latest round from the pool's Switchboard aggregator
+//! - [`refresh_pyth_price`]: Read the pool's Pyth price feed(s) into its Pyth price source
 //! - [`swap`]: Execute token swaps at oracle-determined prices
-//! - [`add_liquidity`]: Provide liquidity to trading pools
+//! - [`add_liquidity`]: Provide liquidity to trading pools in exchange for LP tokens
+//! - [`remove_liquidity`]: Burn LP tokens for a pro-rata share of the pool's vaults
 //! - [`update_pool_config`]: Modify pool parameters and fee structures
+//! - [`transfer_oracle_authority`] / [`accept_oracle_authority`]: Two-step rotation of the key that pushes prices
+//! - [`collect_fees`]: Sweep accrued trading fees out of the vaults to the oracle authority
+//! - [`get_current_fee`]: Read-only quote of the fee the next swap will pay
+//! - [`health_check`]: Logs and returns the program's build version
 //!
 //! ## AI Integration
 //!
@@ -45,17 +54,25 @@
 //! - Configurable minimum liquidity requirements
 
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+use anchor_spl::token::{self, Burn, Mint, MintTo, Token, TokenAccount, Transfer};
 use genesis_common::constants::*;
 use genesis_common::utils::*;
 
 pub mod state;
 pub mod error;
+pub mod switchboard;
+pub mod pyth;
 use state::*;
 use error::*;
+use switchboard::*;
+use pyth::*;
 
 declare_id!("DEXy2D1fVf5s3f2y6D4b7j8N1M5P9kH3rW7T4gS6fX8a");
 
+/// Crate version of this build, logged by `health_check` so operators can tell which build
+/// processed a transaction.
+pub const PROGRAM_VERSION: &str = env!("CARGO_PKG_VERSION");
+
 /// Enhanced instruction arguments
 #[derive(AnchorSerialize, AnchorDeserialize)]
 pub struct CreatePoolArgs {
@@ -68,6 +85,47 @@ pub struct CreatePoolArgs {
     pub fee_bps: u16,
     pub dynamic_fee_enabled: bool,
     pub volatility_threshold: u64,
+    /// Oracle price age (seconds) up to which swaps trade normally.
+    pub max_staleness_seconds: i64,
+    /// Oracle price age (seconds) beyond which swaps fail.
+    pub hard_stale_seconds: i64,
+    /// Extra fee charged on swaps while the price is between the two thresholds.
+    pub stale_fee_bps: u16,
+    /// Price swaps against the average of the last `twap_window` price history entries.
+    pub use_twap: bool,
+    /// Number of history entries, 1 to 24, the TWAP averages.
+    pub twap_window: u8,
+    /// Price swaps against an EMA of oracle pushes instead of the latest push.
+    pub smoothing_enabled: bool,
+    /// Weight in basis points given to each new push when updating the EMA.
+    pub ema_alpha_bps: u16,
+    /// Price moves beyond this many basis points are staged for confirmation (0 = disabled).
+    pub max_price_move_bps: u16,
+    /// Seconds a staged price must wait before `confirm_price` can commit it.
+    pub price_confirm_delay_seconds: i64,
+    /// Pushed prices moving further than this many basis points from the current price are
+    /// rejected unless the push overrides the check (0 = disabled).
+    pub max_price_deviation_bps: u16,
+    /// Minimum seconds between `update_pool_config` calls.
+    pub config_update_cooldown_seconds: i64,
+    /// Pushed prices are rounded to the nearest multiple of this (0 = no rounding).
+    pub price_tick: u64,
+    /// Swaps halt while the price confidence exceeds this many basis points of the price (0 = disabled).
+    pub max_confidence_bps: u64,
+    /// Blend the oracle price with the pool's reserve ratio when swapping.
+    pub amm_blend_enabled: bool,
+    /// Oracle's weight in basis points in the blended price; the reserve ratio gets the rest.
+    pub oracle_weight_bps: u16,
+    /// Pyth's weight in basis points in the multi-source price (`DEFAULT_PYTH_WEIGHT` for the usual split).
+    pub pyth_weight: u16,
+    /// Switchboard's weight in basis points in the multi-source price.
+    pub switchboard_weight: u16,
+    /// The AI oracle's weight in basis points in the multi-source price.
+    pub ai_weight: u16,
+    /// Swaps need at least this many price sources, up to 3 (0 = no minimum).
+    pub min_price_sources: u8,
+    /// Reject deposits that don't match the reserve ratio within `DEPOSIT_RATIO_TOLERANCE_BPS`.
+    pub require_ratio: bool,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize)]
@@ -76,6 +134,8 @@ pub struct UpdatePriceArgs {
     pub switchboard_price: Option<u64>,
     pub ai_price: Option<u64>,
     pub price_confidence: Option<u64>,
+    /// Accept a weighted price beyond the pool's `max_price_deviation_bps`, e.g. after a genuine crash.
+    pub override_deviation: bool,
 }
 
 #[program]
@@ -84,6 +144,27 @@ pub mod barter_dex_program {
 
     /// Initializes a new oracle-based liquidity pool with enhanced features.
     pub fn create_pool(ctx: Context<CreatePool>, args: CreatePoolArgs) -> Result<()> {
+        require!(args.max_staleness_seconds > 0 && args.hard_stale_seconds >= args.max_staleness_seconds,
+                BarterError::InvalidPoolConfiguration);
+        LiquidityPool::validate_oracle_sources(
+            args.oracle_provider,
+            args.pyth_price_feed_a,
+            args.pyth_price_feed_b,
+            args.switchboard_feed,
+            args.ai_oracle_program,
+        )?;
+        LiquidityPool::validate_fee_bps(args.fee_bps)?;
+        LiquidityPool::validate_dynamic_fee_config(args.dynamic_fee_enabled, args.volatility_threshold)?;
+        LiquidityPool::validate_amm_blend_config(args.amm_blend_enabled, args.oracle_weight_bps)?;
+        LiquidityPool::validate_twap_config(args.use_twap, args.twap_window)?;
+        LiquidityPool::validate_source_weights(args.pyth_weight, args.switchboard_weight, args.ai_weight)?;
+        require!(args.min_price_sources as usize <= MAX_PRICE_SOURCES, BarterError::InvalidPoolConfiguration);
+        require!(args.config_update_cooldown_seconds >= 0, BarterError::InvalidPoolConfiguration);
+        if args.smoothing_enabled {
+            require!(args.ema_alpha_bps > 0 && args.ema_alpha_bps as u64 <= BPS_PRECISION,
+                    BarterError::InvalidPoolConfiguration);
+        }
+
         let current_time = Clock::get()?.unix_timestamp;
         let pool = &mut ctx.accounts.pool;
 
@@ -91,6 +172,9 @@ pub mod barter_dex_program {
         pool.mint_a = ctx.accounts.mint_a.key();
         pool.mint_b = ctx.accounts.mint_b.key();
         pool.oracle_authority = args.oracle_authority;
+        pool.pending_oracle_authority = None;
+        pool.oracle_signers = [Pubkey::default(); MAX_ORACLE_SIGNERS];
+        pool.oracle_signer_count = 0;
         pool.oracle_price = ORACLE_PRICE_PRECISION; // Default to 1:1 price
         pool.last_oracle_update = current_time;
 
@@ -105,44 +189,134 @@ pub mod barter_dex_program {
         pool.pyth_price = None;
         pool.switchboard_price = None;
         pool.ai_price = None;
+        pool.pyth_updated_at = 0;
+        pool.switchboard_updated_at = 0;
+        pool.ai_updated_at = 0;
         pool.price_confidence = 0;
+        pool.max_confidence_bps = args.max_confidence_bps;
+        pool.pyth_confidence = None;
+        pool.pyth_weight = args.pyth_weight;
+        pool.switchboard_weight = args.switchboard_weight;
+        pool.ai_weight = args.ai_weight;
+        pool.min_price_sources = args.min_price_sources;
 
         // Initialize price history
         pool.price_history = [ORACLE_PRICE_PRECISION; 24];
         pool.history_index = 0;
+        pool.history_len = 0;
+        pool.use_twap = args.use_twap;
+        pool.twap_window = args.twap_window;
 
         // Liquidity tracking
         pool.total_liquidity_a = 0;
         pool.total_liquidity_b = 0;
+        pool.lp_supply = 0;
         pool.fee_bps = args.fee_bps;
+        pool.require_ratio = args.require_ratio;
+        pool.fees_a = 0;
+        pool.fees_b = 0;
+
+        // Oracle/AMM price blend
+        pool.amm_blend_enabled = args.amm_blend_enabled;
+        pool.oracle_weight_bps = args.oracle_weight_bps;
 
         // Dynamic fee configuration
         pool.dynamic_fee_enabled = args.dynamic_fee_enabled;
         pool.volatility_threshold = args.volatility_threshold;
         pool.last_volatility_update = current_time;
 
+        // Oracle staleness configuration
+        pool.max_staleness_seconds = args.max_staleness_seconds;
+        pool.hard_stale_seconds = args.hard_stale_seconds;
+        pool.stale_fee_bps = args.stale_fee_bps;
+
+        // Price smoothing configuration
+        pool.smoothing_enabled = args.smoothing_enabled;
+        pool.ema_alpha_bps = args.ema_alpha_bps;
+        pool.ema_price = pool.oracle_price;
+
+        // Large move staging configuration
+        pool.max_price_move_bps = args.max_price_move_bps;
+        pool.price_confirm_delay_seconds = args.price_confirm_delay_seconds;
+        pool.pending_price = 0;
+        pool.pending_price_time = 0;
+
+        // Price deviation circuit breaker
+        pool.max_price_deviation_bps = args.max_price_deviation_bps;
+
+        // Price granularity
+        pool.price_tick = args.price_tick;
+
+        // Config update rate limit; the first update is allowed one cooldown after creation
+        pool.config_update_cooldown_seconds = args.config_update_cooldown_seconds;
+        pool.last_config_update = current_time;
+
         let bumps = &ctx.bumps;
         pool.vault_a_bump = bumps.vault_a;
         pool.vault_b_bump = bumps.vault_b;
+        pool.lp_mint_bump = bumps.lp_mint;
 
         msg!("Enhanced pool created for mints {} and {} with oracle provider {:?}",
              pool.mint_a, pool.mint_b, pool.oracle_provider);
         Ok(())
     }
 
-    /// Permissioned instruction for the oracle authority to update the on-chain price.
-    pub fn update_oracle_price(ctx: Context<UpdateOraclePrice>, new_price: u64) -> Result<()> {
-        let pool = &mut ctx.accounts.pool;
-        pool.oracle_price = new_price;
-        pool.last_oracle_update = Clock::get()?.unix_timestamp;
-        msg!("Pool price updated to {} by oracle {}", new_price, ctx.accounts.oracle_authority.key());
+    /// Adds liquidity to an existing pool, failing if it would mint fewer than `min_lp_out` LP tokens.
+    pub fn add_liquidity(ctx: Context<AddLiquidity>, amount_a: u64, amount_b: u64, min_lp_out: u64) -> Result<()> {
+        token::transfer(ctx.accounts.transfer_a_context(), amount_a)?;
+        token::transfer(ctx.accounts.transfer_b_context(), amount_b)?;
+
+        // LP tokens are minted in proportion to the deposit's value at the pool price
+        let lp_tokens = ctx.accounts.pool.record_deposit(amount_a, amount_b)?;
+        require!(lp_tokens >= min_lp_out, BarterError::SlippageExceeded);
+
+        let pool = &ctx.accounts.pool;
+        let seeds = pda_utils::liquidity_pool_signer_seeds(&pool.mint_a, &pool.mint_b, &ctx.bumps.pool);
+        token::mint_to(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                MintTo { mint: ctx.accounts.lp_mint.to_account_info(), to: ctx.accounts.user_lp_token_account.to_account_info(), authority: pool.to_account_info() },
+                &[&seeds[..]]
+            ),
+            lp_tokens
+        )?;
+
+        msg!("Liquidity added: {} A + {} B for {} LP tokens", amount_a, amount_b, lp_tokens);
         Ok(())
     }
 
-    /// Adds liquidity to an existing pool.
-    pub fn add_liquidity(ctx: Context<AddLiquidity>, amount_a: u64, amount_b: u64) -> Result<()> {
-        token::transfer(ctx.accounts.transfer_a_context(), amount_a)?;
-        token::transfer(ctx.accounts.transfer_b_context(), amount_b)?;
+    /// Burns LP tokens and returns their pro-rata share of both vaults.
+    pub fn remove_liquidity(ctx: Context<RemoveLiquidity>, lp_amount: u64) -> Result<()> {
+        let (amount_a, amount_b) = ctx.accounts.pool.record_withdrawal(lp_amount)?;
+
+        token::burn(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Burn { mint: ctx.accounts.lp_mint.to_account_info(), from: ctx.accounts.user_lp_token_account.to_account_info(), authority: ctx.accounts.user.to_account_info() }
+            ),
+            lp_amount
+        )?;
+
+        let pool = &ctx.accounts.pool;
+        let seeds = pda_utils::liquidity_pool_signer_seeds(&pool.mint_a, &pool.mint_b, &ctx.bumps.pool);
+        for (vault, destination, amount) in [
+            (&ctx.accounts.vault_a, &ctx.accounts.user_token_account_a, amount_a),
+            (&ctx.accounts.vault_b, &ctx.accounts.user_token_account_b, amount_b),
+        ] {
+            if amount == 0 {
+                continue;
+            }
+            token::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    Transfer { from: vault.to_account_info(), to: destination.to_account_info(), authority: pool.to_account_info() },
+                    &[&seeds[..]]
+                ),
+                amount
+            )?;
+        }
+
+        msg!("Liquidity removed: {} LP tokens for {} A + {} B", lp_amount, amount_a, amount_b);
         Ok(())
     }
 
@@ -151,40 +325,22 @@ pub mod barter_dex_program {
         let pool = &mut ctx.accounts.pool;
         let current_time = Clock::get()?.unix_timestamp;
 
-        // Oracle sanity checks
-        require!(!pool.is_oracle_stale()?, BarterError::OraclePriceStale);
+        // Price against the weighted average of all sources, or its EMA when smoothing is enabled
+        pool.check_price_sources(current_time)?;
+        let oracle_price = pool.calculate_swap_price()?;
+        require!(oracle_price > 0, BarterError::NoValidPriceSources);
+        pool.check_price_confidence(oracle_price)?;
 
-        // Calculate weighted average price from multiple sources
-        let effective_price = pool.calculate_weighted_price()?;
+        // Optionally cushion the oracle price with the pool's own reserve ratio
+        let effective_price = pool.blend_with_amm_price(oracle_price)?;
         require!(effective_price > 0, BarterError::NoValidPriceSources);
 
-        // Calculate dynamic fee
-        let fee_bps = pool.calculate_dynamic_fee()?;
+        // Calculate dynamic fee, failing if the oracle price is past the hard staleness limit
+        let fee_bps = pool.calculate_swap_fee(current_time)?;
 
-        // Calculate amount out with fee
-        let amount_out_before_fee = if ctx.accounts.user_source_token_account.mint == pool.mint_a {
-            // Swapping A for B: amount_out_B = amount_in_A * price_A_in_B
-            (amount_in as u128)
-                .checked_mul(effective_price as u128)
-                .and_then(|v| v.checked_div(ORACLE_PRICE_PRECISION as u128))
-                .ok_or(BarterError::Overflow)? as u64
-        } else {
-            // Swapping B for A: amount_out_A = amount_in_B / price_A_in_B
-            (amount_in as u128)
-                .checked_mul(ORACLE_PRICE_PRECISION as u128)
-                .and_then(|v| v.checked_div(effective_price as u128))
-                .ok_or(BarterError::Overflow)? as u64
-        };
-
-        // Apply trading fee
-        let fee_amount = (amount_out_before_fee as u128)
-            .checked_mul(fee_bps as u128)
-            .and_then(|v| v.checked_div(BPS_PRECISION as u128))
-            .ok_or(BarterError::DynamicFeeCalculationFailed)? as u64;
-
-        let amount_out = amount_out_before_fee
-            .checked_sub(fee_amount)
-            .ok_or(BarterError::Underflow)?;
+        // Calculate amount out with fee, rejecting dust trades that would round to nothing
+        let a_to_b = ctx.accounts.user_source_token_account.mint == pool.mint_a;
+        let (amount_out, fee_amount) = LiquidityPool::calculate_swap_amounts(amount_in, a_to_b, effective_price, fee_bps)?;
 
         require!(amount_out >= min_amount_out, BarterError::SlippageExceeded);
 
@@ -207,7 +363,7 @@ pub mod barter_dex_program {
         )?;
 
         let bumps = &ctx.bumps;
-        let seeds = &[LIQUIDITY_POOL_SEED.as_ref(), pool.mint_a.as_ref(), pool.mint_b.as_ref(), &[bumps.pool]];
+        let seeds = pda_utils::liquidity_pool_signer_seeds(&pool.mint_a, &pool.mint_b, &bumps.pool);
         token::transfer(
              CpiContext::new_with_signer(
                 ctx.accounts.token_program.to_account_info(),
@@ -218,23 +374,31 @@ pub mod barter_dex_program {
         )?;
 
         // Update pool state
-        if ctx.accounts.user_source_token_account.mint == pool.mint_a {
-            pool.total_liquidity_a = pool.total_liquidity_a.checked_add(amount_in).ok_or(BarterError::Overflow)?;
-            pool.total_liquidity_b = pool.total_liquidity_b.checked_sub(amount_out).ok_or(BarterError::Underflow)?;
-        } else {
-            pool.total_liquidity_b = pool.total_liquidity_b.checked_add(amount_in).ok_or(BarterError::Overflow)?;
-            pool.total_liquidity_a = pool.total_liquidity_a.checked_sub(amount_out).ok_or(BarterError::Underflow)?;
-        }
+        pool.record_swap(a_to_b, amount_in, amount_out, fee_amount)?;
 
         // Update price history for volatility tracking
         pool.update_price_history(effective_price);
         pool.last_volatility_update = current_time;
 
+        emit!(SwapEvent {
+            pool: pool.key(),
+            user: ctx.accounts.user.key(),
+            mint_in: ctx.accounts.user_source_token_account.mint,
+            amount_in,
+            amount_out,
+            fee_bps,
+            effective_price,
+            timestamp: current_time,
+        });
+
         msg!("Swap executed: {} in -> {} out with {} bps fee", amount_in, amount_out, fee_bps);
         Ok(())
     }
 
     /// Update oracle price with enhanced multi-source support.
+    ///
+    /// A weighted price beyond the pool's `max_price_deviation_bps` is rejected, guarding against
+    /// fat-fingered or hallucinated pushes, unless `override_deviation` is set.
     pub fn update_oracle_price(ctx: Context<UpdateOraclePrice>, args: UpdatePriceArgs) -> Result<()> {
         let pool = &mut ctx.accounts.pool;
         let current_time = Clock::get()?.unix_timestamp;
@@ -242,44 +406,217 @@ pub mod barter_dex_program {
         // Update individual price sources
         if let Some(pyth_price) = args.pyth_price {
             pool.pyth_price = Some(pyth_price);
+            pool.pyth_confidence = args.price_confidence;
+            pool.pyth_updated_at = current_time;
         }
         if let Some(switchboard_price) = args.switchboard_price {
             pool.switchboard_price = Some(switchboard_price);
+            pool.switchboard_updated_at = current_time;
         }
         if let Some(ai_price) = args.ai_price {
             pool.ai_price = Some(ai_price);
+            pool.ai_updated_at = current_time;
         }
         if let Some(confidence) = args.price_confidence {
             pool.price_confidence = confidence;
         }
 
-        // Calculate weighted average price
-        let weighted_price = pool.calculate_weighted_price()?;
-        pool.oracle_price = weighted_price;
-        pool.last_oracle_update = current_time;
-
-        // Update price history
-        pool.update_price_history(weighted_price);
+        // Calculate weighted average price, snapped to the pool's tick so pushes that only
+        // jitter don't add noise to the price history; large moves are staged rather than applied
+        let weighted_price = pool.snap_to_tick(pool.calculate_weighted_price(current_time)?);
+        if args.override_deviation {
+            msg!("Price deviation check overridden for weighted price {}", weighted_price);
+        } else {
+            pool.check_price_deviation(weighted_price)?;
+        }
+        if pool.push_oracle_price(weighted_price, current_time) {
+            msg!("Large price move to {} staged; confirm after {} seconds",
+                 weighted_price, pool.price_confirm_delay_seconds);
+            return Ok(());
+        }
 
         msg!("Oracle prices updated: pyth={:?}, switchboard={:?}, ai={:?}, weighted={}",
              pool.pyth_price, pool.switchboard_price, pool.ai_price, weighted_price);
         Ok(())
     }
 
+    /// Permissionless refresh of the Switchboard price from the pool's configured aggregator.
+    pub fn refresh_switchboard_price(ctx: Context<RefreshSwitchboardPrice>) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        let round = AggregatorRound::parse(&ctx.accounts.aggregator.try_borrow_data()?)?;
+
+        let pool = &mut ctx.accounts.pool;
+        round.check_fresh(current_time, pool.max_staleness_seconds)?;
+        let switchboard_price = round.result.to_precision(ORACLE_PRICE_PRECISION)?;
+        require!(switchboard_price > 0, BarterError::OraclePriceFetchFailed);
+
+        pool.switchboard_price = Some(switchboard_price);
+        pool.switchboard_updated_at = round.round_open_timestamp;
+        pool.price_confidence = round.std_deviation.to_precision(ORACLE_PRICE_PRECISION)?;
+
+        let weighted_price = pool.calculate_weighted_price(current_time)?;
+        if pool.push_oracle_price(weighted_price, current_time) {
+            msg!("Large price move to {} staged; confirm after {} seconds",
+                 weighted_price, pool.price_confirm_delay_seconds);
+            return Ok(());
+        }
+
+        msg!("Switchboard price refreshed: switchboard={}, confidence={}, weighted={}",
+             switchboard_price, pool.price_confidence, weighted_price);
+        Ok(())
+    }
+
+    /// Permissionless refresh of the Pyth price from the pool's configured price feed(s).
+    ///
+    /// `remaining_accounts` holds the Pyth price account for `pyth_price_feed_a`, followed by
+    /// the one for `pyth_price_feed_b` when the pool prices A and B against a common quote.
+    pub fn refresh_pyth_price(ctx: Context<RefreshPythPrice>) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        let pool = &mut ctx.accounts.pool;
+        let expected_feeds: Vec<Pubkey> = [pool.pyth_price_feed_a, pool.pyth_price_feed_b].into_iter().flatten().collect();
+        require!(ctx.remaining_accounts.len() == expected_feeds.len(), BarterError::PythPriceFeedNotFound);
+
+        let mut feeds = Vec::with_capacity(expected_feeds.len());
+        for (account, expected) in ctx.remaining_accounts.iter().zip(&expected_feeds) {
+            require!(account.key() == *expected && *account.owner == PYTH_ORACLE_PROGRAM_ID,
+                    BarterError::PythPriceFeedNotFound);
+            let feed = PythPrice::parse(&account.try_borrow_data()?)?;
+            feed.check_fresh(current_time, MAX_ORACLE_AGE_SECONDS)?;
+            feeds.push(feed);
+        }
+
+        let (pyth_price, confidence) = pool_price_from_feeds(&feeds[0], feeds.get(1), ORACLE_PRICE_PRECISION)?;
+        pool.pyth_price = Some(pyth_price);
+        pool.pyth_confidence = Some(confidence);
+        pool.pyth_updated_at = feeds.iter().map(|feed| feed.publish_time).min().unwrap_or(current_time);
+        pool.price_confidence = confidence;
+
+        let weighted_price = pool.calculate_weighted_price(current_time)?;
+        if pool.push_oracle_price(weighted_price, current_time) {
+            msg!("Large price move to {} staged; confirm after {} seconds",
+                 weighted_price, pool.price_confirm_delay_seconds);
+            return Ok(());
+        }
+
+        msg!("Pyth price refreshed: pyth={}, confidence={}, weighted={}",
+             pyth_price, pool.price_confidence, weighted_price);
+        Ok(())
+    }
+
+    /// Commits a large price move previously staged by `update_oracle_price`, once its delay has
+    /// passed and before it goes stale.
+    pub fn confirm_price(ctx: Context<ConfirmPrice>) -> Result<()> {
+        let price = ctx.accounts.pool.confirm_pending_price(Clock::get()?.unix_timestamp)?;
+        msg!("Staged oracle price {} confirmed", price);
+        Ok(())
+    }
+
     /// Update liquidity pool configuration.
-    pub fn update_pool_config(ctx: Context<UpdatePoolConfig>, fee_bps: u16, dynamic_fee_enabled: bool, volatility_threshold: u64) -> Result<()> {
+    /// Rate limited to one change per `config_update_cooldown_seconds` so fees can't be whipsawed.
+    pub fn update_pool_config(
+        ctx: Context<UpdatePoolConfig>,
+        fee_bps: u16,
+        dynamic_fee_enabled: bool,
+        volatility_threshold: u64,
+        pyth_weight: u16,
+        switchboard_weight: u16,
+        ai_weight: u16,
+    ) -> Result<()> {
+        LiquidityPool::validate_fee_bps(fee_bps)?;
+        LiquidityPool::validate_dynamic_fee_config(dynamic_fee_enabled, volatility_threshold)?;
+        LiquidityPool::validate_source_weights(pyth_weight, switchboard_weight, ai_weight)?;
+        let current_time = Clock::get()?.unix_timestamp;
         let pool = &mut ctx.accounts.pool;
+        pool.check_config_update_allowed(current_time)?;
 
         pool.fee_bps = fee_bps;
         pool.dynamic_fee_enabled = dynamic_fee_enabled;
         pool.volatility_threshold = volatility_threshold;
-        pool.last_volatility_update = Clock::get()?.unix_timestamp;
+        pool.pyth_weight = pyth_weight;
+        pool.switchboard_weight = switchboard_weight;
+        pool.ai_weight = ai_weight;
+        pool.last_volatility_update = current_time;
+        pool.last_config_update = current_time;
+
+        msg!("Pool configuration updated: fee={} bps, dynamic={}, threshold={}, weights={}/{}/{}",
+             fee_bps, dynamic_fee_enabled, volatility_threshold, pyth_weight, switchboard_weight, ai_weight);
+        Ok(())
+    }
+
+    /// Proposes `new_authority` as the pool's oracle authority (current oracle authority only).
+    ///
+    /// The current key stays in charge until the new one calls `accept_oracle_authority`, so a
+    /// mistyped key can't strand the pool. Proposing again replaces the pending key.
+    pub fn transfer_oracle_authority(ctx: Context<TransferOracleAuthority>, new_authority: Pubkey) -> Result<()> {
+        ctx.accounts.pool.pending_oracle_authority = Some(new_authority);
+        msg!("Oracle authority transfer to {} proposed", new_authority);
+        Ok(())
+    }
+
+    /// Authorizes `signer`, e.g. a redundant price keeper, to push prices (oracle authority only).
+    pub fn add_oracle_signer(ctx: Context<ManageOracleSigners>, signer: Pubkey) -> Result<()> {
+        ctx.accounts.pool.add_oracle_signer(signer)?;
+        msg!("Oracle signer {} added", signer);
+        Ok(())
+    }
 
-        msg!("Pool configuration updated: fee={} bps, dynamic={}, threshold={}",
-             fee_bps, dynamic_fee_enabled, volatility_threshold);
+    /// Revokes an oracle signer's permission to push prices (oracle authority only).
+    pub fn remove_oracle_signer(ctx: Context<ManageOracleSigners>, signer: Pubkey) -> Result<()> {
+        ctx.accounts.pool.remove_oracle_signer(&signer)?;
+        msg!("Oracle signer {} removed", signer);
         Ok(())
     }
 
+    /// Completes an oracle authority transfer; must be signed by the proposed key.
+    pub fn accept_oracle_authority(ctx: Context<AcceptOracleAuthority>) -> Result<()> {
+        let new_authority = ctx.accounts.new_oracle_authority.key();
+        ctx.accounts.pool.accept_oracle_authority(&new_authority)?;
+        msg!("Oracle authority transferred to {}", new_authority);
+        Ok(())
+    }
+
+    /// Transfers the pool's accrued trading fees to token accounts chosen by the oracle
+    /// authority, and resets the fee counters. LP reserves are left untouched.
+    pub fn collect_fees(ctx: Context<CollectFees>) -> Result<()> {
+        let (fees_a, fees_b) = ctx.accounts.pool.take_fees();
+
+        let pool = &ctx.accounts.pool;
+        let seeds = pda_utils::liquidity_pool_signer_seeds(&pool.mint_a, &pool.mint_b, &ctx.bumps.pool);
+        for (vault, destination, amount) in [
+            (&ctx.accounts.vault_a, &ctx.accounts.fee_destination_a, fees_a),
+            (&ctx.accounts.vault_b, &ctx.accounts.fee_destination_b, fees_b),
+        ] {
+            if amount == 0 {
+                continue;
+            }
+            token::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    Transfer { from: vault.to_account_info(), to: destination.to_account_info(), authority: pool.to_account_info() },
+                    &[&seeds[..]]
+                ),
+                amount
+            )?;
+        }
+
+        msg!("Collected trading fees: {} A, {} B", fees_a, fees_b);
+        Ok(())
+    }
+
+    /// Read-only quote of the fee the next swap will be charged, returned via return data.
+    pub fn get_current_fee(ctx: Context<GetCurrentFee>) -> Result<FeeQuote> {
+        let quote = ctx.accounts.pool.quote_fee(Clock::get()?.unix_timestamp)?;
+        msg!("Current fee: {} bps (volatility={}, threshold={})",
+             quote.fee_bps, quote.volatility, quote.volatility_threshold);
+        Ok(quote)
+    }
+
+    /// Logs and returns this program's build version via return data.
+    pub fn health_check(ctx: Context<HealthCheck>) -> Result<String> {
+        msg!("barter-dex-program v{} at slot {}", PROGRAM_VERSION, ctx.accounts.clock.slot);
+        Ok(PROGRAM_VERSION.to_string())
+    }
+
     /// Emergency pause/unpause pool trading.
     pub fn emergency_pause(ctx: Context<EmergencyControl>, paused: bool) -> Result<()> {
         let pool = &mut ctx.accounts.pool;
@@ -290,6 +627,19 @@ pub mod barter_dex_program {
     }
 }
 
+/// Event emitted on every swap
+#[event]
+pub struct SwapEvent {
+    pub pool: Pubkey,
+    pub user: Pubkey,
+    pub mint_in: Pubkey,
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub fee_bps: u16,
+    pub effective_price: u64,
+    pub timestamp: i64,
+}
+
 /// Event emitted when prices are updated
 #[event]
 pub struct PriceUpdateEvent {
@@ -331,6 +681,15 @@ pub struct CreatePool<'info> {
         bump
     )]
     pub vault_b: Account<'info, TokenAccount>,
+    #[account(
+        init,
+        payer = authority,
+        mint::decimals = 9,
+        mint::authority = pool,
+        seeds = [LP_MINT_SEED.as_ref(), mint_a.key().as_ref(), mint_b.key().as_ref()],
+        bump
+    )]
+    pub lp_mint: Account<'info, Mint>,
     pub mint_a: Account<'info, Mint>,
     pub mint_b: Account<'info, Mint>,
     #[account(mut)]
@@ -341,28 +700,33 @@ pub struct CreatePool<'info> {
 }
 
 #[derive(Accounts)]
-pub struct UpdateOraclePrice<'info> {
+pub struct AddLiquidity<'info> {
     #[account(
         mut,
         seeds = [LIQUIDITY_POOL_SEED.as_ref(), pool.mint_a.as_ref(), pool.mint_b.as_ref()],
-        bump,
-        has_one = oracle_authority @ BarterError::InvalidOracleAuthority
+        bump
     )]
     pub pool: Account<'info, LiquidityPool>,
-    pub oracle_authority: Signer<'info>,
-}
-
-#[derive(Accounts)]
-pub struct AddLiquidity<'info> {
     #[account(
-        seeds = [LIQUIDITY_POOL_SEED.as_ref(), pool.mint_a.as_ref(), pool.mint_b.as_ref()],
-        bump
+        mut,
+        seeds = [POOL_VAULT_SEED.as_ref(), pool.mint_a.as_ref(), pool.mint_b.as_ref(), b"a"],
+        bump = pool.vault_a_bump
     )]
-    pub pool: Account<'info, LiquidityPool>,
-    #[account(mut, token::mint = pool.mint_a)]
     pub vault_a: Account<'info, TokenAccount>,
-    #[account(mut, token::mint = pool.mint_b)]
+    #[account(
+        mut,
+        seeds = [POOL_VAULT_SEED.as_ref(), pool.mint_a.as_ref(), pool.mint_b.as_ref(), b"b"],
+        bump = pool.vault_b_bump
+    )]
     pub vault_b: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        seeds = [LP_MINT_SEED.as_ref(), pool.mint_a.as_ref(), pool.mint_b.as_ref()],
+        bump = pool.lp_mint_bump
+    )]
+    pub lp_mint: Account<'info, Mint>,
+    #[account(mut, token::mint = lp_mint)]
+    pub user_lp_token_account: Account<'info, TokenAccount>,
     #[account(mut)]
     pub user_token_account_a: Account<'info, TokenAccount>,
     #[account(mut)]
@@ -374,6 +738,19 @@ pub struct AddLiquidity<'info> {
 #[derive(Accounts)]
 #[instruction(args: UpdatePriceArgs)]
 pub struct UpdateOraclePrice<'info> {
+    #[account(
+        mut,
+        seeds = [LIQUIDITY_POOL_SEED.as_ref(), pool.mint_a.as_ref(), pool.mint_b.as_ref()],
+        bump,
+        constraint = pool.is_price_pusher(&oracle_authority.key()) @ BarterError::InvalidOracleAuthority
+    )]
+    pub pool: Account<'info, LiquidityPool>,
+    /// The pool's oracle authority or one of its oracle signers.
+    pub oracle_authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ConfirmPrice<'info> {
     #[account(
         mut,
         seeds = [LIQUIDITY_POOL_SEED.as_ref(), pool.mint_a.as_ref(), pool.mint_b.as_ref()],
@@ -384,6 +761,31 @@ pub struct UpdateOraclePrice<'info> {
     pub oracle_authority: Signer<'info>,
 }
 
+#[derive(Accounts)]
+pub struct RefreshSwitchboardPrice<'info> {
+    #[account(
+        mut,
+        seeds = [LIQUIDITY_POOL_SEED.as_ref(), pool.mint_a.as_ref(), pool.mint_b.as_ref()],
+        bump,
+        constraint = pool.switchboard_feed == Some(aggregator.key()) @ BarterError::SwitchboardFeedNotFound
+    )]
+    pub pool: Account<'info, LiquidityPool>,
+    /// CHECK: Key is pinned to `pool.switchboard_feed`; data is decoded by `AggregatorRound::parse`.
+    #[account(owner = SWITCHBOARD_V2_PROGRAM_ID @ BarterError::SwitchboardFeedNotFound)]
+    pub aggregator: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RefreshPythPrice<'info> {
+    #[account(
+        mut,
+        seeds = [LIQUIDITY_POOL_SEED.as_ref(), pool.mint_a.as_ref(), pool.mint_b.as_ref()],
+        bump,
+        constraint = pool.pyth_price_feed_a.is_some() @ BarterError::PythPriceFeedNotFound
+    )]
+    pub pool: Account<'info, LiquidityPool>,
+}
+
 #[derive(Accounts)]
 #[instruction(fee_bps: u16, dynamic_fee_enabled: bool, volatility_threshold: u64)]
 pub struct UpdatePoolConfig<'info> {
@@ -397,6 +799,84 @@ pub struct UpdatePoolConfig<'info> {
     pub oracle_authority: Signer<'info>,
 }
 
+#[derive(Accounts)]
+pub struct TransferOracleAuthority<'info> {
+    #[account(
+        mut,
+        seeds = [LIQUIDITY_POOL_SEED.as_ref(), pool.mint_a.as_ref(), pool.mint_b.as_ref()],
+        bump,
+        has_one = oracle_authority @ BarterError::InvalidOracleAuthority
+    )]
+    pub pool: Account<'info, LiquidityPool>,
+    pub oracle_authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ManageOracleSigners<'info> {
+    #[account(
+        mut,
+        seeds = [LIQUIDITY_POOL_SEED.as_ref(), pool.mint_a.as_ref(), pool.mint_b.as_ref()],
+        bump,
+        has_one = oracle_authority @ BarterError::InvalidOracleAuthority
+    )]
+    pub pool: Account<'info, LiquidityPool>,
+    pub oracle_authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptOracleAuthority<'info> {
+    #[account(
+        mut,
+        seeds = [LIQUIDITY_POOL_SEED.as_ref(), pool.mint_a.as_ref(), pool.mint_b.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, LiquidityPool>,
+    pub new_oracle_authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CollectFees<'info> {
+    #[account(
+        mut,
+        seeds = [LIQUIDITY_POOL_SEED.as_ref(), pool.mint_a.as_ref(), pool.mint_b.as_ref()],
+        bump,
+        has_one = oracle_authority @ BarterError::InvalidOracleAuthority
+    )]
+    pub pool: Account<'info, LiquidityPool>,
+    #[account(
+        mut,
+        seeds = [POOL_VAULT_SEED.as_ref(), pool.mint_a.as_ref(), pool.mint_b.as_ref(), b"a"],
+        bump = pool.vault_a_bump
+    )]
+    pub vault_a: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        seeds = [POOL_VAULT_SEED.as_ref(), pool.mint_a.as_ref(), pool.mint_b.as_ref(), b"b"],
+        bump = pool.vault_b_bump
+    )]
+    pub vault_b: Account<'info, TokenAccount>,
+    #[account(mut, token::mint = pool.mint_a)]
+    pub fee_destination_a: Account<'info, TokenAccount>,
+    #[account(mut, token::mint = pool.mint_b)]
+    pub fee_destination_b: Account<'info, TokenAccount>,
+    pub oracle_authority: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct HealthCheck<'info> {
+    pub clock: Sysvar<'info, Clock>,
+}
+
+#[derive(Accounts)]
+pub struct GetCurrentFee<'info> {
+    #[account(
+        seeds = [LIQUIDITY_POOL_SEED.as_ref(), pool.mint_a.as_ref(), pool.mint_b.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, LiquidityPool>,
+}
+
 #[derive(Accounts)]
 #[instruction(paused: bool)]
 pub struct EmergencyControl<'info> {
@@ -426,15 +906,60 @@ impl<'info> AddLiquidity<'info> {
 }
 
 #[derive(Accounts)]
-pub struct Swap<'info> {
+pub struct RemoveLiquidity<'info> {
     #[account(
+        mut,
         seeds = [LIQUIDITY_POOL_SEED.as_ref(), pool.mint_a.as_ref(), pool.mint_b.as_ref()],
         bump
     )]
     pub pool: Account<'info, LiquidityPool>,
-    #[account(mut, token::mint = pool.mint_a)]
+    #[account(
+        mut,
+        seeds = [POOL_VAULT_SEED.as_ref(), pool.mint_a.as_ref(), pool.mint_b.as_ref(), b"a"],
+        bump = pool.vault_a_bump
+    )]
     pub vault_a: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        seeds = [POOL_VAULT_SEED.as_ref(), pool.mint_a.as_ref(), pool.mint_b.as_ref(), b"b"],
+        bump = pool.vault_b_bump
+    )]
+    pub vault_b: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        seeds = [LP_MINT_SEED.as_ref(), pool.mint_a.as_ref(), pool.mint_b.as_ref()],
+        bump = pool.lp_mint_bump
+    )]
+    pub lp_mint: Account<'info, Mint>,
+    #[account(mut, token::mint = lp_mint)]
+    pub user_lp_token_account: Account<'info, TokenAccount>,
+    #[account(mut, token::mint = pool.mint_a)]
+    pub user_token_account_a: Account<'info, TokenAccount>,
     #[account(mut, token::mint = pool.mint_b)]
+    pub user_token_account_b: Account<'info, TokenAccount>,
+    pub user: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct Swap<'info> {
+    #[account(
+        mut,
+        seeds = [LIQUIDITY_POOL_SEED.as_ref(), pool.mint_a.as_ref(), pool.mint_b.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, LiquidityPool>,
+    #[account(
+        mut,
+        seeds = [POOL_VAULT_SEED.as_ref(), pool.mint_a.as_ref(), pool.mint_b.as_ref(), b"a"],
+        bump = pool.vault_a_bump
+    )]
+    pub vault_a: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        seeds = [POOL_VAULT_SEED.as_ref(), pool.mint_a.as_ref(), pool.mint_b.as_ref(), b"b"],
+        bump = pool.vault_b_bump
+    )]
     pub vault_b: Account<'info, TokenAccount>,
     #[account(mut)]
     pub user_source_token_account: Account<'info, TokenAccount>,
@@ -0,0 +1,160 @@
+//! # Pyth Price Account Reader
+//!
+//! Minimal, dependency-free reader for Pyth V2 (push-oracle legacy) price
+//! accounts. Like `switchboard-v2`, the `pyth-sdk-solana` crate pins an older
+//! Solana toolchain, so the fields the DEX needs are decoded directly from the
+//! account's C-compatible layout.
+//!
+//! ## Layout
+//!
+//! Offsets below are measured from the start of the account data. Pyth price
+//! accounts carry no Anchor discriminator; they start with a magic number,
+//! version and account type instead. Only these fields are read:
+//!
+//! - `expo`: `i32` at [`EXPONENT_OFFSET`]
+//! - `timestamp`: `i64` publish time of the aggregate at [`TIMESTAMP_OFFSET`]
+//! - `prev_price`, `prev_conf`, `prev_timestamp`: the last trading aggregate,
+//!   used while the current one is not trading
+//! - `agg.price`, `agg.conf`, `agg.status`: the current aggregate at [`AGG_PRICE_OFFSET`]
+
+use anchor_lang::prelude::*;
+use crate::error::BarterError;
+
+/// Pyth oracle program that owns price accounts.
+pub const PYTH_ORACLE_PROGRAM_ID: Pubkey =
+    anchor_lang::solana_program::pubkey!("FsJ3A3u2vn5cTVofAjvy6y5kwABJAqYWpe4975bi2epH");
+
+/// Magic number at the start of every Pyth account.
+pub const PYTH_MAGIC: u32 = 0xa1b2_c3d4;
+/// Account layout version this reader understands.
+pub const PYTH_VERSION: u32 = 2;
+/// Account type tag of price accounts.
+pub const PYTH_PRICE_ACCOUNT_TYPE: u32 = 3;
+/// `agg.status` value of an aggregate that is currently trading.
+pub const PYTH_STATUS_TRADING: u32 = 1;
+
+/// Offset of the price exponent.
+pub const EXPONENT_OFFSET: usize = 20;
+/// Offset of the aggregate's publish time.
+pub const TIMESTAMP_OFFSET: usize = 96;
+/// Offset of the previous trading aggregate's price.
+pub const PREV_PRICE_OFFSET: usize = 184;
+/// Offset of the previous trading aggregate's confidence.
+pub const PREV_CONF_OFFSET: usize = 192;
+/// Offset of the previous trading aggregate's publish time.
+pub const PREV_TIMESTAMP_OFFSET: usize = 200;
+/// Offset of `agg.price`.
+pub const AGG_PRICE_OFFSET: usize = 208;
+/// Offset of `agg.conf`.
+pub const AGG_CONF_OFFSET: usize = 216;
+/// Offset of `agg.status`.
+pub const AGG_STATUS_OFFSET: usize = 224;
+
+/// Bytes needed to read every field above.
+const MIN_PRICE_ACCOUNT_LEN: usize = AGG_STATUS_OFFSET + 4;
+
+fn read_u32(data: &[u8], offset: usize) -> u32 {
+    let mut bytes = [0u8; 4];
+    bytes.copy_from_slice(&data[offset..offset + 4]);
+    u32::from_le_bytes(bytes)
+}
+
+fn read_u64(data: &[u8], offset: usize) -> u64 {
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(&data[offset..offset + 8]);
+    u64::from_le_bytes(bytes)
+}
+
+/// A Pyth price: `price * 10^expo`, with confidence `conf` in the same units.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PythPrice {
+    pub price: i64,
+    pub conf: u64,
+    pub expo: i32,
+    pub publish_time: i64,
+}
+
+impl PythPrice {
+    /// Decode the latest trading price from raw price account data. While the aggregate is
+    /// not trading, the previous trading aggregate is returned, as `pyth-sdk-solana` does.
+    pub fn parse(data: &[u8]) -> Result<Self> {
+        require!(
+            data.len() >= MIN_PRICE_ACCOUNT_LEN
+                && read_u32(data, 0) == PYTH_MAGIC
+                && read_u32(data, 4) == PYTH_VERSION
+                && read_u32(data, 8) == PYTH_PRICE_ACCOUNT_TYPE,
+            BarterError::PythPriceFeedNotFound
+        );
+
+        let expo = read_u32(data, EXPONENT_OFFSET) as i32;
+        if read_u32(data, AGG_STATUS_OFFSET) == PYTH_STATUS_TRADING {
+            Ok(Self {
+                price: read_u64(data, AGG_PRICE_OFFSET) as i64,
+                conf: read_u64(data, AGG_CONF_OFFSET),
+                expo,
+                publish_time: read_u64(data, TIMESTAMP_OFFSET) as i64,
+            })
+        } else {
+            Ok(Self {
+                price: read_u64(data, PREV_PRICE_OFFSET) as i64,
+                conf: read_u64(data, PREV_CONF_OFFSET),
+                expo,
+                publish_time: read_u64(data, PREV_TIMESTAMP_OFFSET) as i64,
+            })
+        }
+    }
+
+    /// Reject prices published more than `max_age_seconds` ago
+    pub fn check_fresh(&self, current_time: i64, max_age_seconds: i64) -> Result<()> {
+        let age = current_time.saturating_sub(self.publish_time);
+        require!(age <= max_age_seconds, BarterError::OraclePriceStale);
+        Ok(())
+    }
+
+    /// Rescale the price to a fixed-point `u64` with `precision` units per whole value.
+    /// Zero and negative prices are rejected.
+    pub fn to_precision(&self, precision: u64) -> Result<u64> {
+        require!(self.price > 0, BarterError::OraclePriceFetchFailed);
+        let scaled = Self::scale(self.price as u64, self.expo, precision)?;
+        require!(scaled > 0, BarterError::OraclePriceFetchFailed);
+        Ok(scaled)
+    }
+
+    /// Rescale the confidence interval to a fixed-point `u64` with `precision` units
+    pub fn conf_to_precision(&self, precision: u64) -> Result<u64> {
+        Self::scale(self.conf, self.expo, precision)
+    }
+
+    fn scale(value: u64, expo: i32, precision: u64) -> Result<u64> {
+        let power = 10u128.checked_pow(expo.unsigned_abs()).ok_or(BarterError::Overflow)?;
+        let value = (value as u128).checked_mul(precision as u128).ok_or(BarterError::Overflow)?;
+        let scaled = if expo < 0 {
+            value / power
+        } else {
+            value.checked_mul(power).ok_or(BarterError::Overflow)?
+        };
+        u64::try_from(scaled).map_err(|_| error!(BarterError::Overflow))
+    }
+}
+
+/// Price of token A in token B, and its confidence, at `precision`. With only `feed_a` the
+/// feed is taken to quote A in B directly; with `feed_b` both feeds share a quote currency
+/// (e.g. USD) and the pool price is their ratio, with their relative confidences summed.
+pub fn pool_price_from_feeds(feed_a: &PythPrice, feed_b: Option<&PythPrice>, precision: u64) -> Result<(u64, u64)> {
+    let price_a = feed_a.to_precision(precision)?;
+    let conf_a = feed_a.conf_to_precision(precision)?;
+    let Some(feed_b) = feed_b else {
+        return Ok((price_a, conf_a));
+    };
+
+    let price_b = feed_b.to_precision(precision)? as u128;
+    let conf_b = feed_b.conf_to_precision(precision)? as u128;
+    let price = (price_a as u128 * precision as u128) / price_b;
+    require!(price > 0, BarterError::OraclePriceFetchFailed);
+    // conf(a/b) ~= a/b * (conf_a/a + conf_b/b) = (conf_a + a/b * conf_b) / b
+    let confidence = (conf_a as u128 * precision as u128 + price * conf_b) / price_b;
+
+    let price = u64::try_from(price).map_err(|_| error!(BarterError::Overflow))?;
+    let confidence = u64::try_from(confidence).map_err(|_| error!(BarterError::Overflow))?;
+    Ok((price, confidence))
+}
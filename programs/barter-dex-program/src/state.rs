@@ -12,15 +12,17 @@
 //! ## Multi-Oracle Architecture
 //!
 //! The program supports multiple price sources simultaneously:
-//! - **Pyth Network**: Professional oracle with 40% weight in hybrid calculations
-//! - **Switchboard**: Decentralized oracle network with 35% weight
-//! - **AI Oracle**: Custom AI-driven pricing with 25% weight
-//! - **Hybrid Mode**: Weighted average calculation from all available sources
+//! - **Pyth Network**: Professional oracle, 40% weight by default in hybrid calculations
+//! - **Switchboard**: Decentralized oracle network, 35% weight by default
+//! - **AI Oracle**: Custom AI-driven pricing, 25% weight by default
+//! - **Hybrid Mode**: Weighted average calculation from all available sources, with the
+//!   weights configurable per pool
 //!
 //! ## Advanced Features
 //!
 //! - **Dynamic Fee System**: Fees automatically adjust based on price volatility
 //! - **Price History Tracking**: 24-hour circular buffer for volatility calculations
+//! - **TWAP Pricing**: Optional time-weighted average over the price history for swaps
 //! - **Confidence Intervals**: Price reliability tracking and validation
 //! - **Staleness Protection**: Automatic rejection of outdated price data
 //!
@@ -33,9 +35,11 @@
 
 use anchor_lang::prelude::*;
 use genesis_common::constants::*;
+use genesis_common::utils::math_utils::isqrt_u128;
+use crate::error::BarterError;
 
 /// Oracle provider types for price feeds
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
 pub enum OracleProvider {
     /// Pyth Network oracle
     Pyth,
@@ -47,6 +51,29 @@ pub enum OracleProvider {
     Hybrid,
 }
 
+/// How fresh the pool's oracle price is relative to its staleness thresholds
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OracleFreshness {
+    /// Within `max_staleness_seconds`: trade normally
+    Fresh,
+    /// Between `max_staleness_seconds` and `hard_stale_seconds`: trade with the stale-risk fee
+    Stale,
+    /// Beyond `hard_stale_seconds`: swaps are rejected
+    Expired,
+}
+
+/// Fee the next swap on a pool will be charged, with the inputs that produced it
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FeeQuote {
+    /// Total fee in basis points, including any stale-risk surcharge
+    pub fee_bps: u16,
+    pub base_fee_bps: u16,
+    pub dynamic_fee_enabled: bool,
+    pub volatility: u64,
+    pub volatility_threshold: u64,
+    pub oracle_stale: bool,
+}
+
 /// Liquidity pool state with enhanced oracle integration
 #[account]
 pub struct LiquidityPool {
@@ -56,6 +83,11 @@ pub struct LiquidityPool {
     pub mint_b: Pubkey,
     /// The designated authority allowed to push price updates.
     pub oracle_authority: Pubkey,
+    /// Key proposed by `transfer_oracle_authority`; becomes the authority once it accepts.
+    pub pending_oracle_authority: Option<Pubkey>,
+    /// Additional keys allowed to push prices; only the first `oracle_signer_count` are used
+    pub oracle_signers: [Pubkey; MAX_ORACLE_SIGNERS],
+    pub oracle_signer_count: u8,
 
     /// Enhanced oracle configuration
     pub oracle_provider: OracleProvider,
@@ -68,63 +100,136 @@ pub struct LiquidityPool {
     pub oracle_price: u64,
     pub last_oracle_update: i64,
     pub price_confidence: u64, // Confidence interval for price
+    pub max_confidence_bps: u64, // Swaps halt while confidence exceeds this share of the price (0 = disabled)
 
     /// Multiple price sources for hybrid approach
     pub pyth_price: Option<u64>,
+    pub pyth_confidence: Option<u64>, // Pyth prices without a confidence are left out of the weighting
     pub switchboard_price: Option<u64>,
     pub ai_price: Option<u64>,
+    /// When each source's price was last set; sources older than MAX_ORACLE_AGE_SECONDS drop out of the weighting
+    pub pyth_updated_at: i64,
+    pub switchboard_updated_at: i64,
+    pub ai_updated_at: i64,
+
+    /// Per-source weights in basis points; absent sources are skipped and the rest renormalized
+    pub pyth_weight: u16,
+    pub switchboard_weight: u16,
+    pub ai_weight: u16,
+    pub min_price_sources: u8, // Swaps need at least this many weighted sources with a price (0 = no minimum)
 
     /// Price history for volatility calculation (circular buffer)
     pub price_history: [u64; 24], // Last 24 hours (hourly)
     pub history_index: u8,
+    pub history_len: u8, // Entries recorded so far, up to 24; the create-time seed prices don't count
+
+    /// Time-weighted pricing
+    pub use_twap: bool, // Swaps price against the TWAP of the last `twap_window` history entries
+    pub twap_window: u8,
 
     /// Liquidity and trading parameters
     pub total_liquidity_a: u64,
     pub total_liquidity_b: u64,
+    pub lp_supply: u64, // LP tokens outstanding, including the MINIMUM_LIQUIDITY locked by the first deposit
     pub fee_bps: u16, // Trading fee in basis points
+    pub require_ratio: bool, // Deposits must match the reserve ratio within DEPOSIT_RATIO_TOLERANCE_BPS
+
+    /// Accrued trading fees, held in the vaults outside the LP reserves until `collect_fees`
+    pub fees_a: u64,
+    pub fees_b: u64,
+
+    /// Blending the oracle price with the pool's reserve ratio
+    pub amm_blend_enabled: bool,
+    pub oracle_weight_bps: u16, // Oracle's share of the blended price; the reserve ratio gets the rest
+
+    /// Swap analytics
+    pub cumulative_volume_a: u64, // Token A swapped in or out, saturating
+    pub cumulative_volume_b: u64, // Token B swapped in or out, saturating
+    pub swap_count: u64,
 
     /// Advanced trading features
     pub dynamic_fee_enabled: bool,
     pub volatility_threshold: u64, // Price change threshold to trigger higher fees
     pub last_volatility_update: i64,
 
-    /// Vault bump seeds
+    /// Oracle staleness thresholds
+    pub max_staleness_seconds: i64, // Trades are normal up to this price age
+    pub hard_stale_seconds: i64, // Swaps fail beyond this price age
+    pub stale_fee_bps: u16, // Extra fee charged between the two thresholds
+
+    /// Price smoothing
+    pub smoothing_enabled: bool,
+    pub ema_alpha_bps: u16, // Weight of each new push in the EMA
+    pub ema_price: u64,
+
+    /// Two-step confirmation for large price moves
+    pub max_price_move_bps: u16, // Moves beyond this are staged (0 = disabled)
+    pub price_confirm_delay_seconds: i64,
+    pub pending_price: u64, // 0 = nothing staged
+    pub pending_price_time: i64,
+
+    /// Circuit breaker for pushed prices
+    pub max_price_deviation_bps: u16, // Pushes moving the price further are rejected unless overridden (0 = disabled)
+
+    /// Price granularity
+    pub price_tick: u64, // Pushed prices snap to the nearest multiple (0 = disabled)
+
+    /// Config update rate limit
+    pub config_update_cooldown_seconds: i64, // Minimum gap between update_pool_config calls
+    pub last_config_update: i64,
+
+    /// Vault and LP mint bump seeds
     pub vault_a_bump: u8,
     pub vault_b_bump: u8,
+    pub lp_mint_bump: u8,
 }
 
 impl LiquidityPool {
     /// Enhanced space calculation
-    pub const LEN: usize = 32 + 32 + 32 + // mint_a, mint_b, oracle_authority
+    pub const LEN: usize = 32 + 32 + 32 + (1 + 32) + // mint_a, mint_b, oracle_authority, pending_oracle_authority
+        32 * MAX_ORACLE_SIGNERS + 1 + // oracle signers
         1 + (1 + 32) + (1 + 32) + (1 + 32) + (1 + 32) + // oracle config
-        8 + 8 + 8 + // prices and confidence
-        (1 + 8) + (1 + 8) + (1 + 8) + // multiple price sources
-        (8 * 24) + 1 + // price history
-        8 + 8 + 2 + // liquidity and fees
+        8 + 8 + 8 + 8 + // prices, confidence and confidence limit
+        (1 + 8) + (1 + 8) + (1 + 8) + (1 + 8) + // multiple price sources and Pyth confidence
+        8 + 8 + 8 + // per-source update times
+        2 + 2 + 2 + 1 + // price source weights and minimum source count
+        (8 * 24) + 1 + 1 + // price history
+        1 + 1 + // TWAP settings
+        8 + 8 + 8 + 2 + 1 + // liquidity, LP supply, fees and deposit ratio enforcement
+        8 + 8 + // accrued fees
+        1 + 2 + // oracle/AMM price blend
+        8 + 8 + 8 + // swap volume and count
         1 + 8 + 8 + // dynamic fee settings
-        1 + 1; // vault bumps
+        8 + 8 + 2 + // staleness thresholds and stale fee
+        1 + 2 + 8 + // price smoothing
+        2 + 8 + 8 + 8 + // large move staging
+        2 + // price deviation circuit breaker
+        8 + // price tick
+        8 + 8 + // config update rate limit
+        1 + 1 + 1; // vault and LP mint bumps
+
+    /// Each source's price, weight and last update time. Pyth counts only if available with
+    /// a confidence interval.
+    fn price_sources(&self) -> [(Option<u64>, u16, i64); MAX_PRICE_SOURCES] {
+        [
+            (self.pyth_price.filter(|_| self.pyth_confidence.is_some()), self.pyth_weight, self.pyth_updated_at),
+            (self.switchboard_price, self.switchboard_weight, self.switchboard_updated_at),
+            (self.ai_price, self.ai_weight, self.ai_updated_at),
+        ]
+    }
 
-    /// Calculate weighted average price from multiple sources
-    pub fn calculate_weighted_price(&self) -> Result<u64> {
+    /// Calculate weighted average price from multiple sources, using the pool's source
+    /// weights renormalized over the sources that are present and no older than
+    /// `MAX_ORACLE_AGE_SECONDS`
+    pub fn calculate_weighted_price(&self, current_time: i64) -> Result<u64> {
         let mut total_weight: u64 = 0;
         let mut weighted_sum: u128 = 0;
 
-        // Pyth weight: 40% if available
-        if let Some(price) = self.pyth_price {
-            weighted_sum += price as u128 * 40;
-            total_weight += 40;
-        }
-
-        // Switchboard weight: 35% if available
-        if let Some(price) = self.switchboard_price {
-            weighted_sum += price as u128 * 35;
-            total_weight += 35;
-        }
-
-        // AI price weight: 25% if available
-        if let Some(price) = self.ai_price {
-            weighted_sum += price as u128 * 25;
-            total_weight += 25;
+        for (price, weight, updated_at) in self.price_sources() {
+            if let Some(price) = price.filter(|_| current_time.saturating_sub(updated_at) <= MAX_ORACLE_AGE_SECONDS) {
+                weighted_sum += price as u128 * weight as u128;
+                total_weight += weight as u64;
+            }
         }
 
         if total_weight == 0 {
@@ -135,16 +240,12 @@ impl LiquidityPool {
         Ok(weighted_average)
     }
 
-    /// Calculate price volatility based on history
+    /// Calculate price volatility over the recorded price history, walking back from
+    /// `history_index` so the whole ring is sampled once it wraps
     pub fn calculate_volatility(&self) -> Result<u64> {
-        if self.history_index == 0 {
-            return Ok(0);
-        }
-
-        let mut prices = Vec::new();
-        for i in 0..self.history_index {
-            prices.push(self.price_history[i as usize]);
-        }
+        let prices: Vec<u64> = (1..=self.history_len as usize)
+            .map(|back| self.price_history[(self.history_index as usize + 24 - back) % 24])
+            .collect();
 
         if prices.len() < 2 {
             return Ok(0);
@@ -152,15 +253,74 @@ impl LiquidityPool {
 
         let mean = prices.iter().map(|&p| p as u128).sum::<u128>() / prices.len() as u128;
         let variance = prices.iter()
-            .map(|&p| {
-                let diff = if p as u128 > mean { p as u128 - mean } else { mean - p as u128 };
-                diff * diff
+            .try_fold(0u128, |sum, &p| {
+                let diff = (p as u128).abs_diff(mean);
+                sum.checked_add(diff * diff)
             })
-            .sum::<u128>() / prices.len() as u128;
+            .ok_or(BarterError::Overflow)?
+            / prices.len() as u128;
+
+        // Standard deviation scaled by ORACLE_PRICE_PRECISION. sqrt(variance * P^2) keeps the
+        // fractional digits of the root; very large variances fall back to sqrt(variance) * P.
+        let precision = ORACLE_PRICE_PRECISION as u128;
+        let volatility = match variance.checked_mul(precision * precision) {
+            Some(scaled_variance) => isqrt_u128(scaled_variance),
+            None => isqrt_u128(variance).checked_mul(precision).ok_or(BarterError::Overflow)?,
+        };
+        // Saturate like the float conversion this replaced, so extreme histories max out the fee
+        Ok(u64::try_from(volatility).unwrap_or(u64::MAX))
+    }
+
+    /// Validate that the supplied price sources match the declared oracle provider.
+    /// Single-provider pools may only configure that provider's feed: Pyth needs
+    /// `pyth_price_feed_a`, Switchboard needs `switchboard_feed`, and AI-oracle pools take
+    /// pushes from the oracle authority so `ai_oracle_program` is optional. Hybrid pools
+    /// need between `MIN_HYBRID_PRICE_SOURCES` and `MAX_PRICE_SOURCES` sources.
+    pub fn validate_oracle_sources(
+        provider: OracleProvider,
+        pyth_price_feed_a: Option<Pubkey>,
+        pyth_price_feed_b: Option<Pubkey>,
+        switchboard_feed: Option<Pubkey>,
+        ai_oracle_program: Option<Pubkey>,
+    ) -> Result<()> {
+        // A second Pyth feed only makes sense alongside the first
+        require!(pyth_price_feed_a.is_some() || pyth_price_feed_b.is_none(), BarterError::InvalidPoolConfiguration);
+        let has_pyth = pyth_price_feed_a.is_some();
+        let has_switchboard = switchboard_feed.is_some();
+        let has_ai = ai_oracle_program.is_some();
+
+        let consistent = match provider {
+            OracleProvider::Pyth => has_pyth && !has_switchboard && !has_ai,
+            OracleProvider::Switchboard => has_switchboard && !has_pyth && !has_ai,
+            OracleProvider::AIOracle => !has_pyth && !has_switchboard,
+            OracleProvider::Hybrid => {
+                let sources = [has_pyth, has_switchboard, has_ai].iter().filter(|&&s| s).count();
+                (MIN_HYBRID_PRICE_SOURCES..=MAX_PRICE_SOURCES).contains(&sources)
+            }
+        };
+        require!(consistent, BarterError::InvalidPoolConfiguration);
+        Ok(())
+    }
+
+    /// Reject config changes made within `config_update_cooldown_seconds` of the last one
+    pub fn check_config_update_allowed(&self, current_time: i64) -> Result<()> {
+        let elapsed = current_time.saturating_sub(self.last_config_update);
+        require!(elapsed >= self.config_update_cooldown_seconds, BarterError::UpdateTooFrequent);
+        Ok(())
+    }
+
+    /// Validate dynamic fee settings; a zero volatility threshold can't scale fees
+    pub fn validate_dynamic_fee_config(dynamic_fee_enabled: bool, volatility_threshold: u64) -> Result<()> {
+        if dynamic_fee_enabled {
+            require!(volatility_threshold > 0, BarterError::InvalidPoolConfiguration);
+        }
+        Ok(())
+    }
 
-        // Return standard deviation
-        let volatility = ((variance as f64).sqrt() * ORACLE_PRICE_PRECISION as f64) as u64;
-        Ok(volatility)
+    /// Validate a base trading fee against the protocol maximum of `MAX_RATE_BPS`
+    pub fn validate_fee_bps(fee_bps: u16) -> Result<()> {
+        require!(fee_bps <= MAX_RATE_BPS, BarterError::FeeExceedsMaximum);
+        Ok(())
     }
 
     /// Calculate dynamic fee based on volatility
@@ -169,6 +329,11 @@ impl LiquidityPool {
             return Ok(self.fee_bps);
         }
 
+        // Legacy pools may have been created with a zero threshold; treat them as static-fee
+        if self.volatility_threshold == 0 {
+            return Ok(self.fee_bps);
+        }
+
         let volatility = self.calculate_volatility()?;
         let base_fee = self.fee_bps as u64;
 
@@ -183,42 +348,408 @@ impl LiquidityPool {
         Ok(std::cmp::min(dynamic_fee, 1000) as u16) // Cap at 10%
     }
 
+    /// Fold a newly pushed price into the exponential moving average
+    pub fn update_ema(&mut self, new_price: u64) {
+        if self.ema_price == 0 {
+            self.ema_price = new_price;
+            return;
+        }
+
+        let alpha = self.ema_alpha_bps as u128;
+        let weighted_sum = new_price as u128 * alpha + self.ema_price as u128 * (BPS_PRECISION as u128 - alpha);
+        self.ema_price = (weighted_sum / BPS_PRECISION as u128) as u64;
+    }
+
+    /// Price swaps execute against: the TWAP when `use_twap` is set, else the EMA when smoothing
+    /// is enabled, otherwise the committed oracle price
+    pub fn calculate_swap_price(&self) -> Result<u64> {
+        if self.use_twap {
+            return self.calculate_twap(self.twap_window);
+        }
+        if self.smoothing_enabled && self.ema_price > 0 {
+            return Ok(self.ema_price);
+        }
+        Ok(self.oracle_price)
+    }
+
+    /// Record a swap of `amount_in` for `amount_out` in the reserves and volume counters. The
+    /// `fee_amount` withheld from the output leaves the reserves too, and accrues as a fee.
+    pub fn record_swap(&mut self, a_to_b: bool, amount_in: u64, amount_out: u64, fee_amount: u64) -> Result<()> {
+        let debit = amount_out.checked_add(fee_amount).ok_or(BarterError::Overflow)?;
+        let (volume_a, volume_b) = if a_to_b {
+            self.total_liquidity_a = self.total_liquidity_a.checked_add(amount_in).ok_or(BarterError::Overflow)?;
+            self.total_liquidity_b = self.total_liquidity_b.checked_sub(debit).ok_or(BarterError::InsufficientLiquidity)?;
+            self.fees_b = self.fees_b.checked_add(fee_amount).ok_or(BarterError::Overflow)?;
+            (amount_in, amount_out)
+        } else {
+            self.total_liquidity_b = self.total_liquidity_b.checked_add(amount_in).ok_or(BarterError::Overflow)?;
+            self.total_liquidity_a = self.total_liquidity_a.checked_sub(debit).ok_or(BarterError::InsufficientLiquidity)?;
+            self.fees_a = self.fees_a.checked_add(fee_amount).ok_or(BarterError::Overflow)?;
+            (amount_out, amount_in)
+        };
+
+        self.cumulative_volume_a = self.cumulative_volume_a.saturating_add(volume_a);
+        self.cumulative_volume_b = self.cumulative_volume_b.saturating_add(volume_b);
+        self.swap_count = self.swap_count.saturating_add(1);
+        Ok(())
+    }
+
+    /// The additional oracle signers in use
+    pub fn active_oracle_signers(&self) -> &[Pubkey] {
+        &self.oracle_signers[..self.oracle_signer_count as usize]
+    }
+
+    /// Whether `key` may push prices: the oracle authority or one of the oracle signers
+    pub fn is_price_pusher(&self, key: &Pubkey) -> bool {
+        *key == self.oracle_authority || self.active_oracle_signers().contains(key)
+    }
+
+    /// Authorize `signer` to push prices alongside the oracle authority
+    pub fn add_oracle_signer(&mut self, signer: Pubkey) -> Result<()> {
+        require!(!self.is_price_pusher(&signer) && signer != Pubkey::default(), BarterError::InvalidOracleSigner);
+        let count = self.oracle_signer_count as usize;
+        require!(count < MAX_ORACLE_SIGNERS, BarterError::OracleSignerSetFull);
+        self.oracle_signers[count] = signer;
+        self.oracle_signer_count += 1;
+        Ok(())
+    }
+
+    /// Revoke an oracle signer, moving the last signer into its slot
+    pub fn remove_oracle_signer(&mut self, signer: &Pubkey) -> Result<()> {
+        let index = self.active_oracle_signers()
+            .iter()
+            .position(|key| key == signer)
+            .ok_or(BarterError::InvalidOracleSigner)?;
+        let last = self.oracle_signer_count as usize - 1;
+        self.oracle_signers[index] = self.oracle_signers[last];
+        self.oracle_signers[last] = Pubkey::default();
+        self.oracle_signer_count -= 1;
+        Ok(())
+    }
+
+    /// Hand the oracle authority to the proposed key once it accepts
+    pub fn accept_oracle_authority(&mut self, new_authority: &Pubkey) -> Result<()> {
+        require!(self.pending_oracle_authority == Some(*new_authority), BarterError::InvalidOracleAuthority);
+        self.oracle_authority = *new_authority;
+        self.pending_oracle_authority = None;
+        Ok(())
+    }
+
+    /// Take the accrued `(fees_a, fees_b)` for collection, resetting the counters
+    pub fn take_fees(&mut self) -> (u64, u64) {
+        (std::mem::take(&mut self.fees_a), std::mem::take(&mut self.fees_b))
+    }
+
+    /// Blend `oracle_price` with the price implied by the pool's reserves, when enabled:
+    /// `w * oracle_price + (1 - w) * amm_price` with `w = oracle_weight_bps`. Thin or
+    /// one-sided pools have no meaningful reserve price, so the oracle price is used alone.
+    pub fn blend_with_amm_price(&self, oracle_price: u64) -> Result<u64> {
+        if !self.amm_blend_enabled || self.total_liquidity_a == 0 || self.total_liquidity_b == 0 {
+            return Ok(oracle_price);
+        }
+
+        let amm_price = (self.total_liquidity_b as u128)
+            .checked_mul(ORACLE_PRICE_PRECISION as u128)
+            .ok_or(BarterError::Overflow)?
+            / self.total_liquidity_a as u128;
+        let oracle_weight = self.oracle_weight_bps as u128;
+        let amm_weight = (BPS_PRECISION as u128).saturating_sub(oracle_weight);
+        let blended = (oracle_price as u128 * oracle_weight)
+            .checked_add(amm_price.checked_mul(amm_weight).ok_or(BarterError::Overflow)?)
+            .ok_or(BarterError::Overflow)?
+            / BPS_PRECISION as u128;
+        u64::try_from(blended).map_err(|_| error!(BarterError::Overflow))
+    }
+
+    /// Number of sources that currently contribute to the weighted price: present, with a
+    /// non-zero weight, no older than `MAX_ORACLE_AGE_SECONDS`, and for Pyth, carrying a
+    /// confidence interval
+    pub fn active_price_sources(&self, current_time: i64) -> usize {
+        self.price_sources()
+            .into_iter()
+            .filter(|&(price, weight, updated_at)| {
+                price.is_some() && weight > 0 && current_time.saturating_sub(updated_at) <= MAX_ORACLE_AGE_SECONDS
+            })
+            .count()
+    }
+
+    /// Reject swaps while fewer than `min_price_sources` fresh sources back the price, or
+    /// while every source the pool has been given has gone stale
+    pub fn check_price_sources(&self, current_time: i64) -> Result<()> {
+        let active = self.active_price_sources(current_time);
+        let configured = self.price_sources().into_iter().filter(|&(price, weight, _)| price.is_some() && weight > 0).count();
+        require!(configured == 0 || active > 0, BarterError::OraclePriceStale);
+        require!(active >= self.min_price_sources as usize, BarterError::InsufficientPriceSources);
+        Ok(())
+    }
+
+    /// Validate price source weights: each is a share of `BPS_PRECISION` and together they
+    /// sum to exactly `BPS_PRECISION`
+    pub fn validate_source_weights(pyth_weight: u16, switchboard_weight: u16, ai_weight: u16) -> Result<()> {
+        let total = pyth_weight as u64 + switchboard_weight as u64 + ai_weight as u64;
+        require!(total == BPS_PRECISION, BarterError::InvalidPoolConfiguration);
+        Ok(())
+    }
+
+    /// Validate the oracle/AMM blend: the oracle weight is a share of `BPS_PRECISION`
+    pub fn validate_amm_blend_config(amm_blend_enabled: bool, oracle_weight_bps: u16) -> Result<()> {
+        if amm_blend_enabled {
+            require!(oracle_weight_bps as u64 <= BPS_PRECISION, BarterError::InvalidPoolConfiguration);
+        }
+        Ok(())
+    }
+
+    /// Reject swaps while the oracle's confidence interval is wider than `max_confidence_bps`
+    /// of `price`
+    pub fn check_price_confidence(&self, price: u64) -> Result<()> {
+        if self.max_confidence_bps == 0 {
+            return Ok(());
+        }
+        require!(price > 0, BarterError::NoValidPriceSources);
+        // confidence / price <= max_confidence_bps / BPS_PRECISION, cross-multiplied to avoid rounding
+        let confidence_scaled = self.price_confidence as u128 * BPS_PRECISION as u128;
+        let limit_scaled = self.max_confidence_bps as u128 * price as u128;
+        require!(confidence_scaled <= limit_scaled, BarterError::OraclePriceConfidenceTooHigh);
+        Ok(())
+    }
+
+    /// Output of a swap after the trading fee. `a_to_b` swaps token A for token B at `price`
+    /// (B per A); otherwise B is swapped for A. Trades that round down to no output are rejected
+    /// rather than taking the input for nothing.
+    pub fn calculate_swap_output(amount_in: u64, a_to_b: bool, price: u64, fee_bps: u16) -> Result<u64> {
+        Ok(Self::calculate_swap_amounts(amount_in, a_to_b, price, fee_bps)?.0)
+    }
+
+    /// Like `calculate_swap_output`, also returning the fee withheld from the output:
+    /// `(amount_out, fee_amount)`, both in the output token
+    pub fn calculate_swap_amounts(amount_in: u64, a_to_b: bool, price: u64, fee_bps: u16) -> Result<(u64, u64)> {
+        // A fee of 100% or more would leave nothing, or less than nothing, for the trader
+        require!((fee_bps as u64) < BPS_PRECISION, BarterError::FeeExceedsMaximum);
+
+        let amount_out_before_fee = if a_to_b {
+            // Swapping A for B: amount_out_B = amount_in_A * price_A_in_B
+            (amount_in as u128)
+                .checked_mul(price as u128)
+                .and_then(|v| v.checked_div(ORACLE_PRICE_PRECISION as u128))
+                .ok_or(BarterError::Overflow)? as u64
+        } else {
+            // Swapping B for A: amount_out_A = amount_in_B / price_A_in_B
+            (amount_in as u128)
+                .checked_mul(ORACLE_PRICE_PRECISION as u128)
+                .and_then(|v| v.checked_div(price as u128))
+                .ok_or(BarterError::Overflow)? as u64
+        };
+
+        // Apply trading fee
+        let fee_amount = (amount_out_before_fee as u128)
+            .checked_mul(fee_bps as u128)
+            .and_then(|v| v.checked_div(BPS_PRECISION as u128))
+            .ok_or(BarterError::DynamicFeeCalculationFailed)? as u64;
+
+        let amount_out = amount_out_before_fee
+            .checked_sub(fee_amount)
+            .ok_or(BarterError::Underflow)?;
+        require!(amount_out > 0, BarterError::AmountTooSmall);
+        Ok((amount_out, fee_amount))
+    }
+
+    /// Value of `amount_a` plus `amount_b`, in token B at the pool's swap price
+    pub fn liquidity_value(&self, amount_a: u64, amount_b: u64) -> Result<u128> {
+        let price = self.calculate_swap_price()?;
+        require!(price > 0, BarterError::NoValidPriceSources);
+        let value_a = (amount_a as u128)
+            .checked_mul(price as u128)
+            .ok_or(BarterError::Overflow)?
+            / ORACLE_PRICE_PRECISION as u128;
+        Ok(value_a + amount_b as u128)
+    }
+
+    /// With `require_ratio` set, reject deposits whose A:B ratio strays from the reserves' by
+    /// more than `DEPOSIT_RATIO_TOLERANCE_BPS`. The first deposit into an empty pool sets the ratio.
+    pub fn check_deposit_ratio(&self, amount_a: u64, amount_b: u64) -> Result<()> {
+        if !self.require_ratio || self.total_liquidity_a == 0 || self.total_liquidity_b == 0 {
+            return Ok(());
+        }
+        // Cross-multiply so amount_a / amount_b can be compared with total_a / total_b exactly
+        let deposit_side = amount_a as u128 * self.total_liquidity_b as u128;
+        let reserve_side = amount_b as u128 * self.total_liquidity_a as u128;
+        let tolerance = reserve_side * DEPOSIT_RATIO_TOLERANCE_BPS as u128 / BPS_PRECISION as u128;
+        require!(deposit_side.abs_diff(reserve_side) <= tolerance, BarterError::DepositRatioMismatch);
+        Ok(())
+    }
+
+    /// Record a deposit and return the LP tokens it earns, in proportion to its value against
+    /// the pool's. The first deposit sets the LP supply to its value, of which `MINIMUM_LIQUIDITY`
+    /// is never minted so the pool can't be fully drained.
+    pub fn record_deposit(&mut self, amount_a: u64, amount_b: u64) -> Result<u64> {
+        self.check_deposit_ratio(amount_a, amount_b)?;
+        let deposit_value = self.liquidity_value(amount_a, amount_b)?;
+        let lp_tokens = if self.lp_supply == 0 {
+            let initial_supply = u64::try_from(deposit_value).map_err(|_| error!(BarterError::Overflow))?;
+            require!(initial_supply > MINIMUM_LIQUIDITY, BarterError::AmountTooSmall);
+            self.lp_supply = initial_supply;
+            initial_supply - MINIMUM_LIQUIDITY
+        } else {
+            let pool_value = self.liquidity_value(self.total_liquidity_a, self.total_liquidity_b)?;
+            require!(pool_value > 0, BarterError::InsufficientLiquidity);
+            let lp_tokens = deposit_value
+                .checked_mul(self.lp_supply as u128)
+                .ok_or(BarterError::Overflow)?
+                / pool_value;
+            let lp_tokens = u64::try_from(lp_tokens).map_err(|_| error!(BarterError::Overflow))?;
+            require!(lp_tokens > 0, BarterError::AmountTooSmall);
+            self.lp_supply = self.lp_supply.checked_add(lp_tokens).ok_or(BarterError::Overflow)?;
+            lp_tokens
+        };
+
+        self.total_liquidity_a = self.total_liquidity_a.checked_add(amount_a).ok_or(BarterError::Overflow)?;
+        self.total_liquidity_b = self.total_liquidity_b.checked_add(amount_b).ok_or(BarterError::Overflow)?;
+        Ok(lp_tokens)
+    }
+
+    /// Record `lp_amount` LP tokens being burned and return the pro-rata `(amount_a, amount_b)`
+    /// they redeem from the vaults
+    pub fn record_withdrawal(&mut self, lp_amount: u64) -> Result<(u64, u64)> {
+        require!(lp_amount > 0, BarterError::AmountTooSmall);
+        require!(lp_amount < self.lp_supply, BarterError::InsufficientLiquidity);
+        let share = |total: u64| (total as u128 * lp_amount as u128 / self.lp_supply as u128) as u64;
+        let (amount_a, amount_b) = (share(self.total_liquidity_a), share(self.total_liquidity_b));
+        require!(amount_a > 0 || amount_b > 0, BarterError::AmountTooSmall);
+
+        self.lp_supply -= lp_amount;
+        self.total_liquidity_a -= amount_a;
+        self.total_liquidity_b -= amount_b;
+        Ok((amount_a, amount_b))
+    }
+
+    /// Check whether moving to `new_price` exceeds the pool's single-step move limit
+    pub fn is_large_price_move(&self, new_price: u64) -> bool {
+        if self.max_price_move_bps == 0 || self.oracle_price == 0 {
+            return false;
+        }
+        let diff = new_price.abs_diff(self.oracle_price) as u128;
+        diff * BPS_PRECISION as u128 > self.oracle_price as u128 * self.max_price_move_bps as u128
+    }
+
+    /// Reject a pushed `new_price` that moves more than `max_price_deviation_bps` away from
+    /// the current oracle price
+    pub fn check_price_deviation(&self, new_price: u64) -> Result<()> {
+        if self.max_price_deviation_bps == 0 || self.oracle_price == 0 {
+            return Ok(());
+        }
+        let diff = new_price.abs_diff(self.oracle_price) as u128;
+        require!(diff * BPS_PRECISION as u128 <= self.oracle_price as u128 * self.max_price_deviation_bps as u128,
+                BarterError::PriceDeviationTooLarge);
+        Ok(())
+    }
+
+    /// Commit a new oracle price, feeding the EMA and price history
+    pub fn apply_oracle_price(&mut self, new_price: u64, current_time: i64) {
+        self.oracle_price = new_price;
+        self.last_oracle_update = current_time;
+        self.update_ema(new_price);
+        self.update_price_history(new_price);
+    }
+
+    /// Apply a pushed price, staging it for confirmation instead if it is a large move.
+    /// Returns `true` if the price was staged.
+    pub fn push_oracle_price(&mut self, new_price: u64, current_time: i64) -> bool {
+        if self.is_large_price_move(new_price) {
+            self.pending_price = new_price;
+            self.pending_price_time = current_time;
+            return true;
+        }
+        self.pending_price = 0;
+        self.apply_oracle_price(new_price, current_time);
+        false
+    }
+
+    /// Commit the staged price once its confirmation delay has passed. A staged price left
+    /// unconfirmed for longer than `MAX_ORACLE_AGE_SECONDS` after that is stale and expires.
+    pub fn confirm_pending_price(&mut self, current_time: i64) -> Result<u64> {
+        require!(self.pending_price > 0, BarterError::NoPendingPrice);
+        let confirmable_at = self.pending_price_time.saturating_add(self.price_confirm_delay_seconds);
+        require!(current_time >= confirmable_at, BarterError::PriceConfirmationTooEarly);
+        require!(current_time <= confirmable_at.saturating_add(MAX_ORACLE_AGE_SECONDS), BarterError::PendingPriceExpired);
+
+        let price = self.pending_price;
+        self.pending_price = 0;
+        self.apply_oracle_price(price, current_time);
+        Ok(price)
+    }
+
+    /// Quote the fee a swap executed at `current_time` would pay
+    pub fn quote_fee(&self, current_time: i64) -> Result<FeeQuote> {
+        Ok(FeeQuote {
+            fee_bps: self.calculate_swap_fee(current_time)?,
+            base_fee_bps: self.fee_bps,
+            dynamic_fee_enabled: self.dynamic_fee_enabled,
+            volatility: self.calculate_volatility()?,
+            volatility_threshold: self.volatility_threshold,
+            oracle_stale: self.oracle_freshness(current_time) == OracleFreshness::Stale,
+        })
+    }
+
+    /// Round a pushed price to the nearest `price_tick`, never below one tick.
+    /// Returns the price unchanged when ticks are disabled.
+    pub fn snap_to_tick(&self, price: u64) -> u64 {
+        if self.price_tick == 0 || price == 0 {
+            return price;
+        }
+        let tick = self.price_tick as u128;
+        let ticks = (price as u128 + tick / 2) / tick;
+        u64::try_from(ticks.max(1) * tick).unwrap_or(u64::MAX / self.price_tick * self.price_tick)
+    }
+
     /// Update price history
     pub fn update_price_history(&mut self, new_price: u64) {
         self.price_history[self.history_index as usize] = new_price;
         self.history_index = ((self.history_index as usize + 1) % 24) as u8;
+        self.history_len = self.history_len.saturating_add(1).min(24);
     }
 
-    /// Check if oracle price is stale
-    pub fn is_oracle_stale(&self) -> Result<bool> {
-        let current_time = Clock::get()?.unix_timestamp;
-        let age = current_time - self.last_oracle_update;
-        Ok(age > MAX_ORACLE_AGE_SECONDS)
+    /// Average of the most recent `window` recorded prices, walking back from `history_index`
+    /// across the buffer's wraparound. Before any price is recorded, the oracle price is used.
+    pub fn calculate_twap(&self, window: u8) -> Result<u64> {
+        require!(window > 0 && window <= 24, BarterError::InvalidPoolConfiguration);
+        let count = window.min(self.history_len) as usize;
+        if count == 0 {
+            return Ok(self.oracle_price);
+        }
+
+        let sum: u128 = (1..=count)
+            .map(|back| self.price_history[(self.history_index as usize + 24 - back) % 24] as u128)
+            .sum();
+        Ok((sum / count as u128) as u64)
     }
-}
 
-/// State account for a liquidity pool. This is an oracle-based pool.
-/// PDA seeds: `[b"liquidity_pool", mint_a.key().as_ref(), mint_b.key().as_ref()]`
-#[account]
-pub struct LiquidityPool {
-    /// The mint address of the first token in the pair (token A).
-    pub mint_a: Pubkey,
-    /// The mint address of the second token in the pair (token B).
-    pub mint_b: Pubkey,
-    /// The designated authority allowed to push price updates.
-    pub oracle_authority: Pubkey,
-    /// The AI-provided price of token A in terms of token B, with 9 decimals of precision.
-    pub oracle_price: u64,
-    /// The Unix timestamp of the last successful price update.
-    pub last_oracle_update: i64,
-    /// The bump seed for `vault_a`.
-    pub vault_a_bump: u8,
-    /// The bump seed for `vault_b`.
-    pub vault_b_bump: u8,
-}
+    /// Validate TWAP pricing: the window must fit the 24-entry price history
+    pub fn validate_twap_config(use_twap: bool, twap_window: u8) -> Result<()> {
+        if use_twap {
+            require!(twap_window > 0 && twap_window <= 24, BarterError::InvalidPoolConfiguration);
+        }
+        Ok(())
+    }
 
-impl LiquidityPool {
-    /// The total disk space required for a `LiquidityPool` account in bytes.
-    /// Pubkey(32)*3 + u64(8) + i64(8) + u8(1) + u8(1) = 96 + 16 + 2 = 114 bytes.
-    pub const LEN: usize = 32 + 32 + 32 + 8 + 8 + 1 + 1;
-}
\ No newline at end of file
+    /// Classify the oracle price age against the pool's staleness thresholds
+    pub fn oracle_freshness(&self, current_time: i64) -> OracleFreshness {
+        let age = current_time.saturating_sub(self.last_oracle_update);
+        if age > self.hard_stale_seconds {
+            OracleFreshness::Expired
+        } else if age > self.max_staleness_seconds {
+            OracleFreshness::Stale
+        } else {
+            OracleFreshness::Fresh
+        }
+    }
+
+    /// Calculate the fee for a swap, adding the stale-risk fee during the grace window
+    pub fn calculate_swap_fee(&self, current_time: i64) -> Result<u16> {
+        let fee_bps = self.calculate_dynamic_fee()?;
+        match self.oracle_freshness(current_time) {
+            OracleFreshness::Fresh => Ok(fee_bps),
+            OracleFreshness::Stale => Ok(std::cmp::min(fee_bps.saturating_add(self.stale_fee_bps), 1000)), // Cap at 10%
+            OracleFreshness::Expired => err!(BarterError::OraclePriceStale),
+        }
+    }
+}
@@ -0,0 +1,108 @@
+//! # Switchboard V2 Aggregator Reader
+//!
+//! Minimal, dependency-free reader for Switchboard V2 `AggregatorAccountData`
+//! accounts. The upstream `switchboard-v2` crate pins an older Anchor/Solana
+//! toolchain, so the handful of fields the DEX needs are decoded directly from
+//! the account's zero-copy (packed) layout.
+//!
+//! ## Layout
+//!
+//! Offsets below are measured from the start of the account data, including
+//! the 8-byte Anchor discriminator. Only the `latest_confirmed_round` fields
+//! the pool consumes are read:
+//!
+//! - `round_open_timestamp`: `i64` at [`ROUND_OPEN_TIMESTAMP_OFFSET`]
+//! - `result`: `SwitchboardDecimal` (`i128` mantissa + `u32` scale) at [`RESULT_OFFSET`]
+//! - `std_deviation`: `SwitchboardDecimal` at [`STD_DEVIATION_OFFSET`]
+
+use anchor_lang::prelude::*;
+use crate::error::BarterError;
+
+/// Switchboard V2 program that owns aggregator accounts.
+pub const SWITCHBOARD_V2_PROGRAM_ID: Pubkey =
+    anchor_lang::solana_program::pubkey!("SW1TCH7qEPTdLsDHRgPuMQjbQxKdH2aBStViMFnt64f");
+
+/// Anchor discriminator of `AggregatorAccountData`.
+pub const AGGREGATOR_DISCRIMINATOR: [u8; 8] = [217, 230, 65, 101, 201, 162, 27, 125];
+
+/// Offset of `latest_confirmed_round` within the aggregator account.
+pub const LATEST_CONFIRMED_ROUND_OFFSET: usize = 341;
+/// Offset of `latest_confirmed_round.round_open_timestamp`.
+pub const ROUND_OPEN_TIMESTAMP_OFFSET: usize = LATEST_CONFIRMED_ROUND_OFFSET + 17;
+/// Offset of `latest_confirmed_round.result`.
+pub const RESULT_OFFSET: usize = ROUND_OPEN_TIMESTAMP_OFFSET + 8;
+/// Offset of `latest_confirmed_round.std_deviation`.
+pub const STD_DEVIATION_OFFSET: usize = RESULT_OFFSET + SWITCHBOARD_DECIMAL_LEN;
+
+/// Serialized size of a `SwitchboardDecimal`.
+const SWITCHBOARD_DECIMAL_LEN: usize = 16 + 4;
+
+/// Fixed-point decimal as stored by Switchboard: `mantissa * 10^-scale`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SwitchboardDecimal {
+    pub mantissa: i128,
+    pub scale: u32,
+}
+
+impl SwitchboardDecimal {
+    fn read(data: &[u8], offset: usize) -> Self {
+        let mut mantissa = [0u8; 16];
+        mantissa.copy_from_slice(&data[offset..offset + 16]);
+        let mut scale = [0u8; 4];
+        scale.copy_from_slice(&data[offset + 16..offset + SWITCHBOARD_DECIMAL_LEN]);
+        Self {
+            mantissa: i128::from_le_bytes(mantissa),
+            scale: u32::from_le_bytes(scale),
+        }
+    }
+
+    /// Rescale to a fixed-point `u64` with `precision` units per whole value.
+    /// Negative values and values that do not fit in a `u64` are rejected.
+    pub fn to_precision(&self, precision: u64) -> Result<u64> {
+        require!(self.mantissa >= 0, BarterError::OraclePriceFetchFailed);
+        let mantissa = self.mantissa as u128;
+        let precision = precision as u128;
+        let divisor = 10u128.checked_pow(self.scale).ok_or(BarterError::Overflow)?;
+
+        let scaled = mantissa
+            .checked_mul(precision)
+            .ok_or(BarterError::Overflow)?
+            .checked_div(divisor)
+            .ok_or(BarterError::Overflow)?;
+        u64::try_from(scaled).map_err(|_| error!(BarterError::Overflow))
+    }
+}
+
+/// The latest confirmed round of a Switchboard aggregator.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AggregatorRound {
+    pub result: SwitchboardDecimal,
+    pub std_deviation: SwitchboardDecimal,
+    pub round_open_timestamp: i64,
+}
+
+impl AggregatorRound {
+    /// Decode the latest confirmed round from raw aggregator account data
+    pub fn parse(data: &[u8]) -> Result<Self> {
+        require!(
+            data.len() >= STD_DEVIATION_OFFSET + SWITCHBOARD_DECIMAL_LEN
+                && data[..8] == AGGREGATOR_DISCRIMINATOR,
+            BarterError::SwitchboardFeedNotFound
+        );
+
+        let mut timestamp = [0u8; 8];
+        timestamp.copy_from_slice(&data[ROUND_OPEN_TIMESTAMP_OFFSET..RESULT_OFFSET]);
+        Ok(Self {
+            result: SwitchboardDecimal::read(data, RESULT_OFFSET),
+            std_deviation: SwitchboardDecimal::read(data, STD_DEVIATION_OFFSET),
+            round_open_timestamp: i64::from_le_bytes(timestamp),
+        })
+    }
+
+    /// Reject rounds older than `max_staleness_seconds`
+    pub fn check_fresh(&self, current_time: i64, max_staleness_seconds: i64) -> Result<()> {
+        let age = current_time.saturating_sub(self.round_open_timestamp);
+        require!(age <= max_staleness_seconds, BarterError::OraclePriceStale);
+        Ok(())
+    }
+}
@@ -13,10 +13,26 @@ pub enum FactoryError {
     DivisionByZero,
     #[msg("Insufficient SOL funds to complete the purchase at the current token price.")]
     InsufficientFunds,
+    #[msg("The SOL amount is too small to buy a single base unit of token at the current price.")]
+    PurchaseTooSmallForPrice,
+    #[msg("The purchase would mint fewer tokens than the minimum specified, indicating slippage tolerance was exceeded.")]
+    SlippageExceeded,
     #[msg("The signer's public key does not match the authority stored in the launch state.")]
     AuthorityMismatch,
     #[msg("The provided affiliate public key does not match the key stored in the affiliate info account.")]
     AffiliateMismatch,
+    #[msg("Purchases crediting an affiliate must pass the affiliate accounts and the affiliate program.")]
+    AffiliateAccountsMissing,
+    #[msg("Passing the affiliate program requires passing its ecosystem stats account too.")]
+    EcosystemStatsMissing,
+    #[msg("The affiliate is not on this launch's approved affiliate list.")]
+    AffiliateNotApproved,
+    #[msg("The affiliate allowlist is too long or lists an affiliate twice.")]
+    InvalidAffiliateAllowlist,
+    #[msg("Anti-snipe window must be non-negative and its per-wallet cap no looser than the normal cap.")]
+    InvalidAntiSnipeConfig,
+    #[msg("The beneficiary account does not match the purchase recipient.")]
+    RecipientMismatch,
 
     // Launch state errors
     #[msg("Launch is not currently active.")]
@@ -27,6 +43,16 @@ pub enum FactoryError {
     InvalidLaunchTime,
     #[msg("Invalid pricing model specified.")]
     InvalidPricingModel,
+    #[msg("The launch has been finalized and its mint authority revoked.")]
+    LaunchFinalized,
+    #[msg("Purchases are paused by the launch authority.")]
+    LaunchPaused,
+    #[msg("Launch can only be closed once finalized, with all vesting claimed and the SOL vault empty.")]
+    LaunchNotSettled,
+    #[msg("The purchase would take the launch's lifetime raise past the maximum supported amount.")]
+    RaiseCeilingExceeded,
+    #[msg("Fair launches must use a fixed price with no fees, affiliate commission or cashback.")]
+    FairLaunchViolation,
 
     // Vesting errors
     #[msg("Vesting schedule not found or invalid.")]
@@ -37,6 +63,16 @@ pub enum FactoryError {
     VestingNotComplete,
     #[msg("Invalid vesting parameters.")]
     InvalidVestingParams,
+    #[msg("This launch is no longer accepting new vested purchases.")]
+    VestingClosedToNewSchedules,
+    #[msg("This vesting schedule cannot be revoked.")]
+    VestingNotRevocable,
+    #[msg("This vesting schedule has been revoked.")]
+    VestingRevoked,
+    #[msg("Claim would exceed the vesting schedule's total amount.")]
+    VestingOverClaim,
+    #[msg("This beneficiary already holds a revocable vesting grant on this launch.")]
+    VestingGrantExists,
 
     // Anti-bot errors
     #[msg("Purchase amount is below minimum allowed.")]
@@ -45,14 +81,76 @@ pub enum FactoryError {
     PurchaseAmountTooHigh,
     #[msg("Purchase cooldown is still active.")]
     PurchaseCooldownActive,
+    #[msg("Purchase would exceed the launch's per-wallet token cap.")]
+    WalletCapExceeded,
+    #[msg("Purchase exceeds the launch's per-transaction token cap; split it across transactions.")]
+    TransactionCapExceeded,
     #[msg("Anti-bot validation failed.")]
     AntiBotValidationFailed,
 
+    // Withdrawal errors
+    #[msg("Total SOL collected has not reached the withdrawal threshold.")]
+    ThresholdNotMet,
+    #[msg("The withdrawal would leave the SOL vault below its rent-exempt minimum.")]
+    InsufficientVaultBalance,
+
+    // Payment mode errors
+    #[msg("The payment mode can only be changed before the first purchase.")]
+    PaymentModeLocked,
+    #[msg("SPL-token payment requires a payment mint and vault.")]
+    MissingPaymentMint,
+    #[msg("This instruction does not support the launch's payment mode.")]
+    UnsupportedPaymentMode,
+    #[msg("Launches with a soft cap only accept native SOL, the currency refunds are paid in.")]
+    SoftCapRequiresNativeSol,
+    #[msg("The platform does not accept this payment mint.")]
+    PaymentMintNotAllowed,
+    #[msg("Payment mint allowlist must hold at most 8 distinct, non-default mints.")]
+    InvalidPaymentMintAllowlist,
+    #[msg("The affiliate has not opted in to compounding commissions.")]
+    CommissionCompoundingDisabled,
+
+    // Soft cap, hard cap and refund errors
+    #[msg("Refunds are not available until the launch has ended.")]
+    LaunchStillActive,
+    #[msg("The launch reached its soft cap, so refunds are not available.")]
+    SoftCapReached,
+    #[msg("The launch has not reached its soft cap, so funds cannot be withdrawn.")]
+    SoftCapNotMet,
+    #[msg("The purchase would take the launch past its hard cap.")]
+    HardCapReached,
+    #[msg("This buyer has already been refunded.")]
+    AlreadyRefunded,
+
+    // Whitelist errors
+    #[msg("Buyer is not on this launch's whitelist.")]
+    NotWhitelisted,
+    #[msg("Whitelist batch is empty or exceeds the maximum batch size.")]
+    InvalidWhitelistBatch,
+    #[msg("Buyer is blocked from purchasing in this launch.")]
+    BuyerBlocked,
+
     // Fee errors
     #[msg("Invalid fee configuration.")]
     InvalidFeeConfig,
     #[msg("Fee calculation overflow.")]
     FeeCalculationOverflow,
+    #[msg("Platform and affiliate fees consume the entire purchase amount.")]
+    FeesExceedPurchase,
+    #[msg("Fee split must have 1 to 4 distinct recipients with shares summing to 10000 bps.")]
+    InvalidFeeSplit,
+    #[msg("Fee split recipient accounts do not match the launch's fee split configuration.")]
+    FeeSplitRecipientMismatch,
+
+    // Metadata errors
+    #[msg("Metadata name and symbol must be non-empty, and name, symbol and URI within 32, 10 and 200 bytes.")]
+    InvalidMetadata,
+
+    // Snapshot errors
+    #[msg("Merkle root must be non-zero.")]
+    InvalidMerkleRoot,
+    #[msg("Merkle proof is too long or does not prove inclusion in the recorded root.")]
+    InvalidMerkleProof,
 
     // Time-related errors
     #[msg("Invalid timestamp provided.")]
@@ -63,6 +161,8 @@ pub enum FactoryError {
     // Account validation errors
     #[msg("Invalid account state for operation.")]
     InvalidAccountState,
+    #[msg("Token account is not for the launch's token mint.")]
+    InvalidMint,
     #[msg("Account not initialized.")]
     AccountNotInitialized,
 }
\ No newline at end of file
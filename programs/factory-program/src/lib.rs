@@ -17,8 +17,8 @@
 //! ## Key Features
 //!
 //! - **Bonding Curve Pricing**: Dynamic price adjustment based on tokens sold
-//! - **Vesting Schedules**: Linear vesting with configurable cliffs and durations
-//! - **Anti-Bot Measures**: Purchase limits, cooldowns, and amount validation
+//! - **Vesting Schedules**: Linear vesting with configurable cliffs and durations, or stepped milestone unlocks
+//! - **Anti-Bot Measures**: Purchase limits, cooldowns, amount validation, and an anti-snipe opening window
 //! - **Cross-Program Integration**: Direct CPI calls to affiliate program for commission processing
 //! - **Launch Analytics**: Comprehensive tracking of sales, fees, and purchase metrics
 //!
@@ -26,9 +26,35 @@
 //!
 //! - [`create_launch`]: Initialize new token launches with full configuration
 //! - [`buy_tokens`]: Process token purchases with anti-bot validation and affiliate commissions
+//! - [`quote_purchase`]: Read-only quote of what a `buy_tokens` call would cost and mint
+//! - [`buy_tokens_with_spl`]: Token purchases paid in the launch's SPL payment token
+//! - [`compound_commission`]: Reinvests affiliate commission tokens into an SPL-token launch
+//! - [`finalize_launch`]: Revokes the mint authority once the sale has ended
+//! - [`create_vesting_grant`]: Authority-only vested grants for team members and advisors, optionally revocable
+//! - [`revoke_vesting`]: Stops a revocable grant, paying out what vested and burning or reclaiming the rest
+//! - [`close_vesting`]: Closes a fully claimed vesting schedule, returning rent to its rent recipient
+//! - [`close_launch`]: Closes a finalized launch once vesting is claimed and the vault is empty, returning rent to the authority
 //! - [`withdraw_sol`]: Authority-only withdrawal of collected SOL funds
+//! - [`withdraw_sol_amount`]: Authority-only partial withdrawal that keeps the vault rent exempt
+//! - [`withdraw_payment`]: Authority-only withdrawal of collected SPL payment tokens
+//! - [`refund`]: Buyer refunds when a launch ends below its soft cap
+//! - [`sweep_vault_dust`]: Authority-only sweep of residual lamports left in the SOL vault
 //! - [`claim_vested_tokens`]: Claim tokens from vesting schedules
+//! - [`get_vesting_timeline`]: Read-only time to cliff, time to full vesting and release rate
+//! - [`health_check`]: Logs and returns the program's build version
 //! - [`update_launch`]: Modify launch parameters post-creation
+//! - [`set_vesting_accepting_new`]: Stop or resume new vesting schedules without affecting claims
+//! - [`update_fees`]: Atomically replace the launch's fee configuration
+//! - [`set_fee_split`]: Split platform fees across up to four recipients, e.g. a DAO treasury
+//! - [`migrate_payment_mode`]: Switch between native-SOL and SPL-token payment before the first purchase
+//! - [`set_allowed_payment_mints`]: Platform-admin allowlist of SPL mints launches may take payment in
+//! - [`add_whitelist_entries`]: Authority-only batch allowlisting of buyers
+//! - [`set_paused`]: Authority-only emergency stop for purchases; claims and withdrawals stay available
+//! - [`set_whitelist_root`]: Authority-only merkle allowlist for private sales, checked against buyer proofs
+//! - [`set_approved_affiliates`]: Authority-only allowlist of affiliates that may earn commission
+//! - [`block_buyer`] / [`unblock_buyer`]: Authority-only blocklist management
+//! - [`set_launch_metadata`]: Authority-only token name, symbol and URI for wallets and explorers
+//! - [`record_snapshot`] / [`verify_snapshot_claim`]: Off-chain holder snapshots anchored by an on-chain merkle root
 //!
 //! ## Security Features
 //!
@@ -48,10 +74,10 @@
 use anchor_lang::prelude::*;
 use anchor_lang::system_program;
 use anchor_spl::associated_token::AssociatedToken;
-use anchor_spl::token::{self, Mint, Token, TokenAccount};
+use anchor_spl::token::{self, spl_token::instruction::AuthorityType, Mint, Token, TokenAccount};
 
 // CPI client for the affiliate program.
-use affiliate_program::cpi::accounts::ProcessCommission;
+use affiliate_program::cpi::accounts::{ProcessCommission, RecordLaunchActivity};
 use affiliate_program::program::AffiliateProgram;
 use affiliate_program;
 
@@ -66,25 +92,63 @@ use error::*;
 
 declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
 
+/// Crate version of this build, logged by `health_check` so operators can tell which build
+/// processed a transaction.
+pub const PROGRAM_VERSION: &str = env!("CARGO_PKG_VERSION");
+
 /// Enhanced instruction to create a launch with advanced configuration
 #[derive(AnchorSerialize, AnchorDeserialize)]
 pub struct CreateLaunchArgs {
     pub initial_price: u64,
     pub slope: u64,
     pub pricing_model: PricingModel,
+    /// Lowest price a Dutch auction decays to; must not exceed `initial_price`. Ignored by other models.
+    pub floor_price: u64,
     pub max_tokens: u64,
     pub launch_start_time: i64,
     pub launch_end_time: i64,
     pub vesting_enabled: bool,
     pub vesting_duration_seconds: i64,
     pub vesting_cliff_seconds: i64,
+    /// Unlock steps for milestone vesting; empty for linear vesting.
+    pub vesting_milestones: Vec<VestingMilestone>,
     pub anti_bot_level: AntiBotLevel,
     pub min_purchase_amount: u64,
     pub max_purchase_amount: u64,
     pub purchase_cooldown_seconds: i64,
+    /// Most tokens a single wallet may buy across the launch (0 = unlimited).
+    pub max_tokens_per_wallet: u64,
+    /// Most tokens a single purchase may mint (0 = unlimited).
+    pub max_tokens_per_tx: u64,
+    /// Seconds after `launch_start_time` during which anti-snipe rules apply (0 = disabled).
+    pub anti_snipe_seconds: i64,
+    /// Stricter per-wallet cap during the anti-snipe window (0 = the normal cap applies).
+    pub anti_snipe_max_tokens_per_wallet: u64,
+    /// Extra platform fee in basis points during the anti-snipe window (0 = no surcharge).
+    pub anti_snipe_fee_bps: u16,
+    /// Seconds after `launch_start_time` during which fees are reduced (0 = no fee holiday).
+    pub fee_holiday_seconds: i64,
+    /// Share of the platform and affiliate fees, in basis points, waived during the fee holiday.
+    pub fee_holiday_discount_bps: u16,
     pub affiliate_fee_bps: u16,
     pub platform_fee_bps: u16,
     pub platform_fee_recipient: Pubkey,
+    /// Total commission tokens affiliates may earn across the launch (0 = unlimited).
+    pub max_affiliate_commission_tokens: u64,
+    /// Extra tokens minted to every buyer, in basis points of their purchase (0 = disabled).
+    pub buyer_cashback_bps: u16,
+    /// Only buyers with a `WhitelistEntry` may purchase.
+    pub whitelist_enabled: bool,
+    /// Merkle root of allowed buyers; buyers must prove inclusion or hold a `WhitelistEntry` (None = ungated).
+    pub whitelist_root: Option<[u8; 32]>,
+    /// Lamports that must be raised before the authority can withdraw (0 = no minimum).
+    pub withdraw_threshold_sol: u64,
+    /// Minimum raise in lamports; below it buyers can refund after the launch ends (0 = no soft cap).
+    pub soft_cap_lamports: u64,
+    /// Maximum net raise in lamports; purchases past it are rejected (0 = no hard cap).
+    pub hard_cap_lamports: u64,
+    /// Fair launch preset: requires `FixedPrice`, zero fees and zero cashback, and rejects affiliates.
+    pub fair_launch: bool,
 }
 
 /// Instruction to claim vested tokens
@@ -102,6 +166,14 @@ pub struct UpdateLaunchArgs {
     pub new_max_purchase_amount: Option<u64>,
 }
 
+/// Instruction to replace all fee parameters at once
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct UpdateFeesArgs {
+    pub affiliate_fee_bps: u16,
+    pub platform_fee_bps: u16,
+    pub platform_fee_recipient: Pubkey,
+}
+
 #[program]
 pub mod factory_program {
     use super::*;
@@ -118,15 +190,42 @@ pub mod factory_program {
         let current_time = Clock::get()?.unix_timestamp;
         require!(args.launch_start_time >= current_time, FactoryError::InvalidLaunchTime);
         require!(args.launch_end_time > args.launch_start_time, FactoryError::InvalidLaunchTime);
-        require!(args.affiliate_fee_bps <= MAX_RATE_BPS, FactoryError::InvalidFeeConfig);
-        require!(args.platform_fee_bps <= MAX_RATE_BPS, FactoryError::InvalidFeeConfig);
-
-        if args.vesting_enabled {
-            require!(args.vesting_duration_seconds >= MIN_VESTING_DURATION_SECONDS, FactoryError::InvalidVestingParams);
-            require!(args.vesting_duration_seconds <= MAX_VESTING_DURATION_SECONDS, FactoryError::InvalidVestingParams);
-            require!(args.vesting_cliff_seconds <= args.vesting_duration_seconds, FactoryError::InvalidVestingParams);
+        // The anti-snipe surcharge stacks on the platform fee, so validate their combined peak
+        let peak_platform_fee_bps = args.platform_fee_bps.saturating_add(args.anti_snipe_fee_bps);
+        LaunchState::validate_fee_config(args.affiliate_fee_bps, peak_platform_fee_bps)?;
+        require!(args.buyer_cashback_bps <= MAX_RATE_BPS, FactoryError::InvalidFeeConfig);
+        LaunchState::validate_caps(args.soft_cap_lamports, args.hard_cap_lamports)?;
+        LaunchState::validate_whitelist_root(&args.whitelist_root)?;
+        LaunchState::validate_anti_snipe_config(
+            args.anti_snipe_seconds,
+            args.anti_snipe_max_tokens_per_wallet,
+            args.max_tokens_per_wallet,
+        )?;
+        LaunchState::validate_fee_holiday_config(args.fee_holiday_seconds, args.fee_holiday_discount_bps)?;
+        if args.fair_launch {
+            LaunchState::validate_fair_launch_config(
+                args.pricing_model, args.affiliate_fee_bps, peak_platform_fee_bps, args.buyer_cashback_bps,
+            )?;
+        }
+        if args.pricing_model == PricingModel::ExponentialBondingCurve {
+            // The multiplier is (1 + rate) in 1e9 fixed point; below 1.0 the price would decay
+            require!(args.slope >= FIXED_POINT_ONE, FactoryError::InvalidPricingModel);
+        }
+        if args.pricing_model == PricingModel::DutchAuction {
+            require!(args.floor_price <= args.initial_price, FactoryError::InvalidPricingModel);
         }
 
+        LaunchState::validate_vesting_config(
+            args.vesting_enabled,
+            args.vesting_duration_seconds,
+            args.vesting_cliff_seconds,
+            &args.vesting_milestones,
+        )?;
+
+        // The mint is created here today, but keep the guard so a launch can never be
+        // opened over a mint that already has tokens in circulation.
+        LaunchState::validate_fresh_mint(ctx.accounts.token_mint.supply)?;
+
         let state = &mut ctx.accounts.launch_state;
         state.authority = ctx.accounts.authority.key();
         state.token_mint = ctx.accounts.token_mint.key();
@@ -139,11 +238,14 @@ pub mod factory_program {
         state.initial_price = args.initial_price;
         state.slope = args.slope;
         state.tokens_sold = 0;
+        state.floor_price = args.floor_price;
 
         // Vesting configuration
         state.vesting_enabled = args.vesting_enabled;
         state.vesting_duration_seconds = args.vesting_duration_seconds;
         state.vesting_cliff_seconds = args.vesting_cliff_seconds;
+        state.vesting_accepting_new = true;
+        state.vesting_milestones = args.vesting_milestones;
 
         // Anti-bot configuration
         state.anti_bot_level = args.anti_bot_level;
@@ -151,6 +253,13 @@ pub mod factory_program {
         state.max_purchase_amount = args.max_purchase_amount;
         state.purchase_cooldown_seconds = args.purchase_cooldown_seconds;
         state.last_purchase_timestamp = current_time;
+        state.max_tokens_per_wallet = args.max_tokens_per_wallet;
+        state.max_tokens_per_tx = args.max_tokens_per_tx;
+        state.anti_snipe_seconds = args.anti_snipe_seconds;
+        state.anti_snipe_max_tokens_per_wallet = args.anti_snipe_max_tokens_per_wallet;
+        state.anti_snipe_fee_bps = args.anti_snipe_fee_bps;
+        state.fee_holiday_seconds = args.fee_holiday_seconds;
+        state.fee_holiday_discount_bps = args.fee_holiday_discount_bps;
 
         // Launch constraints
         state.max_tokens = args.max_tokens;
@@ -161,14 +270,82 @@ pub mod factory_program {
         state.affiliate_fee_bps = args.affiliate_fee_bps;
         state.platform_fee_bps = args.platform_fee_bps;
         state.platform_fee_recipient = args.platform_fee_recipient;
+        state.max_affiliate_commission_tokens = args.max_affiliate_commission_tokens;
+        state.affiliate_commission_minted = 0;
+        state.approved_affiliates = Vec::new();
+
+        // Buyer cashback
+        state.buyer_cashback_bps = args.buyer_cashback_bps;
+
+        // Whitelist gating
+        state.whitelist_enabled = args.whitelist_enabled;
+        state.whitelist_root = args.whitelist_root;
+
+        // Withdrawal gating
+        state.withdraw_threshold_sol = args.withdraw_threshold_sol;
+
+        // Soft and hard caps
+        state.soft_cap_lamports = args.soft_cap_lamports;
+        state.hard_cap_lamports = args.hard_cap_lamports;
+
+        // Payment configuration; launches start on native SOL
+        state.payment_mode = PaymentMode::NativeSol;
+        state.payment_mint = Pubkey::default();
+
+        // Platform fees go to `platform_fee_recipient` until a fee split is configured
+        state.fee_split_config = Pubkey::default();
+
+        // Launches stay open for minting until finalized
+        state.finalized = false;
+        state.paused = false;
+        state.fair_launch = args.fair_launch;
 
         // Initialize analytics
         state.total_sol_collected = 0;
         state.total_fees_collected = 0;
         state.purchase_count = 0;
+        state.vesting_unclaimed_tokens = 0;
+        state.granted_tokens = 0;
 
         msg!("Enhanced launch created for mint: {} with pricing model: {:?}",
              state.token_mint, state.pricing_model);
+        emit!(LaunchCreatedEvent {
+            launch_state: state.key(),
+            authority: state.authority,
+            token_mint: state.token_mint,
+            pricing_model: state.pricing_model,
+            initial_price: state.initial_price,
+            slope: state.slope,
+            max_tokens: state.max_tokens,
+            launch_start_time: state.launch_start_time,
+            launch_end_time: state.launch_end_time,
+            timestamp: current_time,
+        });
+
+        // Count the launch in the ecosystem-wide stats when the affiliate program is available
+        let Some(affiliate_program) = &ctx.accounts.affiliate_program else {
+            msg!("Affiliate program not provided; launch not counted in ecosystem stats");
+            return Ok(());
+        };
+        let ecosystem_stats = ctx.accounts.ecosystem_stats.as_ref()
+            .ok_or(FactoryError::EcosystemStatsMissing)?;
+        let authority_key = state.authority;
+        let token_mint_key = state.token_mint;
+        let seeds = pda_utils::launch_state_signer_seeds(&authority_key, &token_mint_key, &ctx.bumps.launch_state);
+        affiliate_program::cpi::record_launch_activity(
+            CpiContext::new_with_signer(
+                affiliate_program.to_account_info(),
+                RecordLaunchActivity {
+                    launch_state: state.to_account_info(),
+                    ecosystem_stats: ecosystem_stats.to_account_info(),
+                    payer: ctx.accounts.authority.to_account_info(),
+                    system_program: ctx.accounts.system_program.to_account_info(),
+                },
+                &[&seeds[..]],
+            ),
+            1,
+            0,
+        )?;
         Ok(())
     }
 
@@ -182,54 +359,120 @@ pub mod factory_program {
     /// - `sol_amount`: The amount of SOL (in lamports) the buyer is spending.
     /// - `affiliate_key`: An optional Pubkey of the referring affiliate.
     /// - `enable_vesting`: Whether to create a vesting schedule for the purchased tokens.
-    pub fn buy_tokens(
-        ctx: Context<BuyTokens>,
+    /// - `min_tokens_out`: Slippage guard; the purchase fails if it would mint fewer tokens than this.
+    ///   Pass `0` to opt out.
+    /// - `whitelist_proof`: Merkle proof of the buyer's inclusion in the launch's `whitelist_root`,
+    ///   sibling hashes from leaf to root. Empty when the launch has no root or the buyer has a `WhitelistEntry`.
+    /// - `recipient`: Wallet that receives the tokens (and any vesting schedule) instead of the signer,
+    ///   e.g. a custody account. The signer still pays SOL, fees and rent. Per-wallet rules — blocklist,
+    ///   whitelist, cooldown and wallet cap — apply to the recipient. Must match the `beneficiary` account.
+    ///
+    /// When the launch has a fee split configured, the split's recipient accounts must be passed
+    /// in `remaining_accounts`, in the order they appear in the `FeeSplitConfig`. They are followed
+    /// by the affiliate's ancestors as `(AffiliateInfo, commission token account)` pairs, parent
+    /// first, to share the commission up the referral chain.
+    pub fn buy_tokens<'info>(
+        ctx: Context<'_, '_, '_, 'info, BuyTokens<'info>>,
         sol_amount: u64,
         affiliate_key: Option<Pubkey>,
         enable_vesting: bool,
+        min_tokens_out: u64,
+        whitelist_proof: Vec<[u8; 32]>,
+        recipient: Option<Pubkey>,
     ) -> Result<()> {
         require!(sol_amount > 0, FactoryError::InvalidAmount);
+        // Affiliate accounts are optional and only validated when the purchase credits an affiliate
+        if let Some(key) = affiliate_key {
+            ctx.accounts.check_affiliate_accounts(&key)?;
+        }
         let state = &mut ctx.accounts.launch_state;
+        require!(state.payment_mode == PaymentMode::NativeSol, FactoryError::UnsupportedPaymentMode);
 
         // Validate launch is active and within constraints
+        state.check_not_finalized()?;
+        state.check_not_paused()?;
         require!(state.is_launch_active()?, FactoryError::LaunchNotActive);
         require!(!state.is_max_supply_reached(), FactoryError::MaxSupplyReached);
 
-        // Anti-bot validation
-        state.validate_purchase_amount(sol_amount)?;
+        // Per-wallet rules apply to whoever receives the tokens, which is the signer unless `recipient` is set
+        let beneficiary_key = ctx.accounts.beneficiary.key();
+        let buying_for_recipient = recipient.is_some_and(|recipient| recipient != ctx.accounts.buyer.key());
+
+        // Blocked buyers can never purchase; whitelist-gated launches require the buyer's entry
+        let blocked_buyer = &ctx.accounts.blocked_buyer;
+        LaunchState::check_buyer_not_blocked(blocked_buyer.owner == &crate::ID && !blocked_buyer.data_is_empty())?;
+        state.check_whitelist(ctx.accounts.whitelist_entry.is_some())?;
+        state.check_whitelist_proof(&beneficiary_key, &whitelist_proof, ctx.accounts.whitelist_entry.is_some())?;
+
+        // Wound-down launches stop opening new vesting schedules, and purchases never vest into a team grant
+        state.check_new_vesting(enable_vesting)?;
+        if enable_vesting {
+            ctx.accounts.vesting_schedule.check_accepts_purchases()?;
+        }
+        state.check_affiliate_allowed(affiliate_key.is_some())?;
+
+        // Curated launches pay only approved affiliates; anyone else is dropped and the purchase
+        // goes through as an unreferred one, without commission or affiliate fee
+        let affiliate_key = match affiliate_key {
+            Some(key) if state.check_affiliate_approved(&key).is_err() => {
+                msg!("Affiliate {} is not approved for this launch; purchasing without commission", key);
+                None
+            }
+            other => other,
+        };
+
+        // Anti-bot validation; the cooldown runs from this buyer's own last purchase
+        let current_time = Clock::get()?.unix_timestamp;
+        state.validate_purchase_amount(sol_amount, ctx.accounts.purchase_tracker.last_purchase_time, current_time)?;
 
         // Calculate current price based on pricing model
         let current_price_per_token = state.calculate_current_price()?;
         require!(current_price_per_token > 0, FactoryError::InvalidAmount);
 
-        // Calculate tokens to mint
-        let tokens_to_mint = math_utils::calculate_tokens_to_mint(sol_amount, current_price_per_token)?;
-        require!(tokens_to_mint > 0, FactoryError::InsufficientFunds);
-
-        // Check if we exceed max tokens
-        let new_total_supply = state.tokens_sold.checked_add(tokens_to_mint)
-            .ok_or(FactoryError::Overflow)?;
-        require!(new_total_supply <= state.max_tokens, FactoryError::MaxSupplyReached);
-
-        // Calculate fees
-        let platform_fee = if state.platform_fee_bps > 0 {
-            math_utils::calculate_commission_amount(sol_amount, state.platform_fee_bps)?
+        // Calculate tokens to mint along the pricing curve
+        let tokens_to_mint = state.calculate_curve_purchase_tokens(sol_amount, current_price_per_token)?;
+
+        // The final buyer may overshoot by rounding dust; sell them exactly what is left
+        let tokens_to_mint = state.clamp_to_remaining_supply(tokens_to_mint)?;
+
+        // The price may have moved since the buyer built the transaction
+        LaunchState::check_min_tokens_out(tokens_to_mint, min_tokens_out)?;
+
+        // No single purchase may exceed the per-transaction cap, and no single wallet may buy
+        // more than the launch's per-wallet cap, or the stricter anti-snipe cap while the
+        // opening window is still running
+        state.check_tx_cap(tokens_to_mint)?;
+        state.check_wallet_cap(ctx.accounts.purchase_tracker.total_purchased, tokens_to_mint)?;
+        state.check_anti_snipe_wallet_cap(ctx.accounts.purchase_tracker.total_purchased, tokens_to_mint, current_time)?;
+
+        // Cashback tokens count against max tokens alongside the purchase itself
+        let cashback_tokens = state.calculate_cashback_tokens(tokens_to_mint)?;
+        let new_total_supply = state.supply_after_purchase(tokens_to_mint, cashback_tokens)?;
+
+        // Calculate fees. The affiliate is paid in commission tokens only; a frozen affiliate token
+        // account can't receive them, so the affiliate's share goes to the platform in SOL instead
+        // of failing the whole purchase.
+        let affiliate_account_frozen = affiliate_key.is_some()
+            && ctx.accounts.affiliate_token_account.as_ref().is_some_and(|account| account.is_frozen());
+        let mut fees = state.calculate_fees(sol_amount, affiliate_key.is_some())?;
+        // Early buyers inside the fee holiday pay reduced fees
+        fees.apply_discount(state.fee_holiday_discount_bps(current_time))?;
+        let redirected_affiliate_fee = if affiliate_account_frozen {
+            fees.redirect_affiliate_fee_to_platform()?
         } else {
             0
         };
-
-        let affiliate_fee = if let Some(_) = affiliate_key {
-            math_utils::calculate_commission_amount(sol_amount, state.affiliate_fee_bps)?
-        } else {
-            0
+        // Purchases in the opening anti-snipe window pay a platform surcharge
+        fees.add_platform_surcharge(state.calculate_anti_snipe_fee(sol_amount, current_time)?)?;
+        let FeeBreakdown { platform_fee, net_sol_amount, .. } = fees;
+        state.check_hard_cap(net_sol_amount)?;
+
+        // Transfer platform fee if applicable, split across the fee split recipients when configured
+        let fee_split_recipient_count = match &ctx.accounts.fee_split_config {
+            Some(fee_split) if state.fee_split_config != Pubkey::default() => fee_split.active_recipients().len(),
+            _ => 0,
         };
-
-        let net_sol_amount = sol_amount.checked_sub(platform_fee)
-            .and_then(|v| v.checked_sub(affiliate_fee))
-            .ok_or(FactoryError::FeeCalculationOverflow)?;
-
-        // Transfer platform fee if applicable
-        if platform_fee > 0 {
+        if platform_fee > 0 && state.fee_split_config == Pubkey::default() {
             system_program::transfer(
                 CpiContext::new(
                     ctx.accounts.system_program.to_account_info(),
@@ -240,6 +483,30 @@ pub mod factory_program {
                 ),
                 platform_fee,
             )?;
+        } else if platform_fee > 0 {
+            let fee_split = ctx.accounts.fee_split_config.as_ref()
+                .ok_or(FactoryError::FeeSplitRecipientMismatch)?;
+            require_keys_eq!(fee_split.key(), state.fee_split_config, FactoryError::FeeSplitRecipientMismatch);
+            let recipients = fee_split.active_recipients();
+            require!(ctx.remaining_accounts.len() >= recipients.len(), FactoryError::FeeSplitRecipientMismatch);
+
+            let shares = fee_split.split_fee(platform_fee)?;
+            for ((entry, share), recipient_info) in recipients.iter().zip(shares).zip(ctx.remaining_accounts.iter()) {
+                require_keys_eq!(recipient_info.key(), entry.recipient, FactoryError::FeeSplitRecipientMismatch);
+                if share == 0 {
+                    continue;
+                }
+                system_program::transfer(
+                    CpiContext::new(
+                        ctx.accounts.system_program.to_account_info(),
+                        system_program::Transfer {
+                            from: ctx.accounts.buyer.to_account_info(),
+                            to: recipient_info.clone(),
+                        },
+                    ),
+                    share,
+                )?;
+            }
         }
 
         // Transfer net SOL to vault
@@ -258,17 +525,12 @@ pub mod factory_program {
         let authority_key = state.authority;
         let token_mint_key = state.token_mint;
         let launch_state_bump = ctx.bumps.launch_state;
-        let seeds = &[
-            LAUNCH_STATE_SEED.as_ref(),
-            authority_key.as_ref(),
-            token_mint_key.as_ref(),
-            &[launch_state_bump],
-        ];
+        let seeds = pda_utils::launch_state_signer_seeds(&authority_key, &token_mint_key, &launch_state_bump);
         let signer_seeds = &[&seeds[..]];
 
-        // Mint tokens to buyer (or to vesting schedule if enabled)
+        // Mint tokens to buyer (or to the vesting schedule's token account if enabled)
         let token_destination = if enable_vesting {
-            ctx.accounts.vesting_schedule.to_account_info()
+            ctx.accounts.vesting_token_account.to_account_info()
         } else {
             ctx.accounts.buyer_token_account.to_account_info()
         };
@@ -286,59 +548,285 @@ pub mod factory_program {
             tokens_to_mint,
         )?;
 
-        // Initialize vesting schedule if requested
-        if enable_vesting {
+        // Mint buyer cashback straight to the buyer, outside of any vesting
+        if cashback_tokens > 0 {
+            token::mint_to(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    token::MintTo {
+                        mint: ctx.accounts.token_mint.to_account_info(),
+                        to: ctx.accounts.buyer_token_account.to_account_info(),
+                        authority: state.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                cashback_tokens,
+            )?;
+        }
+
+        // Initialize vesting schedule if requested. A live schedule, which may have been opened by
+        // someone else buying for this beneficiary, is topped up and re-based rather than overwritten.
+        if enable_vesting && ctx.accounts.vesting_schedule.is_live() {
+            ctx.accounts.vesting_schedule.top_up(tokens_to_mint, current_time)?;
+            state.record_vesting_locked(tokens_to_mint)?;
+            msg!("Added {} tokens to the live vesting schedule of {}", tokens_to_mint, beneficiary_key);
+        } else if enable_vesting {
             let vesting_schedule = &mut ctx.accounts.vesting_schedule;
             vesting_schedule.launch_state = state.key();
-            vesting_schedule.beneficiary = ctx.accounts.buyer.key();
+            vesting_schedule.beneficiary = beneficiary_key;
             vesting_schedule.total_amount = tokens_to_mint;
             vesting_schedule.claimed_amount = 0;
+            vesting_schedule.carried_vested_amount = 0;
             vesting_schedule.start_time = Clock::get()?.unix_timestamp;
-            vesting_schedule.duration_seconds = state.vesting_duration_seconds;
-            vesting_schedule.cliff_seconds = state.vesting_cliff_seconds;
+            vesting_schedule.apply_launch_terms(state);
             vesting_schedule.last_claim_time = vesting_schedule.start_time;
+            // The signer paid the schedule's rent, so it returns to them on close
+            vesting_schedule.rent_recipient = if buying_for_recipient {
+                ctx.accounts.buyer.key()
+            } else {
+                Pubkey::default()
+            };
+            // Purchased tokens belong to the buyer and can never be clawed back
+            vesting_schedule.revocable = false;
+            vesting_schedule.revoked = false;
+
+            emit!(VestingScheduleCreatedEvent {
+                launch_state: vesting_schedule.launch_state,
+                beneficiary: vesting_schedule.beneficiary,
+                total_amount: vesting_schedule.total_amount,
+                start_time: vesting_schedule.start_time,
+                duration: vesting_schedule.duration_seconds,
+                cliff: vesting_schedule.cliff_seconds,
+            });
+            state.record_vesting_locked(tokens_to_mint)?;
         }
 
-        // Process affiliate commission if provided
-        if let Some(key) = affiliate_key {
-            require_keys_eq!(key, ctx.accounts.affiliate.key(), FactoryError::AffiliateMismatch);
-
-            let cpi_program = ctx.accounts.affiliate_program.to_account_info();
-            let cpi_accounts = ProcessCommission {
-                launch_state: state.to_account_info(),
-                affiliate_info: ctx.accounts.affiliate_info.to_account_info(),
-                affiliate_token_account: ctx.accounts.affiliate_token_account.to_account_info(),
-                token_mint: ctx.accounts.token_mint.to_account_info(),
-                token_program: ctx.accounts.token_program.to_account_info(),
-            };
-            let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
-            affiliate_program::cpi::process_commission(cpi_ctx, tokens_to_mint)?;
+        // Process affiliate commission if provided and the launch's commission budget allows it,
+        // checked against the worst case of every ancestor up the referral chain being paid too
+        if let (Some(key), Some(affiliate_info), Some(affiliate_token_account), Some(affiliate_program)) = (
+            affiliate_key,
+            &ctx.accounts.affiliate_info,
+            &ctx.accounts.affiliate_token_account,
+            &ctx.accounts.affiliate_program,
+        ) {
+            let commission_amount = math_utils::calculate_commission_amount(
+                tokens_to_mint,
+                affiliate_info.commission_rate_bps,
+            )?;
+            if affiliate_account_frozen {
+                emit!(AffiliateCommissionSkipped {
+                    launch_state: state.key(),
+                    affiliate: key,
+                    commission_amount,
+                    redirected_fee: redirected_affiliate_fee,
+                });
+                msg!("Affiliate token account frozen; skipping {} commission tokens for {}",
+                     commission_amount, key);
+            } else if state.can_pay_affiliate_commission(affiliate_info.max_chain_commission(commission_amount)) {
+                let cpi_program = affiliate_program.to_account_info();
+                let cpi_accounts = ProcessCommission {
+                    launch_state: state.to_account_info(),
+                    affiliate_info: affiliate_info.to_account_info(),
+                    affiliate_token_account: affiliate_token_account.to_account_info(),
+                    token_mint: ctx.accounts.token_mint.to_account_info(),
+                    token_program: ctx.accounts.token_program.to_account_info(),
+                };
+                // The affiliate's ancestors, paid a share of the commission, follow any fee split recipients
+                let ancestor_accounts = ctx.remaining_accounts.get(fee_split_recipient_count..).unwrap_or_default();
+                let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds)
+                    .with_remaining_accounts(ancestor_accounts.to_vec());
+                let commission_minted = affiliate_program::cpi::process_commission(cpi_ctx, tokens_to_mint)?.get();
+
+                state.affiliate_commission_minted = state.affiliate_commission_minted
+                    .checked_add(commission_minted)
+                    .ok_or(FactoryError::Overflow)?;
+            } else {
+                msg!("Affiliate commission budget exhausted; skipping {} commission tokens for {}",
+                     commission_amount, key);
+            }
         }
 
         // Update state
         state.tokens_sold = new_total_supply;
-        state.total_sol_collected = state.total_sol_collected.checked_add(net_sol_amount)
-            .ok_or(FactoryError::Overflow)?;
+        state.record_raise(net_sol_amount)?;
         state.total_fees_collected = state.total_fees_collected.checked_add(platform_fee)
             .ok_or(FactoryError::Overflow)?;
         state.purchase_count = state.purchase_count.checked_add(1)
             .ok_or(FactoryError::Overflow)?;
-        state.last_purchase_timestamp = Clock::get()?.unix_timestamp;
+        state.last_purchase_timestamp = current_time;
+
+        // Record the buyer's contribution so it can be refunded if the soft cap is missed
+        let tokens_received = if enable_vesting {
+            cashback_tokens
+        } else {
+            tokens_to_mint.checked_add(cashback_tokens).ok_or(FactoryError::Overflow)?
+        };
+        ctx.accounts.purchase_tracker.record_purchase(
+            state.key(),
+            beneficiary_key,
+            net_sol_amount,
+            tokens_to_mint,
+            tokens_received,
+            state.last_purchase_timestamp,
+        )?;
+        if enable_vesting {
+            ctx.accounts.purchase_tracker.record_vested(tokens_to_mint)?;
+        }
+
+        // Add the SOL raised to the ecosystem-wide stats when the affiliate program is available
+        if let Some(affiliate_program) = &ctx.accounts.affiliate_program {
+            let ecosystem_stats = ctx.accounts.ecosystem_stats.as_ref()
+                .ok_or(FactoryError::EcosystemStatsMissing)?;
+            affiliate_program::cpi::record_launch_activity(
+                CpiContext::new_with_signer(
+                    affiliate_program.to_account_info(),
+                    RecordLaunchActivity {
+                        launch_state: state.to_account_info(),
+                        ecosystem_stats: ecosystem_stats.to_account_info(),
+                        payer: ctx.accounts.buyer.to_account_info(),
+                        system_program: ctx.accounts.system_program.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                0,
+                net_sol_amount,
+            )?;
+        }
 
-        msg!("Purchase completed: {} tokens minted for {} lamports", tokens_to_mint, sol_amount);
+        msg!("Purchase completed: {} tokens (+{} cashback) minted for {} lamports",
+             tokens_to_mint, cashback_tokens, sol_amount);
+        emit!(PurchaseEvent {
+            buyer: ctx.accounts.buyer.key(),
+            recipient: beneficiary_key,
+            launch_state: state.key(),
+            sol_amount,
+            net_sol_amount,
+            tokens_minted: tokens_to_mint,
+            cashback_tokens,
+            price_per_token: current_price_per_token,
+            platform_fee,
+            affiliate_fee: fees.affiliate_fee,
+            vested: enable_vesting,
+            timestamp: current_time,
+        });
         Ok(())
     }
     
+    /// Executes a token purchase paid in the launch's SPL payment token (e.g. a stablecoin).
+    ///
+    /// Prices and fees are interpreted in the payment token's base units. `amount_in` moves from
+    /// the buyer's payment token account into the launch's payment vault; the platform fee goes
+    /// to the fee recipient's payment token account, or, when a fee split is configured, to each
+    /// split recipient's payment token account passed in `remaining_accounts`. Affiliate referrals
+    /// and vesting are only available on SOL purchases.
+    pub fn buy_tokens_with_spl<'info>(
+        ctx: Context<'_, '_, '_, 'info, BuyTokensWithSpl<'info>>,
+        amount_in: u64,
+        min_tokens_out: u64,
+    ) -> Result<()> {
+        ctx.accounts.process_purchase(ctx.bumps.launch_state, ctx.remaining_accounts, amount_in, min_tokens_out)?;
+        Ok(())
+    }
+
+    /// Reinvests an affiliate's commission tokens as a purchase on a chosen launch.
+    ///
+    /// The chosen launch must take SPL-token payment in the commission token, and the affiliate
+    /// must have opted in with `set_auto_compound` on their `AffiliateInfo`. The commission
+    /// tokens are paid into that launch exactly as in `buy_tokens_with_spl`.
+    pub fn compound_commission<'info>(
+        ctx: Context<'_, '_, '_, 'info, CompoundCommission<'info>>,
+        commission_amount: u64,
+        min_tokens_out: u64,
+    ) -> Result<()> {
+        let tokens_received = ctx.accounts.purchase.process_purchase(
+            ctx.bumps.purchase.launch_state,
+            ctx.remaining_accounts,
+            commission_amount,
+            min_tokens_out,
+        )?;
+
+        emit!(CommissionCompoundedEvent {
+            launch_state: ctx.accounts.purchase.launch_state.key(),
+            affiliate: ctx.accounts.affiliate_info.affiliate_key,
+            commission_mint: ctx.accounts.purchase.payment_mint.key(),
+            commission_amount,
+            tokens_received,
+        });
+        Ok(())
+    }
+
+    /// Allows the authority of an SPL-token launch to withdraw all collected payment tokens.
+    pub fn withdraw_payment(ctx: Context<WithdrawPayment>) -> Result<()> {
+        let state = &ctx.accounts.launch_state;
+        state.check_spl_payment_mint(&ctx.accounts.payment_mint.key())?;
+        state.check_withdraw_threshold()?;
+        state.check_soft_cap_met()?;
+        let amount_to_withdraw = ctx.accounts.payment_vault.amount;
+        require!(amount_to_withdraw > 0, FactoryError::InvalidAmount);
+
+        let bump = ctx.bumps.launch_state;
+        let seeds = pda_utils::launch_state_signer_seeds(&state.authority, &state.token_mint, &bump);
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                token::Transfer {
+                    from: ctx.accounts.payment_vault.to_account_info(),
+                    to: ctx.accounts.authority_payment_account.to_account_info(),
+                    authority: state.to_account_info(),
+                },
+                &[&seeds[..]],
+            ),
+            amount_to_withdraw,
+        )?;
+
+        msg!("Withdrew {} of {} from the payment vault", amount_to_withdraw, state.payment_mint);
+        Ok(())
+    }
+
+    /// Permanently closes minting once the sale has ended by revoking the launch's mint authority.
+    ///
+    /// Callable by the launch authority after `launch_end_time`. Later purchases are rejected;
+    /// tokens already minted into vesting vaults remain claimable.
+    pub fn finalize_launch(ctx: Context<FinalizeLaunch>) -> Result<()> {
+        let state = &mut ctx.accounts.launch_state;
+        state.check_finalize_allowed(Clock::get()?.unix_timestamp)?;
+
+        let authority_key = state.authority;
+        let token_mint_key = state.token_mint;
+        let seeds = pda_utils::launch_state_signer_seeds(&authority_key, &token_mint_key, &ctx.bumps.launch_state);
+        token::set_authority(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                token::SetAuthority {
+                    current_authority: state.to_account_info(),
+                    account_or_mint: ctx.accounts.token_mint.to_account_info(),
+                },
+                &[&seeds[..]],
+            ),
+            AuthorityType::MintTokens,
+            None,
+        )?;
+        state.finalized = true;
+
+        msg!("Launch finalized: mint authority for {} revoked with {} tokens sold",
+             state.token_mint, state.tokens_sold);
+        Ok(())
+    }
+
     /// Allows the authority of the launch to withdraw all collected SOL.
-    pub fn withdraw_sol(ctx: Context<WithdrawSol>) -> Result<()> {
+    ///
+    /// Emits a `SolWithdrawnEvent` and returns the withdrawn lamports as return data.
+    pub fn withdraw_sol(ctx: Context<WithdrawSol>) -> Result<u64> {
         let state = &ctx.accounts.launch_state;
         let sol_vault = &mut ctx.accounts.sol_vault;
         let authority = &ctx.accounts.authority;
+        state.check_withdraw_threshold()?;
+        state.check_soft_cap_met()?;
         let lamports_to_withdraw = sol_vault.lamports();
         require!(lamports_to_withdraw > 0, FactoryError::InvalidAmount);
         
         // Prepare seeds for the SOL vault PDA to sign the transfer.
-        let seeds = &[SOL_VAULT_SEED.as_ref(), state.authority.as_ref(), state.token_mint.as_ref(), &[state.sol_vault_bump]];
+        let seeds = pda_utils::sol_vault_signer_seeds(&state.authority, &state.token_mint, &state.sol_vault_bump);
         let signer = &[&seeds[..]];
         
         // Transfer all lamports from the vault to the authority.
@@ -353,29 +841,186 @@ pub mod factory_program {
             ),
             lamports_to_withdraw
         )?;
+
+        emit!(SolWithdrawnEvent {
+            launch_state: state.key(),
+            authority: authority.key(),
+            amount: lamports_to_withdraw,
+            remaining_vault_balance: sol_vault.lamports(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(lamports_to_withdraw)
+    }
+
+    /// Allows the authority to withdraw exactly `amount` lamports of collected SOL, e.g. to take
+    /// operating funds incrementally while keeping a buffer. The vault must stay rent exempt;
+    /// use `withdraw_sol` to drain it completely. Emits a `SolWithdrawnEvent` and returns `amount`.
+    pub fn withdraw_sol_amount(ctx: Context<WithdrawSol>, amount: u64) -> Result<u64> {
+        let state = &ctx.accounts.launch_state;
+        state.check_withdraw_threshold()?;
+        state.check_soft_cap_met()?;
+        let vault_balance = ctx.accounts.sol_vault.lamports();
+        LaunchState::check_partial_withdrawal(vault_balance, amount, Rent::get()?.minimum_balance(0))?;
+
+        let seeds = pda_utils::sol_vault_signer_seeds(&state.authority, &state.token_mint, &state.sol_vault_bump);
+        system_program::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.sol_vault.to_account_info(),
+                    to: ctx.accounts.authority.to_account_info(),
+                },
+                &[&seeds[..]],
+            ),
+            amount,
+        )?;
+
+        emit!(SolWithdrawnEvent {
+            launch_state: state.key(),
+            authority: ctx.accounts.authority.key(),
+            amount,
+            remaining_vault_balance: ctx.accounts.sol_vault.lamports(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        msg!("Withdrew {} of {} lamports from the SOL vault", amount, vault_balance);
+        Ok(amount)
+    }
+
+    /// Refunds a buyer's SOL once a launch has ended below its soft cap.
+    ///
+    /// The buyer's net contribution is returned from the SOL vault and the tokens they
+    /// received are burned, including vested tokens already claimed. Any unclaimed vested
+    /// tokens are burned from the schedule's token account and the schedule marked fully
+    /// claimed, so the launch can still be closed. Everything the buyer bought returns to
+    /// the unsold supply.
+    pub fn refund(ctx: Context<Refund>) -> Result<()> {
+        let state = &mut ctx.accounts.launch_state;
+        require!(state.payment_mode == PaymentMode::NativeSol, FactoryError::UnsupportedPaymentMode);
+        state.check_refund_available(Clock::get()?.unix_timestamp)?;
+
+        // Void the buyer's vesting schedule: its claimed tokens are burned from the buyer's account
+        // below and its unclaimed ones straight out of the schedule's token account
+        let (vested_claimed, vested_unclaimed) = match ctx.accounts.vesting_schedule.as_mut() {
+            Some(schedule) => {
+                let claimed = schedule.claimed_amount;
+                let unclaimed = schedule.total_amount.saturating_sub(claimed);
+                state.record_vesting_released(unclaimed);
+                schedule.claimed_amount = schedule.total_amount;
+                (Some(claimed), unclaimed)
+            }
+            None => (None, 0),
+        };
+
+        let tracker = &mut ctx.accounts.purchase_tracker;
+        let (lamports_to_refund, tokens_to_burn) = tracker.refund_amounts(vested_claimed)?;
+        tracker.refunded = true;
+        state.record_refund(tracker.tokens_allocated()?);
+
+        if vested_unclaimed > 0 {
+            let vesting_token_account = ctx.accounts.vesting_token_account.as_ref()
+                .ok_or(FactoryError::VestingScheduleNotFound)?;
+            let launch_state_key = state.key();
+            let buyer_key = ctx.accounts.buyer.key();
+            let vesting_schedule_bump = ctx.bumps.vesting_schedule.ok_or(FactoryError::VestingScheduleNotFound)?;
+            let seeds = pda_utils::vesting_schedule_signer_seeds(&launch_state_key, &buyer_key, &vesting_schedule_bump);
+            token::burn(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    token::Burn {
+                        mint: ctx.accounts.token_mint.to_account_info(),
+                        from: vesting_token_account.to_account_info(),
+                        authority: ctx.accounts.vesting_schedule.as_ref()
+                            .ok_or(FactoryError::VestingScheduleNotFound)?
+                            .to_account_info(),
+                    },
+                    &[&seeds[..]],
+                ),
+                vested_unclaimed,
+            )?;
+        }
+
+        if tokens_to_burn > 0 {
+            token::burn(
+                CpiContext::new(
+                    ctx.accounts.token_program.to_account_info(),
+                    token::Burn {
+                        mint: ctx.accounts.token_mint.to_account_info(),
+                        from: ctx.accounts.buyer_token_account.to_account_info(),
+                        authority: ctx.accounts.buyer.to_account_info(),
+                    },
+                ),
+                tokens_to_burn,
+            )?;
+        }
+
+        let seeds = pda_utils::sol_vault_signer_seeds(&state.authority, &state.token_mint, &state.sol_vault_bump);
+        let signer = &[&seeds[..]];
+        system_program::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.sol_vault.to_account_info(),
+                    to: ctx.accounts.buyer.to_account_info(),
+                },
+                signer
+            ),
+            lamports_to_refund
+        )?;
+
+        msg!("Refunded {} lamports and burned {} tokens for {}",
+             lamports_to_refund, tokens_to_burn, ctx.accounts.buyer.key());
+        Ok(())
+    }
+
+    /// Sweeps residual lamports from the SOL vault to the authority.
+    ///
+    /// Gated like `withdraw_sol` on the withdraw threshold and soft cap, so SOL owed to buyers
+    /// as refunds never leaves the vault. While the launch is running only lamports above the
+    /// vault's rent-exempt minimum are swept. Once the launch has ended, passing `closing = true`
+    /// sweeps everything so the launch accounts can be closed cleanly.
+    pub fn sweep_vault_dust(ctx: Context<SweepVaultDust>, closing: bool) -> Result<()> {
+        let state = &ctx.accounts.launch_state;
+        let sol_vault = &ctx.accounts.sol_vault;
+
+        state.check_sweep_allowed(Clock::get()?.unix_timestamp, closing)?;
+
+        let rent_exempt_minimum = Rent::get()?.minimum_balance(0);
+        let lamports_to_sweep = LaunchState::sweepable_vault_lamports(sol_vault.lamports(), rent_exempt_minimum, closing);
+        require!(lamports_to_sweep > 0, FactoryError::InvalidAmount);
+
+        let seeds = pda_utils::sol_vault_signer_seeds(&state.authority, &state.token_mint, &state.sol_vault_bump);
+        let signer = &[&seeds[..]];
+
+        system_program::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: sol_vault.to_account_info(),
+                    to: ctx.accounts.authority.to_account_info(),
+                },
+                signer
+            ),
+            lamports_to_sweep
+        )?;
+
+        msg!("Swept {} lamports of vault dust", lamports_to_sweep);
         Ok(())
     }
 
     /// Claim vested tokens from a vesting schedule.
     pub fn claim_vested_tokens(ctx: Context<ClaimVestedTokens>, _args: ClaimVestedTokensArgs) -> Result<()> {
         let vesting = &mut ctx.accounts.vesting_schedule;
+        vesting.check_not_revoked()?;
         let current_time = Clock::get()?.unix_timestamp;
 
-        // Calculate claimable amount
-        let claimable_amount = vesting.calculate_claimable_amount(current_time)?;
-        require!(claimable_amount > 0, FactoryError::NoTokensToClaim);
+        // Record the claim before the transfer CPI, so state never trails the tokens moved
+        let claimable_amount = vesting.record_claim(current_time)?;
+        ctx.accounts.launch_state.record_vesting_released(claimable_amount);
 
-        // Prepare PDA seeds for signing
-        let launch_state = &ctx.accounts.launch_state;
-        let authority_key = launch_state.authority;
-        let token_mint_key = launch_state.token_mint;
-        let launch_state_bump = ctx.bumps.launch_state;
-        let seeds = &[
-            LAUNCH_STATE_SEED.as_ref(),
-            authority_key.as_ref(),
-            token_mint_key.as_ref(),
-            &[launch_state_bump],
-        ];
+        // The vesting token account is owned by the schedule, so the schedule signs the transfer
+        let launch_state_key = ctx.accounts.launch_state.key();
+        let beneficiary_key = ctx.accounts.beneficiary.key();
+        let seeds = pda_utils::vesting_schedule_signer_seeds(&launch_state_key, &beneficiary_key, &ctx.bumps.vesting_schedule);
         let signer_seeds = &[&seeds[..]];
 
         // Transfer tokens from vesting schedule to beneficiary
@@ -385,22 +1030,240 @@ pub mod factory_program {
                 token::Transfer {
                     from: ctx.accounts.vesting_token_account.to_account_info(),
                     to: ctx.accounts.beneficiary_token_account.to_account_info(),
-                    authority: ctx.accounts.launch_state.to_account_info(),
+                    authority: ctx.accounts.vesting_schedule.to_account_info(),
                 },
                 signer_seeds,
             ),
             claimable_amount,
         )?;
 
-        // Update vesting schedule
-        vesting.claimed_amount = vesting.claimed_amount.checked_add(claimable_amount)
-            .ok_or(FactoryError::Overflow)?;
-        vesting.last_claim_time = current_time;
-
         msg!("Claimed {} vested tokens", claimable_amount);
         Ok(())
     }
 
+    /// Grants vested tokens to a team member or advisor (authority only).
+    ///
+    /// Mints `amount` into a new vesting schedule for `beneficiary` on the launch's vesting terms.
+    /// Granted tokens count against `max_tokens` alongside tokens sold. The beneficiary must not
+    /// already hold a vesting schedule from purchases on this launch. The authority pays the
+    /// schedule's rent and receives it back on close. With `revocable` set, the authority can
+    /// later stop the grant with `revoke_vesting`.
+    pub fn create_vesting_grant(ctx: Context<CreateVestingGrant>, amount: u64, revocable: bool) -> Result<()> {
+        require!(amount > 0, FactoryError::InvalidAmount);
+        ctx.accounts.launch_state.record_grant(amount)?;
+        let state = &ctx.accounts.launch_state;
+        state.check_not_finalized()?;
+        require!(state.vesting_enabled, FactoryError::InvalidVestingParams);
+
+        let authority_key = state.authority;
+        let token_mint_key = state.token_mint;
+        let seeds = pda_utils::launch_state_signer_seeds(&authority_key, &token_mint_key, &ctx.bumps.launch_state);
+        token::mint_to(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                token::MintTo {
+                    mint: ctx.accounts.token_mint.to_account_info(),
+                    to: ctx.accounts.vesting_token_account.to_account_info(),
+                    authority: state.to_account_info(),
+                },
+                &[&seeds[..]],
+            ),
+            amount,
+        )?;
+
+        let vesting_schedule = &mut ctx.accounts.vesting_schedule;
+        vesting_schedule.launch_state = state.key();
+        vesting_schedule.beneficiary = ctx.accounts.beneficiary.key();
+        vesting_schedule.total_amount = amount;
+        vesting_schedule.claimed_amount = 0;
+        vesting_schedule.carried_vested_amount = 0;
+        vesting_schedule.start_time = Clock::get()?.unix_timestamp;
+        vesting_schedule.apply_launch_terms(state);
+        vesting_schedule.last_claim_time = vesting_schedule.start_time;
+        // The project funded this schedule, so its rent goes back to the authority
+        vesting_schedule.rent_recipient = authority_key;
+        vesting_schedule.revocable = revocable;
+        vesting_schedule.revoked = false;
+
+        emit!(VestingScheduleCreatedEvent {
+            launch_state: vesting_schedule.launch_state,
+            beneficiary: vesting_schedule.beneficiary,
+            total_amount: vesting_schedule.total_amount,
+            start_time: vesting_schedule.start_time,
+            duration: vesting_schedule.duration_seconds,
+            cliff: vesting_schedule.cliff_seconds,
+        });
+        ctx.accounts.launch_state.record_vesting_locked(amount)?;
+        Ok(())
+    }
+
+    /// Revokes a revocable vesting grant (authority only).
+    ///
+    /// Tokens vested at the current clock but not yet claimed go to the beneficiary; the unvested
+    /// remainder is burned when `burn_unvested` is set, otherwise sent to `unvested_destination`.
+    /// The schedule is marked revoked so no further claims succeed. Calling it again on a revoked
+    /// schedule moves nothing.
+    pub fn revoke_vesting(ctx: Context<RevokeVesting>, burn_unvested: bool) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        let revocation = ctx.accounts.vesting_schedule.revoke(current_time)?;
+
+        let launch_state_key = ctx.accounts.launch_state.key();
+        let beneficiary_key = ctx.accounts.beneficiary.key();
+        let seeds = pda_utils::vesting_schedule_signer_seeds(&launch_state_key, &beneficiary_key, &ctx.bumps.vesting_schedule);
+        let signer_seeds = &[&seeds[..]];
+
+        if revocation.to_beneficiary > 0 {
+            token::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    token::Transfer {
+                        from: ctx.accounts.vesting_token_account.to_account_info(),
+                        to: ctx.accounts.beneficiary_token_account.to_account_info(),
+                        authority: ctx.accounts.vesting_schedule.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                revocation.to_beneficiary,
+            )?;
+        }
+
+        if revocation.unvested > 0 {
+            if burn_unvested {
+                token::burn(
+                    CpiContext::new_with_signer(
+                        ctx.accounts.token_program.to_account_info(),
+                        token::Burn {
+                            mint: ctx.accounts.token_mint.to_account_info(),
+                            from: ctx.accounts.vesting_token_account.to_account_info(),
+                            authority: ctx.accounts.vesting_schedule.to_account_info(),
+                        },
+                        signer_seeds,
+                    ),
+                    revocation.unvested,
+                )?;
+            } else {
+                let destination = ctx.accounts.unvested_destination.as_ref()
+                    .ok_or(FactoryError::InvalidAccountState)?;
+                token::transfer(
+                    CpiContext::new_with_signer(
+                        ctx.accounts.token_program.to_account_info(),
+                        token::Transfer {
+                            from: ctx.accounts.vesting_token_account.to_account_info(),
+                            to: destination.to_account_info(),
+                            authority: ctx.accounts.vesting_schedule.to_account_info(),
+                        },
+                        signer_seeds,
+                    ),
+                    revocation.unvested,
+                )?;
+            }
+        }
+
+        emit!(VestingRevokedEvent {
+            launch_state: launch_state_key,
+            beneficiary: beneficiary_key,
+            vested_paid: revocation.to_beneficiary,
+            unvested: revocation.unvested,
+            burned: burn_unvested,
+            timestamp: current_time,
+        });
+        ctx.accounts.launch_state.record_vesting_released(revocation.to_beneficiary + revocation.unvested);
+        Ok(())
+    }
+
+    /// Closes a fully claimed vesting schedule and its token account.
+    ///
+    /// Rent from both accounts goes to the schedule's rent destination: the beneficiary, or the
+    /// treasury recorded in `rent_recipient` for schedules the project paid for.
+    pub fn close_vesting(ctx: Context<CloseVesting>) -> Result<()> {
+        let vesting = &ctx.accounts.vesting_schedule;
+        vesting.check_closable()?;
+
+        let launch_state_key = vesting.launch_state;
+        let beneficiary_key = vesting.beneficiary;
+        let seeds = pda_utils::vesting_schedule_signer_seeds(&launch_state_key, &beneficiary_key, &ctx.bumps.vesting_schedule);
+        token::close_account(CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            token::CloseAccount {
+                account: ctx.accounts.vesting_token_account.to_account_info(),
+                destination: ctx.accounts.rent_recipient.to_account_info(),
+                authority: vesting.to_account_info(),
+            },
+            &[&seeds[..]],
+        ))?;
+
+        msg!("Closed vesting schedule for {}; rent returned to {}", beneficiary_key, ctx.accounts.rent_recipient.key());
+        Ok(())
+    }
+
+    /// Closes a settled launch, returning the launch state's rent to the authority.
+    ///
+    /// The launch must be finalized, have no unclaimed vesting outstanding and an empty SOL
+    /// vault (see `withdraw_sol` and `sweep_vault_dust` with `closing = true`).
+    pub fn close_launch(ctx: Context<CloseLaunch>) -> Result<()> {
+        ctx.accounts.launch_state.check_settled(ctx.accounts.sol_vault.lamports())?;
+        msg!("Closed launch {}; rent returned to {}", ctx.accounts.launch_state.key(), ctx.accounts.authority.key());
+        Ok(())
+    }
+
+    /// Read-only timeline of a vesting schedule, returned via return data.
+    ///
+    /// Reports seconds until the cliff, seconds until fully vested and the linear release rate
+    /// in tokens per second; each is 0 once that point has passed.
+    pub fn get_vesting_timeline(ctx: Context<GetVestingTimeline>) -> Result<VestingTimeline> {
+        let timeline = ctx.accounts.vesting_schedule.calculate_timeline(Clock::get()?.unix_timestamp)?;
+        msg!("Vesting timeline: cliff in {}s, fully vested in {}s, {} tokens/s",
+             timeline.time_until_cliff, timeline.time_until_full, timeline.tokens_per_second);
+        Ok(timeline)
+    }
+
+    /// Logs and returns this program's build version via return data.
+    pub fn health_check(ctx: Context<HealthCheck>) -> Result<String> {
+        msg!("factory-program v{} at slot {}", PROGRAM_VERSION, ctx.accounts.clock.slot);
+        Ok(PROGRAM_VERSION.to_string())
+    }
+
+    /// Quotes a purchase without executing it, emitting a `QuoteEvent`.
+    ///
+    /// Runs the same launch and anti-bot validation as `buy_tokens` and prices the purchase with
+    /// the same curve and fee math, but makes no transfers and does not modify the launch.
+    pub fn quote_purchase(ctx: Context<QuotePurchase>, sol_amount: u64, affiliate_key: Option<Pubkey>) -> Result<()> {
+        require!(sol_amount > 0, FactoryError::InvalidAmount);
+        let state = &ctx.accounts.launch_state;
+        require!(state.payment_mode == PaymentMode::NativeSol, FactoryError::UnsupportedPaymentMode);
+        state.check_not_finalized()?;
+        state.check_not_paused()?;
+        require!(state.is_launch_active()?, FactoryError::LaunchNotActive);
+        require!(!state.is_max_supply_reached(), FactoryError::MaxSupplyReached);
+        state.check_affiliate_allowed(affiliate_key.is_some())?;
+        let affiliate_key = affiliate_key.filter(|key| state.check_affiliate_approved(key).is_ok());
+        // Quotes aren't tied to a buyer, so no per-buyer cooldown applies
+        let current_time = Clock::get()?.unix_timestamp;
+        state.validate_purchase_amount(sol_amount, 0, current_time)?;
+
+        let price_per_token = state.calculate_current_price()?;
+        require!(price_per_token > 0, FactoryError::InvalidAmount);
+        let tokens_to_mint = state.calculate_curve_purchase_tokens(sol_amount, price_per_token)?;
+        let tokens_to_mint = state.clamp_to_remaining_supply(tokens_to_mint)?;
+        state.check_tx_cap(tokens_to_mint)?;
+        let cashback_tokens = state.calculate_cashback_tokens(tokens_to_mint)?;
+        state.supply_after_purchase(tokens_to_mint, cashback_tokens)?;
+        let mut fees = state.calculate_fees(sol_amount, affiliate_key.is_some())?;
+        fees.apply_discount(state.fee_holiday_discount_bps(current_time))?;
+        fees.add_platform_surcharge(state.calculate_anti_snipe_fee(sol_amount, current_time)?)?;
+        state.check_hard_cap(fees.net_sol_amount)?;
+
+        emit!(QuoteEvent {
+            launch_state: state.key(),
+            sol_amount,
+            price_per_token,
+            tokens_to_mint,
+            platform_fee: fees.platform_fee,
+            affiliate_fee: fees.affiliate_fee,
+        });
+        Ok(())
+    }
+
     /// Update launch configuration (authority only).
     pub fn update_launch(ctx: Context<UpdateLaunch>, args: UpdateLaunchArgs) -> Result<()> {
         let state = &mut ctx.accounts.launch_state;
@@ -411,7 +1274,7 @@ pub mod factory_program {
         }
 
         if let Some(new_max_tokens) = args.new_max_tokens {
-            require!(new_max_tokens >= state.tokens_sold, FactoryError::InvalidAmount);
+            require!(new_max_tokens >= state.allocated_supply(), FactoryError::InvalidAmount);
             state.max_tokens = new_max_tokens;
         }
 
@@ -426,25 +1289,393 @@ pub mod factory_program {
         msg!("Launch configuration updated");
         Ok(())
     }
-}
 
-#[derive(Accounts)]
-pub struct CreateLaunch<'info> {
-    #[account(
-        init,
-        payer = authority,
-        space = LaunchState::LEN + 8,
-        seeds = [LAUNCH_STATE_SEED.as_ref(), authority.key().as_ref(), token_mint.key().as_ref()],
-        bump
-    )]
-    pub launch_state: Account<'info, LaunchState>,
+    /// Adds buyers to a launch's whitelist (authority only).
+    ///
+    /// Each buyer's `WhitelistEntry` PDA must be passed in `remaining_accounts`, in the same
+    /// order as `buyers`. Buyers that are already whitelisted are skipped.
+    pub fn add_whitelist_entries<'info>(
+        ctx: Context<'_, '_, '_, 'info, AddWhitelistEntries<'info>>,
+        buyers: Vec<Pubkey>,
+    ) -> Result<()> {
+        LaunchState::validate_whitelist_batch(buyers.len())?;
+        require!(ctx.remaining_accounts.len() == buyers.len(), FactoryError::InvalidWhitelistBatch);
+
+        let launch_state_key = ctx.accounts.launch_state.key();
+        let space = WhitelistEntry::LEN + 8;
+        let lamports = Rent::get()?.minimum_balance(space);
+        let mut added: u32 = 0;
+
+        for (buyer, entry_info) in buyers.iter().zip(ctx.remaining_accounts.iter()) {
+            let (expected_entry, bump) = Pubkey::find_program_address(
+                &[WHITELIST_ENTRY_SEED, launch_state_key.as_ref(), buyer.as_ref()],
+                ctx.program_id,
+            );
+            require_keys_eq!(entry_info.key(), expected_entry, FactoryError::InvalidAccountState);
+            if !entry_info.data_is_empty() {
+                continue;
+            }
+
+            let seeds = &[WHITELIST_ENTRY_SEED, launch_state_key.as_ref(), buyer.as_ref(), &[bump]];
+            system_program::create_account(
+                CpiContext::new_with_signer(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::CreateAccount {
+                        from: ctx.accounts.authority.to_account_info(),
+                        to: entry_info.clone(),
+                    },
+                    &[&seeds[..]],
+                ),
+                lamports,
+                space as u64,
+                ctx.program_id,
+            )?;
 
-    #[account(
-        init,
-        payer = authority,
-        mint::decimals = 9,
-        mint::authority = launch_state
-    )]
+            let entry = WhitelistEntry { launch_state: launch_state_key, buyer: *buyer, bump };
+            entry.try_serialize(&mut &mut entry_info.try_borrow_mut_data()?[..])?;
+            added += 1;
+        }
+
+        msg!("Added {} whitelist entries", added);
+        Ok(())
+    }
+
+    /// Toggle whether vested purchases may open new vesting schedules (authority only).
+    ///
+    /// Existing schedules keep vesting and remain claimable either way.
+    pub fn set_vesting_accepting_new(ctx: Context<SetVestingAcceptingNew>, accepting_new: bool) -> Result<()> {
+        ctx.accounts.launch_state.vesting_accepting_new = accepting_new;
+        msg!("New vesting schedules {}", if accepting_new { "accepted" } else { "paused" });
+        Ok(())
+    }
+
+    /// Pause or resume purchases in an emergency, e.g. a pricing bug found mid-sale (authority only).
+    ///
+    /// Only buying stops; vesting claims, refunds and withdrawals remain available while paused.
+    pub fn set_paused(ctx: Context<SetPaused>, paused: bool) -> Result<()> {
+        ctx.accounts.launch_state.paused = paused;
+        msg!("Launch purchases {}", if paused { "paused" } else { "resumed" });
+        Ok(())
+    }
+
+    /// Replaces the launch's merkle whitelist root, or clears it with `None` (authority only).
+    pub fn set_whitelist_root(ctx: Context<SetWhitelistRoot>, whitelist_root: Option<[u8; 32]>) -> Result<()> {
+        LaunchState::validate_whitelist_root(&whitelist_root)?;
+        ctx.accounts.launch_state.whitelist_root = whitelist_root;
+        msg!("Whitelist root {}", if whitelist_root.is_some() { "set" } else { "cleared" });
+        Ok(())
+    }
+
+    /// Replaces the launch's affiliate allowlist; an empty list lets any affiliate earn (authority only).
+    ///
+    /// Purchases referred by an affiliate not on a non-empty list still go through, but without
+    /// commission.
+    pub fn set_approved_affiliates(ctx: Context<SetApprovedAffiliates>, affiliates: Vec<Pubkey>) -> Result<()> {
+        LaunchState::validate_approved_affiliates(&affiliates)?;
+        msg!("Affiliate allowlist set to {} affiliates", affiliates.len());
+        ctx.accounts.launch_state.approved_affiliates = affiliates;
+        Ok(())
+    }
+
+    /// Blocks a buyer from purchasing in this launch (authority only).
+    pub fn block_buyer(ctx: Context<BlockBuyer>, buyer: Pubkey) -> Result<()> {
+        let entry = &mut ctx.accounts.blocked_buyer;
+        entry.launch_state = ctx.accounts.launch_state.key();
+        entry.buyer = buyer;
+        msg!("Buyer {} blocked", buyer);
+        Ok(())
+    }
+
+    /// Removes a buyer from this launch's blocklist (authority only).
+    pub fn unblock_buyer(_ctx: Context<UnblockBuyer>, buyer: Pubkey) -> Result<()> {
+        msg!("Buyer {} unblocked", buyer);
+        Ok(())
+    }
+
+    /// Switches the launch between native-SOL and SPL-token payment (authority only).
+    ///
+    /// Only allowed before the first purchase. Switching to `SplToken` requires a payment
+    /// mint on the platform's allowlist and creates the launch's payment token vault if it
+    /// doesn't exist yet. Launches with a soft cap stay on native SOL, since refunds pay back SOL.
+    pub fn migrate_payment_mode(ctx: Context<MigratePaymentMode>, payment_mode: PaymentMode) -> Result<()> {
+        let state = &mut ctx.accounts.launch_state;
+        state.check_payment_mode_migratable()?;
+        state.check_payment_mode_supported(payment_mode)?;
+
+        state.payment_mint = match payment_mode {
+            PaymentMode::NativeSol => Pubkey::default(),
+            PaymentMode::SplToken => {
+                let payment_mint = ctx.accounts.payment_mint.as_ref().ok_or(FactoryError::MissingPaymentMint)?;
+                require!(ctx.accounts.payment_vault.is_some(), FactoryError::MissingPaymentMint);
+                ctx.accounts.platform_config.check_payment_mint_allowed(&payment_mint.key())?;
+                payment_mint.key()
+            }
+        };
+        state.payment_mode = payment_mode;
+
+        msg!("Payment mode migrated to {:?} (mint {})", state.payment_mode, state.payment_mint);
+        Ok(())
+    }
+
+    /// Replaces the platform's allowlist of SPL payment mints.
+    ///
+    /// Creates the `PlatformConfig` on first use, making the caller its admin; afterwards only
+    /// the admin may change it. `migrate_payment_mode` rejects mints that aren't listed.
+    pub fn set_allowed_payment_mints(ctx: Context<SetAllowedPaymentMints>, mints: Vec<Pubkey>) -> Result<()> {
+        PlatformConfig::validate_payment_mints(&mints)?;
+        let config = &mut ctx.accounts.platform_config;
+        if config.admin == Pubkey::default() {
+            config.admin = ctx.accounts.admin.key();
+            config.bump = ctx.bumps.platform_config;
+        }
+        require_keys_eq!(config.admin, ctx.accounts.admin.key(), FactoryError::AuthorityMismatch);
+        config.allowed_payment_mints = mints;
+
+        msg!("Platform accepts {} payment mints", config.allowed_payment_mints.len());
+        Ok(())
+    }
+
+    /// Update all fee parameters together (authority only).
+    ///
+    /// The combined configuration is validated before anything is written, so the launch
+    /// never passes through an intermediate state with an invalid fee split.
+    pub fn update_fees(ctx: Context<UpdateFees>, args: UpdateFeesArgs) -> Result<()> {
+        let state = &mut ctx.accounts.launch_state;
+        let peak_platform_fee_bps = args.platform_fee_bps.saturating_add(state.anti_snipe_fee_bps);
+        LaunchState::validate_fee_config(args.affiliate_fee_bps, peak_platform_fee_bps)?;
+
+        // A fair launch can never start charging fees
+        if state.fair_launch {
+            LaunchState::validate_fair_launch_config(
+                state.pricing_model, args.affiliate_fee_bps, args.platform_fee_bps, state.buyer_cashback_bps,
+            )?;
+        }
+        state.affiliate_fee_bps = args.affiliate_fee_bps;
+        state.platform_fee_bps = args.platform_fee_bps;
+        state.platform_fee_recipient = args.platform_fee_recipient;
+
+        msg!("Fees updated: affiliate={} bps, platform={} bps, recipient={}",
+             args.affiliate_fee_bps, args.platform_fee_bps, args.platform_fee_recipient);
+        Ok(())
+    }
+
+    /// Split the launch's platform fees across up to four recipients (authority only).
+    ///
+    /// Shares are in basis points and must sum to 10000. Once set, `buy_tokens` pays the
+    /// platform fee to these recipients instead of `platform_fee_recipient`.
+    pub fn set_fee_split(ctx: Context<SetFeeSplit>, recipients: Vec<FeeSplitRecipient>) -> Result<()> {
+        let fee_split = &mut ctx.accounts.fee_split_config;
+        fee_split.set_recipients(&recipients)?;
+        fee_split.launch_state = ctx.accounts.launch_state.key();
+        fee_split.bump = ctx.bumps.fee_split_config;
+        ctx.accounts.launch_state.fee_split_config = fee_split.key();
+
+        msg!("Platform fee split across {} recipients", recipients.len());
+        Ok(())
+    }
+
+    /// Sets the launch's token name, symbol and metadata URI (authority only).
+    ///
+    /// Creates the `LaunchMetadata` account on first use and overwrites it afterwards. Fields are
+    /// bounded to 32, 10 and 200 bytes; the values are also emitted in a `LaunchMetadataSetEvent`.
+    pub fn set_launch_metadata(ctx: Context<SetLaunchMetadata>, name: String, symbol: String, uri: String) -> Result<()> {
+        LaunchMetadata::validate(&name, &symbol, &uri)?;
+
+        let metadata = &mut ctx.accounts.launch_metadata;
+        metadata.launch_state = ctx.accounts.launch_state.key();
+        metadata.name = name;
+        metadata.symbol = symbol;
+        metadata.uri = uri;
+        metadata.updated_at = Clock::get()?.unix_timestamp;
+        metadata.bump = ctx.bumps.launch_metadata;
+
+        emit!(LaunchMetadataSetEvent {
+            launch_state: metadata.launch_state,
+            name: metadata.name.clone(),
+            symbol: metadata.symbol.clone(),
+            uri: metadata.uri.clone(),
+        });
+        Ok(())
+    }
+
+    /// Records a merkle root of holder balances computed off-chain (authority only).
+    ///
+    /// Iterating every holder on-chain isn't feasible, so the tree is built off-chain and only
+    /// its root is stored; holders later prove their balance with `verify_snapshot_claim`.
+    pub fn record_snapshot(ctx: Context<RecordSnapshot>, snapshot_id: u64, merkle_root: [u8; 32]) -> Result<()> {
+        Snapshot::validate_root(&merkle_root)?;
+
+        let snapshot = &mut ctx.accounts.snapshot;
+        snapshot.launch_state = ctx.accounts.launch_state.key();
+        snapshot.snapshot_id = snapshot_id;
+        snapshot.merkle_root = merkle_root;
+        snapshot.recorded_at = Clock::get()?.unix_timestamp;
+        snapshot.bump = ctx.bumps.snapshot;
+
+        emit!(SnapshotRecordedEvent {
+            launch_state: snapshot.launch_state,
+            snapshot_id,
+            merkle_root,
+            recorded_at: snapshot.recorded_at,
+        });
+        Ok(())
+    }
+
+    /// Verifies that `holder` held `balance` tokens in a recorded snapshot.
+    ///
+    /// Read-only; fails with `InvalidMerkleProof` unless `proof` (sibling hashes, leaf to root)
+    /// proves the holder's leaf is in the snapshot's tree.
+    pub fn verify_snapshot_claim(
+        ctx: Context<VerifySnapshotClaim>,
+        holder: Pubkey,
+        balance: u64,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        ctx.accounts.snapshot.verify_claim(&holder, balance, &proof)?;
+        msg!("Snapshot {} includes {} with balance {}", ctx.accounts.snapshot.snapshot_id, holder, balance);
+        Ok(())
+    }
+}
+
+/// Event emitted when a new launch is created
+#[event]
+pub struct LaunchCreatedEvent {
+    pub launch_state: Pubkey,
+    pub authority: Pubkey,
+    pub token_mint: Pubkey,
+    pub pricing_model: PricingModel,
+    pub initial_price: u64,
+    pub slope: u64,
+    pub max_tokens: u64,
+    pub launch_start_time: i64,
+    pub launch_end_time: i64,
+    pub timestamp: i64,
+}
+
+/// Event emitted when the authority sets a launch's token metadata
+#[event]
+pub struct LaunchMetadataSetEvent {
+    pub launch_state: Pubkey,
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+}
+
+/// Event emitted when the authority records a holder balance snapshot
+#[event]
+pub struct SnapshotRecordedEvent {
+    pub launch_state: Pubkey,
+    pub snapshot_id: u64,
+    pub merkle_root: [u8; 32],
+    pub recorded_at: i64,
+}
+
+/// Event emitted at the end of every successful `buy_tokens`
+#[event]
+pub struct PurchaseEvent {
+    /// Signer who paid for the purchase
+    pub buyer: Pubkey,
+    /// Wallet that received the tokens; equal to `buyer` unless bought on someone's behalf
+    pub recipient: Pubkey,
+    pub launch_state: Pubkey,
+    pub sol_amount: u64,
+    pub net_sol_amount: u64,
+    /// Purchased tokens, excluding cashback
+    pub tokens_minted: u64,
+    pub cashback_tokens: u64,
+    /// Price for one whole token at purchase time, in lamports
+    pub price_per_token: u64,
+    pub platform_fee: u64,
+    pub affiliate_fee: u64,
+    /// Whether the purchased tokens went into a vesting schedule
+    pub vested: bool,
+    pub timestamp: i64,
+}
+
+/// Event emitted by `quote_purchase` describing what an equivalent `buy_tokens` call would do
+#[event]
+pub struct QuoteEvent {
+    pub launch_state: Pubkey,
+    pub sol_amount: u64,
+    /// Current price for one whole token, in lamports
+    pub price_per_token: u64,
+    pub tokens_to_mint: u64,
+    pub platform_fee: u64,
+    pub affiliate_fee: u64,
+}
+
+/// Event emitted when the authority withdraws SOL from a launch's vault
+#[event]
+pub struct SolWithdrawnEvent {
+    pub launch_state: Pubkey,
+    pub authority: Pubkey,
+    pub amount: u64,
+    /// Lamports left in the SOL vault after the withdrawal
+    pub remaining_vault_balance: u64,
+    pub timestamp: i64,
+}
+
+/// Event emitted when an affiliate reinvests commission tokens into a launch
+#[event]
+pub struct CommissionCompoundedEvent {
+    pub launch_state: Pubkey,
+    pub affiliate: Pubkey,
+    pub commission_mint: Pubkey,
+    pub commission_amount: u64,
+    pub tokens_received: u64,
+}
+
+/// Event emitted when a purchase locks tokens in a new vesting schedule
+#[event]
+pub struct VestingScheduleCreatedEvent {
+    pub launch_state: Pubkey,
+    pub beneficiary: Pubkey,
+    pub total_amount: u64,
+    pub start_time: i64,
+    pub duration: i64,
+    pub cliff: i64,
+}
+
+/// Event emitted when the authority revokes a vesting grant
+#[event]
+pub struct VestingRevokedEvent {
+    pub launch_state: Pubkey,
+    pub beneficiary: Pubkey,
+    /// Vested but unclaimed tokens paid to the beneficiary
+    pub vested_paid: u64,
+    /// Unvested tokens burned or returned to the authority
+    pub unvested: u64,
+    pub burned: bool,
+    pub timestamp: i64,
+}
+
+/// Event emitted when an affiliate commission is skipped because the affiliate's token account is frozen
+#[event]
+pub struct AffiliateCommissionSkipped {
+    pub launch_state: Pubkey,
+    pub affiliate: Pubkey,
+    pub commission_amount: u64,
+    /// Affiliate fee (lamports) paid to the platform instead
+    pub redirected_fee: u64,
+}
+
+#[derive(Accounts)]
+pub struct CreateLaunch<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = LaunchState::LEN + 8,
+        seeds = [LAUNCH_STATE_SEED.as_ref(), authority.key().as_ref(), token_mint.key().as_ref()],
+        bump
+    )]
+    pub launch_state: Account<'info, LaunchState>,
+
+    #[account(
+        init,
+        payer = authority,
+        mint::decimals = 9,
+        mint::authority = launch_state
+    )]
     pub token_mint: Account<'info, Mint>,
     
     #[account(
@@ -454,15 +1685,29 @@ pub struct CreateLaunch<'info> {
     /// CHECK: This is a PDA used as a SOL vault. Its address is derived and verified by seeds.
     pub sol_vault: SystemAccount<'info>,
 
+    /// CHECK: The affiliate program's `EcosystemStats` singleton; validated by that program.
+    /// Required whenever `affiliate_program` is passed; omit both where the affiliate program
+    /// isn't deployed.
+    #[account(mut)]
+    pub ecosystem_stats: Option<UncheckedAccount<'info>>,
+
     #[account(mut)]
     pub authority: Signer<'info>,
+    pub affiliate_program: Option<Program<'info, AffiliateProgram>>,
     pub system_program: Program<'info, System>,
     pub token_program: Program<'info, Token>,
     pub rent: Sysvar<'info, Rent>,
 }
 
 #[derive(Accounts)]
-#[instruction(sol_amount: u64, affiliate_key: Option<Pubkey>)]
+#[instruction(
+    sol_amount: u64,
+    affiliate_key: Option<Pubkey>,
+    enable_vesting: bool,
+    min_tokens_out: u64,
+    whitelist_proof: Vec<[u8; 32]>,
+    recipient: Option<Pubkey>
+)]
 pub struct BuyTokens<'info> {
     #[account(
         mut,
@@ -482,11 +1727,12 @@ pub struct BuyTokens<'info> {
     /// CHECK: Vault address is derived from seeds and verified by Anchor.
     pub sol_vault: SystemAccount<'info>,
     
+    /// The beneficiary's token account; the buyer's own unless `recipient` is set.
     #[account(
         init_if_needed,
         payer = buyer,
         associated_token::mint = token_mint,
-        associated_token::authority = buyer,
+        associated_token::authority = beneficiary,
     )]
     pub buyer_token_account: Account<'info, TokenAccount>,
 
@@ -497,7 +1743,7 @@ pub struct BuyTokens<'info> {
         seeds = [
             VESTING_SCHEDULE_SEED.as_ref(),
             launch_state.key().as_ref(),
-            buyer.key().as_ref()
+            beneficiary.key().as_ref()
         ],
         bump
     )]
@@ -511,122 +1757,1004 @@ pub struct BuyTokens<'info> {
     )]
     pub vesting_token_account: Account<'info, TokenAccount>,
     
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        space = PurchaseTracker::LEN + 8,
+        seeds = [PURCHASE_TRACKER_SEED.as_ref(), launch_state.key().as_ref(), beneficiary.key().as_ref()],
+        bump
+    )]
+    pub purchase_tracker: Account<'info, PurchaseTracker>,
+
+    /// Pays for the purchase, fees and any new accounts
     #[account(mut)]
     pub buyer: Signer<'info>,
 
+    /// CHECK: Wallet receiving the tokens; must be `recipient` when set, otherwise the buyer.
+    #[account(
+        constraint = beneficiary.key() == recipient.unwrap_or(buyer.key()) @ FactoryError::RecipientMismatch
+    )]
+    pub beneficiary: UncheckedAccount<'info>,
+
     #[account(
         mut,
         address = launch_state.platform_fee_recipient
     )]
     pub platform_fee_recipient: SystemAccount<'info>,
 
+    /// CHECK: Address is verified by seeds; an initialized entry means the buyer is blocked.
+    #[account(
+        seeds = [BLOCKED_BUYER_SEED.as_ref(), launch_state.key().as_ref(), beneficiary.key().as_ref()],
+        bump
+    )]
+    pub blocked_buyer: UncheckedAccount<'info>,
+
+    /// Required when the launch has a platform fee split configured.
+    #[account(
+        seeds = [FEE_SPLIT_CONFIG_SEED.as_ref(), launch_state.key().as_ref()],
+        bump = fee_split_config.bump
+    )]
+    pub fee_split_config: Option<Account<'info, FeeSplitConfig>>,
+
+    /// Required when the launch is whitelist-gated.
+    #[account(
+        seeds = [WHITELIST_ENTRY_SEED.as_ref(), launch_state.key().as_ref(), beneficiary.key().as_ref()],
+        bump = whitelist_entry.bump
+    )]
+    pub whitelist_entry: Option<Account<'info, WhitelistEntry>>,
+
     /// --- Affiliate Accounts (Optional) ---
-    /// CHECK: The affiliate's main wallet account. Its public key is used as a seed.
+    /// Only needed when `affiliate_key` is set; non-affiliate buys may omit all of them, which
+    /// lets the factory run on clusters without the affiliate program.
+    /// CHECK: The affiliate's main wallet account; checked against `affiliate_key` in the handler.
     #[account(mut)]
-    pub affiliate: AccountInfo<'info>,
+    pub affiliate: Option<UncheckedAccount<'info>>,
 
     /// The affiliate's state account from the affiliate program.
     #[account(
-        seeds = [AFFILIATE_INFO_SEED.as_ref(), affiliate.key().as_ref()],
+        seeds = [AFFILIATE_INFO_SEED.as_ref(), affiliate_info.seed_key.as_ref()],
         bump,
-        seeds::program = affiliate_program.key()
+        seeds::program = affiliate_program::ID
     )]
     // Use the AffiliateInfo account type from the affiliate program crate
-    pub affiliate_info: Account<'info, affiliate_program::state::AffiliateInfo>,
+    pub affiliate_info: Option<Account<'info, affiliate_program::state::AffiliateInfo>>,
 
+    /// The affiliate's commission token account; must already exist. Not an ATA, so its mint
+    /// is checked explicitly.
     #[account(
-        init_if_needed,
-        payer = buyer,
-        associated_token::mint = token_mint,
-        associated_token::authority = affiliate
+        mut,
+        constraint = affiliate_token_account.mint == launch_state.token_mint @ FactoryError::InvalidMint
     )]
-    pub affiliate_token_account: Account<'info, TokenAccount>,
+    pub affiliate_token_account: Option<Account<'info, TokenAccount>>,
+
+    /// CHECK: The affiliate program's `EcosystemStats` singleton; validated by that program.
+    /// Required whenever `affiliate_program` is passed.
+    #[account(mut)]
+    pub ecosystem_stats: Option<UncheckedAccount<'info>>,
     
-    pub affiliate_program: Program<'info, AffiliateProgram>,
+    pub affiliate_program: Option<Program<'info, AffiliateProgram>>,
     pub system_program: Program<'info, System>,
     pub token_program: Program<'info, Token>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub rent: Sysvar<'info, Rent>,
 }
 
+impl<'info> BuyTokens<'info> {
+    /// Ensure every affiliate account is present and belongs to the affiliate credited by `affiliate_key`
+    pub fn check_affiliate_accounts(&self, affiliate_key: &Pubkey) -> Result<()> {
+        let (Some(affiliate), Some(affiliate_info), Some(affiliate_token_account), Some(_)) = (
+            &self.affiliate,
+            &self.affiliate_info,
+            &self.affiliate_token_account,
+            &self.affiliate_program,
+        ) else {
+            return err!(FactoryError::AffiliateAccountsMissing);
+        };
+        require_keys_eq!(*affiliate_key, affiliate.key(), FactoryError::AffiliateMismatch);
+        require!(
+            LaunchState::affiliate_accounts_match(
+                &affiliate.key(),
+                &affiliate_info.affiliate_key,
+                &affiliate_token_account.owner,
+            ),
+            FactoryError::AffiliateMismatch
+        );
+        Ok(())
+    }
+}
+
 #[derive(Accounts)]
-pub struct WithdrawSol<'info> {
+#[instruction(amount_in: u64)]
+pub struct BuyTokensWithSpl<'info> {
     #[account(
-        seeds = [LAUNCH_STATE_SEED.as_ref(), authority.key().as_ref(), launch_state.token_mint.as_ref()],
-        bump,
-        has_one = authority @ FactoryError::AuthorityMismatch
+        mut,
+        seeds = [LAUNCH_STATE_SEED.as_ref(), launch_state.authority.as_ref(), launch_state.token_mint.as_ref()],
+        bump
     )]
     pub launch_state: Account<'info, LaunchState>,
 
+    #[account(mut, address = launch_state.token_mint)]
+    pub token_mint: Account<'info, Mint>,
+
+    #[account(address = launch_state.payment_mint @ FactoryError::MissingPaymentMint)]
+    pub payment_mint: Account<'info, Mint>,
+
     #[account(
         mut,
-        seeds = [SOL_VAULT_SEED.as_ref(), authority.key().as_ref(), launch_state.token_mint.as_ref()],
-        bump = launch_state.sol_vault_bump
+        associated_token::mint = payment_mint,
+        associated_token::authority = launch_state
     )]
-    /// CHECK: Vault address is derived from seeds and verified by Anchor.
-    pub sol_vault: SystemAccount<'info>,
-    
-    #[account(mut)]
-    pub authority: Signer<'info>,
+    pub payment_vault: Account<'info, TokenAccount>,
 
-    pub system_program: Program<'info, System>,
-}
+    #[account(mut, token::mint = payment_mint, token::authority = buyer)]
+    pub buyer_payment_account: Account<'info, TokenAccount>,
 
-#[derive(Accounts)]
-#[instruction(args: ClaimVestedTokensArgs)]
-pub struct ClaimVestedTokens<'info> {
+    /// Receives the platform fee when no fee split is configured.
     #[account(
         mut,
-        seeds = [LAUNCH_STATE_SEED.as_ref(), launch_state.authority.as_ref(), launch_state.token_mint.as_ref()],
-        bump
+        token::mint = payment_mint,
+        token::authority = launch_state.platform_fee_recipient
     )]
-    pub launch_state: Account<'info, LaunchState>,
+    pub platform_fee_token_account: Account<'info, TokenAccount>,
 
     #[account(
-        mut,
-        seeds = [
-            VESTING_SCHEDULE_SEED.as_ref(),
-            launch_state.key().as_ref(),
-            vesting_schedule.beneficiary.as_ref()
-        ],
-        bump,
-        has_one = launch_state @ FactoryError::VestingScheduleNotFound,
-        has_one = beneficiary @ FactoryError::AuthorityMismatch
+        init_if_needed,
+        payer = buyer,
+        associated_token::mint = token_mint,
+        associated_token::authority = buyer,
     )]
-    pub vesting_schedule: Account<'info, VestingSchedule>,
+    pub buyer_token_account: Account<'info, TokenAccount>,
 
     #[account(
-        mut,
-        associated_token::mint = launch_state.token_mint,
-        associated_token::authority = vesting_schedule
+        init_if_needed,
+        payer = buyer,
+        space = PurchaseTracker::LEN + 8,
+        seeds = [PURCHASE_TRACKER_SEED.as_ref(), launch_state.key().as_ref(), buyer.key().as_ref()],
+        bump
     )]
-    pub vesting_token_account: Account<'info, TokenAccount>,
+    pub purchase_tracker: Account<'info, PurchaseTracker>,
+
+    #[account(mut)]
+    pub buyer: Signer<'info>,
 
+    /// CHECK: Address is verified by seeds; an initialized entry means the buyer is blocked.
     #[account(
-        mut,
-        associated_token::mint = launch_state.token_mint,
-        associated_token::authority = beneficiary
+        seeds = [BLOCKED_BUYER_SEED.as_ref(), launch_state.key().as_ref(), buyer.key().as_ref()],
+        bump
     )]
-    pub beneficiary_token_account: Account<'info, TokenAccount>,
+    pub blocked_buyer: UncheckedAccount<'info>,
 
-    #[account(mut)]
-    pub beneficiary: Signer<'info>,
+    /// Required when the launch has a platform fee split configured.
+    #[account(
+        seeds = [FEE_SPLIT_CONFIG_SEED.as_ref(), launch_state.key().as_ref()],
+        bump = fee_split_config.bump
+    )]
+    pub fee_split_config: Option<Account<'info, FeeSplitConfig>>,
+
+    /// Required when the launch is whitelist-gated.
+    #[account(
+        seeds = [WHITELIST_ENTRY_SEED.as_ref(), launch_state.key().as_ref(), buyer.key().as_ref()],
+        bump = whitelist_entry.bump
+    )]
+    pub whitelist_entry: Option<Account<'info, WhitelistEntry>>,
 
+    pub system_program: Program<'info, System>,
     pub token_program: Program<'info, Token>,
     pub associated_token_program: Program<'info, AssociatedToken>,
 }
 
-#[derive(Accounts)]
-#[instruction(args: UpdateLaunchArgs)]
-pub struct UpdateLaunch<'info> {
-    #[account(
-        mut,
-        seeds = [LAUNCH_STATE_SEED.as_ref(), authority.key().as_ref(), launch_state.token_mint.as_ref()],
-        bump,
-        has_one = authority @ FactoryError::AuthorityMismatch
-    )]
-    pub launch_state: Account<'info, LaunchState>,
+impl<'info> BuyTokensWithSpl<'info> {
+    /// Executes an SPL-token purchase and returns the tokens received, including cashback.
+    pub fn process_purchase(
+        &mut self,
+        launch_state_bump: u8,
+        remaining_accounts: &[AccountInfo<'info>],
+        amount_in: u64,
+        min_tokens_out: u64,
+    ) -> Result<u64> {
+        require!(amount_in > 0, FactoryError::InvalidAmount);
+        let state = &mut self.launch_state;
+        state.check_spl_payment_mint(&self.payment_mint.key())?;
+
+        // Same launch, access and anti-bot checks as SOL purchases
+        state.check_not_finalized()?;
+        state.check_not_paused()?;
+        require!(state.is_launch_active()?, FactoryError::LaunchNotActive);
+        require!(!state.is_max_supply_reached(), FactoryError::MaxSupplyReached);
+        let blocked_buyer = &self.blocked_buyer;
+        LaunchState::check_buyer_not_blocked(blocked_buyer.owner == &crate::ID && !blocked_buyer.data_is_empty())?;
+        state.check_whitelist(self.whitelist_entry.is_some())?;
+        // SPL purchases carry no merkle proof, so root-gated launches need a `WhitelistEntry` here
+        state.check_whitelist_proof(&self.buyer.key(), &[], self.whitelist_entry.is_some())?;
+        let current_time = Clock::get()?.unix_timestamp;
+        state.validate_purchase_amount(amount_in, self.purchase_tracker.last_purchase_time, current_time)?;
 
-    #[account(mut)]
-    pub authority: Signer<'info>,
-}
\ No newline at end of file
+        // Price and size the purchase in payment token units
+        let current_price_per_token = state.calculate_current_price()?;
+        require!(current_price_per_token > 0, FactoryError::InvalidAmount);
+        let tokens_to_mint = state.calculate_curve_purchase_tokens(amount_in, current_price_per_token)?;
+        let tokens_to_mint = state.clamp_to_remaining_supply(tokens_to_mint)?;
+        LaunchState::check_min_tokens_out(tokens_to_mint, min_tokens_out)?;
+        state.check_tx_cap(tokens_to_mint)?;
+        state.check_wallet_cap(self.purchase_tracker.total_purchased, tokens_to_mint)?;
+        state.check_anti_snipe_wallet_cap(self.purchase_tracker.total_purchased, tokens_to_mint, current_time)?;
+        let cashback_tokens = state.calculate_cashback_tokens(tokens_to_mint)?;
+        let new_total_supply = state.supply_after_purchase(tokens_to_mint, cashback_tokens)?;
+
+        let mut fees = state.calculate_fees(amount_in, false)?;
+        fees.apply_discount(state.fee_holiday_discount_bps(current_time))?;
+        fees.add_platform_surcharge(state.calculate_anti_snipe_fee(amount_in, current_time)?)?;
+        let FeeBreakdown { platform_fee, net_sol_amount: net_amount, .. } = fees;
+        state.check_hard_cap(net_amount)?;
+
+        // Transfer the platform fee, split across the fee split recipients when configured
+        if platform_fee > 0 && state.fee_split_config == Pubkey::default() {
+            token::transfer(
+                CpiContext::new(
+                    self.token_program.to_account_info(),
+                    token::Transfer {
+                        from: self.buyer_payment_account.to_account_info(),
+                        to: self.platform_fee_token_account.to_account_info(),
+                        authority: self.buyer.to_account_info(),
+                    },
+                ),
+                platform_fee,
+            )?;
+        } else if platform_fee > 0 {
+            let fee_split = self.fee_split_config.as_ref()
+                .ok_or(FactoryError::FeeSplitRecipientMismatch)?;
+            require_keys_eq!(fee_split.key(), state.fee_split_config, FactoryError::FeeSplitRecipientMismatch);
+            let recipients = fee_split.active_recipients();
+            require!(remaining_accounts.len() >= recipients.len(), FactoryError::FeeSplitRecipientMismatch);
+
+            let shares = fee_split.split_fee(platform_fee)?;
+            for ((entry, share), recipient_info) in recipients.iter().zip(shares).zip(remaining_accounts.iter()) {
+                require_keys_eq!(*recipient_info.owner, token::ID, FactoryError::FeeSplitRecipientMismatch);
+                let recipient_account = TokenAccount::try_deserialize(&mut &recipient_info.try_borrow_data()?[..])?;
+                require!(recipient_account.owner == entry.recipient && recipient_account.mint == state.payment_mint,
+                        FactoryError::FeeSplitRecipientMismatch);
+                if share == 0 {
+                    continue;
+                }
+                token::transfer(
+                    CpiContext::new(
+                        self.token_program.to_account_info(),
+                        token::Transfer {
+                            from: self.buyer_payment_account.to_account_info(),
+                            to: recipient_info.clone(),
+                            authority: self.buyer.to_account_info(),
+                        },
+                    ),
+                    share,
+                )?;
+            }
+        }
+
+        // Transfer the net payment to the launch's payment vault
+        token::transfer(
+            CpiContext::new(
+                self.token_program.to_account_info(),
+                token::Transfer {
+                    from: self.buyer_payment_account.to_account_info(),
+                    to: self.payment_vault.to_account_info(),
+                    authority: self.buyer.to_account_info(),
+                },
+            ),
+            net_amount,
+        )?;
+
+        // Mint the purchase plus any cashback to the buyer
+        let authority_key = state.authority;
+        let token_mint_key = state.token_mint;
+        let seeds = pda_utils::launch_state_signer_seeds(&authority_key, &token_mint_key, &launch_state_bump);
+        let tokens_received = tokens_to_mint.checked_add(cashback_tokens).ok_or(FactoryError::Overflow)?;
+        token::mint_to(
+            CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                token::MintTo {
+                    mint: self.token_mint.to_account_info(),
+                    to: self.buyer_token_account.to_account_info(),
+                    authority: state.to_account_info(),
+                },
+                &[&seeds[..]],
+            ),
+            tokens_received,
+        )?;
+
+        // Update state; raise totals are in payment token units for SPL launches
+        state.tokens_sold = new_total_supply;
+        state.record_raise(net_amount)?;
+        state.total_fees_collected = state.total_fees_collected.checked_add(platform_fee)
+            .ok_or(FactoryError::Overflow)?;
+        state.purchase_count = state.purchase_count.checked_add(1)
+            .ok_or(FactoryError::Overflow)?;
+        state.last_purchase_timestamp = current_time;
+
+        self.purchase_tracker.record_purchase(
+            state.key(),
+            self.buyer.key(),
+            net_amount,
+            tokens_to_mint,
+            tokens_received,
+            state.last_purchase_timestamp,
+        )?;
+
+        msg!("SPL purchase completed: {} tokens (+{} cashback) minted for {} of {}",
+             tokens_to_mint, cashback_tokens, amount_in, state.payment_mint);
+        Ok(tokens_received)
+    }
+}
+
+#[derive(Accounts)]
+pub struct CompoundCommission<'info> {
+    pub purchase: BuyTokensWithSpl<'info>,
+
+    #[account(
+        seeds = [AFFILIATE_INFO_SEED.as_ref(), affiliate_info.seed_key.as_ref()],
+        bump,
+        seeds::program = affiliate_program::ID,
+        constraint = affiliate_info.affiliate_key == purchase.buyer.key() @ FactoryError::AffiliateMismatch,
+        constraint = affiliate_info.auto_compound_enabled @ FactoryError::CommissionCompoundingDisabled
+    )]
+    pub affiliate_info: Account<'info, affiliate_program::state::AffiliateInfo>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawPayment<'info> {
+    #[account(
+        seeds = [LAUNCH_STATE_SEED.as_ref(), authority.key().as_ref(), launch_state.token_mint.as_ref()],
+        bump,
+        has_one = authority @ FactoryError::AuthorityMismatch
+    )]
+    pub launch_state: Account<'info, LaunchState>,
+
+    #[account(address = launch_state.payment_mint @ FactoryError::MissingPaymentMint)]
+    pub payment_mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        associated_token::mint = payment_mint,
+        associated_token::authority = launch_state
+    )]
+    pub payment_vault: Account<'info, TokenAccount>,
+
+    #[account(mut, token::mint = payment_mint, token::authority = authority)]
+    pub authority_payment_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct FinalizeLaunch<'info> {
+    #[account(
+        mut,
+        seeds = [LAUNCH_STATE_SEED.as_ref(), authority.key().as_ref(), launch_state.token_mint.as_ref()],
+        bump,
+        has_one = authority @ FactoryError::AuthorityMismatch
+    )]
+    pub launch_state: Account<'info, LaunchState>,
+
+    #[account(mut, address = launch_state.token_mint)]
+    pub token_mint: Account<'info, Mint>,
+
+    pub authority: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawSol<'info> {
+    #[account(
+        seeds = [LAUNCH_STATE_SEED.as_ref(), authority.key().as_ref(), launch_state.token_mint.as_ref()],
+        bump,
+        has_one = authority @ FactoryError::AuthorityMismatch
+    )]
+    pub launch_state: Account<'info, LaunchState>,
+
+    #[account(
+        mut,
+        seeds = [SOL_VAULT_SEED.as_ref(), authority.key().as_ref(), launch_state.token_mint.as_ref()],
+        bump = launch_state.sol_vault_bump
+    )]
+    /// CHECK: Vault address is derived from seeds and verified by Anchor.
+    pub sol_vault: SystemAccount<'info>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct Refund<'info> {
+    #[account(
+        mut,
+        seeds = [LAUNCH_STATE_SEED.as_ref(), launch_state.authority.as_ref(), launch_state.token_mint.as_ref()],
+        bump
+    )]
+    pub launch_state: Account<'info, LaunchState>,
+
+    #[account(mut, address = launch_state.token_mint)]
+    pub token_mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [SOL_VAULT_SEED.as_ref(), launch_state.authority.as_ref(), launch_state.token_mint.as_ref()],
+        bump = launch_state.sol_vault_bump
+    )]
+    pub sol_vault: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [PURCHASE_TRACKER_SEED.as_ref(), launch_state.key().as_ref(), buyer.key().as_ref()],
+        bump,
+        has_one = launch_state @ FactoryError::InvalidAccountState,
+        has_one = buyer @ FactoryError::AuthorityMismatch
+    )]
+    pub purchase_tracker: Account<'info, PurchaseTracker>,
+
+    /// Required when the buyer made vested purchases.
+    #[account(
+        mut,
+        seeds = [VESTING_SCHEDULE_SEED.as_ref(), launch_state.key().as_ref(), buyer.key().as_ref()],
+        bump
+    )]
+    pub vesting_schedule: Option<Account<'info, VestingSchedule>>,
+
+    /// The vesting schedule's token account, required alongside it; its unclaimed tokens are burned.
+    #[account(
+        mut,
+        associated_token::mint = token_mint,
+        associated_token::authority = vesting_schedule
+    )]
+    pub vesting_token_account: Option<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = token_mint,
+        associated_token::authority = buyer
+    )]
+    pub buyer_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(closing: bool)]
+pub struct SweepVaultDust<'info> {
+    #[account(
+        seeds = [LAUNCH_STATE_SEED.as_ref(), authority.key().as_ref(), launch_state.token_mint.as_ref()],
+        bump,
+        has_one = authority @ FactoryError::AuthorityMismatch
+    )]
+    pub launch_state: Account<'info, LaunchState>,
+
+    #[account(
+        mut,
+        seeds = [SOL_VAULT_SEED.as_ref(), authority.key().as_ref(), launch_state.token_mint.as_ref()],
+        bump = launch_state.sol_vault_bump
+    )]
+    pub sol_vault: SystemAccount<'info>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(args: ClaimVestedTokensArgs)]
+pub struct ClaimVestedTokens<'info> {
+    #[account(
+        mut,
+        seeds = [LAUNCH_STATE_SEED.as_ref(), launch_state.authority.as_ref(), launch_state.token_mint.as_ref()],
+        bump
+    )]
+    pub launch_state: Account<'info, LaunchState>,
+
+    #[account(
+        mut,
+        seeds = [
+            VESTING_SCHEDULE_SEED.as_ref(),
+            launch_state.key().as_ref(),
+            vesting_schedule.beneficiary.as_ref()
+        ],
+        bump,
+        has_one = launch_state @ FactoryError::VestingScheduleNotFound,
+        has_one = beneficiary @ FactoryError::AuthorityMismatch
+    )]
+    pub vesting_schedule: Account<'info, VestingSchedule>,
+
+    #[account(
+        mut,
+        associated_token::mint = launch_state.token_mint,
+        associated_token::authority = vesting_schedule
+    )]
+    pub vesting_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        associated_token::mint = launch_state.token_mint,
+        associated_token::authority = beneficiary
+    )]
+    pub beneficiary_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub beneficiary: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+#[derive(Accounts)]
+pub struct CreateVestingGrant<'info> {
+    #[account(
+        mut,
+        seeds = [LAUNCH_STATE_SEED.as_ref(), authority.key().as_ref(), launch_state.token_mint.as_ref()],
+        bump,
+        has_one = authority @ FactoryError::AuthorityMismatch
+    )]
+    pub launch_state: Account<'info, LaunchState>,
+
+    #[account(mut, address = launch_state.token_mint)]
+    pub token_mint: Account<'info, Mint>,
+
+    /// CHECK: Only used as the grant's beneficiary key and vesting schedule seed.
+    pub beneficiary: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = VestingSchedule::LEN + 8,
+        seeds = [
+            VESTING_SCHEDULE_SEED.as_ref(),
+            launch_state.key().as_ref(),
+            beneficiary.key().as_ref()
+        ],
+        bump
+    )]
+    pub vesting_schedule: Account<'info, VestingSchedule>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        associated_token::mint = token_mint,
+        associated_token::authority = vesting_schedule
+    )]
+    pub vesting_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeVesting<'info> {
+    #[account(
+        mut,
+        seeds = [LAUNCH_STATE_SEED.as_ref(), authority.key().as_ref(), launch_state.token_mint.as_ref()],
+        bump,
+        has_one = authority @ FactoryError::AuthorityMismatch
+    )]
+    pub launch_state: Account<'info, LaunchState>,
+
+    #[account(mut, address = launch_state.token_mint)]
+    pub token_mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [
+            VESTING_SCHEDULE_SEED.as_ref(),
+            launch_state.key().as_ref(),
+            beneficiary.key().as_ref()
+        ],
+        bump,
+        has_one = launch_state @ FactoryError::VestingScheduleNotFound,
+        has_one = beneficiary @ FactoryError::AuthorityMismatch
+    )]
+    pub vesting_schedule: Account<'info, VestingSchedule>,
+
+    #[account(
+        mut,
+        associated_token::mint = token_mint,
+        associated_token::authority = vesting_schedule
+    )]
+    pub vesting_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK: Must match the schedule's beneficiary; only used as a key.
+    pub beneficiary: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        associated_token::mint = token_mint,
+        associated_token::authority = beneficiary
+    )]
+    pub beneficiary_token_account: Account<'info, TokenAccount>,
+
+    /// Receives unvested tokens unless they are burned; must be owned by the launch authority.
+    #[account(
+        mut,
+        constraint = unvested_destination.mint == launch_state.token_mint @ FactoryError::InvalidMint,
+        token::authority = authority
+    )]
+    pub unvested_destination: Option<Account<'info, TokenAccount>>,
+
+    pub authority: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct CloseVesting<'info> {
+    #[account(
+        mut,
+        seeds = [
+            VESTING_SCHEDULE_SEED.as_ref(),
+            vesting_schedule.launch_state.as_ref(),
+            vesting_schedule.beneficiary.as_ref()
+        ],
+        bump,
+        has_one = beneficiary @ FactoryError::AuthorityMismatch,
+        constraint = vesting_schedule.rent_destination() == rent_recipient.key() @ FactoryError::AuthorityMismatch,
+        close = rent_recipient
+    )]
+    pub vesting_schedule: Account<'info, VestingSchedule>,
+
+    #[account(
+        mut,
+        token::authority = vesting_schedule
+    )]
+    pub vesting_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK: Must match the schedule's rent destination; only receives lamports.
+    #[account(mut)]
+    pub rent_recipient: UncheckedAccount<'info>,
+
+    pub beneficiary: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct CloseLaunch<'info> {
+    #[account(
+        mut,
+        close = authority,
+        seeds = [LAUNCH_STATE_SEED.as_ref(), authority.key().as_ref(), launch_state.token_mint.as_ref()],
+        bump,
+        has_one = authority @ FactoryError::AuthorityMismatch
+    )]
+    pub launch_state: Account<'info, LaunchState>,
+
+    #[account(
+        seeds = [SOL_VAULT_SEED.as_ref(), authority.key().as_ref(), launch_state.token_mint.as_ref()],
+        bump = launch_state.sol_vault_bump
+    )]
+    pub sol_vault: SystemAccount<'info>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct HealthCheck<'info> {
+    pub clock: Sysvar<'info, Clock>,
+}
+
+#[derive(Accounts)]
+pub struct GetVestingTimeline<'info> {
+    #[account(
+        seeds = [
+            VESTING_SCHEDULE_SEED.as_ref(),
+            vesting_schedule.launch_state.as_ref(),
+            vesting_schedule.beneficiary.as_ref()
+        ],
+        bump
+    )]
+    pub vesting_schedule: Account<'info, VestingSchedule>,
+}
+
+#[derive(Accounts)]
+#[instruction(sol_amount: u64, affiliate_key: Option<Pubkey>)]
+pub struct QuotePurchase<'info> {
+    #[account(
+        seeds = [LAUNCH_STATE_SEED.as_ref(), launch_state.authority.as_ref(), launch_state.token_mint.as_ref()],
+        bump
+    )]
+    pub launch_state: Account<'info, LaunchState>,
+}
+
+#[derive(Accounts)]
+#[instruction(args: UpdateLaunchArgs)]
+pub struct UpdateLaunch<'info> {
+    #[account(
+        mut,
+        seeds = [LAUNCH_STATE_SEED.as_ref(), authority.key().as_ref(), launch_state.token_mint.as_ref()],
+        bump,
+        has_one = authority @ FactoryError::AuthorityMismatch
+    )]
+    pub launch_state: Account<'info, LaunchState>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(buyers: Vec<Pubkey>)]
+pub struct AddWhitelistEntries<'info> {
+    #[account(
+        seeds = [LAUNCH_STATE_SEED.as_ref(), authority.key().as_ref(), launch_state.token_mint.as_ref()],
+        bump,
+        has_one = authority @ FactoryError::AuthorityMismatch
+    )]
+    pub launch_state: Account<'info, LaunchState>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(accepting_new: bool)]
+pub struct SetVestingAcceptingNew<'info> {
+    #[account(
+        mut,
+        seeds = [LAUNCH_STATE_SEED.as_ref(), authority.key().as_ref(), launch_state.token_mint.as_ref()],
+        bump,
+        has_one = authority @ FactoryError::AuthorityMismatch
+    )]
+    pub launch_state: Account<'info, LaunchState>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(paused: bool)]
+pub struct SetPaused<'info> {
+    #[account(
+        mut,
+        seeds = [LAUNCH_STATE_SEED.as_ref(), authority.key().as_ref(), launch_state.token_mint.as_ref()],
+        bump,
+        has_one = authority @ FactoryError::AuthorityMismatch
+    )]
+    pub launch_state: Account<'info, LaunchState>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetWhitelistRoot<'info> {
+    #[account(
+        mut,
+        seeds = [LAUNCH_STATE_SEED.as_ref(), authority.key().as_ref(), launch_state.token_mint.as_ref()],
+        bump,
+        has_one = authority @ FactoryError::AuthorityMismatch
+    )]
+    pub launch_state: Account<'info, LaunchState>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetApprovedAffiliates<'info> {
+    #[account(
+        mut,
+        seeds = [LAUNCH_STATE_SEED.as_ref(), authority.key().as_ref(), launch_state.token_mint.as_ref()],
+        bump,
+        has_one = authority @ FactoryError::AuthorityMismatch
+    )]
+    pub launch_state: Account<'info, LaunchState>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(buyer: Pubkey)]
+pub struct BlockBuyer<'info> {
+    #[account(
+        seeds = [LAUNCH_STATE_SEED.as_ref(), authority.key().as_ref(), launch_state.token_mint.as_ref()],
+        bump,
+        has_one = authority @ FactoryError::AuthorityMismatch
+    )]
+    pub launch_state: Account<'info, LaunchState>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = BlockedBuyer::LEN + 8,
+        seeds = [BLOCKED_BUYER_SEED.as_ref(), launch_state.key().as_ref(), buyer.as_ref()],
+        bump
+    )]
+    pub blocked_buyer: Account<'info, BlockedBuyer>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(buyer: Pubkey)]
+pub struct UnblockBuyer<'info> {
+    #[account(
+        seeds = [LAUNCH_STATE_SEED.as_ref(), authority.key().as_ref(), launch_state.token_mint.as_ref()],
+        bump,
+        has_one = authority @ FactoryError::AuthorityMismatch
+    )]
+    pub launch_state: Account<'info, LaunchState>,
+
+    #[account(
+        mut,
+        close = authority,
+        seeds = [BLOCKED_BUYER_SEED.as_ref(), launch_state.key().as_ref(), buyer.as_ref()],
+        bump
+    )]
+    pub blocked_buyer: Account<'info, BlockedBuyer>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(payment_mode: PaymentMode)]
+pub struct MigratePaymentMode<'info> {
+    #[account(
+        mut,
+        seeds = [LAUNCH_STATE_SEED.as_ref(), authority.key().as_ref(), launch_state.token_mint.as_ref()],
+        bump,
+        has_one = authority @ FactoryError::AuthorityMismatch
+    )]
+    pub launch_state: Account<'info, LaunchState>,
+
+    /// Required when migrating to `SplToken`.
+    pub payment_mint: Option<Account<'info, Mint>>,
+
+    /// Platform allowlist the payment mint is checked against.
+    #[account(seeds = [PLATFORM_CONFIG_SEED.as_ref()], bump = platform_config.bump)]
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    /// The launch's vault for SPL-token payments, required when migrating to `SplToken`.
+    #[account(
+        init_if_needed,
+        payer = authority,
+        associated_token::mint = payment_mint,
+        associated_token::authority = launch_state
+    )]
+    pub payment_vault: Option<Account<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+#[derive(Accounts)]
+pub struct SetAllowedPaymentMints<'info> {
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = PlatformConfig::LEN + 8,
+        seeds = [PLATFORM_CONFIG_SEED.as_ref()],
+        bump
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(args: UpdateFeesArgs)]
+pub struct UpdateFees<'info> {
+    #[account(
+        mut,
+        seeds = [LAUNCH_STATE_SEED.as_ref(), authority.key().as_ref(), launch_state.token_mint.as_ref()],
+        bump,
+        has_one = authority @ FactoryError::AuthorityMismatch
+    )]
+    pub launch_state: Account<'info, LaunchState>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(recipients: Vec<FeeSplitRecipient>)]
+pub struct SetFeeSplit<'info> {
+    #[account(
+        mut,
+        seeds = [LAUNCH_STATE_SEED.as_ref(), authority.key().as_ref(), launch_state.token_mint.as_ref()],
+        bump,
+        has_one = authority @ FactoryError::AuthorityMismatch
+    )]
+    pub launch_state: Account<'info, LaunchState>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = FeeSplitConfig::LEN + 8,
+        seeds = [FEE_SPLIT_CONFIG_SEED.as_ref(), launch_state.key().as_ref()],
+        bump
+    )]
+    pub fee_split_config: Account<'info, FeeSplitConfig>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetLaunchMetadata<'info> {
+    #[account(
+        seeds = [LAUNCH_STATE_SEED.as_ref(), authority.key().as_ref(), launch_state.token_mint.as_ref()],
+        bump,
+        has_one = authority @ FactoryError::AuthorityMismatch
+    )]
+    pub launch_state: Account<'info, LaunchState>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = LaunchMetadata::LEN + 8,
+        seeds = [LAUNCH_METADATA_SEED.as_ref(), launch_state.key().as_ref()],
+        bump
+    )]
+    pub launch_metadata: Account<'info, LaunchMetadata>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(snapshot_id: u64)]
+pub struct RecordSnapshot<'info> {
+    #[account(
+        seeds = [LAUNCH_STATE_SEED.as_ref(), authority.key().as_ref(), launch_state.token_mint.as_ref()],
+        bump,
+        has_one = authority @ FactoryError::AuthorityMismatch
+    )]
+    pub launch_state: Account<'info, LaunchState>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = Snapshot::LEN + 8,
+        seeds = [SNAPSHOT_SEED.as_ref(), launch_state.key().as_ref(), snapshot_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub snapshot: Account<'info, Snapshot>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct VerifySnapshotClaim<'info> {
+    #[account(
+        seeds = [SNAPSHOT_SEED.as_ref(), snapshot.launch_state.as_ref(), snapshot.snapshot_id.to_le_bytes().as_ref()],
+        bump = snapshot.bump
+    )]
+    pub snapshot: Account<'info, Snapshot>,
+}
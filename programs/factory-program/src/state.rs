@@ -6,11 +6,12 @@ use genesis_common::constants::*;
 pub enum PricingModel {
     /// Linear bonding curve: price = initial_price + (slope * tokens_sold)
     LinearBondingCurve,
-    /// Exponential bonding curve: price = initial_price * (1 + slope)^tokens_sold
+    /// Exponential bonding curve: price = initial_price * slope^tokens_sold, where slope is
+    /// `(1 + rate)` in 1e9 fixed point and compounds once per whole token sold
     ExponentialBondingCurve,
     /// Fixed price: constant price regardless of tokens sold
     FixedPrice,
-    /// Dutch auction: price decreases over time
+    /// Dutch auction: price decreases over time from initial_price, never below floor_price
     DutchAuction,
 }
 
@@ -27,6 +28,98 @@ pub enum AntiBotLevel {
     Maximum,
 }
 
+/// Currency buyers pay in
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PaymentMode {
+    /// Native SOL into the `sol_vault` PDA
+    NativeSol,
+    /// An SPL token (e.g. a stablecoin) into the launch's payment token vault
+    SplToken,
+}
+
+/// Fee split of a single purchase, in lamports
+///
+/// The buyer pays `platform_fee + net_sol_amount`, which always equals the purchase amount.
+/// `affiliate_fee` is the SOL value of the affiliate's share; it is settled in commission
+/// tokens rather than SOL, so it is informational unless redirected to the platform.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FeeBreakdown {
+    pub platform_fee: u64,
+    pub affiliate_fee: u64,
+    /// What remains for the SOL vault after fees
+    pub net_sol_amount: u64,
+}
+
+impl FeeBreakdown {
+    /// Move the affiliate's share to the platform as SOL, returning the amount moved
+    ///
+    /// Used when the affiliate can't receive commission tokens; the share comes out of the
+    /// vault's net amount so the buyer's total is unchanged.
+    pub fn redirect_affiliate_fee_to_platform(&mut self) -> Result<u64> {
+        let redirected = self.affiliate_fee;
+        self.platform_fee = self.platform_fee.checked_add(redirected)
+            .ok_or(FactoryError::FeeCalculationOverflow)?;
+        self.net_sol_amount = self.net_sol_amount.checked_sub(redirected)
+            .ok_or(FactoryError::FeesExceedPurchase)?;
+        self.affiliate_fee = 0;
+        Ok(redirected)
+    }
+
+    /// Waive `discount_bps` of the platform and affiliate fees; the platform fee saved stays
+    /// in the vault's net amount
+    pub fn apply_discount(&mut self, discount_bps: u16) -> Result<()> {
+        let waived = |fee: u64| genesis_common::utils::math_utils::calculate_commission_amount(fee, discount_bps);
+        let platform_waived = waived(self.platform_fee)?;
+        self.affiliate_fee = self.affiliate_fee.checked_sub(waived(self.affiliate_fee)?)
+            .ok_or(FactoryError::Overflow)?;
+        self.platform_fee = self.platform_fee.checked_sub(platform_waived)
+            .ok_or(FactoryError::Overflow)?;
+        self.net_sol_amount = self.net_sol_amount.checked_add(platform_waived)
+            .ok_or(FactoryError::FeeCalculationOverflow)?;
+        Ok(())
+    }
+
+    /// Add a surcharge to the platform fee, taken out of the vault's net amount
+    pub fn add_platform_surcharge(&mut self, surcharge: u64) -> Result<()> {
+        self.platform_fee = self.platform_fee.checked_add(surcharge)
+            .ok_or(FactoryError::FeeCalculationOverflow)?;
+        self.net_sol_amount = self.net_sol_amount.checked_sub(surcharge)
+            .ok_or(FactoryError::FeesExceedPurchase)?;
+        require!(self.net_sol_amount > 0, FactoryError::FeesExceedPurchase);
+        Ok(())
+    }
+}
+
+/// How a vesting schedule releases tokens
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum VestingKind {
+    /// Nothing before the cliff, then linear until the end of the duration
+    Linear,
+    /// Unlocks in steps at each `VestingMilestone`
+    Milestones,
+}
+
+/// One step of a milestone vesting schedule
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub struct VestingMilestone {
+    /// Seconds after the schedule's start time at which this step unlocks
+    pub offset_seconds: i64,
+    /// Share of the total unlocked once this step is reached, cumulative, in basis points
+    pub cumulative_bps: u64,
+}
+
+impl VestingMilestone {
+    /// Serialized size of one milestone
+    pub const LEN: usize = 8 + 8;
+}
+
+/// A platform fee recipient and its share of the platform fee
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub struct FeeSplitRecipient {
+    pub recipient: Pubkey,
+    pub share_bps: u16,
+}
+
 /// State account for a token launch with advanced features
 #[account]
 pub struct LaunchState {
@@ -41,22 +134,33 @@ pub struct LaunchState {
     pub pricing_model: PricingModel,
     /// The starting price for one whole token (10^9 units), in lamports.
     pub initial_price: u64,
-    /// The rate at which the price increases per whole token sold (slope for linear, multiplier for exponential).
+    /// The rate at which the price increases per whole token sold (slope for linear, 1e9 fixed-point multiplier for exponential).
     pub slope: u64,
     /// The cumulative number of tokens sold so far (in whole token units).
     pub tokens_sold: u64,
+    /// Lowest price a Dutch auction decays to, in lamports per whole token. Unused by other models.
+    pub floor_price: u64,
 
     /// Vesting configuration
     pub vesting_enabled: bool,
     pub vesting_duration_seconds: i64,
     pub vesting_cliff_seconds: i64,
+    pub vesting_accepting_new: bool, // false stops new schedules while claims continue
+    pub vesting_milestones: Vec<VestingMilestone>, // empty = linear vesting
 
     /// Anti-bot protection settings
     pub anti_bot_level: AntiBotLevel,
     pub min_purchase_amount: u64,
     pub max_purchase_amount: u64,
-    pub purchase_cooldown_seconds: i64,
+    pub purchase_cooldown_seconds: i64, // enforced per buyer against their PurchaseTracker
     pub last_purchase_timestamp: i64,
+    pub max_tokens_per_wallet: u64, // 0 = no per-wallet cap
+    pub max_tokens_per_tx: u64, // 0 = no per-transaction cap
+    pub anti_snipe_seconds: i64, // 0 = no anti-snipe window after launch start
+    pub anti_snipe_max_tokens_per_wallet: u64, // per-wallet cap inside the window; 0 = normal cap
+    pub anti_snipe_fee_bps: u16, // extra platform fee inside the window
+    pub fee_holiday_seconds: i64, // 0 = no reduced-fee window after launch start
+    pub fee_holiday_discount_bps: u16, // share of the platform and affiliate fees waived inside it
 
     /// Launch constraints
     pub max_tokens: u64,
@@ -68,21 +172,78 @@ pub struct LaunchState {
     pub platform_fee_bps: u16,
     pub platform_fee_recipient: Pubkey,
 
+    /// Affiliate commission budget
+    pub max_affiliate_commission_tokens: u64, // 0 = unlimited
+    pub affiliate_commission_minted: u64,
+    pub approved_affiliates: Vec<Pubkey>, // empty = any affiliate earns commission
+
+    /// Buyer cashback
+    pub buyer_cashback_bps: u16, // 0 = disabled
+
+    /// Whitelist gating
+    pub whitelist_enabled: bool,
+    pub whitelist_root: Option<[u8; 32]>, // merkle root of allowed buyers; None = no merkle gating
+
+    /// Withdrawal gating
+    pub withdraw_threshold_sol: u64, // 0 = withdraw any time
+
+    /// Soft and hard caps
+    pub soft_cap_lamports: u64, // 0 = no soft cap, refunds disabled
+    pub hard_cap_lamports: u64, // 0 = no hard cap; compared against net amounts like total_sol_collected
+
+    /// Payment configuration
+    pub payment_mode: PaymentMode,
+    pub payment_mint: Pubkey, // Default pubkey for native SOL
+
+    /// Platform fee split
+    pub fee_split_config: Pubkey, // Default pubkey = all platform fees to platform_fee_recipient
+
+    /// Finalization
+    pub finalized: bool, // Mint authority revoked; no further purchases
+
+    /// Emergency pause
+    pub paused: bool, // purchases rejected; claims and withdrawals continue
+
+    /// Fair launch preset
+    pub fair_launch: bool, // fixed price; no fees, affiliates or cashback
+
     /// Analytics and tracking
+    /// Net lamports raised (payment token units for SPL launches), capped at `MAX_LAUNCH_RAISE_LAMPORTS`
+    /// so it can never approach the u64 ceiling of ~18.4B SOL
     pub total_sol_collected: u64,
     pub total_fees_collected: u64,
     pub purchase_count: u64,
+    /// Tokens locked in vesting schedules and not yet claimed, revoked or burned
+    pub vesting_unclaimed_tokens: u64,
+    /// Tokens minted into team and advisor vesting grants; they count against `max_tokens`
+    /// alongside `tokens_sold`
+    pub granted_tokens: u64,
 }
 
 impl LaunchState {
     /// The total disk space required for a `LaunchState` account in bytes.
     pub const LEN: usize = 32 + 32 + 1 + // authority, token_mint, sol_vault_bump
-        1 + 8 + 8 + 8 + // pricing_model, initial_price, slope, tokens_sold
-        1 + 8 + 8 + // vesting_enabled, vesting_duration, vesting_cliff
-        1 + 8 + 8 + 8 + 8 + // anti_bot_level, min/max_purchase, cooldown, last_purchase
+        1 + 8 + 8 + 8 + 8 + // pricing_model, initial_price, slope, tokens_sold, floor_price
+        1 + 8 + 8 + 1 + // vesting_enabled, vesting_duration, vesting_cliff, vesting_accepting_new
+        4 + MAX_VESTING_MILESTONES * VestingMilestone::LEN + // vesting_milestones
+        1 + 8 + 8 + 8 + 8 + 8 + 8 + // anti_bot_level, min/max_purchase, cooldown, last_purchase, max_per_wallet, max_per_tx
+        8 + 8 + 2 + // anti_snipe_seconds, anti_snipe_max_tokens_per_wallet, anti_snipe_fee_bps
+        8 + 2 + // fee_holiday_seconds, fee_holiday_discount_bps
         8 + 8 + 8 + // max_tokens, launch_start/end_time
         2 + 2 + 32 + // affiliate_fee, platform_fee, platform_recipient
-        8 + 8 + 8; // total_sol, total_fees, purchase_count
+        8 + 8 + // max_affiliate_commission, affiliate_commission_minted
+        4 + MAX_APPROVED_AFFILIATES * 32 + // approved_affiliates
+        2 + // buyer_cashback_bps
+        1 + 1 + 32 + // whitelist_enabled, whitelist_root
+        8 + 8 + 8 + // withdraw_threshold_sol, soft_cap_lamports, hard_cap_lamports
+        1 + 32 + // payment_mode, payment_mint
+        32 + // fee_split_config
+        1 + // finalized
+        1 + // paused
+        1 + // fair_launch
+        8 + 8 + 8 + // total_sol, total_fees, purchase_count
+        8 + // vesting_unclaimed_tokens
+        8; // granted_tokens
 
     /// Check if the launch is currently active
     pub fn is_launch_active(&self) -> Result<bool> {
@@ -92,7 +253,410 @@ impl LaunchState {
 
     /// Check if maximum token supply has been reached
     pub fn is_max_supply_reached(&self) -> bool {
-        self.tokens_sold >= self.max_tokens
+        self.allocated_supply() >= self.max_tokens
+    }
+
+    /// Tokens counted against `max_tokens`: everything sold plus everything granted
+    pub fn allocated_supply(&self) -> u64 {
+        self.tokens_sold.saturating_add(self.granted_tokens)
+    }
+
+    /// Record a vesting grant of `amount`, rejecting grants that would overshoot max tokens
+    pub fn record_grant(&mut self, amount: u64) -> Result<()> {
+        let allocated = self.allocated_supply().checked_add(amount)
+            .ok_or(FactoryError::Overflow)?;
+        require!(allocated <= self.max_tokens, FactoryError::MaxSupplyReached);
+        self.granted_tokens = self.granted_tokens.checked_add(amount)
+            .ok_or(FactoryError::Overflow)?;
+        Ok(())
+    }
+
+    /// Check whether an affiliate commission still fits within the launch's commission budget
+    pub fn can_pay_affiliate_commission(&self, commission_amount: u64) -> bool {
+        if self.max_affiliate_commission_tokens == 0 {
+            return true;
+        }
+        self.affiliate_commission_minted
+            .checked_add(commission_amount)
+            .is_some_and(|total| total <= self.max_affiliate_commission_tokens)
+    }
+
+    /// Tokens bought by `sol_amount` at `current_price`, rejecting spends that round down to nothing
+    pub fn calculate_purchase_tokens(sol_amount: u64, current_price: u64) -> Result<u64> {
+        let tokens_to_mint = genesis_common::utils::math_utils::calculate_tokens_to_mint(sol_amount, current_price)?;
+        require!(tokens_to_mint > 0, FactoryError::PurchaseTooSmallForPrice);
+        Ok(tokens_to_mint)
+    }
+
+    /// Tokens bought by `sol_amount`. Linear and exponential launches price the purchase along the
+    /// curve, so a large buy pays for the price rise it causes; fixed-price and Dutch auction
+    /// launches charge `current_price` for every token.
+    pub fn calculate_curve_purchase_tokens(&self, sol_amount: u64, current_price: u64) -> Result<u64> {
+        use genesis_common::utils::math_utils::{tokens_for_sol, CurveModel};
+
+        let model = match self.pricing_model {
+            PricingModel::LinearBondingCurve => CurveModel::Linear,
+            PricingModel::ExponentialBondingCurve => CurveModel::Exponential,
+            PricingModel::FixedPrice | PricingModel::DutchAuction => {
+                return Self::calculate_purchase_tokens(sol_amount, current_price);
+            }
+        };
+        let tokens_to_mint = tokens_for_sol(self.initial_price, self.slope, self.tokens_sold, sol_amount, model)?;
+        require!(tokens_to_mint > 0, FactoryError::PurchaseTooSmallForPrice);
+        Ok(tokens_to_mint)
+    }
+
+    /// Clamp a purchase that overshoots `max_tokens` by rounding dust down to the most tokens that,
+    /// together with their cashback, still fit in the remaining supply
+    pub fn clamp_to_remaining_supply(&self, tokens_to_mint: u64) -> Result<u64> {
+        let remaining = self.max_tokens.saturating_sub(self.allocated_supply());
+        let with_cashback = |tokens: u64| -> Result<u64> {
+            tokens.checked_add(self.calculate_cashback_tokens(tokens)?)
+                .ok_or(FactoryError::Overflow.into())
+        };
+        let requested = with_cashback(tokens_to_mint)?;
+        if requested <= remaining {
+            return Ok(tokens_to_mint);
+        }
+        require!(requested - remaining <= SUPPLY_ROUNDING_TOLERANCE, FactoryError::MaxSupplyReached);
+
+        // Cashback rounds down, so this estimate fits; the next token up may fit as well
+        let clamped = (remaining as u128 * BPS_PRECISION as u128
+            / (BPS_PRECISION + self.buyer_cashback_bps as u64) as u128) as u64;
+        if with_cashback(clamped + 1)? <= remaining {
+            return Ok(clamped + 1);
+        }
+        Ok(clamped)
+    }
+
+    /// Reject purchases that would mint fewer tokens than the buyer's slippage limit (0 = no limit)
+    pub fn check_min_tokens_out(tokens_to_mint: u64, min_tokens_out: u64) -> Result<()> {
+        require!(tokens_to_mint >= min_tokens_out, FactoryError::SlippageExceeded);
+        Ok(())
+    }
+
+    /// Cashback tokens owed to a buyer on top of `tokens_to_mint`
+    pub fn calculate_cashback_tokens(&self, tokens_to_mint: u64) -> Result<u64> {
+        if self.buyer_cashback_bps == 0 {
+            return Ok(0);
+        }
+        genesis_common::utils::math_utils::calculate_commission_amount(tokens_to_mint, self.buyer_cashback_bps)
+    }
+
+    /// Tokens sold after a purchase and its cashback, rejecting purchases that overshoot max tokens
+    pub fn supply_after_purchase(&self, tokens_to_mint: u64, cashback_tokens: u64) -> Result<u64> {
+        let new_total_supply = self.tokens_sold.checked_add(tokens_to_mint)
+            .and_then(|v| v.checked_add(cashback_tokens))
+            .ok_or(FactoryError::Overflow)?;
+        require!(new_total_supply <= self.max_tokens.saturating_sub(self.granted_tokens), FactoryError::MaxSupplyReached);
+        Ok(new_total_supply)
+    }
+
+    /// Check that the affiliate info and commission token account both belong to the affiliate wallet
+    pub fn affiliate_accounts_match(affiliate: &Pubkey, affiliate_info_key: &Pubkey, token_account_owner: &Pubkey) -> bool {
+        affiliate_info_key == affiliate && token_account_owner == affiliate
+    }
+
+    /// Ensure enough SOL has been raised for the authority to withdraw
+    pub fn check_withdraw_threshold(&self) -> Result<()> {
+        require!(self.total_sol_collected >= self.withdraw_threshold_sol, FactoryError::ThresholdNotMet);
+        Ok(())
+    }
+
+    /// Ensure a partial withdrawal of `amount` leaves the SOL vault rent exempt
+    pub fn check_partial_withdrawal(vault_balance: u64, amount: u64, rent_exempt_minimum: u64) -> Result<()> {
+        require!(amount > 0, FactoryError::InvalidAmount);
+        let remaining = vault_balance.checked_sub(amount).ok_or(FactoryError::InsufficientVaultBalance)?;
+        require!(remaining >= rent_exempt_minimum, FactoryError::InsufficientVaultBalance);
+        Ok(())
+    }
+
+    /// Ensure the soft cap has been reached before the authority withdraws
+    pub fn check_soft_cap_met(&self) -> Result<()> {
+        require!(self.total_sol_collected >= self.soft_cap_lamports, FactoryError::SoftCapNotMet);
+        Ok(())
+    }
+
+    /// Validate soft and hard caps together: a hard cap, when set, must leave room to reach the soft cap
+    pub fn validate_caps(soft_cap_lamports: u64, hard_cap_lamports: u64) -> Result<()> {
+        require!(hard_cap_lamports == 0 || hard_cap_lamports >= soft_cap_lamports, FactoryError::InvalidAmount);
+        Ok(())
+    }
+
+    /// Ensure a purchase adding `net_amount` to the raise stays within the hard cap
+    pub fn check_hard_cap(&self, net_amount: u64) -> Result<()> {
+        if self.hard_cap_lamports == 0 {
+            return Ok(());
+        }
+        let total = self.total_sol_collected.checked_add(net_amount).ok_or(FactoryError::HardCapReached)?;
+        require!(total <= self.hard_cap_lamports, FactoryError::HardCapReached);
+        Ok(())
+    }
+
+    /// Ensure buyers may be refunded: the launch has ended without reaching its soft cap
+    pub fn check_refund_available(&self, current_time: i64) -> Result<()> {
+        require!(current_time > self.launch_end_time, FactoryError::LaunchStillActive);
+        require!(self.total_sol_collected < self.soft_cap_lamports, FactoryError::SoftCapReached);
+        Ok(())
+    }
+
+    /// Ensure the authority may sweep the SOL vault: the same threshold and soft cap gates as a
+    /// withdrawal, and no closing sweep before the launch ends or while buyers can still refund
+    pub fn check_sweep_allowed(&self, current_time: i64, closing: bool) -> Result<()> {
+        self.check_withdraw_threshold()?;
+        self.check_soft_cap_met()?;
+        if closing {
+            require!(current_time > self.launch_end_time, FactoryError::OutsideTimeWindow);
+            require!(self.check_refund_available(current_time).is_err(), FactoryError::SoftCapNotMet);
+        }
+        Ok(())
+    }
+
+    /// Ensure the launch may be finalized: it has ended and was not finalized already
+    pub fn check_finalize_allowed(&self, current_time: i64) -> Result<()> {
+        require!(current_time > self.launch_end_time, FactoryError::LaunchStillActive);
+        require!(!self.finalized, FactoryError::LaunchFinalized);
+        Ok(())
+    }
+
+    /// Ensure the launch has not been finalized, after which nothing more can be minted
+    pub fn check_not_finalized(&self) -> Result<()> {
+        require!(!self.finalized, FactoryError::LaunchFinalized);
+        Ok(())
+    }
+
+    /// Track tokens newly locked in a vesting schedule
+    pub fn record_vesting_locked(&mut self, amount: u64) -> Result<()> {
+        self.vesting_unclaimed_tokens = self.vesting_unclaimed_tokens.checked_add(amount)
+            .ok_or(FactoryError::Overflow)?;
+        Ok(())
+    }
+
+    /// Track tokens leaving vesting through a claim or a revocation
+    pub fn record_vesting_released(&mut self, amount: u64) {
+        self.vesting_unclaimed_tokens = self.vesting_unclaimed_tokens.saturating_sub(amount);
+    }
+
+    /// Return a refunded buyer's tokens, burned or voided, to the unsold supply
+    pub fn record_refund(&mut self, tokens_returned: u64) {
+        self.tokens_sold = self.tokens_sold.saturating_sub(tokens_returned);
+    }
+
+    /// Ensure the launch is settled and can be closed: finalized, every vested token claimed
+    /// (or revoked) and the SOL vault emptied
+    pub fn check_settled(&self, vault_lamports: u64) -> Result<()> {
+        require!(self.finalized, FactoryError::LaunchNotSettled);
+        require!(self.vesting_unclaimed_tokens == 0, FactoryError::LaunchNotSettled);
+        require!(vault_lamports == 0, FactoryError::LaunchNotSettled);
+        Ok(())
+    }
+
+    /// Ensure the launch has not been paused by its authority
+    pub fn check_not_paused(&self) -> Result<()> {
+        require!(!self.paused, FactoryError::LaunchPaused);
+        Ok(())
+    }
+
+    /// Ensure the launch takes SPL-token payment in `payment_mint`
+    pub fn check_spl_payment_mint(&self, payment_mint: &Pubkey) -> Result<()> {
+        require!(self.payment_mode == PaymentMode::SplToken, FactoryError::UnsupportedPaymentMode);
+        require_keys_eq!(*payment_mint, self.payment_mint, FactoryError::MissingPaymentMint);
+        Ok(())
+    }
+
+    /// Ensure the payment mode can still change, which is only before the first purchase
+    pub fn check_payment_mode_migratable(&self) -> Result<()> {
+        require!(self.purchase_count == 0, FactoryError::PaymentModeLocked);
+        Ok(())
+    }
+
+    /// Ensure the launch can take `payment_mode`. Soft-cap refunds pay back native SOL, so a
+    /// launch with a soft cap can't switch to SPL-token payment.
+    pub fn check_payment_mode_supported(&self, payment_mode: PaymentMode) -> Result<()> {
+        if payment_mode == PaymentMode::SplToken {
+            require!(self.soft_cap_lamports == 0, FactoryError::SoftCapRequiresNativeSol);
+        }
+        Ok(())
+    }
+
+    /// Validate vesting terms. Negative durations or cliffs are rejected even when vesting is
+    /// disabled, since they are copied verbatim into any schedule opened later; enabled vesting
+    /// also needs a positive duration within bounds and a cliff no longer than the duration.
+    pub fn validate_vesting_config(
+        vesting_enabled: bool,
+        duration_seconds: i64,
+        cliff_seconds: i64,
+        milestones: &[VestingMilestone],
+    ) -> Result<()> {
+        require!(cliff_seconds >= 0 && duration_seconds >= 0, FactoryError::InvalidVestingParams);
+        if !vesting_enabled {
+            return Ok(());
+        }
+
+        require!(duration_seconds > 0, FactoryError::InvalidVestingParams);
+        require!(duration_seconds >= MIN_VESTING_DURATION_SECONDS, FactoryError::InvalidVestingParams);
+        require!(duration_seconds <= MAX_VESTING_DURATION_SECONDS, FactoryError::InvalidVestingParams);
+        require!(cliff_seconds <= duration_seconds, FactoryError::InvalidVestingParams);
+        if !milestones.is_empty() {
+            VestingSchedule::validate_milestones(milestones)?;
+        }
+        Ok(())
+    }
+
+    /// Ensure a vested purchase may still open a new vesting schedule
+    pub fn check_new_vesting(&self, enable_vesting: bool) -> Result<()> {
+        if enable_vesting {
+            require!(self.vesting_accepting_new, FactoryError::VestingClosedToNewSchedules);
+        }
+        Ok(())
+    }
+
+    /// Ensure the buyer may purchase when the launch is whitelist-gated
+    pub fn check_whitelist(&self, has_whitelist_entry: bool) -> Result<()> {
+        if self.whitelist_enabled {
+            require!(has_whitelist_entry, FactoryError::NotWhitelisted);
+        }
+        Ok(())
+    }
+
+    /// Merkle leaf allowing `buyer` into a root-gated launch
+    pub fn whitelist_leaf(buyer: &Pubkey) -> [u8; 32] {
+        genesis_common::utils::merkle_utils::hash_leaf(&[buyer.as_ref()])
+    }
+
+    /// Validate a whitelist root before storing it; an all-zero root can't come from a real tree
+    pub fn validate_whitelist_root(whitelist_root: &Option<[u8; 32]>) -> Result<()> {
+        require!(*whitelist_root != Some([0u8; 32]), FactoryError::InvalidMerkleRoot);
+        Ok(())
+    }
+
+    /// Enforce merkle gating: when a root is set, the buyer needs a `WhitelistEntry` or a proof
+    /// of inclusion in the root
+    pub fn check_whitelist_proof(&self, buyer: &Pubkey, proof: &[[u8; 32]], has_whitelist_entry: bool) -> Result<()> {
+        let Some(root) = self.whitelist_root else {
+            return Ok(());
+        };
+        if has_whitelist_entry {
+            return Ok(());
+        }
+        require!(
+            proof.len() <= MAX_MERKLE_PROOF_LEN
+                && genesis_common::utils::merkle_utils::verify_proof(proof, &root, Self::whitelist_leaf(buyer)),
+            FactoryError::NotWhitelisted
+        );
+        Ok(())
+    }
+
+    /// Reject buyers the authority has blocked
+    pub fn check_buyer_not_blocked(is_blocked: bool) -> Result<()> {
+        require!(!is_blocked, FactoryError::BuyerBlocked);
+        Ok(())
+    }
+
+    /// Validate the size of a batch of whitelist additions
+    pub fn validate_whitelist_batch(batch_len: usize) -> Result<()> {
+        require!(batch_len > 0 && batch_len <= MAX_BATCH_SIZE, FactoryError::InvalidWhitelistBatch);
+        Ok(())
+    }
+
+    /// Add a purchase's net amount to the lifetime raise, rejecting anything past `MAX_LAUNCH_RAISE_LAMPORTS`
+    pub fn record_raise(&mut self, net_amount: u64) -> Result<()> {
+        let total = self.total_sol_collected.checked_add(net_amount)
+            .filter(|total| *total <= MAX_LAUNCH_RAISE_LAMPORTS)
+            .ok_or(FactoryError::RaiseCeilingExceeded)?;
+        self.total_sol_collected = total;
+        Ok(())
+    }
+
+    /// Validate a fee configuration as a whole: each fee within its own cap and the sum within the combined cap
+    pub fn validate_fee_config(affiliate_fee_bps: u16, platform_fee_bps: u16) -> Result<()> {
+        require!(affiliate_fee_bps <= MAX_RATE_BPS, FactoryError::InvalidFeeConfig);
+        require!(platform_fee_bps <= MAX_RATE_BPS, FactoryError::InvalidFeeConfig);
+        require!(affiliate_fee_bps as u32 + platform_fee_bps as u32 <= MAX_TOTAL_FEE_BPS as u32,
+                FactoryError::InvalidFeeConfig);
+        Ok(())
+    }
+
+    /// Validate that a fair launch's configuration is consistent: fixed price, no fees and no cashback
+    pub fn validate_fair_launch_config(
+        pricing_model: PricingModel,
+        affiliate_fee_bps: u16,
+        platform_fee_bps: u16,
+        buyer_cashback_bps: u16,
+    ) -> Result<()> {
+        require!(pricing_model == PricingModel::FixedPrice, FactoryError::FairLaunchViolation);
+        require!(affiliate_fee_bps == 0 && platform_fee_bps == 0, FactoryError::FairLaunchViolation);
+        require!(buyer_cashback_bps == 0, FactoryError::FairLaunchViolation);
+        Ok(())
+    }
+
+    /// Ensure an affiliate may be credited on this launch; fair launches pay no affiliate commission
+    pub fn check_affiliate_allowed(&self, has_affiliate: bool) -> Result<()> {
+        require!(!(self.fair_launch && has_affiliate), FactoryError::FairLaunchViolation);
+        Ok(())
+    }
+
+    /// Validate a replacement affiliate allowlist: at most `MAX_APPROVED_AFFILIATES`, no duplicates
+    pub fn validate_approved_affiliates(affiliates: &[Pubkey]) -> Result<()> {
+        require!(affiliates.len() <= MAX_APPROVED_AFFILIATES, FactoryError::InvalidAffiliateAllowlist);
+        for (index, affiliate) in affiliates.iter().enumerate() {
+            require!(!affiliates[..index].contains(affiliate), FactoryError::InvalidAffiliateAllowlist);
+        }
+        Ok(())
+    }
+
+    /// Ensure an affiliate may earn commission on this launch; with an allowlist set, only listed
+    /// affiliates are approved
+    pub fn check_affiliate_approved(&self, affiliate: &Pubkey) -> Result<()> {
+        require!(
+            self.approved_affiliates.is_empty() || self.approved_affiliates.contains(affiliate),
+            FactoryError::AffiliateNotApproved
+        );
+        Ok(())
+    }
+
+    /// Split a purchase amount into platform fee, affiliate fee and net SOL for the vault
+    pub fn calculate_fees(&self, sol_amount: u64, with_affiliate: bool) -> Result<FeeBreakdown> {
+        let platform_fee = if self.platform_fee_bps > 0 {
+            genesis_common::utils::math_utils::calculate_commission_amount(sol_amount, self.platform_fee_bps)?
+        } else {
+            0
+        };
+
+        let affiliate_fee = if with_affiliate {
+            genesis_common::utils::math_utils::calculate_commission_amount(sol_amount, self.affiliate_fee_bps)?
+        } else {
+            0
+        };
+
+        // Fees must leave something for the vault, otherwise the buyer pays purely for fees
+        let remaining = sol_amount.checked_sub(platform_fee)
+            .and_then(|v| v.checked_sub(affiliate_fee))
+            .ok_or(FactoryError::FeesExceedPurchase)?;
+        require!(remaining > 0, FactoryError::FeesExceedPurchase);
+
+        // The affiliate is paid in commission tokens, so its share is not taken out of the SOL
+        // that reaches the vault. Only `platform_fee` leaves the purchase as SOL.
+        let net_sol_amount = sol_amount - platform_fee;
+
+        Ok(FeeBreakdown { platform_fee, affiliate_fee, net_sol_amount })
+    }
+
+    /// Ensure a launch mint has no circulating supply before the launch takes it over
+    pub fn validate_fresh_mint(mint_supply: u64) -> Result<()> {
+        require!(mint_supply == 0, FactoryError::InvalidAccountState);
+        Ok(())
+    }
+
+    /// Lamports that may be swept from a SOL vault holding `vault_lamports`
+    ///
+    /// Outside of closing, the vault keeps its rent-exempt minimum so it stays valid.
+    pub fn sweepable_vault_lamports(vault_lamports: u64, rent_exempt_minimum: u64, closing: bool) -> u64 {
+        if closing {
+            vault_lamports
+        } else {
+            vault_lamports.saturating_sub(rent_exempt_minimum)
+        }
     }
 
     /// Calculate current price based on pricing model
@@ -106,41 +670,43 @@ impl LaunchState {
                 )
             }
             PricingModel::ExponentialBondingCurve => {
-                // For exponential: price = initial_price * (1 + slope)^tokens_sold
-                // Using approximation for on-chain computation
-                let multiplier = self.slope as u128;
-                let tokens_sold_u128 = self.tokens_sold as u128;
-                let initial_price_u128 = self.initial_price as u128;
-
-                let exponential_factor = multiplier.checked_pow(tokens_sold_u128 as u32)
-                    .ok_or(error!(FactoryError::Overflow))?;
-
-                let current_price_u128 = initial_price_u128.checked_mul(exponential_factor)
-                    .ok_or(error!(FactoryError::Overflow))?;
+                // price = initial_price * slope^whole_tokens_sold, with slope = (1 + rate) in 1e9 fixed point.
+                // Growth too large to represent is capped like any other runaway price.
+                let whole_tokens_sold = self.tokens_sold / TOKEN_BASE_UNITS;
+                let growth = match genesis_common::utils::math_utils::pow_fixed(self.slope, whole_tokens_sold) {
+                    Ok(growth) => growth,
+                    Err(_) => return Ok(MAX_CURVE_PRICE_LAMPORTS),
+                };
 
-                Ok(current_price_u128.try_into().map_err(|_| FactoryError::Overflow)?)
+                let current_price = (self.initial_price as u128)
+                    .checked_mul(growth as u128)
+                    .ok_or(FactoryError::Overflow)?
+                    / FIXED_POINT_ONE as u128;
+                Ok(std::cmp::min(current_price, MAX_CURVE_PRICE_LAMPORTS as u128) as u64)
             }
             PricingModel::FixedPrice => Ok(self.initial_price),
-            PricingModel::DutchAuction => {
-                // For Dutch auction, price decreases over time
-                let current_time = Clock::get()?.unix_timestamp;
-                let time_elapsed = current_time.saturating_sub(self.launch_start_time);
-                let total_duration = self.launch_end_time.saturating_sub(self.launch_start_time);
-
-                if total_duration == 0 {
-                    return Ok(self.initial_price);
-                }
-
-                let price_reduction = ((self.initial_price as u128) * (time_elapsed as u128)) / (total_duration as u128);
-                let current_price = self.initial_price.saturating_sub(price_reduction as u64);
+            PricingModel::DutchAuction => Ok(self.calculate_dutch_auction_price(Clock::get()?.unix_timestamp)),
+        }
+    }
 
-                Ok(std::cmp::max(current_price, self.slope)) // slope acts as minimum price
-            }
+    /// Dutch auction price at `current_time`: decays linearly from `initial_price` over the
+    /// launch window and is clamped to `floor_price`
+    pub fn calculate_dutch_auction_price(&self, current_time: i64) -> u64 {
+        let total_duration = self.launch_end_time.saturating_sub(self.launch_start_time);
+        if total_duration <= 0 {
+            return self.initial_price;
         }
+
+        let time_elapsed = current_time.saturating_sub(self.launch_start_time).clamp(0, total_duration);
+        let price_reduction = ((self.initial_price as u128) * (time_elapsed as u128)) / (total_duration as u128);
+        let current_price = self.initial_price.saturating_sub(price_reduction as u64);
+
+        std::cmp::max(current_price, self.floor_price)
     }
 
-    /// Validate purchase amount against anti-bot rules
-    pub fn validate_purchase_amount(&self, amount: u64) -> Result<()> {
+    /// Validate purchase amount against anti-bot rules. The cooldown is measured from the
+    /// buyer's own `last_purchase_time`, so one buyer's purchase never delays another's.
+    pub fn validate_purchase_amount(&self, amount: u64, last_purchase_time: i64, current_time: i64) -> Result<()> {
         match self.anti_bot_level {
             AntiBotLevel::None => {},
             _ => {
@@ -148,8 +714,7 @@ impl LaunchState {
                 require!(amount <= self.max_purchase_amount, FactoryError::PurchaseAmountTooHigh);
 
                 if self.anti_bot_level >= AntiBotLevel::Advanced {
-                    let current_time = Clock::get()?.unix_timestamp;
-                    let time_since_last_purchase = current_time - self.last_purchase_timestamp;
+                    let time_since_last_purchase = current_time.saturating_sub(last_purchase_time);
                     require!(time_since_last_purchase >= self.purchase_cooldown_seconds,
                             FactoryError::PurchaseCooldownActive);
                 }
@@ -157,6 +722,80 @@ impl LaunchState {
         }
         Ok(())
     }
+
+    /// Ensure a purchase keeps the buyer within `max_tokens_per_wallet`
+    pub fn check_wallet_cap(&self, already_purchased: u64, tokens_to_mint: u64) -> Result<()> {
+        if self.max_tokens_per_wallet == 0 {
+            return Ok(());
+        }
+        let total = already_purchased.checked_add(tokens_to_mint).ok_or(FactoryError::Overflow)?;
+        require!(total <= self.max_tokens_per_wallet, FactoryError::WalletCapExceeded);
+        Ok(())
+    }
+
+    /// Ensure a single purchase stays within `max_tokens_per_tx`. Larger buys must be split
+    /// across transactions, where the per-wallet caps still apply to the total.
+    pub fn check_tx_cap(&self, tokens_to_mint: u64) -> Result<()> {
+        require!(
+            self.max_tokens_per_tx == 0 || tokens_to_mint <= self.max_tokens_per_tx,
+            FactoryError::TransactionCapExceeded
+        );
+        Ok(())
+    }
+
+    /// Validate the anti-snipe window: a non-negative length and a per-wallet cap no looser than the normal one
+    pub fn validate_anti_snipe_config(
+        anti_snipe_seconds: i64,
+        anti_snipe_max_tokens_per_wallet: u64,
+        max_tokens_per_wallet: u64,
+    ) -> Result<()> {
+        require!(anti_snipe_seconds >= 0, FactoryError::InvalidAntiSnipeConfig);
+        if anti_snipe_max_tokens_per_wallet > 0 && max_tokens_per_wallet > 0 {
+            require!(anti_snipe_max_tokens_per_wallet <= max_tokens_per_wallet, FactoryError::InvalidAntiSnipeConfig);
+        }
+        Ok(())
+    }
+
+    /// Whether `current_time` falls in `[launch_start_time, launch_start_time + anti_snipe_seconds]`
+    pub fn in_anti_snipe_window(&self, current_time: i64) -> bool {
+        self.anti_snipe_seconds > 0
+            && current_time >= self.launch_start_time
+            && current_time <= self.launch_start_time.saturating_add(self.anti_snipe_seconds)
+    }
+
+    /// Ensure a purchase inside the anti-snipe window keeps the buyer within the window's stricter cap
+    pub fn check_anti_snipe_wallet_cap(&self, already_purchased: u64, tokens_to_mint: u64, current_time: i64) -> Result<()> {
+        if self.anti_snipe_max_tokens_per_wallet == 0 || !self.in_anti_snipe_window(current_time) {
+            return Ok(());
+        }
+        let total = already_purchased.checked_add(tokens_to_mint).ok_or(FactoryError::Overflow)?;
+        require!(total <= self.anti_snipe_max_tokens_per_wallet, FactoryError::WalletCapExceeded);
+        Ok(())
+    }
+
+    /// Validate the fee holiday: a non-negative length and a discount of at most 100%
+    pub fn validate_fee_holiday_config(fee_holiday_seconds: i64, fee_holiday_discount_bps: u16) -> Result<()> {
+        require!(fee_holiday_seconds >= 0 && fee_holiday_discount_bps as u64 <= BPS_PRECISION,
+                FactoryError::InvalidFeeConfig);
+        Ok(())
+    }
+
+    /// Fee discount in basis points for a purchase at `current_time`: `fee_holiday_discount_bps`
+    /// within `[launch_start_time, launch_start_time + fee_holiday_seconds]`, 0 otherwise
+    pub fn fee_holiday_discount_bps(&self, current_time: i64) -> u16 {
+        let in_holiday = self.fee_holiday_seconds > 0
+            && current_time >= self.launch_start_time
+            && current_time <= self.launch_start_time.saturating_add(self.fee_holiday_seconds);
+        if in_holiday { self.fee_holiday_discount_bps } else { 0 }
+    }
+
+    /// Extra platform fee charged on a purchase inside the anti-snipe window (0 outside it)
+    pub fn calculate_anti_snipe_fee(&self, amount: u64, current_time: i64) -> Result<u64> {
+        if self.anti_snipe_fee_bps == 0 || !self.in_anti_snipe_window(current_time) {
+            return Ok(0);
+        }
+        genesis_common::utils::math_utils::calculate_commission_amount(amount, self.anti_snipe_fee_bps)
+    }
 }
 
 /// Vesting schedule account for tracking token vesting
@@ -170,6 +809,9 @@ pub struct VestingSchedule {
     pub total_amount: u64,
     /// Amount of tokens already claimed
     pub claimed_amount: u64,
+    /// Tokens that had already vested when the schedule was last topped up. They stay unlocked
+    /// while the rest of `total_amount` vests from `start_time`.
+    pub carried_vested_amount: u64,
     /// Vesting start timestamp
     pub start_time: i64,
     /// Total vesting duration in seconds
@@ -178,28 +820,193 @@ pub struct VestingSchedule {
     pub cliff_seconds: i64,
     /// Last claim timestamp
     pub last_claim_time: i64,
+    /// Receives the rent when the schedule is closed; default pubkey = the beneficiary.
+    /// Set to a treasury when the project, not the beneficiary, funded the schedule.
+    pub rent_recipient: Pubkey,
+    /// Linear or milestone release
+    pub kind: VestingKind,
+    /// Unlock steps for `VestingKind::Milestones`, at most `MAX_VESTING_MILESTONES`; empty for linear
+    pub milestones: Vec<VestingMilestone>,
+    /// Whether the launch authority may revoke unvested tokens; false for buyer vesting
+    pub revocable: bool,
+    /// Set once revoked; the schedule then only holds what had vested at revocation
+    pub revoked: bool,
+}
+
+/// Token movements resulting from revoking a vesting schedule
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VestingRevocation {
+    /// Vested but unclaimed tokens owed to the beneficiary
+    pub to_beneficiary: u64,
+    /// Unvested tokens returned to the authority or burned
+    pub unvested: u64,
+}
+
+/// Where a vesting schedule stands on its timeline, returned by `get_vesting_timeline`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VestingTimeline {
+    /// Seconds until the first tokens unlock (0 once past the cliff or first milestone)
+    pub time_until_cliff: i64,
+    /// Seconds until the schedule is fully vested (0 once complete)
+    pub time_until_full: i64,
+    /// Linear release rate after the cliff; 0 for milestone schedules and completed schedules
+    pub tokens_per_second: u64,
 }
 
 impl VestingSchedule {
     /// Space required for vesting schedule account
-    pub const LEN: usize = 32 + 32 + 8 + 8 + 8 + 8 + 8 + 8; // 104 bytes
+    pub const LEN: usize = 32 + 32 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 32 + // 152 bytes of fixed fields
+        1 + 4 + MAX_VESTING_MILESTONES * VestingMilestone::LEN + // kind, bounded milestones
+        1 + 1; // revocable, revoked
+
+    /// Validate milestone steps: at most `MAX_VESTING_MILESTONES`, offsets strictly increasing,
+    /// cumulative bps non-decreasing and never above 100%
+    pub fn validate_milestones(milestones: &[VestingMilestone]) -> Result<()> {
+        require!(!milestones.is_empty() && milestones.len() <= MAX_VESTING_MILESTONES,
+                FactoryError::InvalidVestingParams);
+        require!(milestones[0].offset_seconds >= 0, FactoryError::InvalidVestingParams);
+        for pair in milestones.windows(2) {
+            require!(pair[1].offset_seconds > pair[0].offset_seconds, FactoryError::InvalidVestingParams);
+            require!(pair[1].cumulative_bps >= pair[0].cumulative_bps, FactoryError::InvalidVestingParams);
+        }
+        require!(milestones[milestones.len() - 1].cumulative_bps <= BPS_PRECISION,
+                FactoryError::InvalidVestingParams);
+        Ok(())
+    }
+
+    /// Copy the launch's vesting terms (duration, cliff and any milestones) onto this schedule
+    pub fn apply_launch_terms(&mut self, launch: &LaunchState) {
+        self.duration_seconds = launch.vesting_duration_seconds;
+        self.cliff_seconds = launch.vesting_cliff_seconds;
+        if launch.vesting_milestones.is_empty() {
+            self.kind = VestingKind::Linear;
+            self.milestones = Vec::new();
+        } else {
+            self.kind = VestingKind::Milestones;
+            self.milestones = launch.vesting_milestones.clone();
+        }
+    }
+
+    /// Account that receives the schedule's rent when it is closed
+    pub fn rent_destination(&self) -> Pubkey {
+        if self.rent_recipient == Pubkey::default() {
+            self.beneficiary
+        } else {
+            self.rent_recipient
+        }
+    }
+
+    /// Ensure the beneficiary may still claim from this schedule
+    pub fn check_not_revoked(&self) -> Result<()> {
+        require!(!self.revoked, FactoryError::VestingRevoked);
+        Ok(())
+    }
+
+    /// Revoke the schedule at `current_time`, settling everything vested so far with the
+    /// beneficiary and releasing the rest. Revoking an already revoked schedule moves nothing.
+    pub fn revoke(&mut self, current_time: i64) -> Result<VestingRevocation> {
+        require!(self.revocable, FactoryError::VestingNotRevocable);
+        if self.revoked {
+            return Ok(VestingRevocation { to_beneficiary: 0, unvested: 0 });
+        }
+
+        let vested = self.calculate_vested_amount(current_time)?;
+        let revocation = VestingRevocation {
+            to_beneficiary: vested.saturating_sub(self.claimed_amount),
+            unvested: self.total_amount.saturating_sub(vested),
+        };
+
+        // The schedule shrinks to what vested, all of it paid out, so it can be closed afterwards
+        self.total_amount = vested;
+        self.claimed_amount = vested;
+        self.carried_vested_amount = vested;
+        self.revoked = true;
+        Ok(revocation)
+    }
+
+    /// Ensure purchases may vest into this schedule; revocable team grants share the vesting
+    /// PDA, and purchased tokens must never become revocable
+    pub fn check_accepts_purchases(&self) -> Result<()> {
+        require!(!self.revocable, FactoryError::VestingGrantExists);
+        Ok(())
+    }
+
+    /// Whether the schedule still holds unclaimed tokens
+    pub fn is_live(&self) -> bool {
+        self.total_amount > self.claimed_amount
+    }
+
+    /// Add a further vested purchase to a live schedule at `current_time`, so the tokens already
+    /// locked in it are never overwritten. The schedule is re-based: what has vested so far stays
+    /// unlocked, and the remainder plus the new tokens vest on the schedule's terms from now.
+    pub fn top_up(&mut self, amount: u64, current_time: i64) -> Result<()> {
+        self.check_not_revoked()?;
+        self.carried_vested_amount = self.calculate_vested_amount(current_time)?;
+        self.total_amount = self.total_amount.checked_add(amount)
+            .ok_or(FactoryError::Overflow)?;
+        self.start_time = current_time;
+        Ok(())
+    }
 
-    /// Calculate vested amount at current time
+    /// Ensure the schedule can be closed: every vested token has been claimed
+    pub fn check_closable(&self) -> Result<()> {
+        require!(self.claimed_amount >= self.total_amount, FactoryError::VestingNotComplete);
+        Ok(())
+    }
+
+    /// Calculate vested amount at current time. Linear schedules vest nothing before the cliff,
+    /// then linearly from the cliff point until `start_time + duration_seconds`; milestone
+    /// schedules vest the share of the highest milestone reached. Tokens carried over from
+    /// before a top-up are vested throughout.
     pub fn calculate_vested_amount(&self, current_time: i64) -> Result<u64> {
-        if current_time < self.start_time + self.cliff_seconds {
-            return Ok(0);
+        if self.kind == VestingKind::Milestones {
+            return self.calculate_milestone_vested_amount(current_time);
         }
 
-        let time_since_start = current_time - self.start_time;
-        if time_since_start >= self.duration_seconds {
+        let cliff_end = self.start_time.checked_add(self.cliff_seconds).ok_or(FactoryError::Overflow)?;
+        if current_time < cliff_end {
+            return Ok(self.carried_vested_amount.min(self.total_amount));
+        }
+
+        // A schedule whose duration is all cliff unlocks in full at the cliff
+        let vesting_time = self.duration_seconds.saturating_sub(self.cliff_seconds);
+        let time_since_cliff = current_time - cliff_end;
+        if vesting_time <= 0 || time_since_cliff >= vesting_time {
             return Ok(self.total_amount);
         }
 
         // Linear vesting after cliff
-        let vesting_time = self.duration_seconds - self.cliff_seconds;
-        let vested_amount = ((self.total_amount as u128) * (time_since_start as u128)) / (vesting_time as u128);
+        let vested_amount = (self.vesting_amount() as u128)
+            .checked_mul(time_since_cliff as u128)
+            .ok_or(FactoryError::Overflow)?
+            .checked_div(vesting_time as u128)
+            .ok_or(FactoryError::DivisionByZero)?
+            + self.carried_vested_amount as u128;
+
+        Ok(std::cmp::min(vested_amount, self.total_amount as u128) as u64)
+    }
+
+    /// Vested amount of a milestone schedule: the cumulative share of the last milestone reached
+    fn calculate_milestone_vested_amount(&self, current_time: i64) -> Result<u64> {
+        let elapsed = current_time.saturating_sub(self.start_time);
+        let reached_bps = self.milestones.iter()
+            .take_while(|milestone| milestone.offset_seconds <= elapsed)
+            .last()
+            .map_or(0, |milestone| milestone.cumulative_bps);
+
+        let vested_amount = (self.vesting_amount() as u128)
+            .checked_mul(reached_bps as u128)
+            .ok_or(FactoryError::Overflow)?
+            / BPS_PRECISION as u128
+            + self.carried_vested_amount as u128;
 
-        Ok(vested_amount as u64)
+        Ok(std::cmp::min(vested_amount, self.total_amount as u128) as u64)
+    }
+
+    /// Tokens still vesting along the schedule's timeline, excluding any carried over from
+    /// before a top-up
+    fn vesting_amount(&self) -> u64 {
+        self.total_amount.saturating_sub(self.carried_vested_amount)
     }
 
     /// Calculate claimable amount
@@ -207,9 +1014,264 @@ impl VestingSchedule {
         let vested_amount = self.calculate_vested_amount(current_time)?;
         Ok(vested_amount.saturating_sub(self.claimed_amount))
     }
+
+    /// Record a claim at `current_time` and return the amount to pay out. Never lets the
+    /// claimed total pass `total_amount`, whatever the schedule's other fields say.
+    pub fn record_claim(&mut self, current_time: i64) -> Result<u64> {
+        let claimable_amount = self.calculate_claimable_amount(current_time)?;
+        require!(claimable_amount > 0, FactoryError::NoTokensToClaim);
+
+        let claimed_amount = self.claimed_amount.checked_add(claimable_amount).ok_or(FactoryError::Overflow)?;
+        require!(claimed_amount <= self.total_amount, FactoryError::VestingOverClaim);
+        self.claimed_amount = claimed_amount;
+        self.last_claim_time = current_time;
+        Ok(claimable_amount)
+    }
+
+    /// Time remaining until the cliff and until full vesting, and the linear release rate.
+    /// A revoked schedule has nothing left to vest, so its timeline is complete.
+    pub fn calculate_timeline(&self, current_time: i64) -> Result<VestingTimeline> {
+        if self.revoked {
+            return Ok(VestingTimeline { time_until_cliff: 0, time_until_full: 0, tokens_per_second: 0 });
+        }
+
+        let (cliff_offset, full_offset) = match self.kind {
+            VestingKind::Linear => (self.cliff_seconds, self.duration_seconds.max(self.cliff_seconds)),
+            VestingKind::Milestones => (
+                self.milestones.first().map_or(0, |milestone| milestone.offset_seconds),
+                self.milestones.last().map_or(0, |milestone| milestone.offset_seconds),
+            ),
+        };
+        let cliff_end = self.start_time.checked_add(cliff_offset).ok_or(FactoryError::Overflow)?;
+        let full_end = self.start_time.checked_add(full_offset).ok_or(FactoryError::Overflow)?;
+        let time_until_full = full_end.saturating_sub(current_time).max(0);
+
+        // Milestones unlock in steps, so only linear schedules have a continuous rate
+        let vesting_time = full_offset - cliff_offset;
+        let tokens_per_second = if self.kind == VestingKind::Linear && time_until_full > 0 && vesting_time > 0 {
+            self.vesting_amount() / vesting_time as u64
+        } else {
+            0
+        };
+
+        Ok(VestingTimeline {
+            time_until_cliff: cliff_end.saturating_sub(current_time).max(0),
+            time_until_full,
+            tokens_per_second,
+        })
+    }
+}
+
+/// Whitelist entry allowing a buyer to purchase from a whitelist-gated launch
+/// PDA seeds: `[b"whitelist_entry", launch_state.key().as_ref(), buyer.key().as_ref()]`
+#[account]
+pub struct WhitelistEntry {
+    /// The launch this entry belongs to
+    pub launch_state: Pubkey,
+    /// The whitelisted buyer
+    pub buyer: Pubkey,
+    /// Bump seed of this entry's PDA
+    pub bump: u8,
+}
+
+impl WhitelistEntry {
+    /// Space required for whitelist entry account
+    pub const LEN: usize = 32 + 32 + 1; // 65 bytes
+}
+
+/// Platform fee split between up to `MAX_FEE_SPLIT_RECIPIENTS` recipients, e.g. a DAO treasury and the operator
+/// PDA seeds: `[b"fee_split_config", launch_state.key().as_ref()]`
+#[account]
+pub struct FeeSplitConfig {
+    /// The launch this split belongs to
+    pub launch_state: Pubkey,
+    /// Number of populated entries in `recipients`
+    pub recipient_count: u8,
+    /// Recipients and their shares; only the first `recipient_count` are used
+    pub recipients: [FeeSplitRecipient; MAX_FEE_SPLIT_RECIPIENTS],
+    /// Bump seed of this config's PDA
+    pub bump: u8,
+}
+
+impl FeeSplitConfig {
+    /// Space required for fee split config account
+    pub const LEN: usize = 32 + 1 + (32 + 2) * MAX_FEE_SPLIT_RECIPIENTS + 1; // 170 bytes
+
+    /// Validate a recipient list: 1 to 4 distinct recipients with non-zero shares summing to 100%
+    pub fn validate_recipients(recipients: &[FeeSplitRecipient]) -> Result<()> {
+        require!(!recipients.is_empty() && recipients.len() <= MAX_FEE_SPLIT_RECIPIENTS,
+                FactoryError::InvalidFeeSplit);
+        let mut total_bps: u64 = 0;
+        for (i, entry) in recipients.iter().enumerate() {
+            require!(entry.share_bps > 0 && entry.recipient != Pubkey::default(), FactoryError::InvalidFeeSplit);
+            require!(recipients[..i].iter().all(|other| other.recipient != entry.recipient),
+                    FactoryError::InvalidFeeSplit);
+            total_bps += entry.share_bps as u64;
+        }
+        require!(total_bps == BPS_PRECISION, FactoryError::InvalidFeeSplit);
+        Ok(())
+    }
+
+    /// Replace the recipient list after validating it
+    pub fn set_recipients(&mut self, recipients: &[FeeSplitRecipient]) -> Result<()> {
+        Self::validate_recipients(recipients)?;
+        self.recipients = [FeeSplitRecipient::default(); MAX_FEE_SPLIT_RECIPIENTS];
+        self.recipients[..recipients.len()].copy_from_slice(recipients);
+        self.recipient_count = recipients.len() as u8;
+        Ok(())
+    }
+
+    /// The populated recipients
+    pub fn active_recipients(&self) -> &[FeeSplitRecipient] {
+        &self.recipients[..self.recipient_count as usize]
+    }
+
+    /// Split `platform_fee` by share, in recipient order. Rounding dust goes to the last recipient
+    /// so the parts always sum to `platform_fee`.
+    pub fn split_fee(&self, platform_fee: u64) -> Result<Vec<u64>> {
+        let recipients = self.active_recipients();
+        let mut amounts = Vec::with_capacity(recipients.len());
+        let mut remaining = platform_fee;
+        for (i, entry) in recipients.iter().enumerate() {
+            let amount = if i + 1 == recipients.len() {
+                remaining
+            } else {
+                genesis_common::utils::math_utils::calculate_commission_amount(platform_fee, entry.share_bps)?
+            };
+            remaining = remaining.checked_sub(amount).ok_or(FactoryError::FeeCalculationOverflow)?;
+            amounts.push(amount);
+        }
+        Ok(amounts)
+    }
+}
+
+/// Blocklist entry barring a buyer from purchasing in a launch
+/// PDA seeds: `[b"blocked_buyer", launch_state.key().as_ref(), buyer.key().as_ref()]`
+#[account]
+pub struct BlockedBuyer {
+    /// The launch this entry belongs to
+    pub launch_state: Pubkey,
+    /// The blocked buyer
+    pub buyer: Pubkey,
+}
+
+impl BlockedBuyer {
+    /// Space required for blocked buyer account
+    pub const LEN: usize = 32 + 32; // 64 bytes
+}
+
+/// Merkle root of holder balances taken off-chain at a point in time, e.g. for airdrops
+/// PDA seeds: `[b"snapshot", launch_state.key().as_ref(), snapshot_id.to_le_bytes().as_ref()]`
+///
+/// Leaves are `merkle_utils::hash_leaf(&[holder, balance as little-endian u64])`.
+#[account]
+pub struct Snapshot {
+    /// The launch whose token holders were snapshotted
+    pub launch_state: Pubkey,
+    /// Authority-chosen identifier, unique per launch
+    pub snapshot_id: u64,
+    /// Root of the holder balance tree
+    pub merkle_root: [u8; 32],
+    /// When the root was recorded
+    pub recorded_at: i64,
+    /// Bump seed of this snapshot's PDA
+    pub bump: u8,
 }
 
-/// Purchase tracking for anti-bot measures
+impl Snapshot {
+    /// Space required for snapshot account
+    pub const LEN: usize = 32 + 8 + 32 + 8 + 1; // 81 bytes
+
+    /// Leaf committing to `holder` owning `balance` base units at snapshot time
+    pub fn leaf(holder: &Pubkey, balance: u64) -> [u8; 32] {
+        genesis_common::utils::merkle_utils::hash_leaf(&[holder.as_ref(), &balance.to_le_bytes()])
+    }
+
+    /// Ensure a root is usable: an all-zero root is what an unset snapshot looks like
+    pub fn validate_root(merkle_root: &[u8; 32]) -> Result<()> {
+        require!(*merkle_root != [0u8; 32], FactoryError::InvalidMerkleRoot);
+        Ok(())
+    }
+
+    /// Ensure `proof` shows `holder` held `balance` in this snapshot
+    pub fn verify_claim(&self, holder: &Pubkey, balance: u64, proof: &[[u8; 32]]) -> Result<()> {
+        require!(proof.len() <= MAX_MERKLE_PROOF_LEN, FactoryError::InvalidMerkleProof);
+        require!(
+            genesis_common::utils::merkle_utils::verify_proof(proof, &self.merkle_root, Self::leaf(holder, balance)),
+            FactoryError::InvalidMerkleProof
+        );
+        Ok(())
+    }
+}
+
+/// Human-readable token info for a launch, for wallets and explorers
+/// PDA seeds: `[b"launch_metadata", launch_state.key().as_ref()]`
+#[account]
+pub struct LaunchMetadata {
+    /// The launch this metadata describes
+    pub launch_state: Pubkey,
+    /// Token name, at most `MAX_METADATA_NAME_LEN` bytes
+    pub name: String,
+    /// Token symbol, at most `MAX_METADATA_SYMBOL_LEN` bytes
+    pub symbol: String,
+    /// Off-chain JSON metadata URI, at most `MAX_METADATA_URI_LEN` bytes
+    pub uri: String,
+    /// When the metadata was last set
+    pub updated_at: i64,
+    /// Bump seed of this metadata's PDA
+    pub bump: u8,
+}
+
+impl LaunchMetadata {
+    /// Space required for launch metadata account, with every string at its maximum length
+    pub const LEN: usize = 32 + // launch_state
+        4 + MAX_METADATA_NAME_LEN + 4 + MAX_METADATA_SYMBOL_LEN + 4 + MAX_METADATA_URI_LEN + // name, symbol, uri
+        8 + 1; // updated_at, bump
+
+    /// Ensure name and symbol are non-empty and every field fits its bound
+    pub fn validate(name: &str, symbol: &str, uri: &str) -> Result<()> {
+        require!(!name.is_empty() && name.len() <= MAX_METADATA_NAME_LEN, FactoryError::InvalidMetadata);
+        require!(!symbol.is_empty() && symbol.len() <= MAX_METADATA_SYMBOL_LEN, FactoryError::InvalidMetadata);
+        require!(uri.len() <= MAX_METADATA_URI_LEN, FactoryError::InvalidMetadata);
+        Ok(())
+    }
+}
+
+/// Platform-wide settings shared by every launch
+/// PDA seeds: `[b"platform_config"]`
+#[account]
+pub struct PlatformConfig {
+    /// Signer allowed to change the config; set by the first `set_allowed_payment_mints` call
+    pub admin: Pubkey,
+    /// SPL mints launches may take payment in, e.g. USDC and USDT
+    pub allowed_payment_mints: Vec<Pubkey>,
+    /// Bump seed of this config's PDA
+    pub bump: u8,
+}
+
+impl PlatformConfig {
+    /// Space required for platform config account, with a full payment mint allowlist
+    pub const LEN: usize = 32 + 4 + MAX_ALLOWED_PAYMENT_MINTS * 32 + 1; // 293 bytes
+
+    /// Validate a payment mint allowlist: at most `MAX_ALLOWED_PAYMENT_MINTS` distinct mints
+    pub fn validate_payment_mints(mints: &[Pubkey]) -> Result<()> {
+        require!(mints.len() <= MAX_ALLOWED_PAYMENT_MINTS, FactoryError::InvalidPaymentMintAllowlist);
+        for (index, mint) in mints.iter().enumerate() {
+            require!(*mint != Pubkey::default() && !mints[..index].contains(mint),
+                    FactoryError::InvalidPaymentMintAllowlist);
+        }
+        Ok(())
+    }
+
+    /// Ensure launches may take payment in `payment_mint`
+    pub fn check_payment_mint_allowed(&self, payment_mint: &Pubkey) -> Result<()> {
+        require!(self.allowed_payment_mints.contains(payment_mint), FactoryError::PaymentMintNotAllowed);
+        Ok(())
+    }
+}
+
+/// Per-buyer purchase tracking for anti-bot measures and soft-cap refunds
+/// PDA seeds: `[b"purchase_tracker", launch_state.key().as_ref(), buyer.key().as_ref()]`
 #[account]
 pub struct PurchaseTracker {
     /// The buyer who made the purchase
@@ -220,9 +1282,68 @@ pub struct PurchaseTracker {
     pub total_purchased: u64,
     /// Number of purchases made by this buyer
     pub purchase_count: u32,
+    /// The launch this tracker belongs to
+    pub launch_state: Pubkey,
+    /// Net lamports this buyer has paid into the SOL vault
+    pub total_sol_contributed: u64,
+    /// Tokens minted straight to the buyer's token account (unvested purchases and cashback)
+    pub tokens_received: u64,
+    /// Tokens locked in the buyer's vesting schedule by vested purchases
+    pub tokens_vested: u64,
+    /// Whether this buyer has already been refunded
+    pub refunded: bool,
 }
 
 impl PurchaseTracker {
     /// Space required for purchase tracker account
-    pub const LEN: usize = 32 + 8 + 8 + 4; // 52 bytes
+    pub const LEN: usize = 32 + 8 + 8 + 4 + 32 + 8 + 8 + 8 + 1; // 109 bytes
+
+    /// Record a purchase against this buyer's totals
+    pub fn record_purchase(
+        &mut self,
+        launch_state: Pubkey,
+        buyer: Pubkey,
+        net_sol_amount: u64,
+        tokens_purchased: u64,
+        tokens_received: u64,
+        current_time: i64,
+    ) -> Result<()> {
+        self.launch_state = launch_state;
+        self.buyer = buyer;
+        self.last_purchase_time = current_time;
+        self.total_purchased = self.total_purchased.checked_add(tokens_purchased)
+            .ok_or(FactoryError::Overflow)?;
+        self.purchase_count = self.purchase_count.checked_add(1)
+            .ok_or(FactoryError::Overflow)?;
+        self.total_sol_contributed = self.total_sol_contributed.checked_add(net_sol_amount)
+            .ok_or(FactoryError::Overflow)?;
+        self.tokens_received = self.tokens_received.checked_add(tokens_received)
+            .ok_or(FactoryError::Overflow)?;
+        Ok(())
+    }
+
+    /// Record tokens a vested purchase locked in the buyer's vesting schedule
+    pub fn record_vested(&mut self, amount: u64) -> Result<()> {
+        self.tokens_vested = self.tokens_vested.checked_add(amount)
+            .ok_or(FactoryError::Overflow)?;
+        Ok(())
+    }
+
+    /// Tokens this buyer's purchases added to the launch's `tokens_sold`, vested or not
+    pub fn tokens_allocated(&self) -> Result<u64> {
+        self.tokens_received.checked_add(self.tokens_vested).ok_or(FactoryError::Overflow.into())
+    }
+
+    /// Lamports to return and tokens to burn for a refund, given the vested tokens already claimed
+    /// from the buyer's vesting schedule, or `None` when no schedule was passed. A buyer with
+    /// vested purchases must pass their schedule so its claimed tokens are burned too.
+    pub fn refund_amounts(&self, vested_claimed: Option<u64>) -> Result<(u64, u64)> {
+        require!(!self.refunded, FactoryError::AlreadyRefunded);
+        require!(self.total_sol_contributed > 0, FactoryError::InvalidAmount);
+        require!(self.tokens_vested == 0 || vested_claimed.is_some(), FactoryError::VestingScheduleNotFound);
+        let vested_claimed = vested_claimed.unwrap_or(0);
+        let tokens_to_burn = self.tokens_received.checked_add(vested_claimed)
+            .ok_or(FactoryError::Overflow)?;
+        Ok((self.total_sol_contributed, tokens_to_burn))
+    }
 }
\ No newline at end of file
@@ -0,0 +1,326 @@
+//! # Affiliate State Tests
+//!
+//! Unit-level tests for the pure `AffiliateInfo` helpers in the affiliate program.
+//! These tests exercise multi-level referral logic directly on the state structs,
+//! without spinning up a program-test bank.
+//!
+//! ## Usage
+//!
+//! Run with:
+//! ```bash
+//! cargo test -p launchpad-tests --test affiliate_state
+//! ```
+
+use affiliate_program::error::AffiliateError;
+use affiliate_program::state::*;
+use anchor_lang::prelude::Pubkey;
+use genesis_common::constants::{
+    MAX_AFFILIATE_DESCENDANTS, MAX_RATE_BPS, MIN_RATE_BPS, PERFORMANCE_DECAY_PERIOD_SECONDS, SECONDS_PER_DAY,
+    VOLUME_MONTH_SECONDS,
+};
+
+fn sample_affiliate() -> AffiliateInfo {
+    let affiliate = Pubkey::new_unique();
+    AffiliateInfo {
+        affiliate_key: affiliate,
+        seed_key: affiliate,
+        pending_authority: None,
+        total_referred_volume: 0,
+        commission_rate_bps: 1000,
+        performance_tier: PerformanceTier::Bronze,
+        monthly_referred_volume: 0,
+        quarterly_referred_volume: 0,
+        yearly_referred_volume: 0,
+        successful_referrals: 0,
+        total_clicks: 0,
+        conversion_rate_bps: 0,
+        rate_caps_enabled: false,
+        max_commission_rate_bps: 2000,
+        min_commission_rate_bps: 50,
+        ai_optimization_enabled: true,
+        referral_level: 1,
+        parent_affiliate: None,
+        total_descendants: 0,
+        active_descendants: 0,
+        registration_time: 0,
+        last_activity_time: 0,
+        last_rate_update_time: 0,
+        tier_upgrade_time: 0,
+        monthly_volume_history: [0; 12],
+        volume_month: 0,
+        performance_score: 0,
+        last_score_decay_time: 0,
+        auto_compound_enabled: false,
+    }
+}
+
+#[test]
+fn duplicated_ancestor_is_paid_only_once() {
+    let direct = Pubkey::new_unique();
+    let parent = Pubkey::new_unique();
+    let grandparent = Pubkey::new_unique();
+
+    // A malformed tree loops back to the parent two levels further up.
+    let chain = [direct, parent, grandparent, parent];
+    let levels = AffiliateInfo::distinct_commission_levels(&chain);
+    assert_eq!(levels, vec![0, 1, 2]);
+
+    let paid: Vec<Pubkey> = levels.iter().map(|&level| chain[level]).collect();
+    assert_eq!(paid.iter().filter(|&&key| key == parent).count(), 1);
+
+    // Well-formed chains are paid at every level.
+    assert_eq!(AffiliateInfo::distinct_commission_levels(&chain[..3]), vec![0, 1, 2]);
+}
+
+#[test]
+fn ancestors_share_a_shrinking_part_of_the_commission() {
+    let mut child = sample_affiliate();
+    child.referral_level = 2;
+    child.parent_affiliate = Some(Pubkey::new_unique());
+
+    // A level-2 affiliate's commission is shared with its parent only.
+    assert_eq!(child.max_ancestor_levels(), 1);
+    assert_eq!(sample_affiliate().max_ancestor_levels(), 0);
+
+    // The direct affiliate keeps its whole commission; the parent earns half of it on top,
+    // and a grandparent half again.
+    assert_eq!(AffiliateInfo::ancestor_commission(1_000_000, 0), 1_000_000);
+    assert_eq!(AffiliateInfo::ancestor_commission(1_000_000, 1), 500_000);
+    assert_eq!(AffiliateInfo::ancestor_commission(1_000_000, 2), 250_000);
+    assert_eq!(AffiliateInfo::ancestor_commission(1, 1), 0);
+    assert_eq!(AffiliateInfo::ancestor_commission(u64::MAX, 1), u64::MAX / 2);
+
+    // Commission budgets are checked against the whole chain: 1 + 1/2 for a level-2 affiliate,
+    // 1 + 1/2 + 1/4 for a level-3 one.
+    assert_eq!(child.max_chain_commission(1_000_000), 1_500_000);
+    child.referral_level = 3;
+    assert_eq!(child.max_chain_commission(1_000_000), 1_750_000);
+    assert_eq!(sample_affiliate().max_chain_commission(1_000_000), 1_000_000);
+}
+
+#[test]
+fn affiliate_transfer_needs_the_proposed_wallet() {
+    let mut info = sample_affiliate();
+    let original = info.affiliate_key;
+    let new_wallet = Pubkey::new_unique();
+
+    // Nothing is pending yet, and only the proposed wallet can accept.
+    let err = info.accept_transfer(&new_wallet).unwrap_err();
+    assert_eq!(err, AffiliateError::AuthorityMismatch.into());
+    info.pending_authority = Some(new_wallet);
+    assert!(info.accept_transfer(&Pubkey::new_unique()).is_err());
+    assert_eq!(info.affiliate_key, original);
+
+    info.accept_transfer(&new_wallet).unwrap();
+    assert_eq!((info.affiliate_key, info.pending_authority), (new_wallet, None));
+    // The account stays where it was registered.
+    assert_eq!(info.seed_key, original);
+}
+
+#[test]
+fn ecosystem_stats_aggregate_across_operations() {
+    let mut stats = EcosystemStats::default();
+
+    // Two launches, three affiliates, and purchases of 1.5 and 2 SOL.
+    stats.record_launch_activity(1, 0).unwrap();
+    stats.record_affiliate().unwrap();
+    stats.record_launch_activity(1, 0).unwrap();
+    stats.record_affiliate().unwrap();
+    stats.record_affiliate().unwrap();
+    stats.record_launch_activity(0, 1_500_000_000).unwrap();
+    stats.record_launch_activity(0, 2_000_000_000).unwrap();
+
+    assert_eq!(stats.total_launches, 2);
+    assert_eq!(stats.total_affiliates, 3);
+    assert_eq!(stats.total_sol_raised, 3_500_000_000);
+
+    // A closed affiliate no longer counts.
+    stats.record_affiliate_closed();
+    assert_eq!(stats.total_affiliates, 2);
+
+    stats.total_sol_raised = u64::MAX;
+    assert!(stats.record_launch_activity(0, 1).is_err());
+}
+
+#[test]
+fn program_version_matches_crate_version() {
+    let manifest = include_str!("../programs/affiliate-program/Cargo.toml");
+    let crate_version = manifest.lines()
+        .find_map(|line| line.strip_prefix("version = "))
+        .unwrap()
+        .trim_matches('"');
+    assert!(!affiliate_program::PROGRAM_VERSION.is_empty());
+    assert_eq!(affiliate_program::PROGRAM_VERSION, crate_version);
+}
+
+#[test]
+fn descendants_are_capped_per_parent() {
+    let mut parent = sample_affiliate();
+    for _ in 0..MAX_AFFILIATE_DESCENDANTS {
+        parent.record_descendant().unwrap();
+    }
+    assert_eq!(parent.total_descendants, MAX_AFFILIATE_DESCENDANTS);
+    assert_eq!(parent.active_descendants, MAX_AFFILIATE_DESCENDANTS);
+
+    let err = parent.record_descendant().unwrap_err();
+    assert_eq!(err, AffiliateError::DescendantLimitReached.into());
+    assert_eq!(parent.total_descendants, MAX_AFFILIATE_DESCENDANTS);
+
+    // A closed sub-affiliate stops being active but still counts against the cap.
+    parent.record_descendant_closed();
+    assert_eq!(parent.active_descendants, MAX_AFFILIATE_DESCENDANTS - 1);
+    assert!(parent.record_descendant().is_err());
+}
+
+#[test]
+fn affiliates_with_descendants_cannot_close() {
+    let mut parent = sample_affiliate();
+    assert!(parent.check_closable().is_ok());
+
+    // Once a sub-affiliate registers, the parent stays open even after it closes, so closing and
+    // re-registering can't reset the count against the cap.
+    parent.record_descendant().unwrap();
+    parent.record_descendant_closed();
+    let err = parent.check_closable().unwrap_err();
+    assert_eq!(err, AffiliateError::AffiliateHasDescendants.into());
+}
+
+#[test]
+fn registration_rejects_bad_levels_and_self_referral() {
+    let affiliate = Pubkey::new_unique();
+    let args = |parent_affiliate, referral_level| affiliate_program::RegisterAffiliateArgs {
+        parent_affiliate,
+        referral_level,
+        rate_caps_enabled: false,
+        max_commission_rate_bps: 2000,
+        min_commission_rate_bps: 50,
+    };
+
+    assert!(AffiliateInfo::validate_registration(&affiliate, &args(Some(Pubkey::new_unique()), 2)).is_ok());
+    for level in [0, 6] {
+        let err = AffiliateInfo::validate_registration(&affiliate, &args(None, level)).unwrap_err();
+        assert_eq!(err, AffiliateError::InvalidReferralLevel.into());
+    }
+    let err = AffiliateInfo::validate_registration(&affiliate, &args(Some(affiliate), 2)).unwrap_err();
+    assert_eq!(err, AffiliateError::CircularReferral.into());
+
+    // Without rate caps the program-wide bounds apply, whatever the arguments say.
+    let info = AffiliateInfo::new(affiliate, &args(None, 1), 1_000);
+    assert_eq!(info.affiliate_key, affiliate);
+    assert_eq!((info.min_commission_rate_bps, info.max_commission_rate_bps), (MIN_RATE_BPS, MAX_RATE_BPS));
+    assert_eq!((info.registration_time, info.last_rate_update_time), (1_000, 1_000));
+}
+
+#[test]
+fn inactive_affiliate_score_decays_over_time() {
+    let mut info = sample_affiliate();
+    info.total_referred_volume = 100_000_000_000; // 100k points of volume
+    info.last_activity_time = 1_000;
+
+    info.update_performance_score(1_000).unwrap();
+    assert_eq!(info.performance_score, 100_000);
+    assert_eq!(info.last_score_decay_time, 0);
+
+    // Less than a full period of inactivity leaves the score untouched.
+    info.update_performance_score(1_000 + PERFORMANCE_DECAY_PERIOD_SECONDS - 1).unwrap();
+    assert_eq!(info.performance_score, 100_000);
+
+    // Each full period keeps 90% of the score.
+    info.update_performance_score(1_000 + 2 * PERFORMANCE_DECAY_PERIOD_SECONDS).unwrap();
+    assert_eq!(info.performance_score, 81_000);
+    assert_eq!(info.last_score_decay_time, 1_000 + 2 * PERFORMANCE_DECAY_PERIOD_SECONDS);
+
+    // A long absence decays the score away entirely.
+    info.update_performance_score(1_000 + 1_000 * PERFORMANCE_DECAY_PERIOD_SECONDS).unwrap();
+    assert_eq!(info.performance_score, 0);
+
+    // New activity restores the score earned from lifetime totals.
+    let now = 1_000 + 1_000 * PERFORMANCE_DECAY_PERIOD_SECONDS;
+    info.last_activity_time = now;
+    info.update_performance_score(now).unwrap();
+    assert_eq!(info.performance_score, 100_000);
+}
+
+#[test]
+fn analytics_buckets_updates_by_utc_day() {
+    let mut analytics = AffiliateAnalytics {
+        affiliate_key: Pubkey::new_unique(),
+        daily_volume: [0; 30],
+        daily_clicks: [0; 30],
+        last_update: 0,
+        current_day_index: 0,
+    };
+    let day = 20_000 * SECONDS_PER_DAY;
+
+    // The first update lands in a fresh slot; later ones the same day are summed into it.
+    analytics.add_daily_stats(100, 10, day + 60).unwrap();
+    analytics.add_daily_stats(50, 5, day + 3_600).unwrap();
+    analytics.add_daily_stats(25, 1, day + SECONDS_PER_DAY - 1).unwrap();
+    let today = analytics.current_day_index as usize;
+    assert_eq!((analytics.daily_volume[today], analytics.daily_clicks[today]), (175, 16));
+    assert_eq!(analytics.daily_volume.iter().sum::<u64>(), 175);
+
+    // Crossing midnight moves to the next slot.
+    analytics.add_daily_stats(40, 4, day + SECONDS_PER_DAY).unwrap();
+    let tomorrow = analytics.current_day_index as usize;
+    assert_eq!(tomorrow, (today + 1) % 30);
+    assert_eq!((analytics.daily_volume[tomorrow], analytics.daily_clicks[tomorrow]), (40, 4));
+    assert_eq!(analytics.daily_volume[today], 175);
+
+    // A three-day gap skips two zeroed days.
+    analytics.daily_volume[(tomorrow + 1) % 30] = 999;
+    analytics.add_daily_stats(10, 1, day + 4 * SECONDS_PER_DAY).unwrap();
+    assert_eq!(analytics.current_day_index as usize, (tomorrow + 3) % 30);
+    assert_eq!(analytics.daily_volume[(tomorrow + 1) % 30], 0);
+    assert_eq!(analytics.daily_volume.iter().sum::<u64>(), 175 + 40 + 10);
+    assert_eq!(analytics.get_30_day_avg_volume(), 7);
+
+    // After a month away, the whole window has aged out.
+    analytics.add_daily_stats(30, 3, day + 40 * SECONDS_PER_DAY).unwrap();
+    assert_eq!(analytics.daily_volume.iter().sum::<u64>(), 30);
+    assert_eq!(analytics.daily_clicks.iter().sum::<u32>(), 3);
+}
+
+#[test]
+fn volume_windows_roll_over_by_month() {
+    let mut info = sample_affiliate();
+    let month = |n: i64| n * VOLUME_MONTH_SECONDS;
+
+    // Volume within one month only accumulates.
+    info.roll_periods(month(0) + 1);
+    info.record_period_volume(100).unwrap();
+    info.roll_periods(month(1) - 1);
+    info.record_period_volume(50).unwrap();
+    assert_eq!(
+        (info.monthly_referred_volume, info.quarterly_referred_volume, info.yearly_referred_volume),
+        (150, 150, 150)
+    );
+
+    // The next month starts empty, with the finished month moved into the history.
+    info.roll_periods(month(1));
+    assert_eq!(info.monthly_referred_volume, 0);
+    assert_eq!(info.monthly_volume_history[..2], [150, 0]);
+    info.record_period_volume(20).unwrap();
+    assert_eq!((info.quarterly_referred_volume, info.yearly_referred_volume), (170, 170));
+
+    // Skipping a month leaves it empty; month 0's volume falls out of the quarter once three
+    // months have passed, but stays in the year.
+    info.roll_periods(month(3));
+    assert_eq!(info.monthly_volume_history[..3], [0, 20, 150]);
+    assert_eq!((info.quarterly_referred_volume, info.yearly_referred_volume), (20, 170));
+    info.roll_periods(month(4));
+    assert_eq!((info.quarterly_referred_volume, info.yearly_referred_volume), (0, 170));
+
+    // Rolling again within a month changes nothing.
+    info.roll_periods(month(4) + 10);
+    assert_eq!(info.monthly_volume_history[..4], [0, 0, 20, 150]);
+
+    // After a year the old volume has aged out entirely.
+    info.record_period_volume(5).unwrap();
+    info.roll_periods(month(30));
+    assert_eq!(info.monthly_volume_history, [0; 12]);
+    assert_eq!(
+        (info.monthly_referred_volume, info.quarterly_referred_volume, info.yearly_referred_volume),
+        (0, 0, 0)
+    );
+}
@@ -0,0 +1,886 @@
+//! # Barter DEX State Tests
+//!
+//! Unit-level tests for the pure `LiquidityPool` helpers in the barter DEX program.
+//! These tests exercise fee and oracle freshness logic directly on the state
+//! structs, without spinning up a program-test bank.
+//!
+//! ## Usage
+//!
+//! Run with:
+//! ```bash
+//! cargo test -p launchpad-tests --test dex_state
+//! ```
+
+use anchor_lang::prelude::Pubkey;
+use barter_dex_program::error::BarterError;
+use barter_dex_program::state::*;
+use barter_dex_program::pyth::*;
+use barter_dex_program::switchboard::*;
+use genesis_common::constants::{DEFAULT_AI_WEIGHT, DEFAULT_PYTH_WEIGHT, DEFAULT_SWITCHBOARD_WEIGHT, MAX_ORACLE_AGE_SECONDS, MAX_ORACLE_SIGNERS};
+
+const NOW: i64 = 1_700_000_000;
+
+/// Build raw aggregator account data whose latest confirmed round holds the given values.
+fn mock_aggregator(result: (i128, u32), std_deviation: (i128, u32), round_open_timestamp: i64) -> Vec<u8> {
+    let mut data = vec![0u8; 1_024];
+    data[..8].copy_from_slice(&AGGREGATOR_DISCRIMINATOR);
+    data[ROUND_OPEN_TIMESTAMP_OFFSET..RESULT_OFFSET].copy_from_slice(&round_open_timestamp.to_le_bytes());
+    data[RESULT_OFFSET..RESULT_OFFSET + 16].copy_from_slice(&result.0.to_le_bytes());
+    data[RESULT_OFFSET + 16..STD_DEVIATION_OFFSET].copy_from_slice(&result.1.to_le_bytes());
+    data[STD_DEVIATION_OFFSET..STD_DEVIATION_OFFSET + 16].copy_from_slice(&std_deviation.0.to_le_bytes());
+    data[STD_DEVIATION_OFFSET + 16..STD_DEVIATION_OFFSET + 20].copy_from_slice(&std_deviation.1.to_le_bytes());
+    data
+}
+
+/// Build raw Pyth price account data whose current aggregate holds the given values.
+fn mock_pyth_price(price: i64, conf: u64, expo: i32, publish_time: i64) -> Vec<u8> {
+    let mut data = vec![0u8; 3_312];
+    data[..4].copy_from_slice(&PYTH_MAGIC.to_le_bytes());
+    data[4..8].copy_from_slice(&PYTH_VERSION.to_le_bytes());
+    data[8..12].copy_from_slice(&PYTH_PRICE_ACCOUNT_TYPE.to_le_bytes());
+    data[EXPONENT_OFFSET..EXPONENT_OFFSET + 4].copy_from_slice(&expo.to_le_bytes());
+    data[TIMESTAMP_OFFSET..TIMESTAMP_OFFSET + 8].copy_from_slice(&publish_time.to_le_bytes());
+    data[AGG_PRICE_OFFSET..AGG_PRICE_OFFSET + 8].copy_from_slice(&price.to_le_bytes());
+    data[AGG_CONF_OFFSET..AGG_CONF_OFFSET + 8].copy_from_slice(&conf.to_le_bytes());
+    data[AGG_STATUS_OFFSET..AGG_STATUS_OFFSET + 4].copy_from_slice(&PYTH_STATUS_TRADING.to_le_bytes());
+    data
+}
+
+/// Build a pool with a fresh price and static fees that individual tests can tweak.
+fn sample_pool() -> LiquidityPool {
+    LiquidityPool {
+        mint_a: Pubkey::new_unique(),
+        mint_b: Pubkey::new_unique(),
+        oracle_authority: Pubkey::new_unique(),
+        pending_oracle_authority: None,
+        oracle_signers: [Pubkey::default(); MAX_ORACLE_SIGNERS],
+        oracle_signer_count: 0,
+        oracle_provider: OracleProvider::AIOracle,
+        pyth_price_feed_a: None,
+        pyth_price_feed_b: None,
+        switchboard_feed: None,
+        ai_oracle_program: None,
+        oracle_price: 1_000_000_000,
+        last_oracle_update: NOW,
+        price_confidence: 0,
+        max_confidence_bps: 0,
+        pyth_price: None,
+        pyth_confidence: None,
+        switchboard_price: None,
+        ai_price: Some(1_000_000_000),
+        pyth_updated_at: NOW,
+        switchboard_updated_at: NOW,
+        ai_updated_at: NOW,
+        price_history: [1_000_000_000; 24],
+        history_index: 0,
+        history_len: 0,
+        use_twap: false,
+        twap_window: 0,
+        total_liquidity_a: 0,
+        total_liquidity_b: 0,
+        lp_supply: 0,
+        fee_bps: 30,
+        require_ratio: false,
+        fees_a: 0,
+        fees_b: 0,
+        amm_blend_enabled: false,
+        oracle_weight_bps: 10_000,
+        pyth_weight: DEFAULT_PYTH_WEIGHT,
+        switchboard_weight: DEFAULT_SWITCHBOARD_WEIGHT,
+        ai_weight: DEFAULT_AI_WEIGHT,
+        min_price_sources: 1,
+        cumulative_volume_a: 0,
+        cumulative_volume_b: 0,
+        swap_count: 0,
+        dynamic_fee_enabled: false,
+        volatility_threshold: 0,
+        last_volatility_update: NOW,
+        max_staleness_seconds: 300,
+        hard_stale_seconds: 900,
+        stale_fee_bps: 50,
+        smoothing_enabled: false,
+        ema_alpha_bps: 2_000,
+        ema_price: 1_000_000_000,
+        max_price_move_bps: 0,
+        price_confirm_delay_seconds: 60,
+        pending_price: 0,
+        pending_price_time: 0,
+        max_price_deviation_bps: 0,
+        price_tick: 0,
+        config_update_cooldown_seconds: 3_600,
+        last_config_update: NOW - 7_200,
+        vault_a_bump: 255,
+        vault_b_bump: 255,
+        lp_mint_bump: 255,
+    }
+}
+
+#[test]
+fn fresh_oracle_price_trades_at_base_fee() {
+    let pool = sample_pool();
+    assert_eq!(pool.oracle_freshness(NOW + 300), OracleFreshness::Fresh);
+    assert_eq!(pool.calculate_swap_fee(NOW + 300).unwrap(), 30);
+}
+
+#[test]
+fn stale_oracle_price_trades_at_elevated_fee() {
+    let pool = sample_pool();
+    assert_eq!(pool.oracle_freshness(NOW + 301), OracleFreshness::Stale);
+    assert_eq!(pool.calculate_swap_fee(NOW + 301).unwrap(), 80);
+    assert_eq!(pool.oracle_freshness(NOW + 900), OracleFreshness::Stale);
+    assert_eq!(pool.calculate_swap_fee(NOW + 900).unwrap(), 80);
+}
+
+#[test]
+fn expired_oracle_price_fails_swaps() {
+    let pool = sample_pool();
+    assert_eq!(pool.oracle_freshness(NOW + 901), OracleFreshness::Expired);
+    let err = pool.calculate_swap_fee(NOW + 901).unwrap_err();
+    assert_eq!(err, BarterError::OraclePriceStale.into());
+}
+
+#[test]
+fn ema_converges_towards_pushed_price() {
+    let mut pool = sample_pool();
+    pool.smoothing_enabled = true;
+
+    // A single spike to 2.0 only moves the EMA by alpha (20%).
+    pool.update_ema(2_000_000_000);
+    assert_eq!(pool.ema_price, 1_200_000_000);
+    assert_eq!(pool.calculate_swap_price().unwrap(), 1_200_000_000);
+
+    // Sustained pushes at the new level pull the EMA monotonically towards it.
+    let mut previous = pool.ema_price;
+    for _ in 0..30 {
+        pool.update_ema(2_000_000_000);
+        assert!(pool.ema_price >= previous);
+        previous = pool.ema_price;
+    }
+    assert!(2_000_000_000 - pool.ema_price < 2_000_000);
+}
+
+#[test]
+fn swaps_use_committed_price_when_smoothing_disabled() {
+    let mut pool = sample_pool();
+    pool.apply_oracle_price(1_500_000_000, NOW);
+
+    assert_eq!(pool.calculate_swap_price().unwrap(), 1_500_000_000);
+    assert_eq!(pool.ema_price, 1_100_000_000);
+}
+
+#[test]
+fn fee_quote_matches_swap_fee() {
+    let mut pool = sample_pool();
+    pool.dynamic_fee_enabled = true;
+    pool.volatility_threshold = 1;
+    pool.price_history[..4].copy_from_slice(&[1_000_000_000, 1_100_000_000, 900_000_000, 1_050_000_000]);
+    pool.history_index = 4;
+
+    for now in [NOW, NOW + 600] {
+        let quote = pool.quote_fee(now).unwrap();
+        assert_eq!(quote.fee_bps, pool.calculate_swap_fee(now).unwrap());
+        assert_eq!(quote.base_fee_bps, 30);
+        assert_eq!(quote.volatility, pool.calculate_volatility().unwrap());
+    }
+    assert!(!pool.quote_fee(NOW).unwrap().oracle_stale);
+    assert!(pool.quote_fee(NOW + 600).unwrap().oracle_stale);
+
+    // No quote once swaps would fail outright.
+    assert!(pool.quote_fee(NOW + 901).is_err());
+}
+
+#[test]
+fn dynamic_fees_require_nonzero_volatility_threshold() {
+    let err = LiquidityPool::validate_dynamic_fee_config(true, 0).unwrap_err();
+    assert_eq!(err, BarterError::InvalidPoolConfiguration.into());
+    assert!(LiquidityPool::validate_dynamic_fee_config(true, 1).is_ok());
+    assert!(LiquidityPool::validate_dynamic_fee_config(false, 0).is_ok());
+}
+
+#[test]
+fn absurd_pool_fees_are_rejected_without_underflow() {
+    assert!(LiquidityPool::validate_fee_bps(2_000).is_ok());
+    for fee_bps in [2_001, 10_000, u16::MAX] {
+        let err = LiquidityPool::validate_fee_bps(fee_bps).unwrap_err();
+        assert_eq!(err, BarterError::FeeExceedsMaximum.into());
+    }
+
+    // A pool that somehow carries a fee of 100% or more fails the swap cleanly instead of underflowing.
+    for fee_bps in [10_000, u16::MAX] {
+        let err = LiquidityPool::calculate_swap_output(1_000_000, true, 1_000_000_000, fee_bps).unwrap_err();
+        assert_eq!(err, BarterError::FeeExceedsMaximum.into());
+    }
+}
+
+#[test]
+fn legacy_pool_with_zero_threshold_falls_back_to_base_fee() {
+    let mut pool = sample_pool();
+    pool.dynamic_fee_enabled = true;
+    pool.volatility_threshold = 0;
+    pool.price_history[..3].copy_from_slice(&[1_000_000_000, 2_000_000_000, 500_000_000]);
+    pool.history_index = 3;
+
+    assert_eq!(pool.calculate_dynamic_fee().unwrap(), 30);
+    assert_eq!(pool.calculate_swap_fee(NOW).unwrap(), 30);
+}
+
+#[test]
+fn small_price_moves_apply_immediately() {
+    let mut pool = sample_pool();
+    pool.max_price_move_bps = 1_000; // 10%
+
+    assert!(!pool.push_oracle_price(1_050_000_000, NOW + 10));
+    assert_eq!(pool.oracle_price, 1_050_000_000);
+    assert_eq!(pool.last_oracle_update, NOW + 10);
+    assert_eq!(pool.pending_price, 0);
+}
+
+#[test]
+fn price_deviation_breaker_rejects_wild_pushes() {
+    let mut pool = sample_pool();
+    pool.max_price_deviation_bps = 5_000; // 50%
+
+    // A 10x push is rejected outright.
+    let err = pool.check_price_deviation(10_000_000_000).unwrap_err();
+    assert_eq!(err, BarterError::PriceDeviationTooLarge.into());
+
+    // Moves up to the threshold, either way, are accepted.
+    assert!(pool.check_price_deviation(1_200_000_000).is_ok());
+    assert!(pool.check_price_deviation(1_500_000_000).is_ok());
+    assert!(pool.check_price_deviation(500_000_000).is_ok());
+    let err = pool.check_price_deviation(499_999_999).unwrap_err();
+    assert_eq!(err, BarterError::PriceDeviationTooLarge.into());
+
+    pool.max_price_deviation_bps = 0;
+    assert!(pool.check_price_deviation(10_000_000_000).is_ok());
+}
+
+#[test]
+fn large_price_move_is_staged_then_confirmed_after_delay() {
+    let mut pool = sample_pool();
+    pool.max_price_move_bps = 1_000; // 10%
+
+    // A 50% jump is staged and does not move the committed price.
+    assert!(pool.push_oracle_price(1_500_000_000, NOW + 10));
+    assert_eq!(pool.pending_price, 1_500_000_000);
+    assert_eq!(pool.oracle_price, 1_000_000_000);
+    assert_eq!(pool.calculate_swap_price().unwrap(), 1_000_000_000);
+
+    // Confirming before the delay has passed is rejected.
+    let err = pool.confirm_pending_price(NOW + 69).unwrap_err();
+    assert_eq!(err, BarterError::PriceConfirmationTooEarly.into());
+
+    assert_eq!(pool.confirm_pending_price(NOW + 70).unwrap(), 1_500_000_000);
+    assert_eq!(pool.oracle_price, 1_500_000_000);
+    assert_eq!(pool.last_oracle_update, NOW + 70);
+    assert_eq!(pool.pending_price, 0);
+
+    // Nothing left to confirm.
+    let err = pool.confirm_pending_price(NOW + 200).unwrap_err();
+    assert_eq!(err, BarterError::NoPendingPrice.into());
+
+    // A staged price left unconfirmed past the oracle age limit expires.
+    assert!(pool.push_oracle_price(3_000_000_000, NOW + 300));
+    let confirmable_at = NOW + 300 + pool.price_confirm_delay_seconds;
+    let err = pool.confirm_pending_price(confirmable_at + MAX_ORACLE_AGE_SECONDS + 1).unwrap_err();
+    assert_eq!(err, BarterError::PendingPriceExpired.into());
+    assert_eq!(pool.confirm_pending_price(confirmable_at + MAX_ORACLE_AGE_SECONDS).unwrap(), 3_000_000_000);
+}
+
+#[test]
+fn switchboard_round_is_scaled_to_oracle_precision() {
+    // 1.2345 with a 0.0012 standard deviation.
+    let data = mock_aggregator((12_345, 4), (12, 4), NOW - 10);
+    let round = AggregatorRound::parse(&data).unwrap();
+
+    assert_eq!(round.round_open_timestamp, NOW - 10);
+    assert_eq!(round.result.to_precision(1_000_000_000).unwrap(), 1_234_500_000);
+    assert_eq!(round.std_deviation.to_precision(1_000_000_000).unwrap(), 1_200_000);
+
+    // Scales finer than the pool precision truncate.
+    let fine = SwitchboardDecimal { mantissa: 1_234_567_890_123, scale: 12 };
+    assert_eq!(fine.to_precision(1_000_000_000).unwrap(), 1_234_567_890);
+
+    // Negative results are not valid prices.
+    let negative = SwitchboardDecimal { mantissa: -1, scale: 0 };
+    assert_eq!(negative.to_precision(1_000_000_000).unwrap_err(), BarterError::OraclePriceFetchFailed.into());
+}
+
+#[test]
+fn switchboard_round_freshness_and_layout_checks() {
+    let data = mock_aggregator((1, 0), (0, 0), NOW - 300);
+    let round = AggregatorRound::parse(&data).unwrap();
+    assert!(round.check_fresh(NOW, 300).is_ok());
+    assert_eq!(round.check_fresh(NOW + 1, 300).unwrap_err(), BarterError::OraclePriceStale.into());
+
+    let mut wrong_discriminator = data.clone();
+    wrong_discriminator[0] ^= 0xff;
+    let err = AggregatorRound::parse(&wrong_discriminator).unwrap_err();
+    assert_eq!(err, BarterError::SwitchboardFeedNotFound.into());
+
+    let err = AggregatorRound::parse(&data[..STD_DEVIATION_OFFSET]).unwrap_err();
+    assert_eq!(err, BarterError::SwitchboardFeedNotFound.into());
+}
+
+#[test]
+fn pyth_price_is_scaled_to_oracle_precision() {
+    // 1.2345 +/- 0.0012 with an exponent of -8.
+    let feed = PythPrice::parse(&mock_pyth_price(123_450_000, 120_000, -8, NOW - 10)).unwrap();
+    assert_eq!(feed.publish_time, NOW - 10);
+    assert_eq!(feed.to_precision(1_000_000_000).unwrap(), 1_234_500_000);
+    assert_eq!(feed.conf_to_precision(1_000_000_000).unwrap(), 1_200_000);
+
+    // A single feed quotes A in B directly.
+    assert_eq!(pool_price_from_feeds(&feed, None, 1_000_000_000).unwrap(), (1_234_500_000, 1_200_000));
+
+    // Two USD feeds price A in B by their ratio: $150 / $2 = 75 B per A.
+    let usd_a = PythPrice { price: 15_000, conf: 15, expo: -2, publish_time: NOW };
+    let usd_b = PythPrice { price: 200, conf: 1, expo: -2, publish_time: NOW };
+    let (price, confidence) = pool_price_from_feeds(&usd_a, Some(&usd_b), 1_000_000_000).unwrap();
+    assert_eq!(price, 75_000_000_000);
+    // 0.1% relative confidence on A plus 0.5% on B is 0.6% of 75.
+    assert_eq!(confidence, 450_000_000);
+
+    // Zero and negative prices are not valid.
+    for price in [0, -5] {
+        let feed = PythPrice { price, conf: 0, expo: -8, publish_time: NOW };
+        assert_eq!(feed.to_precision(1_000_000_000).unwrap_err(), BarterError::OraclePriceFetchFailed.into());
+    }
+}
+
+#[test]
+fn pyth_price_freshness_and_layout_checks() {
+    let data = mock_pyth_price(1, 0, 0, NOW - 60);
+    let feed = PythPrice::parse(&data).unwrap();
+    assert!(feed.check_fresh(NOW, 60).is_ok());
+    assert_eq!(feed.check_fresh(NOW + 1, 60).unwrap_err(), BarterError::OraclePriceStale.into());
+
+    // While not trading, the last trading aggregate is used, along with its publish time.
+    let mut halted = data.clone();
+    halted[AGG_STATUS_OFFSET..AGG_STATUS_OFFSET + 4].copy_from_slice(&0u32.to_le_bytes());
+    halted[PREV_PRICE_OFFSET..PREV_PRICE_OFFSET + 8].copy_from_slice(&7i64.to_le_bytes());
+    halted[PREV_TIMESTAMP_OFFSET..PREV_TIMESTAMP_OFFSET + 8].copy_from_slice(&(NOW - 600).to_le_bytes());
+    let feed = PythPrice::parse(&halted).unwrap();
+    assert_eq!((feed.price, feed.publish_time), (7, NOW - 600));
+
+    let mut wrong_magic = data.clone();
+    wrong_magic[0] ^= 0xff;
+    assert_eq!(PythPrice::parse(&wrong_magic).unwrap_err(), BarterError::PythPriceFeedNotFound.into());
+
+    let mut product_account = data.clone();
+    product_account[8..12].copy_from_slice(&2u32.to_le_bytes());
+    assert_eq!(PythPrice::parse(&product_account).unwrap_err(), BarterError::PythPriceFeedNotFound.into());
+
+    assert_eq!(PythPrice::parse(&data[..AGG_STATUS_OFFSET]).unwrap_err(), BarterError::PythPriceFeedNotFound.into());
+}
+
+#[test]
+fn dust_swap_rounding_to_zero_output_is_rejected() {
+    // At 0.5 B per A, a single base unit of A rounds down to no B at all.
+    let err = LiquidityPool::calculate_swap_output(1, true, 500_000_000, 30).unwrap_err();
+    assert_eq!(err, BarterError::AmountTooSmall.into());
+    // At 2 B per A, one base unit of B likewise buys nothing.
+    let err = LiquidityPool::calculate_swap_output(1, false, 2_000_000_000, 30).unwrap_err();
+    assert_eq!(err, BarterError::AmountTooSmall.into());
+
+    assert_eq!(LiquidityPool::calculate_swap_output(1_000_000, true, 500_000_000, 30).unwrap(), 498_500);
+    assert_eq!(LiquidityPool::calculate_swap_output(1_000_000, false, 2_000_000_000, 30).unwrap(), 498_500);
+}
+
+#[test]
+fn oracle_feeds_must_match_the_declared_provider() {
+    let feed = || Some(Pubkey::new_unique());
+    let rejected = |provider, pyth_a, pyth_b, switchboard, ai| {
+        let err = LiquidityPool::validate_oracle_sources(provider, pyth_a, pyth_b, switchboard, ai).unwrap_err();
+        assert_eq!(err, BarterError::InvalidPoolConfiguration.into());
+    };
+
+    // Each single provider accepts its own feed...
+    assert!(LiquidityPool::validate_oracle_sources(OracleProvider::Pyth, feed(), feed(), None, None).is_ok());
+    assert!(LiquidityPool::validate_oracle_sources(OracleProvider::Switchboard, None, None, feed(), None).is_ok());
+    assert!(LiquidityPool::validate_oracle_sources(OracleProvider::AIOracle, None, None, None, feed()).is_ok());
+    assert!(LiquidityPool::validate_oracle_sources(OracleProvider::AIOracle, None, None, None, None).is_ok());
+
+    // ...but rejects a missing feed or another provider's feed.
+    rejected(OracleProvider::Pyth, None, None, None, feed());
+    rejected(OracleProvider::Pyth, None, feed(), None, None);
+    rejected(OracleProvider::Pyth, feed(), None, feed(), None);
+    rejected(OracleProvider::Switchboard, None, None, None, None);
+    rejected(OracleProvider::Switchboard, feed(), None, feed(), None);
+    rejected(OracleProvider::AIOracle, feed(), None, None, feed());
+    rejected(OracleProvider::AIOracle, None, None, feed(), None);
+
+    // Hybrid pools need at least two sources.
+    rejected(OracleProvider::Hybrid, None, None, None, None);
+    rejected(OracleProvider::Hybrid, feed(), feed(), None, None);
+    assert!(LiquidityPool::validate_oracle_sources(OracleProvider::Hybrid, feed(), None, None, feed()).is_ok());
+    assert!(LiquidityPool::validate_oracle_sources(OracleProvider::Hybrid, feed(), feed(), feed(), feed()).is_ok());
+}
+
+#[test]
+fn config_updates_are_rate_limited() {
+    let mut pool = sample_pool();
+    assert!(pool.check_config_update_allowed(NOW).is_ok());
+
+    // A second change ten minutes after the first is rejected until the hour is up.
+    pool.last_config_update = NOW;
+    let err = pool.check_config_update_allowed(NOW + 600).unwrap_err();
+    assert_eq!(err, BarterError::UpdateTooFrequent.into());
+    assert!(pool.check_config_update_allowed(NOW + 3_600).is_ok());
+
+    // A zero cooldown disables the rate limit.
+    pool.config_update_cooldown_seconds = 0;
+    assert!(pool.check_config_update_allowed(NOW).is_ok());
+}
+
+#[test]
+fn pushed_prices_snap_to_the_pool_tick() {
+    let mut pool = sample_pool();
+    assert_eq!(pool.snap_to_tick(1_000_123), 1_000_123);
+
+    pool.price_tick = 1_000;
+    assert_eq!(pool.snap_to_tick(1_000_499), 1_000_000);
+    assert_eq!(pool.snap_to_tick(1_000_500), 1_001_000);
+    assert_eq!(pool.snap_to_tick(100), 1_000);
+    assert_eq!(pool.snap_to_tick(0), 0);
+
+    // Jitter below half a tick collapses to one price, so volatility disappears.
+    let jittery = [1_000_000_200, 999_999_700, 1_000_000_400, 999_999_900, 1_000_000_100];
+    let mut raw = sample_pool();
+    let mut snapped = sample_pool();
+    snapped.price_tick = 1_000;
+    for price in jittery {
+        raw.update_price_history(price);
+        snapped.update_price_history(snapped.snap_to_tick(price));
+    }
+    assert!(raw.calculate_volatility().unwrap() > 0);
+    assert_eq!(snapped.calculate_volatility().unwrap(), 0);
+}
+
+#[test]
+fn lp_tokens_track_each_deposits_share_of_pool_value() {
+    let mut pool = sample_pool();
+    pool.oracle_price = 2_000_000_000; // 2 B per A
+
+    // Deposits worth no more than the locked minimum can't open the pool.
+    let err = pool.record_deposit(250_000, 500_000).unwrap_err();
+    assert_eq!(err, BarterError::AmountTooSmall.into());
+
+    // The first deposit is worth 4M B; 1M of that supply is locked and never minted.
+    assert_eq!(pool.record_deposit(1_000_000, 2_000_000).unwrap(), 3_000_000);
+    assert_eq!(pool.lp_supply, 4_000_000);
+
+    // A one-sided deposit worth a quarter of the pool earns a quarter of the supply.
+    assert_eq!(pool.record_deposit(500_000, 0).unwrap(), 1_000_000);
+    assert_eq!((pool.total_liquidity_a, pool.total_liquidity_b, pool.lp_supply), (1_500_000, 2_000_000, 5_000_000));
+
+    // Withdrawing it returns a fifth of each vault, the same 1M B of value that went in.
+    assert_eq!(pool.record_withdrawal(1_000_000).unwrap(), (300_000, 400_000));
+    assert_eq!((pool.total_liquidity_a, pool.total_liquidity_b, pool.lp_supply), (1_200_000, 1_600_000, 4_000_000));
+
+    // The first depositor gets back everything except the locked minimum's share.
+    assert_eq!(pool.record_withdrawal(3_000_000).unwrap(), (900_000, 1_200_000));
+    assert_eq!((pool.total_liquidity_a, pool.total_liquidity_b, pool.lp_supply), (300_000, 400_000, 1_000_000));
+
+    assert_eq!(pool.record_withdrawal(0).unwrap_err(), BarterError::AmountTooSmall.into());
+    assert_eq!(pool.record_withdrawal(1_000_000).unwrap_err(), BarterError::InsufficientLiquidity.into());
+}
+
+#[test]
+fn ratio_enforcing_pools_reject_off_ratio_deposits() {
+    let mut pool = sample_pool();
+    pool.oracle_price = 2_000_000_000;
+    pool.require_ratio = true;
+
+    // The first deposit sets the 1:2 reserve ratio.
+    assert_eq!(pool.record_deposit(1_000_000, 2_000_000).unwrap(), 3_000_000);
+
+    // A one-sided or skewed deposit is rejected and leaves the reserves untouched.
+    assert_eq!(pool.record_deposit(500_000, 0).unwrap_err(), BarterError::DepositRatioMismatch.into());
+    assert_eq!(pool.record_deposit(500_000, 900_000).unwrap_err(), BarterError::DepositRatioMismatch.into());
+    assert_eq!((pool.total_liquidity_a, pool.total_liquidity_b, pool.lp_supply), (1_000_000, 2_000_000, 4_000_000));
+
+    // Deposits within the 1% tolerance go through.
+    assert_eq!(pool.record_deposit(500_000, 1_000_000).unwrap(), 2_000_000);
+    assert!(pool.record_deposit(100_000, 199_000).is_ok());
+
+    // Without enforcement the skewed deposit is accepted.
+    pool.require_ratio = false;
+    assert!(pool.record_deposit(500_000, 0).is_ok());
+}
+
+#[test]
+fn integer_square_root_matches_known_squares() {
+    use genesis_common::utils::math_utils::isqrt_u128;
+
+    for root in [0u128, 1, 2, 3, 10, 1_000_000_007, u64::MAX as u128 - 1] {
+        assert_eq!(isqrt_u128(root * root), root);
+        if root > 0 {
+            // Just below the next square, the root still rounds down.
+            assert_eq!(isqrt_u128((root + 1) * (root + 1) - 1), root);
+        }
+    }
+    assert_eq!(isqrt_u128(u128::MAX), u64::MAX as u128);
+}
+
+#[test]
+fn volatility_of_a_sample_history_is_deterministic() {
+    let mut pool = sample_pool();
+    for price in [1_000_000_000, 1_000_000_100, 999_999_900, 1_000_000_050, 999_999_950] {
+        pool.update_price_history(price);
+    }
+    // Variance 5,000, so the deviation is sqrt(5000) = 70.710678118... price units.
+    assert_eq!(pool.calculate_volatility().unwrap(), 70_710_678_118);
+
+    let mut pool = sample_pool();
+    for price in [2_000_000_000, 2_010_000_000, 1_990_000_000, 2_005_000_000] {
+        pool.update_price_history(price);
+    }
+    assert_eq!(pool.calculate_volatility().unwrap(), 7_395_099_728_874_520);
+
+    // Swings too wide for the scaled result saturate rather than fail the swap.
+    let mut pool = sample_pool();
+    for price in [1, u64::MAX] {
+        pool.update_price_history(price);
+    }
+    assert_eq!(pool.calculate_volatility().unwrap(), u64::MAX);
+}
+
+#[test]
+fn volatility_samples_the_whole_history_after_it_wraps() {
+    let swings = [1_000_000_000, 1_000_000_100, 999_999_900, 1_000_000_050, 999_999_950, 1_000_000_000];
+    let mut pool = sample_pool();
+    for price in swings {
+        pool.update_price_history(price);
+    }
+    let one_cycle = pool.calculate_volatility().unwrap();
+    assert!(one_cycle > 0);
+
+    // One push past a full ring leaves the write index at 1, but the ring still holds four
+    // whole cycles of the same swings, so the volatility is unchanged.
+    let mut pool = sample_pool();
+    for &price in swings.iter().cycle().take(25) {
+        pool.update_price_history(price);
+    }
+    assert_eq!((pool.history_index, pool.history_len), (1, 24));
+    assert_eq!(pool.calculate_volatility().unwrap(), one_cycle);
+}
+
+#[test]
+fn wide_oracle_confidence_halts_swaps() {
+    let mut pool = sample_pool();
+    pool.max_confidence_bps = 100; // 1% of the price
+    pool.price_confidence = 10_000_000;
+    assert!(pool.check_price_confidence(1_000_000_000).is_ok());
+
+    pool.price_confidence = 10_000_001;
+    let err = pool.check_price_confidence(1_000_000_000).unwrap_err();
+    assert_eq!(err, BarterError::OraclePriceConfidenceTooHigh.into());
+
+    pool.max_confidence_bps = 0;
+    assert!(pool.check_price_confidence(1_000_000_000).is_ok());
+}
+
+#[test]
+fn pyth_prices_without_confidence_are_left_out_of_the_weighting() {
+    let mut pool = sample_pool();
+    pool.oracle_provider = OracleProvider::Hybrid;
+    pool.pyth_price = Some(3_000_000_000);
+    assert_eq!(pool.calculate_weighted_price(NOW).unwrap(), 1_000_000_000);
+
+    // With a confidence, Pyth carries its 40 to the AI price's 25.
+    pool.pyth_confidence = Some(1_000_000);
+    assert_eq!(pool.calculate_weighted_price(NOW).unwrap(), 2_230_769_230);
+}
+
+#[test]
+fn custom_source_weights_shape_the_weighted_price() {
+    let mut pool = sample_pool();
+    pool.oracle_provider = OracleProvider::Hybrid;
+    pool.pyth_price = Some(3_000_000_000);
+    pool.pyth_confidence = Some(1_000_000);
+    pool.switchboard_price = Some(2_000_000_000);
+
+    // Default 40/35/25 split.
+    assert_eq!(pool.calculate_weighted_price(NOW).unwrap(), 2_150_000_000);
+
+    // A pool that trusts Switchboard most.
+    pool.pyth_weight = 1_000;
+    pool.switchboard_weight = 8_000;
+    pool.ai_weight = 1_000;
+    assert_eq!(pool.calculate_weighted_price(NOW).unwrap(), 2_000_000_000);
+
+    // With only Switchboard left, it carries the whole price despite its weight.
+    pool.pyth_price = None;
+    pool.ai_price = None;
+    assert_eq!(pool.calculate_weighted_price(NOW).unwrap(), 2_000_000_000);
+    pool.switchboard_price = Some(2_500_000_000);
+    assert_eq!(pool.calculate_weighted_price(NOW).unwrap(), 2_500_000_000);
+}
+
+#[test]
+fn stale_sources_drop_out_of_the_weighted_price() {
+    let mut pool = sample_pool();
+    pool.oracle_provider = OracleProvider::Hybrid;
+    pool.pyth_price = Some(3_000_000_000);
+    pool.pyth_confidence = Some(1_000_000);
+    pool.switchboard_price = Some(2_000_000_000);
+    assert_eq!(pool.calculate_weighted_price(NOW).unwrap(), 2_150_000_000);
+
+    // Pyth stopped updating while the AI price kept being pushed.
+    let later = NOW + MAX_ORACLE_AGE_SECONDS + 1;
+    pool.switchboard_updated_at = later;
+    pool.ai_updated_at = later;
+    assert_eq!(pool.active_price_sources(later), 2);
+    assert_eq!(pool.calculate_weighted_price(later).unwrap(), 1_583_333_333);
+    assert!(pool.check_price_sources(later).is_ok());
+
+    // Once every source is stale, swaps halt.
+    let much_later = later + MAX_ORACLE_AGE_SECONDS + 1;
+    assert_eq!(pool.active_price_sources(much_later), 0);
+    let err = pool.check_price_sources(much_later).unwrap_err();
+    assert_eq!(err, BarterError::OraclePriceStale.into());
+}
+
+#[test]
+fn source_weights_must_split_the_whole_price() {
+    assert!(LiquidityPool::validate_source_weights(DEFAULT_PYTH_WEIGHT, DEFAULT_SWITCHBOARD_WEIGHT, DEFAULT_AI_WEIGHT).is_ok());
+    assert!(LiquidityPool::validate_source_weights(0, 10_000, 0).is_ok());
+
+    let err = LiquidityPool::validate_source_weights(4_000, 3_500, 3_000).unwrap_err();
+    assert_eq!(err, BarterError::InvalidPoolConfiguration.into());
+    let err = LiquidityPool::validate_source_weights(0, 0, 0).unwrap_err();
+    assert_eq!(err, BarterError::InvalidPoolConfiguration.into());
+}
+
+#[test]
+fn skewed_reserves_pull_the_blended_price_towards_the_pool_ratio() {
+    let mut pool = sample_pool();
+    pool.oracle_price = 2_000_000_000;
+    // The reserves imply 1 B per A against the oracle's 2.
+    pool.total_liquidity_a = 1_000_000_000;
+    pool.total_liquidity_b = 1_000_000_000;
+
+    // Pure oracle pricing until the blend is switched on.
+    assert_eq!(pool.blend_with_amm_price(pool.oracle_price).unwrap(), 2_000_000_000);
+    let pure_output = LiquidityPool::calculate_swap_output(1_000_000, true, 2_000_000_000, 30).unwrap();
+    assert_eq!(pure_output, 1_994_000);
+
+    pool.amm_blend_enabled = true;
+    pool.oracle_weight_bps = 7_500;
+    let blended = pool.blend_with_amm_price(pool.oracle_price).unwrap();
+    assert_eq!(blended, 1_750_000_000);
+    let blended_output = LiquidityPool::calculate_swap_output(1_000_000, true, blended, 30).unwrap();
+    assert_eq!(blended_output, 1_744_750);
+
+    // A one-sided pool has no reserve price to blend with.
+    pool.total_liquidity_b = 0;
+    assert_eq!(pool.blend_with_amm_price(pool.oracle_price).unwrap(), 2_000_000_000);
+}
+
+#[test]
+fn oracle_weight_must_be_a_share_of_the_price() {
+    assert!(LiquidityPool::validate_amm_blend_config(true, 0).is_ok());
+    assert!(LiquidityPool::validate_amm_blend_config(true, 10_000).is_ok());
+    let err = LiquidityPool::validate_amm_blend_config(true, 10_001).unwrap_err();
+    assert_eq!(err, BarterError::InvalidPoolConfiguration.into());
+    // Ignored while the blend is off.
+    assert!(LiquidityPool::validate_amm_blend_config(false, u16::MAX).is_ok());
+}
+
+#[test]
+fn swaps_accumulate_volume_and_count() {
+    let mut pool = sample_pool();
+    pool.total_liquidity_a = 1_000_000_000;
+    pool.total_liquidity_b = 2_000_000_000;
+
+    // 1 A in for 1.994 B out, then 2 B in for 0.997 A out.
+    pool.record_swap(true, 1_000_000, 1_994_000, 6_000).unwrap();
+    pool.record_swap(false, 2_000_000, 997_000, 3_000).unwrap();
+
+    assert_eq!(pool.swap_count, 2);
+    assert_eq!(pool.cumulative_volume_a, 1_997_000);
+    assert_eq!(pool.cumulative_volume_b, 3_994_000);
+
+    // Paying out more than the reserves hold is rejected rather than saturating.
+    let err = pool.record_swap(true, 1, 3_000_000_000, 0).unwrap_err();
+    assert_eq!(err, BarterError::InsufficientLiquidity.into());
+}
+
+#[test]
+fn swap_fees_accrue_outside_the_lp_reserves() {
+    let mut pool = sample_pool();
+    pool.oracle_price = 2_000_000_000;
+    assert_eq!(pool.record_deposit(1_000_000_000, 2_000_000_000).unwrap(), 3_999_000_000);
+
+    // Three swaps at 30 bps: two A->B, one B->A.
+    for (a_to_b, amount_in) in [(true, 1_000_000), (true, 3_000_000), (false, 2_000_000)] {
+        let (amount_out, fee) = LiquidityPool::calculate_swap_amounts(amount_in, a_to_b, pool.oracle_price, 30).unwrap();
+        pool.record_swap(a_to_b, amount_in, amount_out, fee).unwrap();
+    }
+    assert_eq!((pool.fees_a, pool.fees_b), (3_000, 24_000));
+    // Reserves moved by the full pre-fee output: +4 A -1 A, -8 B +2 B.
+    assert_eq!((pool.total_liquidity_a, pool.total_liquidity_b), (1_003_000_000, 1_994_000_000));
+
+    // Withdrawals are paid from the reserves only, so no LP share includes the fees.
+    let (amount_a, amount_b) = pool.record_withdrawal(3_999_000_000).unwrap();
+    assert_eq!((amount_a, amount_b), (1_002_749_250, 1_993_501_500));
+    assert_eq!((pool.fees_a, pool.fees_b), (3_000, 24_000));
+
+    assert_eq!(pool.take_fees(), (3_000, 24_000));
+    assert_eq!(pool.take_fees(), (0, 0));
+}
+
+#[test]
+fn swap_event_round_trips_through_log_data() {
+    use anchor_lang::{AnchorDeserialize, Discriminator, Event};
+    use barter_dex_program::SwapEvent;
+
+    let pool = Pubkey::new_unique();
+    let user = Pubkey::new_unique();
+    let mint_in = Pubkey::new_unique();
+    let event = SwapEvent {
+        pool,
+        user,
+        mint_in,
+        amount_in: 1_000_000,
+        amount_out: 1_994_000,
+        fee_bps: 30,
+        effective_price: 2_000_000_000,
+        timestamp: NOW,
+    };
+
+    let data = event.data();
+    assert_eq!(&data[..8], SwapEvent::DISCRIMINATOR);
+    let decoded = SwapEvent::try_from_slice(&data[8..]).unwrap();
+    assert_eq!((decoded.pool, decoded.user, decoded.mint_in), (pool, user, mint_in));
+    assert_eq!((decoded.amount_in, decoded.amount_out), (1_000_000, 1_994_000));
+    assert_eq!((decoded.fee_bps, decoded.effective_price, decoded.timestamp), (30, 2_000_000_000, NOW));
+}
+
+#[test]
+fn program_version_matches_crate_version() {
+    let manifest = include_str!("../programs/barter-dex-program/Cargo.toml");
+    let crate_version = manifest.lines()
+        .find_map(|line| line.strip_prefix("version = "))
+        .unwrap()
+        .trim_matches('"');
+    assert!(!barter_dex_program::PROGRAM_VERSION.is_empty());
+    assert_eq!(barter_dex_program::PROGRAM_VERSION, crate_version);
+}
+
+#[test]
+fn twap_averages_a_partially_filled_history() {
+    let mut pool = sample_pool();
+    // Nothing recorded yet: the seed prices are ignored in favour of the oracle price.
+    assert_eq!(pool.calculate_twap(24).unwrap(), 1_000_000_000);
+
+    pool.update_price_history(2_000_000_000);
+    pool.update_price_history(4_000_000_000);
+    pool.update_price_history(6_000_000_000);
+    assert_eq!(pool.history_len, 3);
+    assert_eq!(pool.calculate_twap(2).unwrap(), 5_000_000_000);
+    // A window larger than the history averages what there is.
+    assert_eq!(pool.calculate_twap(24).unwrap(), 4_000_000_000);
+
+    let err = pool.calculate_twap(0).unwrap_err();
+    assert_eq!(err, BarterError::InvalidPoolConfiguration.into());
+    let err = pool.calculate_twap(25).unwrap_err();
+    assert_eq!(err, BarterError::InvalidPoolConfiguration.into());
+}
+
+#[test]
+fn twap_over_a_full_and_wrapped_history() {
+    let mut pool = sample_pool();
+    for hour in 1..=24u64 {
+        pool.update_price_history(hour * 1_000_000_000);
+    }
+    assert_eq!(pool.history_index, 0);
+    assert_eq!(pool.history_len, 24);
+    assert_eq!(pool.calculate_twap(24).unwrap(), 12_500_000_000);
+    assert_eq!(pool.calculate_twap(4).unwrap(), 22_500_000_000);
+
+    // Two more prices overwrite the oldest slots; the window walks back across the wrap.
+    pool.update_price_history(100_000_000_000);
+    pool.update_price_history(200_000_000_000);
+    assert_eq!(pool.history_index, 2);
+    assert_eq!(pool.history_len, 24);
+    assert_eq!(pool.calculate_twap(4).unwrap(), (200 + 100 + 24 + 23) * 1_000_000_000 / 4);
+    assert_eq!(pool.calculate_twap(24).unwrap(), (200 + 100 + (3..=24).sum::<u64>()) * 1_000_000_000 / 24);
+
+    // With use_twap set, swaps price against the TWAP instead of the spot price.
+    pool.use_twap = true;
+    pool.twap_window = 2;
+    assert_eq!(pool.calculate_swap_price().unwrap(), 150_000_000_000);
+}
+
+#[test]
+fn oracle_authority_moves_only_when_the_proposed_key_accepts() {
+    let mut pool = sample_pool();
+    let old_authority = pool.oracle_authority;
+    let new_authority = Pubkey::new_unique();
+
+    // Nothing proposed yet.
+    let err = pool.accept_oracle_authority(&new_authority).unwrap_err();
+    assert_eq!(err, BarterError::InvalidOracleAuthority.into());
+
+    pool.pending_oracle_authority = Some(new_authority);
+    let err = pool.accept_oracle_authority(&Pubkey::new_unique()).unwrap_err();
+    assert_eq!(err, BarterError::InvalidOracleAuthority.into());
+    assert_eq!(pool.oracle_authority, old_authority);
+
+    pool.accept_oracle_authority(&new_authority).unwrap();
+    assert_eq!(pool.oracle_authority, new_authority);
+    assert_eq!(pool.pending_oracle_authority, None);
+}
+
+#[test]
+fn swaps_need_the_minimum_number_of_price_sources() {
+    let mut pool = sample_pool();
+    pool.oracle_provider = OracleProvider::Hybrid;
+    pool.min_price_sources = 2;
+
+    // Only the AI price is present.
+    assert_eq!(pool.active_price_sources(NOW), 1);
+    let err = pool.check_price_sources(NOW).unwrap_err();
+    assert_eq!(err, BarterError::InsufficientPriceSources.into());
+
+    // A Pyth price without a confidence doesn't count; with one it does.
+    pool.pyth_price = Some(1_000_000_000);
+    assert!(pool.check_price_sources(NOW).is_err());
+    pool.pyth_confidence = Some(1_000_000);
+    assert_eq!(pool.active_price_sources(NOW), 2);
+    assert!(pool.check_price_sources(NOW).is_ok());
+
+    // A source weighted at zero doesn't count either.
+    pool.pyth_weight = 0;
+    pool.ai_weight = 6_500;
+    assert!(pool.check_price_sources(NOW).is_err());
+}
+
+#[test]
+fn oracle_signers_can_be_added_up_to_the_limit_and_removed() {
+    let mut pool = sample_pool();
+    let keepers = [Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique()];
+    let outsider = Pubkey::new_unique();
+
+    assert!(pool.is_price_pusher(&pool.oracle_authority));
+    for keeper in keepers {
+        assert!(!pool.is_price_pusher(&keeper));
+        pool.add_oracle_signer(keeper).unwrap();
+        assert!(pool.is_price_pusher(&keeper));
+    }
+    assert!(!pool.is_price_pusher(&outsider));
+
+    let err = pool.add_oracle_signer(outsider).unwrap_err();
+    assert_eq!(err, BarterError::OracleSignerSetFull.into());
+    let err = pool.add_oracle_signer(keepers[0]).unwrap_err();
+    assert_eq!(err, BarterError::InvalidOracleSigner.into());
+
+    // Removing a signer keeps the remaining ones authorized.
+    pool.remove_oracle_signer(&keepers[0]).unwrap();
+    assert!(!pool.is_price_pusher(&keepers[0]));
+    assert_eq!(pool.active_oracle_signers(), &[keepers[2], keepers[1]]);
+    let err = pool.remove_oracle_signer(&keepers[0]).unwrap_err();
+    assert_eq!(err, BarterError::InvalidOracleSigner.into());
+}
@@ -0,0 +1,1512 @@
+//! # Factory State Tests
+//!
+//! Unit-level tests for the pure `LaunchState` helpers in the factory program.
+//! These tests exercise the validation and accounting logic directly on the
+//! state structs, without spinning up a program-test bank.
+//!
+//! ## Usage
+//!
+//! Run with:
+//! ```bash
+//! cargo test -p launchpad-tests --test factory_state
+//! ```
+
+use anchor_lang::prelude::Pubkey;
+use factory_program::error::FactoryError;
+use factory_program::state::*;
+
+/// Build a launch with permissive defaults that individual tests can tweak.
+fn sample_launch() -> LaunchState {
+    LaunchState {
+        authority: Pubkey::new_unique(),
+        token_mint: Pubkey::new_unique(),
+        sol_vault_bump: 255,
+        pricing_model: PricingModel::FixedPrice,
+        initial_price: 100_000_000,
+        slope: 0,
+        tokens_sold: 0,
+        floor_price: 0,
+        vesting_enabled: false,
+        vesting_duration_seconds: 0,
+        vesting_cliff_seconds: 0,
+        vesting_accepting_new: true,
+        vesting_milestones: Vec::new(),
+        anti_bot_level: AntiBotLevel::None,
+        min_purchase_amount: 0,
+        max_purchase_amount: u64::MAX,
+        purchase_cooldown_seconds: 0,
+        last_purchase_timestamp: 0,
+        max_tokens_per_wallet: 0,
+        max_tokens_per_tx: 0,
+        anti_snipe_seconds: 0,
+        anti_snipe_max_tokens_per_wallet: 0,
+        anti_snipe_fee_bps: 0,
+        fee_holiday_seconds: 0,
+        fee_holiday_discount_bps: 0,
+        max_tokens: 1_000_000 * 1_000_000_000,
+        launch_start_time: 0,
+        launch_end_time: i64::MAX,
+        affiliate_fee_bps: 0,
+        platform_fee_bps: 0,
+        platform_fee_recipient: Pubkey::new_unique(),
+        max_affiliate_commission_tokens: 0,
+        affiliate_commission_minted: 0,
+        approved_affiliates: vec![],
+        buyer_cashback_bps: 0,
+        whitelist_enabled: false,
+        whitelist_root: None,
+        withdraw_threshold_sol: 0,
+        soft_cap_lamports: 0,
+        hard_cap_lamports: 0,
+        payment_mode: PaymentMode::NativeSol,
+        payment_mint: Pubkey::default(),
+        fee_split_config: Pubkey::default(),
+        finalized: false,
+        paused: false,
+        fair_launch: false,
+        total_sol_collected: 0,
+        total_fees_collected: 0,
+        purchase_count: 0,
+        vesting_unclaimed_tokens: 0,
+        granted_tokens: 0,
+    }
+}
+
+#[test]
+fn affiliate_commission_budget_is_unlimited_when_zero() {
+    let launch = sample_launch();
+    assert!(launch.can_pay_affiliate_commission(u64::MAX));
+}
+
+#[test]
+fn affiliate_commission_budget_exhausts_and_skips_later_commissions() {
+    let mut launch = sample_launch();
+    launch.max_affiliate_commission_tokens = 3_000_000_000; // 3 tokens
+
+    // Two 1-token commissions fit, bringing the total to 2 tokens.
+    for _ in 0..2 {
+        assert!(launch.can_pay_affiliate_commission(1_000_000_000));
+        launch.affiliate_commission_minted += 1_000_000_000;
+    }
+
+    // A 2-token commission would overshoot the remaining 1 token budget.
+    assert!(!launch.can_pay_affiliate_commission(2_000_000_000));
+
+    // Exactly filling the budget is allowed, after which every commission is skipped.
+    assert!(launch.can_pay_affiliate_commission(1_000_000_000));
+    launch.affiliate_commission_minted += 1_000_000_000;
+    assert!(!launch.can_pay_affiliate_commission(1));
+}
+
+#[test]
+fn lifetime_raise_stops_cleanly_at_the_ceiling() {
+    use genesis_common::constants::MAX_LAUNCH_RAISE_LAMPORTS;
+
+    let mut launch = sample_launch();
+    launch.total_sol_collected = MAX_LAUNCH_RAISE_LAMPORTS - 1;
+    launch.record_raise(1).unwrap();
+    assert_eq!(launch.total_sol_collected, MAX_LAUNCH_RAISE_LAMPORTS);
+
+    // Past the ceiling, and even past u64::MAX, the raise is rejected with the descriptive error.
+    let err = launch.record_raise(1).unwrap_err();
+    assert_eq!(err, FactoryError::RaiseCeilingExceeded.into());
+    let err = launch.record_raise(u64::MAX).unwrap_err();
+    assert_eq!(err, FactoryError::RaiseCeilingExceeded.into());
+    assert_eq!(launch.total_sol_collected, MAX_LAUNCH_RAISE_LAMPORTS);
+}
+
+#[test]
+fn purchase_straddling_the_hard_cap_is_rejected() {
+    let mut launch = sample_launch();
+    launch.platform_fee_bps = 200;
+    launch.hard_cap_lamports = 10_000_000_000;
+    launch.total_sol_collected = 9_000_000_000;
+
+    // 1 SOL gross nets 0.98 SOL after the platform fee, which still fits.
+    let fees = launch.calculate_fees(1_000_000_000, false).unwrap();
+    assert!(launch.check_hard_cap(fees.net_sol_amount).is_ok());
+
+    // 1.1 SOL gross nets 1.078 SOL, straddling the cap.
+    let fees = launch.calculate_fees(1_100_000_000, false).unwrap();
+    let err = launch.check_hard_cap(fees.net_sol_amount).unwrap_err();
+    assert_eq!(err, FactoryError::HardCapReached.into());
+
+    // Exactly reaching the cap is allowed; 0 disables it.
+    assert!(launch.check_hard_cap(1_000_000_000).is_ok());
+    launch.hard_cap_lamports = 0;
+    assert!(launch.check_hard_cap(u64::MAX).is_ok());
+
+    // A hard cap below the soft cap could never be met.
+    assert!(LaunchState::validate_caps(5_000_000_000, 0).is_ok());
+    assert!(LaunchState::validate_caps(5_000_000_000, 5_000_000_000).is_ok());
+    assert!(LaunchState::validate_caps(5_000_000_000, 4_000_000_000).is_err());
+}
+
+#[test]
+fn fees_are_split_from_the_purchase() {
+    let mut launch = sample_launch();
+    launch.platform_fee_bps = 200; // 2%
+    launch.affiliate_fee_bps = 500; // 5%
+
+    let fees = launch.calculate_fees(1_000_000_000, true).unwrap();
+    assert_eq!(fees.platform_fee, 20_000_000);
+    assert_eq!(fees.affiliate_fee, 50_000_000);
+    assert_eq!(fees.net_sol_amount, 980_000_000);
+
+    let fees = launch.calculate_fees(1_000_000_000, false).unwrap();
+    assert_eq!(fees.affiliate_fee, 0);
+    assert_eq!(fees.net_sol_amount, 980_000_000);
+}
+
+#[test]
+fn fees_consuming_whole_purchase_are_rejected() {
+    let mut launch = sample_launch();
+    launch.platform_fee_bps = 6_000;
+    launch.affiliate_fee_bps = 4_000;
+
+    let err = launch.calculate_fees(1_000_000_000, true).unwrap_err();
+    assert_eq!(err, FactoryError::FeesExceedPurchase.into());
+
+    // Without the affiliate leg there is still something left for the vault.
+    assert!(launch.calculate_fees(1_000_000_000, false).is_ok());
+}
+
+#[test]
+fn vault_dust_is_swept_above_rent_or_fully_when_closing() {
+    let rent_exempt_minimum = 890_880;
+
+    // Dust sitting above the rent-exempt floor is swept, the floor stays behind.
+    assert_eq!(LaunchState::sweepable_vault_lamports(rent_exempt_minimum + 1_234, rent_exempt_minimum, false), 1_234);
+    // Dust below the floor can't be touched until the launch closes.
+    assert_eq!(LaunchState::sweepable_vault_lamports(5_000, rent_exempt_minimum, false), 0);
+    assert_eq!(LaunchState::sweepable_vault_lamports(5_000, rent_exempt_minimum, true), 5_000);
+}
+
+#[test]
+fn launch_mint_with_circulating_supply_is_rejected() {
+    assert!(LaunchState::validate_fresh_mint(0).is_ok());
+
+    let err = LaunchState::validate_fresh_mint(1).unwrap_err();
+    assert_eq!(err, FactoryError::InvalidAccountState.into());
+}
+
+#[test]
+fn vesting_created_event_round_trips_through_log_data() {
+    use anchor_lang::{AnchorDeserialize, Discriminator, Event};
+    use factory_program::VestingScheduleCreatedEvent;
+
+    let launch_state = Pubkey::new_unique();
+    let beneficiary = Pubkey::new_unique();
+    let event = VestingScheduleCreatedEvent {
+        launch_state,
+        beneficiary,
+        total_amount: 5_000_000_000,
+        start_time: 1_700_000_000,
+        duration: 86_400 * 30,
+        cliff: 86_400 * 7,
+    };
+
+    // Off-chain indexers see the discriminator followed by the borsh-encoded fields.
+    let data = event.data();
+    assert_eq!(&data[..8], VestingScheduleCreatedEvent::DISCRIMINATOR);
+    let decoded = VestingScheduleCreatedEvent::try_from_slice(&data[8..]).unwrap();
+    assert_eq!(decoded.launch_state, launch_state);
+    assert_eq!(decoded.beneficiary, beneficiary);
+    assert_eq!(decoded.total_amount, 5_000_000_000);
+    assert_eq!(decoded.start_time, 1_700_000_000);
+    assert_eq!(decoded.duration, 86_400 * 30);
+    assert_eq!(decoded.cliff, 86_400 * 7);
+}
+
+#[test]
+fn fair_launch_charges_no_fees_and_takes_no_affiliates() {
+    LaunchState::validate_fair_launch_config(PricingModel::FixedPrice, 0, 0, 0).unwrap();
+
+    let mut launch = sample_launch();
+    launch.fair_launch = true;
+    launch.pricing_model = PricingModel::FixedPrice;
+    launch.affiliate_fee_bps = 0;
+    launch.platform_fee_bps = 0;
+
+    // Every lamport of a buy reaches the vault.
+    let fees = launch.calculate_fees(1_000_000_000, false).unwrap();
+    assert_eq!(fees.platform_fee, 0);
+    assert_eq!(fees.affiliate_fee, 0);
+    assert_eq!(fees.net_sol_amount, 1_000_000_000);
+    assert_eq!(launch.calculate_cashback_tokens(1_000_000_000).unwrap(), 0);
+
+    assert!(launch.check_affiliate_allowed(false).is_ok());
+    let err = launch.check_affiliate_allowed(true).unwrap_err();
+    assert_eq!(err, FactoryError::FairLaunchViolation.into());
+}
+
+#[test]
+fn inconsistent_fair_launch_configs_are_rejected() {
+    let invalid = [
+        (PricingModel::LinearBondingCurve, 0, 0, 0),
+        (PricingModel::FixedPrice, 100, 0, 0),
+        (PricingModel::FixedPrice, 0, 100, 0),
+        (PricingModel::FixedPrice, 0, 0, 100),
+    ];
+    for (pricing_model, affiliate_fee_bps, platform_fee_bps, buyer_cashback_bps) in invalid {
+        let err = LaunchState::validate_fair_launch_config(
+            pricing_model, affiliate_fee_bps, platform_fee_bps, buyer_cashback_bps,
+        ).unwrap_err();
+        assert_eq!(err, FactoryError::FairLaunchViolation.into());
+    }
+}
+
+#[test]
+fn frozen_affiliate_fee_is_redirected_to_platform() {
+    let mut launch = sample_launch();
+    launch.platform_fee_bps = 200;
+    launch.affiliate_fee_bps = 500;
+
+    let mut fees = launch.calculate_fees(1_000_000_000, true).unwrap();
+    let redirected = fees.redirect_affiliate_fee_to_platform().unwrap();
+
+    // The buyer pays the same total; the affiliate's share now comes out of the vault's net.
+    assert_eq!(redirected, 50_000_000);
+    assert_eq!(fees.platform_fee, 70_000_000);
+    assert_eq!(fees.affiliate_fee, 0);
+    assert_eq!(fees.net_sol_amount, 930_000_000);
+    assert_eq!(fees.platform_fee + fees.net_sol_amount, 1_000_000_000);
+}
+
+#[test]
+fn affiliate_is_compensated_exactly_once() {
+    let mut launch = sample_launch();
+    launch.platform_fee_bps = 200;
+    launch.affiliate_fee_bps = 500;
+    let sol_amount = 1_000_000_000;
+
+    // Commission tokens: no affiliate SOL is withheld, and every lamport is accounted for.
+    let fees = launch.calculate_fees(sol_amount, true).unwrap();
+    let without_affiliate = launch.calculate_fees(sol_amount, false).unwrap();
+    assert_eq!(fees.net_sol_amount, without_affiliate.net_sol_amount);
+    assert_eq!(fees.platform_fee + fees.net_sol_amount, sol_amount);
+
+    // SOL redirect: the affiliate's share is paid once, to the platform, and still balances.
+    let mut redirected = fees;
+    redirected.redirect_affiliate_fee_to_platform().unwrap();
+    assert_eq!(redirected.platform_fee - fees.platform_fee, fees.affiliate_fee);
+    assert_eq!(fees.net_sol_amount - redirected.net_sol_amount, fees.affiliate_fee);
+    assert_eq!(redirected.platform_fee + redirected.net_sol_amount, sol_amount);
+}
+
+#[test]
+fn buyer_cashback_is_minted_and_counted_against_supply() {
+    let mut launch = sample_launch();
+    assert_eq!(launch.calculate_cashback_tokens(1_000_000_000).unwrap(), 0);
+
+    launch.buyer_cashback_bps = 100; // 1%
+    let cashback = launch.calculate_cashback_tokens(1_000_000_000).unwrap();
+    assert_eq!(cashback, 10_000_000);
+
+    launch.tokens_sold = 5_000_000_000;
+    assert_eq!(launch.supply_after_purchase(1_000_000_000, cashback).unwrap(), 6_010_000_000);
+
+    // The purchase alone fits, but not once its cashback is added.
+    launch.max_tokens = 6_000_000_000;
+    let err = launch.supply_after_purchase(1_000_000_000, cashback).unwrap_err();
+    assert_eq!(err, FactoryError::MaxSupplyReached.into());
+}
+
+#[test]
+fn fee_config_is_validated_as_a_whole() {
+    // Both fees at or under their individual caps and together under the combined cap.
+    assert!(LaunchState::validate_fee_config(1_000, 2_000).is_ok());
+
+    // Each fee is individually valid, but together they exceed the combined cap.
+    let err = LaunchState::validate_fee_config(2_000, 2_000).unwrap_err();
+    assert_eq!(err, FactoryError::InvalidFeeConfig.into());
+
+    // A single fee over its own cap is rejected regardless of the other.
+    assert!(LaunchState::validate_fee_config(2_001, 0).is_err());
+}
+
+#[test]
+fn whitelist_gates_buyers_only_when_enabled() {
+    let mut launch = sample_launch();
+    assert!(launch.check_whitelist(false).is_ok());
+
+    launch.whitelist_enabled = true;
+    assert!(launch.check_whitelist(true).is_ok());
+    let err = launch.check_whitelist(false).unwrap_err();
+    assert_eq!(err, FactoryError::NotWhitelisted.into());
+}
+
+#[test]
+fn whitelist_batches_are_bounded() {
+    assert!(LaunchState::validate_whitelist_batch(1).is_ok());
+    assert!(LaunchState::validate_whitelist_batch(genesis_common::constants::MAX_BATCH_SIZE).is_ok());
+
+    for len in [0, genesis_common::constants::MAX_BATCH_SIZE + 1] {
+        let err = LaunchState::validate_whitelist_batch(len).unwrap_err();
+        assert_eq!(err, FactoryError::InvalidWhitelistBatch.into());
+    }
+}
+
+#[test]
+fn purchase_too_small_for_high_price_has_clear_error() {
+    // 1 lamport buys 0.1 base units at 10 SOL per whole token, which rounds to zero.
+    let err = LaunchState::calculate_purchase_tokens(1, 10_000_000_000).unwrap_err();
+    assert_eq!(err, FactoryError::PurchaseTooSmallForPrice.into());
+
+    assert_eq!(LaunchState::calculate_purchase_tokens(10, 10_000_000_000).unwrap(), 1);
+}
+
+#[test]
+fn pausing_new_vesting_rejects_vested_buys_but_not_claims() {
+    let mut launch = sample_launch();
+    assert!(launch.check_new_vesting(true).is_ok());
+
+    launch.vesting_accepting_new = false;
+    let err = launch.check_new_vesting(true).unwrap_err();
+    assert_eq!(err, FactoryError::VestingClosedToNewSchedules.into());
+    // Non-vested buys are unaffected.
+    assert!(launch.check_new_vesting(false).is_ok());
+
+    // Existing schedules keep vesting and stay claimable.
+    let schedule = VestingSchedule {
+        launch_state: Pubkey::new_unique(),
+        beneficiary: Pubkey::new_unique(),
+        total_amount: 1_000,
+        claimed_amount: 0,
+        carried_vested_amount: 0,
+        start_time: 0,
+        duration_seconds: 100,
+        cliff_seconds: 0,
+        last_claim_time: 0,
+        rent_recipient: Pubkey::default(),
+        kind: VestingKind::Linear,
+        milestones: Vec::new(),
+        revocable: false,
+        revoked: false,
+    };
+    assert_eq!(schedule.calculate_claimable_amount(100).unwrap(), 1_000);
+}
+
+#[test]
+fn vesting_timeline_tracks_cliff_and_completion() {
+    let mut schedule = VestingSchedule {
+        launch_state: Pubkey::new_unique(),
+        beneficiary: Pubkey::new_unique(),
+        total_amount: 9_000,
+        claimed_amount: 0,
+        carried_vested_amount: 0,
+        start_time: 1_000,
+        duration_seconds: 100,
+        cliff_seconds: 10,
+        last_claim_time: 1_000,
+        rent_recipient: Pubkey::default(),
+        kind: VestingKind::Linear,
+        milestones: Vec::new(),
+        revocable: true,
+        revoked: false,
+    };
+
+    // Before the cliff both countdowns run and the post-cliff rate is reported.
+    let timeline = schedule.calculate_timeline(1_004).unwrap();
+    assert_eq!(timeline, VestingTimeline { time_until_cliff: 6, time_until_full: 96, tokens_per_second: 100 });
+
+    // Past the cliff only the full-vesting countdown remains.
+    let timeline = schedule.calculate_timeline(1_050).unwrap();
+    assert_eq!(timeline, VestingTimeline { time_until_cliff: 0, time_until_full: 50, tokens_per_second: 100 });
+
+    // Once complete, nothing is left to release.
+    let timeline = schedule.calculate_timeline(2_000).unwrap();
+    assert_eq!(timeline, VestingTimeline { time_until_cliff: 0, time_until_full: 0, tokens_per_second: 0 });
+
+    // Milestone schedules count down to their first and last steps and have no continuous rate.
+    schedule.kind = VestingKind::Milestones;
+    schedule.milestones = vec![
+        VestingMilestone { offset_seconds: 30, cumulative_bps: 5_000 },
+        VestingMilestone { offset_seconds: 90, cumulative_bps: 10_000 },
+    ];
+    let timeline = schedule.calculate_timeline(1_040).unwrap();
+    assert_eq!(timeline, VestingTimeline { time_until_cliff: 0, time_until_full: 50, tokens_per_second: 0 });
+
+    // A revoked schedule is settled, so its timeline is complete.
+    schedule.revoke(1_040).unwrap();
+    let timeline = schedule.calculate_timeline(1_040).unwrap();
+    assert_eq!(timeline, VestingTimeline { time_until_cliff: 0, time_until_full: 0, tokens_per_second: 0 });
+}
+
+#[test]
+fn final_purchase_is_clamped_to_exactly_max_tokens() {
+    let mut launch = sample_launch();
+    launch.max_tokens = 10_000_000_000;
+    launch.tokens_sold = 9_999_999_990;
+
+    // Ten base units remain; a purchase rounding to a few more is clamped to the remainder.
+    let tokens = launch.clamp_to_remaining_supply(11).unwrap();
+    assert_eq!(tokens, 10);
+    assert_eq!(launch.supply_after_purchase(tokens, 0).unwrap(), launch.max_tokens);
+
+    // Purchases that fit are untouched, and large overshoots are still rejected.
+    assert_eq!(launch.clamp_to_remaining_supply(7).unwrap(), 7);
+    let err = launch.clamp_to_remaining_supply(1_000_000_000).unwrap_err();
+    assert_eq!(err, FactoryError::MaxSupplyReached.into());
+}
+
+#[test]
+fn final_purchase_with_cashback_is_clamped_to_exactly_max_tokens() {
+    let mut launch = sample_launch();
+    launch.max_tokens = 10_000_000_000;
+    launch.tokens_sold = 9_999_998_990;
+    launch.buyer_cashback_bps = 100;
+
+    // 1,010 base units remain: 1,000 tokens plus their 1% cashback fill them exactly.
+    let tokens = launch.clamp_to_remaining_supply(1_005).unwrap();
+    assert_eq!(tokens, 1_000);
+    let cashback = launch.calculate_cashback_tokens(tokens).unwrap();
+    assert_eq!(launch.supply_after_purchase(tokens, cashback).unwrap(), launch.max_tokens);
+
+    // A purchase that fits along with its cashback is untouched.
+    assert_eq!(launch.clamp_to_remaining_supply(999).unwrap(), 999);
+    let err = launch.clamp_to_remaining_supply(1_000_000_000).unwrap_err();
+    assert_eq!(err, FactoryError::MaxSupplyReached.into());
+}
+
+#[test]
+fn vesting_grants_count_against_max_tokens() {
+    let mut launch = sample_launch();
+    launch.max_tokens = 10_000_000_000;
+    launch.tokens_sold = 6_000_000_000;
+
+    launch.record_grant(3_000_000_000).unwrap();
+    assert_eq!(launch.allocated_supply(), 9_000_000_000);
+    // Grants can't overshoot the supply left after sales...
+    let err = launch.record_grant(1_000_000_001).unwrap_err();
+    assert_eq!(err, FactoryError::MaxSupplyReached.into());
+    // ...and purchases can't overshoot what grants left over.
+    let err = launch.supply_after_purchase(1_000_000_001, 0).unwrap_err();
+    assert_eq!(err, FactoryError::MaxSupplyReached.into());
+    assert_eq!(launch.supply_after_purchase(1_000_000_000, 0).unwrap(), 7_000_000_000);
+
+    launch.record_grant(1_000_000_000).unwrap();
+    assert!(launch.is_max_supply_reached());
+}
+
+#[test]
+fn mismatched_affiliate_token_account_is_rejected() {
+    let affiliate = Pubkey::new_unique();
+    let someone_else = Pubkey::new_unique();
+
+    assert!(LaunchState::affiliate_accounts_match(&affiliate, &affiliate, &affiliate));
+    // Commission ATA owned by another wallet.
+    assert!(!LaunchState::affiliate_accounts_match(&affiliate, &affiliate, &someone_else));
+    // Affiliate info registered to another wallet.
+    assert!(!LaunchState::affiliate_accounts_match(&affiliate, &someone_else, &affiliate));
+}
+
+#[test]
+fn withdrawals_wait_for_threshold() {
+    let mut launch = sample_launch();
+    assert!(launch.check_withdraw_threshold().is_ok());
+
+    launch.withdraw_threshold_sol = 10_000_000_000;
+    launch.total_sol_collected = 9_999_999_999;
+    let err = launch.check_withdraw_threshold().unwrap_err();
+    assert_eq!(err, FactoryError::ThresholdNotMet.into());
+
+    launch.total_sol_collected = 10_000_000_000;
+    assert!(launch.check_withdraw_threshold().is_ok());
+}
+
+#[test]
+fn blocked_buyers_are_rejected() {
+    assert!(LaunchState::check_buyer_not_blocked(false).is_ok());
+
+    let err = LaunchState::check_buyer_not_blocked(true).unwrap_err();
+    assert_eq!(err, FactoryError::BuyerBlocked.into());
+}
+
+#[test]
+fn refunds_open_only_after_launch_ends_below_soft_cap() {
+    let mut launch = sample_launch();
+    launch.launch_end_time = 1_000;
+    launch.soft_cap_lamports = 10_000_000_000;
+    launch.total_sol_collected = 4_000_000_000;
+
+    let err = launch.check_refund_available(1_000).unwrap_err();
+    assert_eq!(err, FactoryError::LaunchStillActive.into());
+    assert!(launch.check_refund_available(1_001).is_ok());
+
+    // Under the soft cap the authority can't withdraw.
+    let err = launch.check_soft_cap_met().unwrap_err();
+    assert_eq!(err, FactoryError::SoftCapNotMet.into());
+
+    launch.total_sol_collected = 10_000_000_000;
+    let err = launch.check_refund_available(1_001).unwrap_err();
+    assert_eq!(err, FactoryError::SoftCapReached.into());
+    assert!(launch.check_soft_cap_met().is_ok());
+}
+
+#[test]
+fn vault_sweeps_wait_for_the_soft_cap() {
+    let mut launch = sample_launch();
+    launch.launch_end_time = 1_000;
+    launch.soft_cap_lamports = 10_000_000_000;
+    launch.total_sol_collected = 4_000_000_000;
+
+    // An underfunded launch owes its vault to refunding buyers, running or ended.
+    let err = launch.check_sweep_allowed(500, false).unwrap_err();
+    assert_eq!(err, FactoryError::SoftCapNotMet.into());
+    let err = launch.check_sweep_allowed(1_001, true).unwrap_err();
+    assert_eq!(err, FactoryError::SoftCapNotMet.into());
+
+    launch.total_sol_collected = 10_000_000_000;
+    assert!(launch.check_sweep_allowed(500, false).is_ok());
+    // Closing sweeps still wait for the launch to end.
+    let err = launch.check_sweep_allowed(1_000, true).unwrap_err();
+    assert_eq!(err, FactoryError::OutsideTimeWindow.into());
+    assert!(launch.check_sweep_allowed(1_001, true).is_ok());
+
+    launch.withdraw_threshold_sol = 20_000_000_000;
+    let err = launch.check_sweep_allowed(1_001, true).unwrap_err();
+    assert_eq!(err, FactoryError::ThresholdNotMet.into());
+}
+
+/// A buyer's tracker before their first purchase.
+fn empty_tracker(launch_state: Pubkey, buyer: Pubkey) -> PurchaseTracker {
+    PurchaseTracker {
+        buyer,
+        last_purchase_time: 0,
+        total_purchased: 0,
+        purchase_count: 0,
+        launch_state,
+        total_sol_contributed: 0,
+        tokens_received: 0,
+        tokens_vested: 0,
+        refunded: false,
+    }
+}
+
+#[test]
+fn refund_returns_contribution_and_burns_received_tokens() {
+    let launch_state = Pubkey::new_unique();
+    let buyer = Pubkey::new_unique();
+    let mut tracker = empty_tracker(launch_state, buyer);
+
+    // One liquid purchase and one vested purchase.
+    tracker.record_purchase(launch_state, buyer, 980_000_000, 9_800_000_000, 9_800_000_000, 10).unwrap();
+    tracker.record_purchase(launch_state, buyer, 490_000_000, 4_900_000_000, 0, 20).unwrap();
+    tracker.record_vested(4_900_000_000).unwrap();
+    assert_eq!(tracker.purchase_count, 2);
+    assert_eq!(tracker.total_purchased, 14_700_000_000);
+
+    // A buyer with vested purchases can't skip burning their claimed tokens by leaving out the schedule.
+    let err = tracker.refund_amounts(None).unwrap_err();
+    assert_eq!(err, FactoryError::VestingScheduleNotFound.into());
+
+    // Vested tokens the buyer already claimed are burned alongside the liquid ones.
+    let (lamports, tokens_to_burn) = tracker.refund_amounts(Some(1_000_000_000)).unwrap();
+    assert_eq!(lamports, 1_470_000_000);
+    assert_eq!(tokens_to_burn, 10_800_000_000);
+
+    tracker.refunded = true;
+    let err = tracker.refund_amounts(Some(0)).unwrap_err();
+    assert_eq!(err, FactoryError::AlreadyRefunded.into());
+}
+
+#[test]
+fn refunded_tokens_return_to_the_unsold_supply() {
+    let launch_state = Pubkey::new_unique();
+    let mut launch = sample_launch();
+
+    // Two buyers, one of them vesting part of their purchase and earning cashback.
+    let mut refunded = empty_tracker(launch_state, Pubkey::new_unique());
+    refunded.record_purchase(launch_state, refunded.buyer, 980_000_000, 9_800_000_000, 10_290_000_000, 10).unwrap();
+    refunded.record_purchase(launch_state, refunded.buyer, 490_000_000, 4_900_000_000, 245_000_000, 20).unwrap();
+    refunded.record_vested(4_900_000_000).unwrap();
+    let mut kept = empty_tracker(launch_state, Pubkey::new_unique());
+    kept.record_purchase(launch_state, kept.buyer, 980_000_000, 9_800_000_000, 9_800_000_000, 30).unwrap();
+    launch.tokens_sold = refunded.tokens_allocated().unwrap() + kept.tokens_allocated().unwrap();
+    launch.record_vesting_locked(4_900_000_000).unwrap();
+
+    // The refund voids the whole vesting schedule and hands every token back to the supply.
+    launch.record_vesting_released(4_900_000_000);
+    launch.record_refund(refunded.tokens_allocated().unwrap());
+    assert_eq!(launch.tokens_sold, 9_800_000_000);
+    assert_eq!(launch.vesting_unclaimed_tokens, 0);
+}
+
+#[test]
+fn payment_mode_migrates_only_before_first_purchase() {
+    let mut launch = sample_launch();
+    assert!(launch.check_payment_mode_migratable().is_ok());
+
+    launch.purchase_count = 1;
+    let err = launch.check_payment_mode_migratable().unwrap_err();
+    assert_eq!(err, FactoryError::PaymentModeLocked.into());
+}
+
+#[test]
+fn soft_capped_launches_only_take_native_sol() {
+    let mut launch = sample_launch();
+    assert!(launch.check_payment_mode_supported(PaymentMode::SplToken).is_ok());
+
+    // Refunds below the soft cap are paid in SOL, so the launch can't leave native SOL.
+    launch.soft_cap_lamports = 5_000_000_000;
+    let err = launch.check_payment_mode_supported(PaymentMode::SplToken).unwrap_err();
+    assert_eq!(err, FactoryError::SoftCapRequiresNativeSol.into());
+    assert!(launch.check_payment_mode_supported(PaymentMode::NativeSol).is_ok());
+}
+
+#[test]
+fn live_vesting_schedules_are_topped_up() {
+    let mut schedule = VestingSchedule {
+        launch_state: Pubkey::new_unique(),
+        beneficiary: Pubkey::new_unique(),
+        total_amount: 1_000,
+        claimed_amount: 400,
+        carried_vested_amount: 0,
+        start_time: 1_000,
+        duration_seconds: 400,
+        cliff_seconds: 0,
+        last_claim_time: 1_160,
+        rent_recipient: Pubkey::new_unique(),
+        kind: VestingKind::Linear,
+        milestones: Vec::new(),
+        revocable: false,
+        revoked: false,
+    };
+    assert!(schedule.is_live());
+
+    // A second vested purchase adds to the unclaimed tokens instead of replacing them.
+    let rent_recipient = schedule.rent_recipient;
+    schedule.top_up(500, 1_200).unwrap();
+    assert_eq!(schedule.total_amount, 1_500);
+    assert_eq!(schedule.claimed_amount, 400);
+    assert_eq!(schedule.rent_recipient, rent_recipient);
+
+    // A fully claimed schedule is no longer live and is started afresh instead.
+    schedule.claimed_amount = 1_500;
+    assert!(!schedule.is_live());
+
+    schedule.revoked = true;
+    let err = schedule.top_up(500, 1_200).unwrap_err();
+    assert_eq!(err, FactoryError::VestingRevoked.into());
+
+    // Purchases never vest into a revocable team grant sharing the beneficiary's PDA.
+    assert!(schedule.check_accepts_purchases().is_ok());
+    schedule.revocable = true;
+    let err = schedule.check_accepts_purchases().unwrap_err();
+    assert_eq!(err, FactoryError::VestingGrantExists.into());
+}
+
+#[test]
+fn topped_up_tokens_vest_from_the_top_up() {
+    let mut schedule = VestingSchedule {
+        launch_state: Pubkey::new_unique(),
+        beneficiary: Pubkey::new_unique(),
+        total_amount: 1_000,
+        claimed_amount: 400,
+        carried_vested_amount: 0,
+        start_time: 1_000,
+        duration_seconds: 400,
+        cliff_seconds: 100,
+        last_claim_time: 1_160,
+        rent_recipient: Pubkey::new_unique(),
+        kind: VestingKind::Linear,
+        milestones: Vec::new(),
+        revocable: false,
+        revoked: false,
+    };
+
+    // Long after the schedule fully vested, a second vested purchase is added.
+    schedule.top_up(500, 2_000).unwrap();
+    assert_eq!(schedule.start_time, 2_000);
+
+    // Only the tokens that had already vested are claimable; the new ones wait for the cliff.
+    assert_eq!(schedule.calculate_claimable_amount(2_000).unwrap(), 600);
+    assert_eq!(schedule.calculate_claimable_amount(2_099).unwrap(), 600);
+
+    // The new tokens then vest linearly over the rest of the schedule's duration.
+    assert_eq!(schedule.calculate_vested_amount(2_250).unwrap(), 1_250);
+    assert_eq!(schedule.calculate_vested_amount(2_400).unwrap(), 1_500);
+    let timeline = schedule.calculate_timeline(2_250).unwrap();
+    assert_eq!(timeline, VestingTimeline { time_until_cliff: 0, time_until_full: 150, tokens_per_second: 1 });
+
+    // A top-up midway re-bases what has vested so far without relocking it.
+    let mut schedule = VestingSchedule {
+        total_amount: 1_000,
+        claimed_amount: 0,
+        carried_vested_amount: 0,
+        start_time: 1_000,
+        cliff_seconds: 0,
+        ..schedule
+    };
+    schedule.top_up(1_000, 1_200).unwrap();
+    assert_eq!(schedule.calculate_vested_amount(1_200).unwrap(), 500);
+    assert_eq!(schedule.calculate_vested_amount(1_400).unwrap(), 1_250);
+    assert_eq!(schedule.calculate_vested_amount(1_600).unwrap(), 2_000);
+}
+
+#[test]
+fn vesting_releases_linearly_from_the_cliff() {
+    let mut schedule = VestingSchedule {
+        launch_state: Pubkey::new_unique(),
+        beneficiary: Pubkey::new_unique(),
+        total_amount: 1_000,
+        claimed_amount: 0,
+        carried_vested_amount: 0,
+        start_time: 1_000,
+        duration_seconds: 400,
+        cliff_seconds: 200,
+        last_claim_time: 0,
+        rent_recipient: Pubkey::default(),
+        kind: VestingKind::Linear,
+        milestones: Vec::new(),
+        revocable: false,
+        revoked: false,
+    };
+
+    // Nothing before the cliff, and nothing yet at exactly the cliff.
+    assert_eq!(schedule.calculate_vested_amount(1_199).unwrap(), 0);
+    assert_eq!(schedule.calculate_vested_amount(1_200).unwrap(), 0);
+    // Halfway between the cliff and the end.
+    assert_eq!(schedule.calculate_vested_amount(1_300).unwrap(), 500);
+    // Fully vested at the end and never more afterwards.
+    assert_eq!(schedule.calculate_vested_amount(1_399).unwrap(), 995);
+    assert_eq!(schedule.calculate_vested_amount(1_400).unwrap(), 1_000);
+    assert_eq!(schedule.calculate_vested_amount(i64::MAX).unwrap(), 1_000);
+
+    // A schedule that is all cliff unlocks in full at the cliff.
+    schedule.duration_seconds = schedule.cliff_seconds;
+    assert_eq!(schedule.calculate_vested_amount(1_199).unwrap(), 0);
+    assert_eq!(schedule.calculate_vested_amount(1_200).unwrap(), 1_000);
+}
+
+#[test]
+fn claims_never_exceed_the_schedule_total() {
+    let mut schedule = VestingSchedule {
+        launch_state: Pubkey::new_unique(),
+        beneficiary: Pubkey::new_unique(),
+        total_amount: 1_000,
+        claimed_amount: 0,
+        carried_vested_amount: 0,
+        start_time: 1_000,
+        duration_seconds: 400,
+        cliff_seconds: 200,
+        last_claim_time: 0,
+        rent_recipient: Pubkey::default(),
+        kind: VestingKind::Linear,
+        milestones: Vec::new(),
+        revocable: false,
+        revoked: false,
+    };
+
+    assert_eq!(schedule.record_claim(1_100).unwrap_err(), FactoryError::NoTokensToClaim.into());
+    assert_eq!(schedule.record_claim(1_300).unwrap(), 500);
+    assert_eq!((schedule.claimed_amount, schedule.last_claim_time), (500, 1_300));
+
+    // The final claim takes exactly what remains, and nothing is left to claim after it.
+    assert_eq!(schedule.record_claim(i64::MAX).unwrap(), 500);
+    assert_eq!(schedule.claimed_amount, schedule.total_amount);
+    assert_eq!(schedule.record_claim(i64::MAX).unwrap_err(), FactoryError::NoTokensToClaim.into());
+    assert!(schedule.check_closable().is_ok());
+}
+
+#[test]
+fn negative_cliff_and_zero_duration_are_rejected() {
+    use genesis_common::constants::MIN_VESTING_DURATION_SECONDS;
+
+    assert!(LaunchState::validate_vesting_config(true, MIN_VESTING_DURATION_SECONDS, 0, &[]).is_ok());
+    // Disabled vesting still accepts the zeroed terms launches pass today.
+    assert!(LaunchState::validate_vesting_config(false, 0, 0, &[]).is_ok());
+
+    let invalid = [
+        (true, MIN_VESTING_DURATION_SECONDS, -1),
+        (true, 0, 0),
+        (true, -MIN_VESTING_DURATION_SECONDS, 0),
+        (true, MIN_VESTING_DURATION_SECONDS, MIN_VESTING_DURATION_SECONDS + 1),
+        // Copied into schedules even when vesting is off, so never allowed negative.
+        (false, 0, -1),
+        (false, -1, 0),
+    ];
+    for (enabled, duration, cliff) in invalid {
+        let err = LaunchState::validate_vesting_config(enabled, duration, cliff, &[]).unwrap_err();
+        assert_eq!(err, FactoryError::InvalidVestingParams.into());
+    }
+}
+
+#[test]
+fn vesting_unlocks_in_quarterly_milestones() {
+    const QUARTER: i64 = 7_776_000;
+    let milestones: Vec<VestingMilestone> = (1..=4)
+        .map(|step| VestingMilestone { offset_seconds: step * QUARTER, cumulative_bps: step as u64 * 2_500 })
+        .collect();
+    VestingSchedule::validate_milestones(&milestones).unwrap();
+
+    let mut schedule = VestingSchedule {
+        launch_state: Pubkey::new_unique(),
+        beneficiary: Pubkey::new_unique(),
+        total_amount: 1_000,
+        claimed_amount: 0,
+        carried_vested_amount: 0,
+        start_time: 1_000,
+        duration_seconds: 4 * QUARTER,
+        cliff_seconds: 0,
+        last_claim_time: 0,
+        rent_recipient: Pubkey::default(),
+        kind: VestingKind::Milestones,
+        milestones,
+        revocable: false,
+        revoked: false,
+    };
+
+    // Nothing until the first milestone, then each step holds until the next one.
+    assert_eq!(schedule.calculate_vested_amount(1_000).unwrap(), 0);
+    assert_eq!(schedule.calculate_vested_amount(1_000 + QUARTER - 1).unwrap(), 0);
+    assert_eq!(schedule.calculate_vested_amount(1_000 + QUARTER).unwrap(), 250);
+    assert_eq!(schedule.calculate_vested_amount(1_000 + 2 * QUARTER - 1).unwrap(), 250);
+    assert_eq!(schedule.calculate_vested_amount(1_000 + 2 * QUARTER).unwrap(), 500);
+    assert_eq!(schedule.calculate_vested_amount(1_000 + 3 * QUARTER).unwrap(), 750);
+    assert_eq!(schedule.calculate_vested_amount(1_000 + 4 * QUARTER).unwrap(), 1_000);
+    assert_eq!(schedule.calculate_vested_amount(i64::MAX).unwrap(), 1_000);
+
+    // Claims are measured against the highest milestone reached.
+    schedule.claimed_amount = 250;
+    assert_eq!(schedule.calculate_claimable_amount(1_000 + 2 * QUARTER).unwrap(), 250);
+}
+
+#[test]
+fn revoking_a_grant_pays_out_vested_tokens_once() {
+    let mut schedule = VestingSchedule {
+        launch_state: Pubkey::new_unique(),
+        beneficiary: Pubkey::new_unique(),
+        total_amount: 1_000,
+        claimed_amount: 100,
+        carried_vested_amount: 0,
+        start_time: 0,
+        duration_seconds: 100,
+        cliff_seconds: 0,
+        last_claim_time: 0,
+        rent_recipient: Pubkey::new_unique(),
+        kind: VestingKind::Linear,
+        milestones: Vec::new(),
+        revocable: true,
+        revoked: false,
+    };
+
+    // Halfway through: 400 vested but unclaimed go to the beneficiary, 500 never vest.
+    let revocation = schedule.revoke(50).unwrap();
+    assert_eq!(revocation, VestingRevocation { to_beneficiary: 400, unvested: 500 });
+    assert!(schedule.revoked);
+    assert_eq!(schedule.calculate_claimable_amount(100).unwrap(), 0);
+    assert!(schedule.check_closable().is_ok());
+    assert_eq!(schedule.check_not_revoked().unwrap_err(), FactoryError::VestingRevoked.into());
+
+    // A second revocation, even later, moves nothing.
+    assert_eq!(schedule.revoke(100).unwrap(), VestingRevocation { to_beneficiary: 0, unvested: 0 });
+
+    // Buyer vesting is never revocable.
+    schedule.revocable = false;
+    schedule.revoked = false;
+    assert_eq!(schedule.revoke(50).unwrap_err(), FactoryError::VestingNotRevocable.into());
+}
+
+#[test]
+fn malformed_vesting_milestones_are_rejected() {
+    let milestone = |offset_seconds, cumulative_bps| VestingMilestone { offset_seconds, cumulative_bps };
+    let invalid = [
+        // Empty
+        vec![],
+        // Offsets not strictly increasing
+        vec![milestone(100, 5_000), milestone(100, 10_000)],
+        // Cumulative share decreasing
+        vec![milestone(100, 6_000), milestone(200, 5_000)],
+        // Above 100%
+        vec![milestone(100, 5_000), milestone(200, 10_001)],
+        // Negative offset
+        vec![milestone(-1, 10_000)],
+        // Longer than the bounded maximum
+        (1..=genesis_common::constants::MAX_VESTING_MILESTONES as i64 + 1).map(|step| milestone(step, 100)).collect(),
+    ];
+    for milestones in invalid {
+        let err = VestingSchedule::validate_milestones(&milestones).unwrap_err();
+        assert_eq!(err, FactoryError::InvalidVestingParams.into());
+    }
+
+    // The bounded maximum itself fits.
+    let full: Vec<_> = (1..=genesis_common::constants::MAX_VESTING_MILESTONES as i64).map(|step| milestone(step, 100)).collect();
+    assert!(VestingSchedule::validate_milestones(&full).is_ok());
+}
+
+#[test]
+fn platform_fee_splits_sixty_forty_between_dao_and_operator() {
+    let dao_treasury = Pubkey::new_unique();
+    let operator = Pubkey::new_unique();
+    let recipients = [
+        FeeSplitRecipient { recipient: dao_treasury, share_bps: 6_000 },
+        FeeSplitRecipient { recipient: operator, share_bps: 4_000 },
+    ];
+    let mut config = FeeSplitConfig {
+        launch_state: Pubkey::new_unique(),
+        recipient_count: 0,
+        recipients: Default::default(),
+        bump: 255,
+    };
+    config.set_recipients(&recipients).unwrap();
+    assert_eq!(config.active_recipients(), &recipients);
+
+    assert_eq!(config.split_fee(1_000_000).unwrap(), vec![600_000, 400_000]);
+    // Rounding dust lands on the last recipient so nothing is lost.
+    assert_eq!(config.split_fee(7).unwrap(), vec![4, 3]);
+}
+
+#[test]
+fn fee_split_shares_must_sum_to_whole_fee() {
+    let split = |shares: &[u16]| -> Vec<FeeSplitRecipient> {
+        shares.iter().map(|&share_bps| FeeSplitRecipient { recipient: Pubkey::new_unique(), share_bps }).collect()
+    };
+
+    assert!(FeeSplitConfig::validate_recipients(&split(&[10_000])).is_ok());
+    assert!(FeeSplitConfig::validate_recipients(&split(&[2_500; 4])).is_ok());
+    for bad in [&[][..], &[6_000, 3_000][..], &[6_000, 5_000][..], &[10_000, 0][..], &[2_000; 5][..]] {
+        let err = FeeSplitConfig::validate_recipients(&split(bad)).unwrap_err();
+        assert_eq!(err, FactoryError::InvalidFeeSplit.into());
+    }
+
+    // The same recipient can't appear twice.
+    let recipient = Pubkey::new_unique();
+    let duplicated = [
+        FeeSplitRecipient { recipient, share_bps: 5_000 },
+        FeeSplitRecipient { recipient, share_bps: 5_000 },
+    ];
+    let err = FeeSplitConfig::validate_recipients(&duplicated).unwrap_err();
+    assert_eq!(err, FactoryError::InvalidFeeSplit.into());
+}
+
+#[test]
+fn buy_with_impossible_min_tokens_out_fails_on_slippage() {
+    let launch = sample_launch();
+    let price = launch.calculate_current_price().unwrap();
+    let tokens = LaunchState::calculate_purchase_tokens(1_000_000_000, price).unwrap();
+
+    let err = LaunchState::check_min_tokens_out(tokens, u64::MAX).unwrap_err();
+    assert_eq!(err, FactoryError::SlippageExceeded.into());
+    assert!(LaunchState::check_min_tokens_out(tokens, tokens).is_ok());
+    // Zero opts out of slippage protection.
+    assert!(LaunchState::check_min_tokens_out(tokens, 0).is_ok());
+}
+
+#[test]
+fn fixed_point_pow_matches_hand_computed_values() {
+    use genesis_common::utils::math_utils::pow_fixed;
+
+    assert_eq!(pow_fixed(1_100_000_000, 0).unwrap(), 1_000_000_000);
+    assert_eq!(pow_fixed(1_100_000_000, 1).unwrap(), 1_100_000_000);
+    assert_eq!(pow_fixed(1_100_000_000, 2).unwrap(), 1_210_000_000);
+    assert_eq!(pow_fixed(1_100_000_000, 3).unwrap(), 1_331_000_000);
+    assert_eq!(pow_fixed(1_500_000_000, 4).unwrap(), 5_062_500_000);
+    assert_eq!(pow_fixed(2_000_000_000, 10).unwrap(), 1_024_000_000_000);
+    assert!(pow_fixed(2_000_000_000, 64).is_err());
+}
+
+#[test]
+fn exponential_curve_compounds_per_whole_token_and_is_capped() {
+    let mut launch = sample_launch();
+    launch.pricing_model = PricingModel::ExponentialBondingCurve;
+    launch.initial_price = 100_000_000; // 0.1 SOL
+    launch.slope = 1_100_000_000; // +10% per whole token
+
+    assert_eq!(launch.calculate_current_price().unwrap(), 100_000_000);
+    // Partial tokens don't compound yet.
+    launch.tokens_sold = 999_999_999;
+    assert_eq!(launch.calculate_current_price().unwrap(), 100_000_000);
+    launch.tokens_sold = 2_000_000_000;
+    assert_eq!(launch.calculate_current_price().unwrap(), 121_000_000);
+    launch.tokens_sold = 3_000_000_000;
+    assert_eq!(launch.calculate_current_price().unwrap(), 133_100_000);
+
+    // Runaway growth saturates at the price ceiling instead of overflowing.
+    launch.tokens_sold = 1_000_000 * 1_000_000_000;
+    assert_eq!(launch.calculate_current_price().unwrap(), 1_000_000_000_000_000);
+}
+
+#[test]
+fn dutch_auction_decays_to_floor_price_and_never_below() {
+    let mut launch = sample_launch();
+    launch.pricing_model = PricingModel::DutchAuction;
+    launch.initial_price = 1_000_000_000;
+    launch.floor_price = 400_000_000;
+    launch.slope = 0;
+    launch.launch_start_time = 1_000;
+    launch.launch_end_time = 2_000;
+
+    assert_eq!(launch.calculate_dutch_auction_price(1_000), 1_000_000_000);
+    assert_eq!(launch.calculate_dutch_auction_price(1_250), 750_000_000);
+    assert_eq!(launch.calculate_dutch_auction_price(1_500), 500_000_000);
+    // Later in the window, and after it, the price holds at the floor.
+    assert_eq!(launch.calculate_dutch_auction_price(1_700), 400_000_000);
+    assert_eq!(launch.calculate_dutch_auction_price(5_000), 400_000_000);
+    // Before the start the auction hasn't begun decaying.
+    assert_eq!(launch.calculate_dutch_auction_price(500), 1_000_000_000);
+}
+
+#[test]
+fn curve_cost_matches_summed_per_token_prices() {
+    use genesis_common::utils::math_utils::{calculate_bonding_curve_price, cost_for_tokens, CurveModel};
+
+    // Small linear buys cost exactly the sum of each base unit's spot price, rounded up.
+    let (initial_price, slope, tokens_sold) = (100_000_000, 3, 5_000);
+    for tokens_to_buy in [1u64, 2, 7, 50] {
+        let summed: u128 = (0..tokens_to_buy)
+            .map(|i| calculate_bonding_curve_price(initial_price, slope, tokens_sold + i).unwrap() as u128)
+            .sum();
+        let expected = summed.div_ceil(1_000_000_000) as u64;
+        let cost = cost_for_tokens(initial_price, slope, tokens_sold, tokens_to_buy, CurveModel::Linear).unwrap();
+        assert_eq!(cost, expected);
+    }
+
+    // Three whole tokens on a +10% exponential curve cost 0.1 + 0.11 + 0.121 SOL.
+    let cost = cost_for_tokens(100_000_000, 1_100_000_000, 0, 3_000_000_000, CurveModel::Exponential).unwrap();
+    assert_eq!(cost, 331_000_000);
+    // Starting halfway through a step prices the rest of that step at its own price.
+    let cost = cost_for_tokens(100_000_000, 1_100_000_000, 500_000_000, 1_000_000_000, CurveModel::Exponential).unwrap();
+    assert_eq!(cost, 50_000_000 + 55_000_000);
+
+    // Fixed prices stay a closed form.
+    assert_eq!(cost_for_tokens(100_000_000, 0, 0, 3_000_000_000, CurveModel::Fixed).unwrap(), 300_000_000);
+}
+
+#[test]
+fn large_curve_buys_pay_for_the_price_rise() {
+    use genesis_common::utils::math_utils::{cost_for_tokens, tokens_for_sol, CurveModel};
+
+    let mut launch = sample_launch();
+    launch.pricing_model = PricingModel::ExponentialBondingCurve;
+    launch.initial_price = 100_000_000; // 0.1 SOL
+    launch.slope = 1_100_000_000; // +10% per whole token
+
+    // 0.331 SOL buys exactly three whole tokens along the curve, where the spot price would give 3.31.
+    let price = launch.calculate_current_price().unwrap();
+    assert_eq!(LaunchState::calculate_purchase_tokens(331_000_000, price).unwrap(), 3_310_000_000);
+    assert_eq!(launch.calculate_curve_purchase_tokens(331_000_000, price).unwrap(), 3_000_000_000);
+
+    // On a linear curve the result is the largest buy the SOL covers.
+    let tokens = tokens_for_sol(100_000_000, 1, 0, 1_000_000_000, CurveModel::Linear).unwrap();
+    assert!(cost_for_tokens(100_000_000, 1, 0, tokens, CurveModel::Linear).unwrap() <= 1_000_000_000);
+    assert!(cost_for_tokens(100_000_000, 1, 0, tokens + 1, CurveModel::Linear).unwrap() > 1_000_000_000);
+    assert!(tokens < 10_000_000_000);
+
+    // Fixed-price launches are unchanged.
+    launch.pricing_model = PricingModel::FixedPrice;
+    assert_eq!(launch.calculate_curve_purchase_tokens(331_000_000, price).unwrap(), 3_310_000_000);
+}
+
+#[test]
+fn spl_launch_is_priced_and_charged_in_payment_token_units() {
+    let usdc = Pubkey::new_unique();
+    let mut launch = sample_launch();
+    launch.payment_mode = PaymentMode::SplToken;
+    launch.payment_mint = usdc;
+    launch.initial_price = 2_000_000; // 2 USDC (6 decimals) per whole token
+    launch.platform_fee_bps = 100;
+
+    assert!(launch.check_spl_payment_mint(&usdc).is_ok());
+    let err = launch.check_spl_payment_mint(&Pubkey::new_unique()).unwrap_err();
+    assert_eq!(err, FactoryError::MissingPaymentMint.into());
+
+    // 10 USDC buys 5 whole tokens and pays a 1% platform fee in USDC.
+    let amount_in = 10_000_000;
+    let price = launch.calculate_current_price().unwrap();
+    assert_eq!(LaunchState::calculate_purchase_tokens(amount_in, price).unwrap(), 5_000_000_000);
+    let fees = launch.calculate_fees(amount_in, false).unwrap();
+    assert_eq!(fees.platform_fee, 100_000);
+    assert_eq!(fees.net_sol_amount, 9_900_000);
+
+    // Native-SOL launches don't accept SPL purchases.
+    launch.payment_mode = PaymentMode::NativeSol;
+    let err = launch.check_spl_payment_mint(&usdc).unwrap_err();
+    assert_eq!(err, FactoryError::UnsupportedPaymentMode.into());
+}
+
+#[test]
+fn cooldown_and_wallet_cap_are_enforced_per_buyer() {
+    let mut launch = sample_launch();
+    launch.anti_bot_level = AntiBotLevel::Advanced;
+    launch.purchase_cooldown_seconds = 60;
+    launch.max_tokens_per_wallet = 10_000_000_000;
+
+    // Another buyer's purchase a second ago doesn't hold this buyer back...
+    launch.last_purchase_timestamp = 999;
+    assert!(launch.validate_purchase_amount(1_000_000_000, 0, 1_000).is_ok());
+
+    // ...but this buyer's own purchase does, until the cooldown has passed.
+    let err = launch.validate_purchase_amount(1_000_000_000, 970, 1_000).unwrap_err();
+    assert_eq!(err, FactoryError::PurchaseCooldownActive.into());
+    assert!(launch.validate_purchase_amount(1_000_000_000, 940, 1_000).is_ok());
+
+    // A wallet can buy up to, but not past, the per-wallet cap.
+    assert!(launch.check_wallet_cap(6_000_000_000, 4_000_000_000).is_ok());
+    let err = launch.check_wallet_cap(6_000_000_000, 4_000_000_001).unwrap_err();
+    assert_eq!(err, FactoryError::WalletCapExceeded.into());
+
+    launch.max_tokens_per_wallet = 0;
+    assert!(launch.check_wallet_cap(u64::MAX - 1, 1).is_ok());
+}
+
+#[test]
+fn oversized_single_buys_must_be_split() {
+    let mut launch = sample_launch();
+    launch.max_tokens_per_tx = 5_000_000_000;
+    launch.max_tokens_per_wallet = 10_000_000_000;
+
+    // One 8-token buy is rejected, though the wallet cap would allow it...
+    let err = launch.check_tx_cap(8_000_000_000).unwrap_err();
+    assert_eq!(err, FactoryError::TransactionCapExceeded.into());
+
+    // ...while the same amount split over two transactions goes through.
+    assert!(launch.check_tx_cap(4_000_000_000).is_ok());
+    assert!(launch.check_wallet_cap(0, 4_000_000_000).is_ok());
+    assert!(launch.check_tx_cap(4_000_000_000).is_ok());
+    assert!(launch.check_wallet_cap(4_000_000_000, 4_000_000_000).is_ok());
+
+    launch.max_tokens_per_tx = 0;
+    assert!(launch.check_tx_cap(u64::MAX).is_ok());
+}
+
+#[test]
+fn fee_holiday_reduces_fees_for_early_buyers() {
+    let mut launch = sample_launch();
+    launch.launch_start_time = 1_000;
+    launch.platform_fee_bps = 200;
+    launch.affiliate_fee_bps = 500;
+    launch.fee_holiday_seconds = 3_600;
+    launch.fee_holiday_discount_bps = 5_000; // half off
+
+    // Inside the window platform and affiliate fees are halved; the saving stays with the vault.
+    assert_eq!(launch.fee_holiday_discount_bps(4_600), 5_000);
+    let mut fees = launch.calculate_fees(1_000_000_000, true).unwrap();
+    fees.apply_discount(launch.fee_holiday_discount_bps(1_000)).unwrap();
+    assert_eq!(fees.platform_fee, 10_000_000);
+    assert_eq!(fees.affiliate_fee, 25_000_000);
+    assert_eq!(fees.net_sol_amount, 990_000_000);
+
+    // Before the launch starts and after the window, normal fees apply.
+    for outside in [999, 4_601] {
+        let mut fees = launch.calculate_fees(1_000_000_000, true).unwrap();
+        fees.apply_discount(launch.fee_holiday_discount_bps(outside)).unwrap();
+        assert_eq!(fees.platform_fee, 20_000_000);
+        assert_eq!(fees.affiliate_fee, 50_000_000);
+        assert_eq!(fees.net_sol_amount, 980_000_000);
+    }
+
+    // A full discount waives the fees entirely.
+    launch.fee_holiday_discount_bps = 10_000;
+    let mut fees = launch.calculate_fees(1_000_000_000, true).unwrap();
+    fees.apply_discount(launch.fee_holiday_discount_bps(2_000)).unwrap();
+    assert_eq!((fees.platform_fee, fees.affiliate_fee, fees.net_sol_amount), (0, 0, 1_000_000_000));
+
+    assert!(LaunchState::validate_fee_holiday_config(3_600, 10_000).is_ok());
+    let err = LaunchState::validate_fee_holiday_config(3_600, 10_001).unwrap_err();
+    assert_eq!(err, FactoryError::InvalidFeeConfig.into());
+    let err = LaunchState::validate_fee_holiday_config(-1, 0).unwrap_err();
+    assert_eq!(err, FactoryError::InvalidFeeConfig.into());
+}
+
+#[test]
+fn anti_snipe_window_tightens_cap_and_adds_fee() {
+    let mut launch = sample_launch();
+    launch.launch_start_time = 1_000;
+    launch.max_tokens_per_wallet = 10_000_000_000;
+    launch.platform_fee_bps = 100;
+    launch.anti_snipe_seconds = 30;
+    launch.anti_snipe_max_tokens_per_wallet = 1_000_000_000;
+    launch.anti_snipe_fee_bps = 400;
+
+    // Inside the window a buy past the stricter cap is rejected and pays the surcharge.
+    assert!(launch.in_anti_snipe_window(1_030));
+    let err = launch.check_anti_snipe_wallet_cap(0, 2_000_000_000, 1_010).unwrap_err();
+    assert_eq!(err, FactoryError::WalletCapExceeded.into());
+    assert!(launch.check_anti_snipe_wallet_cap(0, 1_000_000_000, 1_010).is_ok());
+    let mut fees = launch.calculate_fees(1_000_000_000, false).unwrap();
+    fees.add_platform_surcharge(launch.calculate_anti_snipe_fee(1_000_000_000, 1_010).unwrap()).unwrap();
+    assert_eq!(fees.platform_fee, 50_000_000);
+    assert_eq!(fees.net_sol_amount, 950_000_000);
+
+    // Once the window has passed, only the normal cap and fee apply.
+    assert!(!launch.in_anti_snipe_window(1_031));
+    assert!(launch.check_anti_snipe_wallet_cap(0, 2_000_000_000, 1_031).is_ok());
+    assert_eq!(launch.calculate_anti_snipe_fee(1_000_000_000, 1_031).unwrap(), 0);
+
+    // The anti-snipe cap may only be stricter than the normal one.
+    assert!(LaunchState::validate_anti_snipe_config(30, 1_000_000_000, 10_000_000_000).is_ok());
+    let err = LaunchState::validate_anti_snipe_config(30, 20_000_000_000, 10_000_000_000).unwrap_err();
+    assert_eq!(err, FactoryError::InvalidAntiSnipeConfig.into());
+    let err = LaunchState::validate_anti_snipe_config(-1, 0, 0).unwrap_err();
+    assert_eq!(err, FactoryError::InvalidAntiSnipeConfig.into());
+}
+
+#[test]
+fn buyers_are_not_blocked_by_each_others_cooldowns() {
+    let mut launch = sample_launch();
+    launch.anti_bot_level = AntiBotLevel::Advanced;
+    launch.purchase_cooldown_seconds = 60;
+    let launch_state = Pubkey::new_unique();
+    let mut alice = empty_tracker(launch_state, Pubkey::new_unique());
+    let mut bob = empty_tracker(launch_state, Pubkey::new_unique());
+
+    // Alice and Bob each buy once, ten seconds apart.
+    launch.validate_purchase_amount(1_000_000_000, alice.last_purchase_time, 1_000).unwrap();
+    alice.record_purchase(launch_state, alice.buyer, 1_000_000_000, 10_000_000_000, 10_000_000_000, 1_000).unwrap();
+    launch.last_purchase_timestamp = 1_000;
+    launch.validate_purchase_amount(1_000_000_000, bob.last_purchase_time, 1_010).unwrap();
+    bob.record_purchase(launch_state, bob.buyer, 1_000_000_000, 10_000_000_000, 10_000_000_000, 1_010).unwrap();
+    launch.last_purchase_timestamp = 1_010;
+
+    // At t=1_065 Alice's cooldown has passed even though Bob bought more recently...
+    assert!(launch.validate_purchase_amount(1_000_000_000, alice.last_purchase_time, 1_065).is_ok());
+    // ...while Bob is still cooling down from his own purchase.
+    let err = launch.validate_purchase_amount(1_000_000_000, bob.last_purchase_time, 1_065).unwrap_err();
+    assert_eq!(err, FactoryError::PurchaseCooldownActive.into());
+}
+
+#[test]
+fn finalize_requires_an_ended_launch_and_blocks_purchases() {
+    let mut launch = sample_launch();
+    launch.launch_end_time = 1_000;
+
+    let err = launch.check_finalize_allowed(1_000).unwrap_err();
+    assert_eq!(err, FactoryError::LaunchStillActive.into());
+    assert!(launch.check_finalize_allowed(1_001).is_ok());
+    assert!(launch.check_not_finalized().is_ok());
+
+    launch.finalized = true;
+    let err = launch.check_not_finalized().unwrap_err();
+    assert_eq!(err, FactoryError::LaunchFinalized.into());
+    let err = launch.check_finalize_allowed(2_000).unwrap_err();
+    assert_eq!(err, FactoryError::LaunchFinalized.into());
+}
+
+#[test]
+fn closed_vesting_rent_goes_to_beneficiary_or_treasury() {
+    let beneficiary = Pubkey::new_unique();
+    let mut schedule = VestingSchedule {
+        launch_state: Pubkey::new_unique(),
+        beneficiary,
+        total_amount: 1_000,
+        claimed_amount: 400,
+        carried_vested_amount: 0,
+        start_time: 0,
+        duration_seconds: 100,
+        cliff_seconds: 0,
+        last_claim_time: 0,
+        rent_recipient: Pubkey::default(),
+        kind: VestingKind::Linear,
+        milestones: Vec::new(),
+        revocable: false,
+        revoked: false,
+    };
+
+    // Schedules can't be closed while tokens remain unclaimed.
+    let err = schedule.check_closable().unwrap_err();
+    assert_eq!(err, FactoryError::VestingNotComplete.into());
+    schedule.claimed_amount = 1_000;
+    assert!(schedule.check_closable().is_ok());
+
+    // Buyer-funded schedules return rent to the beneficiary...
+    assert_eq!(schedule.rent_destination(), beneficiary);
+
+    // ...while project-funded ones return it to the configured treasury.
+    let treasury = Pubkey::new_unique();
+    schedule.rent_recipient = treasury;
+    assert_eq!(schedule.rent_destination(), treasury);
+}
+
+#[test]
+fn partial_withdrawals_keep_the_vault_rent_exempt() {
+    let rent_exempt_minimum = 890_880;
+    let mut vault_balance = 10_000_000_000;
+
+    // Two partial withdrawals of 3 and 4 SOL leave 3 SOL behind.
+    for amount in [3_000_000_000, 4_000_000_000] {
+        LaunchState::check_partial_withdrawal(vault_balance, amount, rent_exempt_minimum).unwrap();
+        vault_balance -= amount;
+    }
+    assert_eq!(vault_balance, 3_000_000_000);
+
+    // The vault can be drawn down to its rent-exempt minimum, but not past it.
+    assert!(LaunchState::check_partial_withdrawal(vault_balance, vault_balance - rent_exempt_minimum, rent_exempt_minimum).is_ok());
+    let err = LaunchState::check_partial_withdrawal(vault_balance, vault_balance, rent_exempt_minimum).unwrap_err();
+    assert_eq!(err, FactoryError::InsufficientVaultBalance.into());
+    let err = LaunchState::check_partial_withdrawal(vault_balance, vault_balance + 1, rent_exempt_minimum).unwrap_err();
+    assert_eq!(err, FactoryError::InsufficientVaultBalance.into());
+    let err = LaunchState::check_partial_withdrawal(vault_balance, 0, rent_exempt_minimum).unwrap_err();
+    assert_eq!(err, FactoryError::InvalidAmount.into());
+}
+
+#[test]
+fn launch_metadata_fields_are_bounded() {
+    assert!(LaunchMetadata::validate("Genesis Token", "GEN", "https://example.com/gen.json").is_ok());
+    // Fields exactly at their bounds fit, and the URI may be left empty.
+    assert!(LaunchMetadata::validate(&"n".repeat(32), &"S".repeat(10), &"u".repeat(200)).is_ok());
+    assert!(LaunchMetadata::validate("Genesis Token", "GEN", "").is_ok());
+
+    let invalid = [
+        LaunchMetadata::validate(&"n".repeat(33), "GEN", ""),
+        LaunchMetadata::validate("Genesis Token", &"S".repeat(11), ""),
+        LaunchMetadata::validate("Genesis Token", "GEN", &"u".repeat(201)),
+        LaunchMetadata::validate("", "GEN", ""),
+        LaunchMetadata::validate("Genesis Token", "", ""),
+    ];
+    for result in invalid {
+        assert_eq!(result.unwrap_err(), FactoryError::InvalidMetadata.into());
+    }
+}
+
+#[test]
+fn snapshot_root_proves_each_holder_balance() {
+    use genesis_common::utils::merkle_utils::hash_pair;
+
+    let holders: Vec<(Pubkey, u64)> = (1..=4).map(|i| (Pubkey::new_unique(), i * 1_000)).collect();
+    let leaves: Vec<[u8; 32]> = holders.iter().map(|(holder, balance)| Snapshot::leaf(holder, *balance)).collect();
+    let left = hash_pair(&leaves[0], &leaves[1]);
+    let right = hash_pair(&leaves[2], &leaves[3]);
+    let root = hash_pair(&left, &right);
+
+    Snapshot::validate_root(&root).unwrap();
+    let err = Snapshot::validate_root(&[0u8; 32]).unwrap_err();
+    assert_eq!(err, FactoryError::InvalidMerkleRoot.into());
+
+    let snapshot = Snapshot {
+        launch_state: Pubkey::new_unique(),
+        snapshot_id: 1,
+        merkle_root: root,
+        recorded_at: 1_700_000_000,
+        bump: 255,
+    };
+
+    let proofs = [[leaves[1], right], [leaves[0], right], [leaves[3], left], [leaves[2], left]];
+    for ((holder, balance), proof) in holders.iter().zip(proofs.iter()) {
+        snapshot.verify_claim(holder, *balance, proof).unwrap();
+    }
+
+    // A wrong balance, a proof for someone else, or a missing proof all fail.
+    let (holder, balance) = holders[0];
+    let invalid = [
+        snapshot.verify_claim(&holder, balance + 1, &proofs[0]),
+        snapshot.verify_claim(&holder, balance, &proofs[2]),
+        snapshot.verify_claim(&holder, balance, &[]),
+    ];
+    for result in invalid {
+        assert_eq!(result.unwrap_err(), FactoryError::InvalidMerkleProof.into());
+    }
+}
+
+#[test]
+fn merkle_whitelist_admits_only_proven_buyers() {
+    use genesis_common::utils::merkle_utils::hash_pair;
+
+    let allowed: Vec<Pubkey> = (0..3).map(|_| Pubkey::new_unique()).collect();
+    let leaves: Vec<[u8; 32]> = allowed.iter().map(LaunchState::whitelist_leaf).collect();
+    // Odd leaf counts carry the last leaf up unpaired.
+    let left = hash_pair(&leaves[0], &leaves[1]);
+    let root = hash_pair(&left, &leaves[2]);
+
+    let mut launch = sample_launch();
+    // Without a root, no proof is needed.
+    assert!(launch.check_whitelist_proof(&Pubkey::new_unique(), &[], false).is_ok());
+
+    launch.whitelist_root = Some(root);
+    launch.check_whitelist_proof(&allowed[0], &[leaves[1], leaves[2]], false).unwrap();
+    launch.check_whitelist_proof(&allowed[1], &[leaves[0], leaves[2]], false).unwrap();
+    launch.check_whitelist_proof(&allowed[2], &[left], false).unwrap();
+
+    // An outsider can't reuse an allowed buyer's proof, and an allowed buyer needs a valid one.
+    let outsider = Pubkey::new_unique();
+    for (buyer, proof) in [(&outsider, vec![leaves[1], leaves[2]]), (&allowed[0], vec![]), (&allowed[0], vec![left])] {
+        let err = launch.check_whitelist_proof(buyer, &proof, false).unwrap_err();
+        assert_eq!(err, FactoryError::NotWhitelisted.into());
+    }
+
+    // A `WhitelistEntry` admits a buyer without a proof.
+    assert!(launch.check_whitelist_proof(&outsider, &[], true).is_ok());
+
+    assert!(LaunchState::validate_whitelist_root(&Some(root)).is_ok());
+    assert!(LaunchState::validate_whitelist_root(&None).is_ok());
+    let err = LaunchState::validate_whitelist_root(&Some([0u8; 32])).unwrap_err();
+    assert_eq!(err, FactoryError::InvalidMerkleRoot.into());
+}
+
+#[test]
+fn program_version_matches_crate_version() {
+    let manifest = include_str!("../programs/factory-program/Cargo.toml");
+    let crate_version = manifest.lines()
+        .find_map(|line| line.strip_prefix("version = "))
+        .unwrap()
+        .trim_matches('"');
+    assert!(!factory_program::PROGRAM_VERSION.is_empty());
+    assert_eq!(factory_program::PROGRAM_VERSION, crate_version);
+}
+
+#[test]
+fn launch_closes_only_once_settled() {
+    let mut launch = sample_launch();
+    let err = launch.check_settled(0).unwrap_err();
+    assert_eq!(err, FactoryError::LaunchNotSettled.into());
+
+    launch.finalized = true;
+    launch.record_vesting_locked(1_000).unwrap();
+    let err = launch.check_settled(0).unwrap_err();
+    assert_eq!(err, FactoryError::LaunchNotSettled.into());
+
+    launch.record_vesting_released(1_000);
+    let err = launch.check_settled(1).unwrap_err();
+    assert_eq!(err, FactoryError::LaunchNotSettled.into());
+
+    launch.check_settled(0).unwrap();
+}
+
+#[test]
+fn affiliate_allowlist_approves_only_listed_affiliates() {
+    let mut launch = sample_launch();
+    let approved = Pubkey::new_unique();
+    let outsider = Pubkey::new_unique();
+
+    // Without an allowlist every affiliate may earn.
+    launch.check_affiliate_approved(&outsider).unwrap();
+
+    launch.approved_affiliates = vec![approved];
+    launch.check_affiliate_approved(&approved).unwrap();
+    let err = launch.check_affiliate_approved(&outsider).unwrap_err();
+    assert_eq!(err, FactoryError::AffiliateNotApproved.into());
+
+    // Allowlists are bounded and may not repeat an affiliate.
+    let full: Vec<Pubkey> = (0..genesis_common::constants::MAX_APPROVED_AFFILIATES).map(|_| Pubkey::new_unique()).collect();
+    LaunchState::validate_approved_affiliates(&full).unwrap();
+    LaunchState::validate_approved_affiliates(&[]).unwrap();
+    let mut too_many = full.clone();
+    too_many.push(Pubkey::new_unique());
+    for invalid in [too_many, vec![approved, outsider, approved]] {
+        let err = LaunchState::validate_approved_affiliates(&invalid).unwrap_err();
+        assert_eq!(err, FactoryError::InvalidAffiliateAllowlist.into());
+    }
+}
+
+#[test]
+fn only_allowlisted_payment_mints_are_accepted() {
+    let usdc = Pubkey::new_unique();
+    let usdt = Pubkey::new_unique();
+    let config = PlatformConfig {
+        admin: Pubkey::new_unique(),
+        allowed_payment_mints: vec![usdc, usdt],
+        bump: 255,
+    };
+
+    assert!(config.check_payment_mint_allowed(&usdc).is_ok());
+    assert!(config.check_payment_mint_allowed(&usdt).is_ok());
+    let err = config.check_payment_mint_allowed(&Pubkey::new_unique()).unwrap_err();
+    assert_eq!(err, FactoryError::PaymentMintNotAllowed.into());
+
+    assert!(PlatformConfig::validate_payment_mints(&[usdc, usdt]).is_ok());
+    let err = PlatformConfig::validate_payment_mints(&[usdc, usdc]).unwrap_err();
+    assert_eq!(err, FactoryError::InvalidPaymentMintAllowlist.into());
+    let err = PlatformConfig::validate_payment_mints(&[Pubkey::default()]).unwrap_err();
+    assert_eq!(err, FactoryError::InvalidPaymentMintAllowlist.into());
+}
@@ -12,6 +12,9 @@
 //! 3. **Affiliate Registration**: Register affiliates with commission structures
 //! 4. **Token Purchase Flow**: Complete purchase with affiliate referral commissions
 //! 5. **Balance Verification**: Validate token distributions and SOL transfers
+//! 6. **Event Decoding**: Launch and purchase events decoded from transaction logs
+//! 7. **Standalone Factory**: Launches and buys with the affiliate program absent
+//! 8. **Oracle DEX**: Pool creation, liquidity, price pushes and swaps in both directions
 //!
 //! ## Key Test Scenarios
 //!
@@ -32,13 +35,14 @@
 //!
 //! Run integration tests with:
 //! ```bash
-//! cargo test-bpf -- --nocapture
+//! cargo test -p launchpad-tests --features test-bpf --test integration -- --nocapture
 //! ```
 
 #![cfg(feature = "test-bpf")]
 
-use anchor_lang::{prelude::*, InstructionData, ToAccountMetas};
+use anchor_lang::{prelude::*, solana_program::program_pack::Pack, InstructionData, ToAccountMetas};
 use anchor_spl::token::spl_token;
+use genesis_common::constants::{DEFAULT_AI_WEIGHT, DEFAULT_PYTH_WEIGHT, DEFAULT_SWITCHBOARD_WEIGHT};
 use solana_program_test::*;
 use solana_sdk::{
     instruction::Instruction,
@@ -76,71 +80,213 @@ async fn airdrop(context: &mut ProgramTestContext, receiver: &Pubkey, amount: u6
     context.banks_client.process_transaction(tx).await.unwrap();
 }
 
-#[tokio::test]
-async fn test_full_flow_with_affiliate() {
-    // --- SETUP: Initialize test environment and actors ---
-    // Initialize the Solana program test environment with both the factory and affiliate programs.
+/// Helper function to decode every `T` event emitted in a transaction's logs.
+fn decode_events<T: anchor_lang::Event>(log_messages: &[String]) -> Vec<T> {
+    use anchor_lang::__private::base64::{engine::general_purpose::STANDARD, Engine};
+    log_messages
+        .iter()
+        .filter_map(|log| log.strip_prefix("Program data: "))
+        .filter_map(|data| STANDARD.decode(data).ok())
+        .filter(|bytes| bytes.starts_with(T::DISCRIMINATOR))
+        .map(|bytes| T::try_from_slice(&bytes[T::DISCRIMINATOR.len()..]).unwrap())
+        .collect()
+}
+
+/// Start a bank running the factory program, plus the affiliate program when
+/// `with_affiliate_program` is set.
+async fn start_programs(with_affiliate_program: bool) -> ProgramTestContext {
     let mut pt = ProgramTest::new(
         "factory_program",
         factory_program::id(),
         processor!(factory_program::entry),
     );
-    pt.add_program(
-        "affiliate_program",
-        affiliate_program::id(),
-        processor!(affiliate_program::entry),
-    );
-    let mut context = pt.start_with_context().await;
+    if with_affiliate_program {
+        pt.add_program(
+            "affiliate_program",
+            affiliate_program::id(),
+            processor!(affiliate_program::entry),
+        );
+    }
+    pt.start_with_context().await
+}
+
+/// Addresses of a launch created by `create_launch`, owned by the bank's payer.
+struct TestLaunch {
+    authority: Pubkey,
+    token_mint: Pubkey,
+    launch_state: Pubkey,
+    sol_vault: Pubkey,
+    /// Set when the launch was created with the affiliate program available
+    ecosystem_stats: Option<Pubkey>,
+    /// The clock the launch was created at; it opens immediately
+    clock: Clock,
+    launch_end_time: i64,
+}
+
+impl TestLaunch {
+    /// A per-buyer factory PDA of this launch, e.g. its `purchase_tracker` or `vesting_schedule`
+    fn buyer_pda(&self, seed: &[u8], buyer: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(&[seed, self.launch_state.as_ref(), buyer.as_ref()], &factory_program::id()).0
+    }
+
+    /// The associated token account of `owner` for the launch's token
+    fn ata(&self, owner: &Pubkey) -> Pubkey {
+        anchor_spl::associated_token::get_associated_token_address(owner, &self.token_mint)
+    }
+
+    /// `BuyTokens` accounts for `buyer` purchasing for `beneficiary`, without affiliate, fee split
+    /// or whitelist accounts; override fields with struct update syntax as needed
+    fn buy_accounts(&self, buyer: &Pubkey, beneficiary: &Pubkey) -> factory_program::accounts::BuyTokens {
+        let vesting_schedule = self.buyer_pda(b"vesting_schedule", beneficiary);
+        factory_program::accounts::BuyTokens {
+            launch_state: self.launch_state,
+            token_mint: self.token_mint,
+            sol_vault: self.sol_vault,
+            buyer_token_account: self.ata(beneficiary),
+            vesting_schedule,
+            vesting_token_account: self.ata(&vesting_schedule),
+            purchase_tracker: self.buyer_pda(b"purchase_tracker", beneficiary),
+            buyer: *buyer,
+            beneficiary: *beneficiary,
+            platform_fee_recipient: self.authority,
+            blocked_buyer: self.buyer_pda(b"blocked_buyer", beneficiary),
+            fee_split_config: None,
+            whitelist_entry: None,
+            affiliate: None,
+            affiliate_info: None,
+            affiliate_token_account: None,
+            ecosystem_stats: self.ecosystem_stats,
+            affiliate_program: self.ecosystem_stats.map(|_| affiliate_program::id()),
+            system_program: system_program::id(),
+            token_program: spl_token::id(),
+            associated_token_program: anchor_spl::associated_token::ID,
+            rent: sysvar::rent::id(),
+        }
+    }
+}
+
+/// Arguments for a fee-free, fixed-price launch of 1M tokens at 0.1 SOL each, open for 1_000
+/// seconds from `start_time`, without vesting, anti-bot limits, caps or a whitelist.
+fn default_launch_args(authority: Pubkey, start_time: i64) -> factory_program::CreateLaunchArgs {
+    factory_program::CreateLaunchArgs {
+        initial_price: 100_000_000,
+        slope: 0,
+        pricing_model: factory_program::state::PricingModel::FixedPrice,
+        floor_price: 0,
+        max_tokens: 1_000_000 * 1_000_000_000,
+        launch_start_time: start_time,
+        launch_end_time: start_time + 1_000,
+        vesting_enabled: false,
+        vesting_duration_seconds: 0,
+        vesting_cliff_seconds: 0,
+        vesting_milestones: vec![],
+        anti_bot_level: factory_program::state::AntiBotLevel::None,
+        min_purchase_amount: 0,
+        max_purchase_amount: u64::MAX,
+        purchase_cooldown_seconds: 0,
+        max_tokens_per_wallet: 0,
+        max_tokens_per_tx: 0,
+        anti_snipe_seconds: 0,
+        anti_snipe_max_tokens_per_wallet: 0,
+        anti_snipe_fee_bps: 0,
+        fee_holiday_seconds: 0,
+        fee_holiday_discount_bps: 0,
+        affiliate_fee_bps: 0,
+        platform_fee_bps: 0,
+        platform_fee_recipient: authority,
+        max_affiliate_commission_tokens: 0,
+        buyer_cashback_bps: 0,
+        whitelist_enabled: false,
+        whitelist_root: None,
+        withdraw_threshold_sol: 0,
+        soft_cap_lamports: 0,
+        hard_cap_lamports: 0,
+        fair_launch: false,
+    }
+}
 
-    // Define actors: the project authority, an affiliate, and a buyer.
+/// Create a launch of a new mint owned by the bank's payer, from `default_launch_args` as
+/// adjusted by `args_override`. The ecosystem stats are passed when `with_affiliate_program` is set.
+async fn create_launch(
+    context: &mut ProgramTestContext,
+    with_affiliate_program: bool,
+    args_override: impl FnOnce(&mut factory_program::CreateLaunchArgs),
+) -> TestLaunch {
     let authority = context.payer.pubkey();
     let token_mint_kp = Keypair::new();
-    let affiliate = Keypair::new();
-    let buyer = Keypair::new();
-    // Airdrop SOL to the affiliate and buyer to pay for transactions and the token purchase.
-    airdrop(&mut context, &affiliate.pubkey(), 1_000_000_000).await;
-    airdrop(&mut context, &buyer.pubkey(), 2_000_000_000).await;
+    let token_mint = token_mint_kp.pubkey();
+    let mut launch = TestLaunch {
+        authority,
+        token_mint,
+        launch_state: Pubkey::find_program_address(
+            &[b"launch_state", authority.as_ref(), token_mint.as_ref()],
+            &factory_program::id(),
+        ).0,
+        sol_vault: Pubkey::find_program_address(
+            &[b"sol_vault", authority.as_ref(), token_mint.as_ref()],
+            &factory_program::id(),
+        ).0,
+        ecosystem_stats: with_affiliate_program
+            .then(|| Pubkey::find_program_address(&[b"ecosystem_stats"], &affiliate_program::id()).0),
+        clock: context.banks_client.get_sysvar().await.unwrap(),
+        launch_end_time: 0,
+    };
 
-    // --- SETUP: Calculate all necessary PDA addresses ---
-    let (launch_state_pda, _) = Pubkey::find_program_address(
-        &[b"launch_state", authority.as_ref(), token_mint_kp.pubkey().as_ref()],
-        &factory_program::id(),
-    );
-    let (sol_vault_pda, _) = Pubkey::find_program_address(
-        &[b"sol_vault", authority.as_ref(), token_mint_kp.pubkey().as_ref()],
-        &factory_program::id(),
-    );
-    let (affiliate_info_pda, _) = Pubkey::find_program_address(
-        &[b"affiliate_info", affiliate.pubkey().as_ref()],
-        &affiliate_program::id(),
-    );
-    
-    // --- GIVEN: A registered affiliate and a live ICO ---
-    // Step 1: Create the ICO Launch.
-    // The initial price is 0.1 SOL (100,000,000 lamports) per token, with a small slope.
+    let mut args = default_launch_args(authority, launch.clock.unix_timestamp);
+    args_override(&mut args);
+    launch.launch_end_time = args.launch_end_time;
     let create_launch_ix = Instruction {
         program_id: factory_program::id(),
         accounts: factory_program::accounts::CreateLaunch {
-            launch_state: launch_state_pda,
-            token_mint: token_mint_kp.pubkey(),
-            sol_vault: sol_vault_pda,
+            launch_state: launch.launch_state,
+            token_mint,
+            sol_vault: launch.sol_vault,
+            ecosystem_stats: launch.ecosystem_stats,
             authority,
+            affiliate_program: with_affiliate_program.then(affiliate_program::id),
             system_program: system_program::id(),
             token_program: spl_token::id(),
             rent: sysvar::rent::id(),
         }.to_account_metas(None),
-        data: factory_program::instruction::CreateLaunch {
-            initial_price: 100_000_000,
-            slope: 10_000_000,
-        }.data(),
+        data: factory_program::instruction::CreateLaunch { args }.data(),
     };
     let tx = Transaction::new_signed_with_payer(
         &[create_launch_ix],
-        Some(&context.payer.pubkey()),
+        Some(&authority),
         &[&context.payer, &token_mint_kp],
         context.last_blockhash,
     );
     context.banks_client.process_transaction(tx).await.unwrap();
+    launch
+}
+
+/// Start the programs and create a single launch; see `start_programs` and `create_launch`.
+async fn setup_launch(
+    with_affiliate_program: bool,
+    args_override: impl FnOnce(&mut factory_program::CreateLaunchArgs),
+) -> (ProgramTestContext, TestLaunch) {
+    let mut context = start_programs(with_affiliate_program).await;
+    let launch = create_launch(&mut context, with_affiliate_program, args_override).await;
+    (context, launch)
+}
+
+#[tokio::test]
+async fn test_full_flow_with_affiliate() {
+    // --- GIVEN: A registered affiliate and a live ICO ---
+    // Step 1: Create the ICO Launch.
+    // The fixed price is 0.1 SOL (100,000,000 lamports) per token.
+    let (mut context, launch) = setup_launch(true, |_| {}).await;
+
+    // Define actors: an affiliate and a buyer.
+    let affiliate = Keypair::new();
+    let buyer = Keypair::new();
+    // Airdrop SOL to the affiliate and buyer to pay for transactions and the token purchase.
+    airdrop(&mut context, &affiliate.pubkey(), 1_000_000_000).await;
+    airdrop(&mut context, &buyer.pubkey(), 2_000_000_000).await;
+    let (affiliate_info_pda, _) = Pubkey::find_program_address(
+        &[b"affiliate_info", affiliate.pubkey().as_ref()],
+        &affiliate_program::id(),
+    );
 
     // Step 2: Register the Affiliate.
     // The affiliate is registered with a default 10% commission.
@@ -148,10 +294,20 @@ async fn test_full_flow_with_affiliate() {
         program_id: affiliate_program::id(),
         accounts: affiliate_program::accounts::RegisterAffiliate {
             affiliate_info: affiliate_info_pda,
+            ecosystem_stats: launch.ecosystem_stats.unwrap(),
+            parent_affiliate_info: None,
             affiliate: affiliate.pubkey(),
             system_program: system_program::id(),
         }.to_account_metas(None),
-        data: affiliate_program::instruction::RegisterAffiliate {}.data(),
+        data: affiliate_program::instruction::RegisterAffiliate {
+            args: affiliate_program::RegisterAffiliateArgs {
+                parent_affiliate: None,
+                referral_level: 1,
+                rate_caps_enabled: false,
+                max_commission_rate_bps: 2000,
+                min_commission_rate_bps: 50,
+            },
+        }.data(),
     };
     let tx = Transaction::new_signed_with_payer(
         &[register_ix],
@@ -164,33 +320,35 @@ async fn test_full_flow_with_affiliate() {
     // --- WHEN: A buyer purchases tokens using the affiliate's referral ---
     let sol_to_spend = 1_000_000_000; // 1 SOL.
     // Calculate associated token account addresses.
-    let buyer_ata = anchor_spl::associated_token::get_associated_token_address(&buyer.pubkey(), &token_mint_kp.pubkey());
-    let affiliate_ata = anchor_spl::associated_token::get_associated_token_address(&affiliate.pubkey(), &token_mint_kp.pubkey());
-    
+    let buyer_ata = launch.ata(&buyer.pubkey());
+    let affiliate_ata = launch.ata(&affiliate.pubkey());
+    // The affiliate's commission account must exist before `buy_tokens` credits it.
+    let create_affiliate_ata_ix = anchor_spl::associated_token::spl_associated_token_account::instruction::create_associated_token_account_idempotent(
+        &buyer.pubkey(),
+        &affiliate.pubkey(),
+        &launch.token_mint,
+        &spl_token::id(),
+    );
+
     let buy_ix = Instruction {
         program_id: factory_program::id(),
         accounts: factory_program::accounts::BuyTokens {
-            launch_state: launch_state_pda,
-            token_mint: token_mint_kp.pubkey(),
-            sol_vault: sol_vault_pda,
-            buyer_token_account: buyer_ata,
-            buyer: buyer.pubkey(),
-            affiliate: affiliate.pubkey(),
-            affiliate_info: affiliate_info_pda,
-            affiliate_token_account: affiliate_ata,
-            affiliate_program: affiliate_program::id(),
-            system_program: system_program::id(),
-            token_program: spl_token::id(),
-            associated_token_program: anchor_spl::associated_token::ID,
-            rent: sysvar::rent::id(),
+            affiliate: Some(affiliate.pubkey()),
+            affiliate_info: Some(affiliate_info_pda),
+            affiliate_token_account: Some(affiliate_ata),
+            ..launch.buy_accounts(&buyer.pubkey(), &buyer.pubkey())
         }.to_account_metas(None),
         data: factory_program::instruction::BuyTokens {
             sol_amount: sol_to_spend,
             affiliate_key: Some(affiliate.pubkey()),
+            enable_vesting: false,
+            min_tokens_out: 0,
+            whitelist_proof: vec![],
+            recipient: None,
         }.data(),
     };
     let tx = Transaction::new_signed_with_payer(
-        &[buy_ix],
+        &[create_affiliate_ata_ix, buy_ix],
         Some(&buyer.pubkey()),
         &[&buyer],
         context.last_blockhash,
@@ -209,6 +367,2649 @@ async fn test_full_flow_with_affiliate() {
     assert_eq!(affiliate_token_account.amount, 1_000_000_000 * 1, "Affiliate should receive 1 token commission");
 
     // ASSERTION 3: The SOL vault has received the payment.
-    let vault_balance = context.banks_client.get_balance(sol_vault_pda).await.unwrap();
+    let vault_balance = context.banks_client.get_balance(launch.sol_vault).await.unwrap();
     assert_eq!(vault_balance, sol_to_spend, "SOL vault should contain the 1 SOL spent by the buyer");
-}
\ No newline at end of file
+}
+#[tokio::test]
+async fn test_refund_when_soft_cap_missed() {
+    // --- GIVEN: A fee-free launch with a 5 SOL soft cap, running for 100 seconds ---
+    let (mut context, launch) = setup_launch(true, |args| {
+        args.launch_end_time = args.launch_start_time + 100;
+        args.soft_cap_lamports = 5_000_000_000;
+    }).await;
+    let affiliate = Keypair::new();
+    let buyer = Keypair::new();
+    airdrop(&mut context, &affiliate.pubkey(), 1_000_000_000).await;
+    airdrop(&mut context, &buyer.pubkey(), 2_000_000_000).await;
+    let (affiliate_info_pda, _) = Pubkey::find_program_address(
+        &[b"affiliate_info", affiliate.pubkey().as_ref()],
+        &affiliate_program::id(),
+    );
+    let ecosystem_stats_pda = launch.ecosystem_stats.unwrap();
+
+    // This test passes the optional affiliate accounts to `buy_tokens`, so one must be registered.
+    let register_ix = Instruction {
+        program_id: affiliate_program::id(),
+        accounts: affiliate_program::accounts::RegisterAffiliate {
+            affiliate_info: affiliate_info_pda,
+            ecosystem_stats: ecosystem_stats_pda,
+            parent_affiliate_info: None,
+            affiliate: affiliate.pubkey(),
+            system_program: system_program::id(),
+        }.to_account_metas(None),
+        data: affiliate_program::instruction::RegisterAffiliate {
+            args: affiliate_program::RegisterAffiliateArgs {
+                parent_affiliate: None,
+                referral_level: 1,
+                rate_caps_enabled: false,
+                max_commission_rate_bps: 2000,
+                min_commission_rate_bps: 50,
+            },
+        }.data(),
+    };
+    let tx = Transaction::new_signed_with_payer(
+        &[register_ix],
+        Some(&affiliate.pubkey()),
+        &[&affiliate],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(tx).await.unwrap();
+
+    // --- WHEN: A single 1 SOL purchase leaves the launch under its soft cap ---
+    let sol_to_spend = 1_000_000_000;
+    let buyer_ata = launch.ata(&buyer.pubkey());
+    let buy_ix = Instruction {
+        program_id: factory_program::id(),
+        accounts: factory_program::accounts::BuyTokens {
+            affiliate: Some(affiliate.pubkey()),
+            affiliate_info: Some(affiliate_info_pda),
+            affiliate_token_account: Some(launch.ata(&affiliate.pubkey())),
+            ..launch.buy_accounts(&buyer.pubkey(), &buyer.pubkey())
+        }.to_account_metas(None),
+        data: factory_program::instruction::BuyTokens {
+            sol_amount: sol_to_spend,
+            affiliate_key: None,
+            enable_vesting: false,
+            min_tokens_out: 0,
+            whitelist_proof: vec![],
+            recipient: None,
+        }.data(),
+    };
+    let tx = Transaction::new_signed_with_payer(
+        &[buy_ix],
+        Some(&buyer.pubkey()),
+        &[&buyer],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(tx).await.unwrap();
+    assert_eq!(context.banks_client.get_balance(launch.sol_vault).await.unwrap(), sol_to_spend);
+
+    // The ecosystem-wide stats saw the launch, the affiliate and the SOL raised.
+    let stats_account = context.banks_client.get_account(ecosystem_stats_pda).await.unwrap().unwrap();
+    let stats = affiliate_program::state::EcosystemStats::try_deserialize(&mut stats_account.data.as_slice()).unwrap();
+    assert_eq!(stats.total_launches, 1);
+    assert_eq!(stats.total_affiliates, 1);
+    assert_eq!(stats.total_sol_raised, sol_to_spend);
+
+    // ...and the launch ends.
+    context.set_sysvar(&Clock { unix_timestamp: launch.launch_end_time + 1, ..launch.clock.clone() });
+
+    // The authority can't sweep the underfunded vault out from under the refund.
+    let sweep_ix = Instruction {
+        program_id: factory_program::id(),
+        accounts: factory_program::accounts::SweepVaultDust {
+            launch_state: launch.launch_state,
+            sol_vault: launch.sol_vault,
+            authority: launch.authority,
+            system_program: system_program::id(),
+        }.to_account_metas(None),
+        data: factory_program::instruction::SweepVaultDust { closing: true }.data(),
+    };
+    let tx = Transaction::new_signed_with_payer(
+        &[sweep_ix],
+        Some(&context.payer.pubkey()),
+        &[&context.payer],
+        context.last_blockhash,
+    );
+    assert!(context.banks_client.process_transaction(tx).await.is_err(), "Sweeping an underfunded launch should fail");
+    assert_eq!(context.banks_client.get_balance(launch.sol_vault).await.unwrap(), sol_to_spend);
+
+    let refund_ix = Instruction {
+        program_id: factory_program::id(),
+        accounts: factory_program::accounts::Refund {
+            launch_state: launch.launch_state,
+            token_mint: launch.token_mint,
+            sol_vault: launch.sol_vault,
+            purchase_tracker: launch.buyer_pda(b"purchase_tracker", &buyer.pubkey()),
+            vesting_schedule: None,
+            vesting_token_account: None,
+            buyer_token_account: buyer_ata,
+            buyer: buyer.pubkey(),
+            token_program: spl_token::id(),
+            system_program: system_program::id(),
+        }.to_account_metas(None),
+        data: factory_program::instruction::Refund {}.data(),
+    };
+    // The test payer covers the transaction fee so the buyer's balance change is exactly the refund.
+    let buyer_balance_before = context.banks_client.get_balance(buyer.pubkey()).await.unwrap();
+    let tx = Transaction::new_signed_with_payer(
+        &[refund_ix],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &buyer],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(tx).await.unwrap();
+
+    // --- THEN: The buyer gets their full SOL back and their tokens are burned ---
+    let buyer_balance_after = context.banks_client.get_balance(buyer.pubkey()).await.unwrap();
+    assert_eq!(buyer_balance_after - buyer_balance_before, sol_to_spend, "Buyer should be refunded the full 1 SOL");
+    assert_eq!(context.banks_client.get_balance(launch.sol_vault).await.unwrap(), 0, "SOL vault should be empty");
+    let buyer_token_account = get_token_account(&mut context, &buyer_ata).await;
+    assert_eq!(buyer_token_account.amount, 0, "Buyer's tokens should be burned");
+}
+
+#[tokio::test]
+async fn test_refresh_switchboard_price_from_mocked_aggregator() {
+    use barter_dex_program::switchboard::*;
+
+    // --- SETUP: A pool whose Switchboard feed points at a mocked aggregator account ---
+    let mut pt = ProgramTest::new(
+        "barter_dex_program",
+        barter_dex_program::id(),
+        processor!(barter_dex_program::entry),
+    );
+
+    let mint_a = Pubkey::new_unique();
+    let mint_b = Pubkey::new_unique();
+    for mint in [mint_a, mint_b] {
+        let mut data = vec![0u8; spl_token::state::Mint::LEN];
+        spl_token::state::Mint {
+            mint_authority: anchor_lang::solana_program::program_option::COption::None,
+            supply: 0,
+            decimals: 9,
+            is_initialized: true,
+            freeze_authority: anchor_lang::solana_program::program_option::COption::None,
+        }.pack_into_slice(&mut data);
+        pt.add_account(mint, solana_sdk::account::Account {
+            lamports: 1_000_000_000,
+            data,
+            owner: spl_token::id(),
+            executable: false,
+            rent_epoch: 0,
+        });
+    }
+
+    // Latest confirmed round: 1.25 with a 0.001 standard deviation, opened at t=1_000.
+    let aggregator = Pubkey::new_unique();
+    let mut aggregator_data = vec![0u8; 1_024];
+    aggregator_data[..8].copy_from_slice(&AGGREGATOR_DISCRIMINATOR);
+    aggregator_data[ROUND_OPEN_TIMESTAMP_OFFSET..RESULT_OFFSET].copy_from_slice(&1_000i64.to_le_bytes());
+    aggregator_data[RESULT_OFFSET..RESULT_OFFSET + 16].copy_from_slice(&125i128.to_le_bytes());
+    aggregator_data[RESULT_OFFSET + 16..STD_DEVIATION_OFFSET].copy_from_slice(&2u32.to_le_bytes());
+    aggregator_data[STD_DEVIATION_OFFSET..STD_DEVIATION_OFFSET + 16].copy_from_slice(&1i128.to_le_bytes());
+    aggregator_data[STD_DEVIATION_OFFSET + 16..STD_DEVIATION_OFFSET + 20].copy_from_slice(&3u32.to_le_bytes());
+    pt.add_account(aggregator, solana_sdk::account::Account {
+        lamports: 1_000_000_000,
+        data: aggregator_data,
+        owner: SWITCHBOARD_V2_PROGRAM_ID,
+        executable: false,
+        rent_epoch: 0,
+    });
+
+    let mut context = pt.start_with_context().await;
+    let authority = context.payer.pubkey();
+    let clock: Clock = context.banks_client.get_sysvar().await.unwrap();
+    context.set_sysvar(&Clock { unix_timestamp: 1_060, ..clock });
+
+    let (pool_pda, _) = Pubkey::find_program_address(
+        &[b"liquidity_pool", mint_a.as_ref(), mint_b.as_ref()],
+        &barter_dex_program::id(),
+    );
+    let (vault_a, _) = Pubkey::find_program_address(
+        &[b"pool_vault", mint_a.as_ref(), mint_b.as_ref(), b"a"],
+        &barter_dex_program::id(),
+    );
+    let (vault_b, _) = Pubkey::find_program_address(
+        &[b"pool_vault", mint_a.as_ref(), mint_b.as_ref(), b"b"],
+        &barter_dex_program::id(),
+    );
+    let (lp_mint, _) = Pubkey::find_program_address(
+        &[b"lp_mint", mint_a.as_ref(), mint_b.as_ref()],
+        &barter_dex_program::id(),
+    );
+
+    let create_pool_ix = Instruction {
+        program_id: barter_dex_program::id(),
+        accounts: barter_dex_program::accounts::CreatePool {
+            pool: pool_pda,
+            vault_a,
+            vault_b,
+            lp_mint,
+            mint_a,
+            mint_b,
+            authority,
+            system_program: system_program::id(),
+            token_program: spl_token::id(),
+            rent: sysvar::rent::id(),
+        }.to_account_metas(None),
+        data: barter_dex_program::instruction::CreatePool {
+            args: barter_dex_program::CreatePoolArgs {
+                oracle_authority: authority,
+                oracle_provider: barter_dex_program::state::OracleProvider::Switchboard,
+                pyth_price_feed_a: None,
+                pyth_price_feed_b: None,
+                switchboard_feed: Some(aggregator),
+                ai_oracle_program: None,
+                fee_bps: 30,
+                dynamic_fee_enabled: false,
+                volatility_threshold: 0,
+                max_staleness_seconds: 300,
+                hard_stale_seconds: 900,
+                stale_fee_bps: 50,
+                use_twap: false,
+                twap_window: 0,
+                smoothing_enabled: false,
+                ema_alpha_bps: 2_000,
+                max_price_move_bps: 0,
+                price_confirm_delay_seconds: 60,
+                max_price_deviation_bps: 0,
+                config_update_cooldown_seconds: 3_600,
+                price_tick: 0,
+                max_confidence_bps: 0,
+                amm_blend_enabled: false,
+                oracle_weight_bps: 10_000,
+                pyth_weight: DEFAULT_PYTH_WEIGHT,
+                switchboard_weight: DEFAULT_SWITCHBOARD_WEIGHT,
+                ai_weight: DEFAULT_AI_WEIGHT,
+                min_price_sources: 1,
+                require_ratio: false,
+            },
+        }.data(),
+    };
+    let tx = Transaction::new_signed_with_payer(
+        &[create_pool_ix],
+        Some(&authority),
+        &[&context.payer],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(tx).await.unwrap();
+
+    // --- WHEN: Anyone refreshes the pool from its aggregator ---
+    let refresh_ix = Instruction {
+        program_id: barter_dex_program::id(),
+        accounts: barter_dex_program::accounts::RefreshSwitchboardPrice {
+            pool: pool_pda,
+            aggregator,
+        }.to_account_metas(None),
+        data: barter_dex_program::instruction::RefreshSwitchboardPrice {}.data(),
+    };
+    let tx = Transaction::new_signed_with_payer(
+        &[refresh_ix],
+        Some(&authority),
+        &[&context.payer],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(tx).await.unwrap();
+
+    // --- THEN: The scaled aggregator result becomes the pool price ---
+    let pool_account = context.banks_client.get_account(pool_pda).await.unwrap().unwrap();
+    let pool = barter_dex_program::state::LiquidityPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+    assert_eq!(pool.switchboard_price, Some(1_250_000_000));
+    assert_eq!(pool.switchboard_updated_at, 1_000);
+    assert_eq!(pool.price_confidence, 1_000_000);
+    assert_eq!(pool.oracle_price, 1_250_000_000);
+    assert_eq!(pool.last_oracle_update, 1_060);
+}
+
+#[tokio::test]
+async fn test_refresh_pyth_price_from_mocked_price_account() {
+    use barter_dex_program::pyth::*;
+
+    // --- SETUP: A pool whose Pyth feed points at a mocked price account ---
+    let mut pt = ProgramTest::new(
+        "barter_dex_program",
+        barter_dex_program::id(),
+        processor!(barter_dex_program::entry),
+    );
+
+    let mint_a = Pubkey::new_unique();
+    let mint_b = Pubkey::new_unique();
+    for mint in [mint_a, mint_b] {
+        let mut data = vec![0u8; spl_token::state::Mint::LEN];
+        spl_token::state::Mint {
+            mint_authority: anchor_lang::solana_program::program_option::COption::None,
+            supply: 0,
+            decimals: 9,
+            is_initialized: true,
+            freeze_authority: anchor_lang::solana_program::program_option::COption::None,
+        }.pack_into_slice(&mut data);
+        pt.add_account(mint, solana_sdk::account::Account {
+            lamports: 1_000_000_000,
+            data,
+            owner: spl_token::id(),
+            executable: false,
+            rent_epoch: 0,
+        });
+    }
+
+    // Current aggregate: 1.25 +/- 0.001 with an exponent of -8, published at t=1_000.
+    let price_account = Pubkey::new_unique();
+    let mut price_data = vec![0u8; 3_312];
+    price_data[..4].copy_from_slice(&PYTH_MAGIC.to_le_bytes());
+    price_data[4..8].copy_from_slice(&PYTH_VERSION.to_le_bytes());
+    price_data[8..12].copy_from_slice(&PYTH_PRICE_ACCOUNT_TYPE.to_le_bytes());
+    price_data[EXPONENT_OFFSET..EXPONENT_OFFSET + 4].copy_from_slice(&(-8i32).to_le_bytes());
+    price_data[TIMESTAMP_OFFSET..TIMESTAMP_OFFSET + 8].copy_from_slice(&1_000i64.to_le_bytes());
+    price_data[AGG_PRICE_OFFSET..AGG_PRICE_OFFSET + 8].copy_from_slice(&125_000_000i64.to_le_bytes());
+    price_data[AGG_CONF_OFFSET..AGG_CONF_OFFSET + 8].copy_from_slice(&100_000u64.to_le_bytes());
+    price_data[AGG_STATUS_OFFSET..AGG_STATUS_OFFSET + 4].copy_from_slice(&PYTH_STATUS_TRADING.to_le_bytes());
+    pt.add_account(price_account, solana_sdk::account::Account {
+        lamports: 1_000_000_000,
+        data: price_data,
+        owner: PYTH_ORACLE_PROGRAM_ID,
+        executable: false,
+        rent_epoch: 0,
+    });
+
+    let mut context = pt.start_with_context().await;
+    let authority = context.payer.pubkey();
+    let clock: Clock = context.banks_client.get_sysvar().await.unwrap();
+    context.set_sysvar(&Clock { unix_timestamp: 1_060, ..clock.clone() });
+
+    let (pool_pda, _) = Pubkey::find_program_address(
+        &[b"liquidity_pool", mint_a.as_ref(), mint_b.as_ref()],
+        &barter_dex_program::id(),
+    );
+    let (vault_a, _) = Pubkey::find_program_address(
+        &[b"pool_vault", mint_a.as_ref(), mint_b.as_ref(), b"a"],
+        &barter_dex_program::id(),
+    );
+    let (vault_b, _) = Pubkey::find_program_address(
+        &[b"pool_vault", mint_a.as_ref(), mint_b.as_ref(), b"b"],
+        &barter_dex_program::id(),
+    );
+    let (lp_mint, _) = Pubkey::find_program_address(
+        &[b"lp_mint", mint_a.as_ref(), mint_b.as_ref()],
+        &barter_dex_program::id(),
+    );
+
+    let create_pool_ix = Instruction {
+        program_id: barter_dex_program::id(),
+        accounts: barter_dex_program::accounts::CreatePool {
+            pool: pool_pda,
+            vault_a,
+            vault_b,
+            lp_mint,
+            mint_a,
+            mint_b,
+            authority,
+            system_program: system_program::id(),
+            token_program: spl_token::id(),
+            rent: sysvar::rent::id(),
+        }.to_account_metas(None),
+        data: barter_dex_program::instruction::CreatePool {
+            args: barter_dex_program::CreatePoolArgs {
+                oracle_authority: authority,
+                oracle_provider: barter_dex_program::state::OracleProvider::Pyth,
+                pyth_price_feed_a: Some(price_account),
+                pyth_price_feed_b: None,
+                switchboard_feed: None,
+                ai_oracle_program: None,
+                fee_bps: 30,
+                dynamic_fee_enabled: false,
+                volatility_threshold: 0,
+                max_staleness_seconds: 300,
+                hard_stale_seconds: 900,
+                stale_fee_bps: 50,
+                use_twap: false,
+                twap_window: 0,
+                smoothing_enabled: false,
+                ema_alpha_bps: 2_000,
+                max_price_move_bps: 0,
+                price_confirm_delay_seconds: 60,
+                max_price_deviation_bps: 0,
+                config_update_cooldown_seconds: 3_600,
+                price_tick: 0,
+                max_confidence_bps: 0,
+                amm_blend_enabled: false,
+                oracle_weight_bps: 10_000,
+                pyth_weight: DEFAULT_PYTH_WEIGHT,
+                switchboard_weight: DEFAULT_SWITCHBOARD_WEIGHT,
+                ai_weight: DEFAULT_AI_WEIGHT,
+                min_price_sources: 1,
+                require_ratio: false,
+            },
+        }.data(),
+    };
+    let tx = Transaction::new_signed_with_payer(
+        &[create_pool_ix],
+        Some(&authority),
+        &[&context.payer],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(tx).await.unwrap();
+
+    // --- WHEN: Anyone refreshes the pool from its Pyth price account ---
+    let mut refresh_ix = Instruction {
+        program_id: barter_dex_program::id(),
+        accounts: barter_dex_program::accounts::RefreshPythPrice {
+            pool: pool_pda,
+        }.to_account_metas(None),
+        data: barter_dex_program::instruction::RefreshPythPrice {}.data(),
+    };
+    refresh_ix.accounts.push(AccountMeta::new_readonly(price_account, false));
+    let tx = Transaction::new_signed_with_payer(
+        &[refresh_ix.clone()],
+        Some(&authority),
+        &[&context.payer],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(tx).await.unwrap();
+
+    // --- THEN: The scaled Pyth price becomes the pool price ---
+    let pool_account = context.banks_client.get_account(pool_pda).await.unwrap().unwrap();
+    let pool = barter_dex_program::state::LiquidityPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+    assert_eq!(pool.pyth_price, Some(1_250_000_000));
+    assert_eq!(pool.pyth_updated_at, 1_000);
+    assert_eq!(pool.price_confidence, 1_000_000);
+    assert_eq!(pool.oracle_price, 1_250_000_000);
+    assert_eq!(pool.last_oracle_update, 1_060);
+
+    // ...and once the price is older than MAX_ORACLE_AGE_SECONDS, refreshing fails.
+    context.set_sysvar(&Clock { unix_timestamp: 1_000 + 301, ..clock });
+    let blockhash = context.get_new_latest_blockhash().await.unwrap();
+    let tx = Transaction::new_signed_with_payer(
+        &[refresh_ix],
+        Some(&authority),
+        &[&context.payer],
+        blockhash,
+    );
+    assert!(context.banks_client.process_transaction(tx).await.is_err(), "A stale Pyth price should be rejected");
+}
+
+#[tokio::test]
+async fn test_compound_commission_into_spl_launch() {
+    // --- GIVEN: Two fee-free launches priced at 0.1 units per whole token ---
+    let mut context = start_programs(true).await;
+    let uncapped_commission = |args: &mut factory_program::CreateLaunchArgs| args.max_affiliate_commission_tokens = u64::MAX;
+    let launch_a = create_launch(&mut context, true, uncapped_commission).await;
+    let launch_b = create_launch(&mut context, true, uncapped_commission).await;
+    let authority = launch_a.authority;
+    let affiliate = Keypair::new();
+    let buyer = Keypair::new();
+    airdrop(&mut context, &affiliate.pubkey(), 1_000_000_000).await;
+    airdrop(&mut context, &buyer.pubkey(), 2_000_000_000).await;
+    let (affiliate_info_pda, _) = Pubkey::find_program_address(
+        &[b"affiliate_info", affiliate.pubkey().as_ref()],
+        &affiliate_program::id(),
+    );
+    let ecosystem_stats_pda = launch_a.ecosystem_stats.unwrap();
+
+    // Launch B takes launch A's token as payment, once the platform accepts it.
+    let (platform_config, _) = Pubkey::find_program_address(&[b"platform_config"], &factory_program::id());
+    let allow_mints_ix = |mints: Vec<Pubkey>| Instruction {
+        program_id: factory_program::id(),
+        accounts: factory_program::accounts::SetAllowedPaymentMints {
+            platform_config,
+            admin: authority,
+            system_program: system_program::id(),
+        }.to_account_metas(None),
+        data: factory_program::instruction::SetAllowedPaymentMints { mints }.data(),
+    };
+    let payment_vault = anchor_spl::associated_token::get_associated_token_address(&launch_b.launch_state, &launch_a.token_mint);
+    let migrate_ix = Instruction {
+        program_id: factory_program::id(),
+        accounts: factory_program::accounts::MigratePaymentMode {
+            launch_state: launch_b.launch_state,
+            payment_mint: Some(launch_a.token_mint),
+            platform_config,
+            payment_vault: Some(payment_vault),
+            authority,
+            system_program: system_program::id(),
+            token_program: spl_token::id(),
+            associated_token_program: anchor_spl::associated_token::ID,
+        }.to_account_metas(None),
+        data: factory_program::instruction::MigratePaymentMode {
+            payment_mode: factory_program::state::PaymentMode::SplToken,
+        }.data(),
+    };
+
+    // An unapproved payment mint is rejected...
+    let tx = Transaction::new_signed_with_payer(
+        &[allow_mints_ix(vec![launch_b.token_mint]), migrate_ix.clone()],
+        Some(&context.payer.pubkey()),
+        &[&context.payer],
+        context.last_blockhash,
+    );
+    assert!(context.banks_client.process_transaction(tx).await.is_err(), "Payment mints off the allowlist should be rejected");
+
+    // ...and accepted once the platform approves it.
+    let tx = Transaction::new_signed_with_payer(
+        &[allow_mints_ix(vec![launch_a.token_mint]), migrate_ix],
+        Some(&context.payer.pubkey()),
+        &[&context.payer],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(tx).await.unwrap();
+
+    // A registered affiliate who opts in to compounding.
+    let register_ix = Instruction {
+        program_id: affiliate_program::id(),
+        accounts: affiliate_program::accounts::RegisterAffiliate {
+            affiliate_info: affiliate_info_pda,
+            ecosystem_stats: ecosystem_stats_pda,
+            parent_affiliate_info: None,
+            affiliate: affiliate.pubkey(),
+            system_program: system_program::id(),
+        }.to_account_metas(None),
+        data: affiliate_program::instruction::RegisterAffiliate {
+            args: affiliate_program::RegisterAffiliateArgs {
+                parent_affiliate: None,
+                referral_level: 1,
+                rate_caps_enabled: false,
+                max_commission_rate_bps: 2000,
+                min_commission_rate_bps: 50,
+            },
+        }.data(),
+    };
+    let auto_compound_ix = Instruction {
+        program_id: affiliate_program::id(),
+        accounts: affiliate_program::accounts::SetAutoCompound {
+            affiliate_info: affiliate_info_pda,
+            affiliate_key: affiliate.pubkey(),
+        }.to_account_metas(None),
+        data: affiliate_program::instruction::SetAutoCompound { enabled: true }.data(),
+    };
+    let tx = Transaction::new_signed_with_payer(
+        &[register_ix, auto_compound_ix],
+        Some(&affiliate.pubkey()),
+        &[&affiliate],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(tx).await.unwrap();
+
+    // A 1 SOL referred purchase on launch A earns the affiliate 1 token of commission (10%).
+    let affiliate_ata_a = launch_a.ata(&affiliate.pubkey());
+    let create_affiliate_ata_ix = anchor_spl::associated_token::spl_associated_token_account::instruction::create_associated_token_account_idempotent(
+        &buyer.pubkey(),
+        &affiliate.pubkey(),
+        &launch_a.token_mint,
+        &spl_token::id(),
+    );
+    let buy_ix = Instruction {
+        program_id: factory_program::id(),
+        accounts: factory_program::accounts::BuyTokens {
+            affiliate: Some(affiliate.pubkey()),
+            affiliate_info: Some(affiliate_info_pda),
+            affiliate_token_account: Some(affiliate_ata_a),
+            ..launch_a.buy_accounts(&buyer.pubkey(), &buyer.pubkey())
+        }.to_account_metas(None),
+        data: factory_program::instruction::BuyTokens {
+            sol_amount: 1_000_000_000,
+            affiliate_key: Some(affiliate.pubkey()),
+            enable_vesting: false,
+            min_tokens_out: 0,
+            whitelist_proof: vec![],
+            recipient: None,
+        }.data(),
+    };
+    let tx = Transaction::new_signed_with_payer(
+        &[create_affiliate_ata_ix, buy_ix],
+        Some(&buyer.pubkey()),
+        &[&buyer],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(tx).await.unwrap();
+    let commission = get_token_account(&mut context, &affiliate_ata_a).await.amount;
+    assert_eq!(commission, 1_000_000_000);
+
+    // --- WHEN: The affiliate compounds the commission into launch B ---
+    let platform_fee_token_account = launch_a.ata(&authority);
+    let create_fee_account_ix = anchor_spl::associated_token::spl_associated_token_account::instruction::create_associated_token_account(
+        &authority,
+        &authority,
+        &launch_a.token_mint,
+        &spl_token::id(),
+    );
+    let affiliate_ata_b = launch_b.ata(&affiliate.pubkey());
+    let compound_ix = Instruction {
+        program_id: factory_program::id(),
+        accounts: factory_program::accounts::CompoundCommission {
+            purchase: factory_program::accounts::BuyTokensWithSpl {
+                launch_state: launch_b.launch_state,
+                token_mint: launch_b.token_mint,
+                payment_mint: launch_a.token_mint,
+                payment_vault,
+                buyer_payment_account: affiliate_ata_a,
+                platform_fee_token_account,
+                buyer_token_account: affiliate_ata_b,
+                purchase_tracker: launch_b.buyer_pda(b"purchase_tracker", &affiliate.pubkey()),
+                buyer: affiliate.pubkey(),
+                blocked_buyer: launch_b.buyer_pda(b"blocked_buyer", &affiliate.pubkey()),
+                fee_split_config: None,
+                whitelist_entry: None,
+                system_program: system_program::id(),
+                token_program: spl_token::id(),
+                associated_token_program: anchor_spl::associated_token::ID,
+            },
+            affiliate_info: affiliate_info_pda,
+        }.to_account_metas(None),
+        data: factory_program::instruction::CompoundCommission {
+            commission_amount: commission,
+            min_tokens_out: 1,
+        }.data(),
+    };
+    let tx = Transaction::new_signed_with_payer(
+        &[create_fee_account_ix, compound_ix],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &affiliate],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(tx).await.unwrap();
+
+    // --- THEN: The commission sits in launch B's vault and bought 10 B tokens ---
+    assert_eq!(get_token_account(&mut context, &affiliate_ata_a).await.amount, 0);
+    assert_eq!(get_token_account(&mut context, &payment_vault).await.amount, commission);
+    assert_eq!(get_token_account(&mut context, &affiliate_ata_b).await.amount, 10 * 1_000_000_000);
+}
+
+#[tokio::test]
+async fn test_launch_purchase_and_withdrawal_events_are_emitted() {
+    // --- SETUP: Initialize test environment and actors ---
+    let mut context = start_programs(true).await;
+    let authority = context.payer.pubkey();
+    let token_mint_kp = Keypair::new();
+    let affiliate = Keypair::new();
+    let buyer = Keypair::new();
+    airdrop(&mut context, &affiliate.pubkey(), 1_000_000_000).await;
+    airdrop(&mut context, &buyer.pubkey(), 2_000_000_000).await;
+
+    let (launch_state_pda, _) = Pubkey::find_program_address(
+        &[b"launch_state", authority.as_ref(), token_mint_kp.pubkey().as_ref()],
+        &factory_program::id(),
+    );
+    let (sol_vault_pda, _) = Pubkey::find_program_address(
+        &[b"sol_vault", authority.as_ref(), token_mint_kp.pubkey().as_ref()],
+        &factory_program::id(),
+    );
+    let (affiliate_info_pda, _) = Pubkey::find_program_address(
+        &[b"affiliate_info", affiliate.pubkey().as_ref()],
+        &affiliate_program::id(),
+    );
+    let (ecosystem_stats_pda, _) = Pubkey::find_program_address(&[b"ecosystem_stats"], &affiliate_program::id());
+
+    // --- WHEN: A launch with a 2% platform fee is created... ---
+    // The launch is created by hand rather than through `create_launch` to capture its logs.
+    let clock: Clock = context.banks_client.get_sysvar().await.unwrap();
+    let launch = TestLaunch {
+        authority,
+        token_mint: token_mint_kp.pubkey(),
+        launch_state: launch_state_pda,
+        sol_vault: sol_vault_pda,
+        ecosystem_stats: Some(ecosystem_stats_pda),
+        clock: clock.clone(),
+        launch_end_time: clock.unix_timestamp + 1_000,
+    };
+    let create_launch_ix = Instruction {
+        program_id: factory_program::id(),
+        accounts: factory_program::accounts::CreateLaunch {
+            launch_state: launch_state_pda,
+            token_mint: token_mint_kp.pubkey(),
+            sol_vault: sol_vault_pda,
+            ecosystem_stats: Some(ecosystem_stats_pda),
+            authority,
+            affiliate_program: Some(affiliate_program::id()),
+            system_program: system_program::id(),
+            token_program: spl_token::id(),
+            rent: sysvar::rent::id(),
+        }.to_account_metas(None),
+        data: factory_program::instruction::CreateLaunch {
+            args: factory_program::CreateLaunchArgs {
+                platform_fee_bps: 200,
+                ..default_launch_args(authority, clock.unix_timestamp)
+            },
+        }.data(),
+    };
+    let tx = Transaction::new_signed_with_payer(
+        &[create_launch_ix],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &token_mint_kp],
+        context.last_blockhash,
+    );
+    let result = context.banks_client.process_transaction_with_metadata(tx).await.unwrap();
+    assert!(result.result.is_ok());
+    let create_logs = result.metadata.unwrap().log_messages;
+
+    // The created mint's authority is the PDA clients derive off-chain.
+    let mint_account = context.banks_client.get_account(token_mint_kp.pubkey()).await.unwrap().unwrap();
+    let mint = spl_token::state::Mint::unpack(&mint_account.data).unwrap();
+    let (mint_authority, _) = genesis_common::utils::pda_utils::derive_mint_authority(&authority, &token_mint_kp.pubkey());
+    assert_eq!(mint.mint_authority, anchor_lang::solana_program::program_option::COption::Some(mint_authority));
+    assert_eq!(mint_authority, launch_state_pda);
+
+    // This test passes the optional affiliate accounts to `buy_tokens`, so one must be registered.
+    let register_ix = Instruction {
+        program_id: affiliate_program::id(),
+        accounts: affiliate_program::accounts::RegisterAffiliate {
+            affiliate_info: affiliate_info_pda,
+            ecosystem_stats: ecosystem_stats_pda,
+            parent_affiliate_info: None,
+            affiliate: affiliate.pubkey(),
+            system_program: system_program::id(),
+        }.to_account_metas(None),
+        data: affiliate_program::instruction::RegisterAffiliate {
+            args: affiliate_program::RegisterAffiliateArgs {
+                parent_affiliate: None,
+                referral_level: 1,
+                rate_caps_enabled: false,
+                max_commission_rate_bps: 2000,
+                min_commission_rate_bps: 50,
+            },
+        }.data(),
+    };
+    let tx = Transaction::new_signed_with_payer(
+        &[register_ix],
+        Some(&affiliate.pubkey()),
+        &[&affiliate],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(tx).await.unwrap();
+
+    // ...and a buyer spends 1 SOL on it.
+    let sol_to_spend = 1_000_000_000;
+    let buy_ix = Instruction {
+        program_id: factory_program::id(),
+        accounts: factory_program::accounts::BuyTokens {
+            affiliate: Some(affiliate.pubkey()),
+            affiliate_info: Some(affiliate_info_pda),
+            affiliate_token_account: Some(launch.ata(&affiliate.pubkey())),
+            ..launch.buy_accounts(&buyer.pubkey(), &buyer.pubkey())
+        }.to_account_metas(None),
+        data: factory_program::instruction::BuyTokens {
+            sol_amount: sol_to_spend,
+            affiliate_key: None,
+            enable_vesting: false,
+            min_tokens_out: 0,
+            whitelist_proof: vec![],
+            recipient: None,
+        }.data(),
+    };
+    let tx = Transaction::new_signed_with_payer(
+        &[buy_ix],
+        Some(&buyer.pubkey()),
+        &[&buyer],
+        context.last_blockhash,
+    );
+    let result = context.banks_client.process_transaction_with_metadata(tx).await.unwrap();
+    assert!(result.result.is_ok());
+    let buy_logs = result.metadata.unwrap().log_messages;
+
+    // --- THEN: Both events decode from the transaction logs ---
+    let launches = decode_events::<factory_program::LaunchCreatedEvent>(&create_logs);
+    assert_eq!(launches.len(), 1);
+    assert_eq!(launches[0].launch_state, launch_state_pda);
+    assert_eq!(launches[0].authority, authority);
+    assert_eq!(launches[0].token_mint, token_mint_kp.pubkey());
+    assert_eq!(launches[0].initial_price, 100_000_000);
+
+    let purchases = decode_events::<factory_program::PurchaseEvent>(&buy_logs);
+    assert_eq!(purchases.len(), 1);
+    let purchase = &purchases[0];
+    assert_eq!(purchase.buyer, buyer.pubkey());
+    assert_eq!(purchase.launch_state, launch_state_pda);
+    assert_eq!(purchase.sol_amount, sol_to_spend);
+    assert_eq!(purchase.platform_fee, 20_000_000);
+    assert_eq!(purchase.affiliate_fee, 0);
+    assert_eq!(purchase.net_sol_amount, 980_000_000);
+    assert_eq!(purchase.price_per_token, 100_000_000);
+    assert_eq!(purchase.tokens_minted, 10_000_000_000);
+    assert!(!purchase.vested);
+
+    // --- WHEN: The authority withdraws the raised SOL ---
+    let withdraw_ix = Instruction {
+        program_id: factory_program::id(),
+        accounts: factory_program::accounts::WithdrawSol {
+            launch_state: launch_state_pda,
+            sol_vault: sol_vault_pda,
+            authority,
+            system_program: system_program::id(),
+        }.to_account_metas(None),
+        data: factory_program::instruction::WithdrawSol {}.data(),
+    };
+    let tx = Transaction::new_signed_with_payer(
+        &[withdraw_ix],
+        Some(&context.payer.pubkey()),
+        &[&context.payer],
+        context.last_blockhash,
+    );
+    let result = context.banks_client.process_transaction_with_metadata(tx).await.unwrap();
+    assert!(result.result.is_ok());
+    let metadata = result.metadata.unwrap();
+
+    // --- THEN: The event and the return data both report the withdrawn amount ---
+    let withdrawals = decode_events::<factory_program::SolWithdrawnEvent>(&metadata.log_messages);
+    assert_eq!(withdrawals.len(), 1);
+    assert_eq!(withdrawals[0].launch_state, launch_state_pda);
+    assert_eq!(withdrawals[0].authority, authority);
+    assert_eq!(withdrawals[0].amount, 980_000_000);
+    assert_eq!(withdrawals[0].remaining_vault_balance, 0);
+
+    let return_data = metadata.return_data.expect("withdraw_sol sets return data");
+    assert_eq!(return_data.program_id, factory_program::id());
+    assert_eq!(u64::try_from_slice(&return_data.data).unwrap(), 980_000_000);
+}
+
+#[tokio::test]
+async fn test_finalize_launch_revokes_minting() {
+    // --- GIVEN: A fee-free launch that has ended ---
+    let (mut context, launch) = setup_launch(true, |args| args.launch_end_time = args.launch_start_time + 100).await;
+    let affiliate = Keypair::new();
+    let buyer = Keypair::new();
+    airdrop(&mut context, &affiliate.pubkey(), 1_000_000_000).await;
+    airdrop(&mut context, &buyer.pubkey(), 2_000_000_000).await;
+    let (affiliate_info_pda, _) = Pubkey::find_program_address(
+        &[b"affiliate_info", affiliate.pubkey().as_ref()],
+        &affiliate_program::id(),
+    );
+    let ecosystem_stats_pda = launch.ecosystem_stats.unwrap();
+
+    // This test passes the optional affiliate accounts to `buy_tokens`, so one must be registered.
+    let register_ix = Instruction {
+        program_id: affiliate_program::id(),
+        accounts: affiliate_program::accounts::RegisterAffiliate {
+            affiliate_info: affiliate_info_pda,
+            ecosystem_stats: ecosystem_stats_pda,
+            parent_affiliate_info: None,
+            affiliate: affiliate.pubkey(),
+            system_program: system_program::id(),
+        }.to_account_metas(None),
+        data: affiliate_program::instruction::RegisterAffiliate {
+            args: affiliate_program::RegisterAffiliateArgs {
+                parent_affiliate: None,
+                referral_level: 1,
+                rate_caps_enabled: false,
+                max_commission_rate_bps: 2000,
+                min_commission_rate_bps: 50,
+            },
+        }.data(),
+    };
+    let tx = Transaction::new_signed_with_payer(
+        &[register_ix],
+        Some(&affiliate.pubkey()),
+        &[&affiliate],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(tx).await.unwrap();
+
+    context.set_sysvar(&Clock { unix_timestamp: launch.launch_end_time + 1, ..launch.clock.clone() });
+
+    // --- WHEN: The authority finalizes the launch ---
+    let finalize_ix = Instruction {
+        program_id: factory_program::id(),
+        accounts: factory_program::accounts::FinalizeLaunch {
+            launch_state: launch.launch_state,
+            token_mint: launch.token_mint,
+            authority: launch.authority,
+            token_program: spl_token::id(),
+        }.to_account_metas(None),
+        data: factory_program::instruction::FinalizeLaunch {}.data(),
+    };
+    let tx = Transaction::new_signed_with_payer(
+        &[finalize_ix],
+        Some(&context.payer.pubkey()),
+        &[&context.payer],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(tx).await.unwrap();
+
+    // --- THEN: The mint has no authority left... ---
+    let mint_account = context.banks_client.get_account(launch.token_mint).await.unwrap().unwrap();
+    let mint = spl_token::state::Mint::unpack(&mint_account.data).unwrap();
+    assert_eq!(mint.mint_authority, anchor_lang::solana_program::program_option::COption::None);
+    let launch_account = context.banks_client.get_account(launch.launch_state).await.unwrap().unwrap();
+    let launch_data = factory_program::state::LaunchState::try_deserialize(&mut launch_account.data.as_slice()).unwrap();
+    assert!(launch_data.finalized);
+
+    // ...so purchases are rejected...
+    let buyer_ata = launch.ata(&buyer.pubkey());
+    let buy_ix = Instruction {
+        program_id: factory_program::id(),
+        accounts: factory_program::accounts::BuyTokens {
+            affiliate: Some(affiliate.pubkey()),
+            affiliate_info: Some(affiliate_info_pda),
+            affiliate_token_account: Some(launch.ata(&affiliate.pubkey())),
+            ..launch.buy_accounts(&buyer.pubkey(), &buyer.pubkey())
+        }.to_account_metas(None),
+        data: factory_program::instruction::BuyTokens {
+            sol_amount: 1_000_000_000,
+            affiliate_key: None,
+            enable_vesting: false,
+            min_tokens_out: 0,
+            whitelist_proof: vec![],
+            recipient: None,
+        }.data(),
+    };
+    let tx = Transaction::new_signed_with_payer(
+        &[buy_ix],
+        Some(&buyer.pubkey()),
+        &[&buyer],
+        context.last_blockhash,
+    );
+    assert!(context.banks_client.process_transaction(tx).await.is_err(), "Buying from a finalized launch should fail");
+
+    // ...and no one, including the former authority's signer, can mint directly.
+    let mint_ix = spl_token::instruction::mint_to(
+        &spl_token::id(),
+        &launch.token_mint,
+        &buyer_ata,
+        &launch.launch_state,
+        &[],
+        1,
+    ).unwrap();
+    let tx = Transaction::new_signed_with_payer(
+        &[mint_ix],
+        Some(&context.payer.pubkey()),
+        &[&context.payer],
+        context.last_blockhash,
+    );
+    assert!(context.banks_client.process_transaction(tx).await.is_err(), "Minting after finalization should be impossible");
+}
+
+#[tokio::test]
+async fn test_buy_without_affiliate_program_deployed() {
+    // --- SETUP: Only the factory program is loaded, as on a cluster without the affiliate program ---
+    let (mut context, launch) = setup_launch(false, |_| {}).await;
+    let buyer = Keypair::new();
+    airdrop(&mut context, &buyer.pubkey(), 2_000_000_000).await;
+
+    // --- WHEN: The launch is bought from with every affiliate account omitted ---
+    let buyer_ata = launch.ata(&buyer.pubkey());
+    let buy_ix = Instruction {
+        program_id: factory_program::id(),
+        accounts: launch.buy_accounts(&buyer.pubkey(), &buyer.pubkey()).to_account_metas(None),
+        data: factory_program::instruction::BuyTokens {
+            sol_amount: 1_000_000_000,
+            affiliate_key: None,
+            enable_vesting: false,
+            min_tokens_out: 0,
+            whitelist_proof: vec![],
+            recipient: None,
+        }.data(),
+    };
+    let tx = Transaction::new_signed_with_payer(
+        &[buy_ix],
+        Some(&buyer.pubkey()),
+        &[&buyer],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(tx).await.unwrap();
+
+    // --- THEN: The buyer received tokens and the vault holds the SOL ---
+    let buyer_token_account = get_token_account(&mut context, &buyer_ata).await;
+    assert_eq!(buyer_token_account.amount, 10_000_000_000);
+    let launch_account = context.banks_client.get_account(launch.launch_state).await.unwrap().unwrap();
+    let launch_data = factory_program::state::LaunchState::try_deserialize(&mut launch_account.data.as_slice()).unwrap();
+    assert_eq!(launch_data.total_sol_collected, 1_000_000_000);
+    assert_eq!(launch_data.purchase_count, 1);
+}
+
+#[tokio::test]
+async fn test_buy_with_affiliate_program_requires_ecosystem_stats() {
+    // --- SETUP: A launch with the affiliate program deployed ---
+    let (mut context, launch) = setup_launch(true, |_| {}).await;
+    let buyer = Keypair::new();
+    airdrop(&mut context, &buyer.pubkey(), 2_000_000_000).await;
+
+    // --- WHEN: The affiliate program is passed but the ecosystem stats account is dropped ---
+    let buy_ix = Instruction {
+        program_id: factory_program::id(),
+        accounts: factory_program::accounts::BuyTokens {
+            ecosystem_stats: None,
+            ..launch.buy_accounts(&buyer.pubkey(), &buyer.pubkey())
+        }.to_account_metas(None),
+        data: factory_program::instruction::BuyTokens {
+            sol_amount: 1_000_000_000,
+            affiliate_key: None,
+            enable_vesting: false,
+            min_tokens_out: 0,
+            whitelist_proof: vec![],
+            recipient: None,
+        }.data(),
+    };
+    let tx = Transaction::new_signed_with_payer(
+        &[buy_ix],
+        Some(&buyer.pubkey()),
+        &[&buyer],
+        context.last_blockhash,
+    );
+
+    // --- THEN: The purchase is rejected rather than silently skipping the ecosystem totals ---
+    assert!(context.banks_client.process_transaction(tx).await.is_err(), "Buying without ecosystem stats should fail");
+    let launch_account = context.banks_client.get_account(launch.launch_state).await.unwrap().unwrap();
+    let launch_data = factory_program::state::LaunchState::try_deserialize(&mut launch_account.data.as_slice()).unwrap();
+    assert_eq!(launch_data.purchase_count, 0);
+}
+
+#[tokio::test]
+async fn test_buy_on_behalf_of_recipient() {
+    // --- SETUP: A standalone factory launch, a paying buyer and a separate recipient wallet ---
+    let (mut context, launch) = setup_launch(false, |_| {}).await;
+    let buyer = Keypair::new();
+    let recipient = Pubkey::new_unique();
+    airdrop(&mut context, &buyer.pubkey(), 2_000_000_000).await;
+
+    // --- WHEN: The buyer pays for a purchase credited to the recipient ---
+    let recipient_ata = launch.ata(&recipient);
+    let purchase_tracker_pda = launch.buyer_pda(b"purchase_tracker", &recipient);
+    let buy_ix = Instruction {
+        program_id: factory_program::id(),
+        accounts: launch.buy_accounts(&buyer.pubkey(), &recipient).to_account_metas(None),
+        data: factory_program::instruction::BuyTokens {
+            sol_amount: 1_000_000_000,
+            affiliate_key: None,
+            enable_vesting: false,
+            min_tokens_out: 0,
+            whitelist_proof: vec![],
+            recipient: Some(recipient),
+        }.data(),
+    };
+    let tx = Transaction::new_signed_with_payer(
+        &[buy_ix],
+        Some(&buyer.pubkey()),
+        &[&buyer],
+        context.last_blockhash,
+    );
+    let buyer_balance_before = context.banks_client.get_balance(buyer.pubkey()).await.unwrap();
+    context.banks_client.process_transaction(tx).await.unwrap();
+
+    // --- THEN: The recipient holds the tokens and is tracked, while the buyer paid ---
+    let recipient_token_account = get_token_account(&mut context, &recipient_ata).await;
+    assert_eq!(recipient_token_account.amount, 10_000_000_000);
+    let buyer_ata = launch.ata(&buyer.pubkey());
+    assert!(context.banks_client.get_account(buyer_ata).await.unwrap().is_none());
+    let tracker_account = context.banks_client.get_account(purchase_tracker_pda).await.unwrap().unwrap();
+    let tracker = factory_program::state::PurchaseTracker::try_deserialize(&mut tracker_account.data.as_slice()).unwrap();
+    assert_eq!(tracker.buyer, recipient);
+    let buyer_balance_after = context.banks_client.get_balance(buyer.pubkey()).await.unwrap();
+    assert!(buyer_balance_before - buyer_balance_after >= 1_000_000_000);
+}
+
+#[tokio::test]
+async fn test_vested_purchase_is_claimed_once_vested() {
+    // --- GIVEN: A standalone launch vesting purchases linearly over a day ---
+    let (mut context, launch) = setup_launch(false, |args| {
+        args.vesting_enabled = true;
+        args.vesting_duration_seconds = 86_400;
+    }).await;
+    let buyer = Keypair::new();
+    airdrop(&mut context, &buyer.pubkey(), 2_000_000_000).await;
+    let buyer_ata = launch.ata(&buyer.pubkey());
+    let vesting_schedule_pda = launch.buyer_pda(b"vesting_schedule", &buyer.pubkey());
+    let vesting_ata = launch.ata(&vesting_schedule_pda);
+
+    // --- WHEN: The buyer makes a vested 1 SOL purchase ---
+    let buy_ix = Instruction {
+        program_id: factory_program::id(),
+        accounts: launch.buy_accounts(&buyer.pubkey(), &buyer.pubkey()).to_account_metas(None),
+        data: factory_program::instruction::BuyTokens {
+            sol_amount: 1_000_000_000,
+            affiliate_key: None,
+            enable_vesting: true,
+            min_tokens_out: 0,
+            whitelist_proof: vec![],
+            recipient: None,
+        }.data(),
+    };
+    let tx = Transaction::new_signed_with_payer(
+        &[buy_ix],
+        Some(&buyer.pubkey()),
+        &[&buyer],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(tx).await.unwrap();
+
+    // --- THEN: The tokens are held by the schedule's token account, not the buyer's ---
+    assert_eq!(get_token_account(&mut context, &vesting_ata).await.amount, 10_000_000_000);
+    assert_eq!(get_token_account(&mut context, &buyer_ata).await.amount, 0);
+
+    let claim_ix = Instruction {
+        program_id: factory_program::id(),
+        accounts: factory_program::accounts::ClaimVestedTokens {
+            launch_state: launch.launch_state,
+            vesting_schedule: vesting_schedule_pda,
+            vesting_token_account: vesting_ata,
+            beneficiary_token_account: buyer_ata,
+            beneficiary: buyer.pubkey(),
+            token_program: spl_token::id(),
+            associated_token_program: anchor_spl::associated_token::ID,
+        }.to_account_metas(None),
+        data: factory_program::instruction::ClaimVestedTokens {
+            _args: factory_program::ClaimVestedTokensArgs { amount: 0 },
+        }.data(),
+    };
+
+    // Nothing has vested yet at the purchase time.
+    let tx = Transaction::new_signed_with_payer(
+        &[claim_ix.clone()],
+        Some(&buyer.pubkey()),
+        &[&buyer],
+        context.last_blockhash,
+    );
+    assert!(context.banks_client.process_transaction(tx).await.is_err(), "Claiming before anything vests should fail");
+
+    // --- WHEN: The buyer claims once the schedule has fully vested ---
+    context.set_sysvar(&Clock { unix_timestamp: launch.clock.unix_timestamp + 86_400, ..launch.clock.clone() });
+    let blockhash = context.get_new_latest_blockhash().await.unwrap();
+    let tx = Transaction::new_signed_with_payer(
+        &[claim_ix],
+        Some(&buyer.pubkey()),
+        &[&buyer],
+        blockhash,
+    );
+    context.banks_client.process_transaction(tx).await.unwrap();
+
+    // --- THEN: The whole purchase moved to the buyer ---
+    assert_eq!(get_token_account(&mut context, &buyer_ata).await.amount, 10_000_000_000);
+    assert_eq!(get_token_account(&mut context, &vesting_ata).await.amount, 0);
+    let schedule_account = context.banks_client.get_account(vesting_schedule_pda).await.unwrap().unwrap();
+    let schedule = factory_program::state::VestingSchedule::try_deserialize(&mut schedule_account.data.as_slice()).unwrap();
+    assert_eq!(schedule.claimed_amount, 10_000_000_000);
+}
+
+#[tokio::test]
+async fn test_pause_blocks_and_resumes_purchases() {
+    // --- SETUP: A standalone factory launch and a funded buyer ---
+    let (mut context, launch) = setup_launch(false, |_| {}).await;
+    let authority = launch.authority;
+    let buyer = Keypair::new();
+    airdrop(&mut context, &buyer.pubkey(), 2_000_000_000).await;
+
+    let buyer_ata = launch.ata(&buyer.pubkey());
+    let buy_ix = Instruction {
+        program_id: factory_program::id(),
+        accounts: launch.buy_accounts(&buyer.pubkey(), &buyer.pubkey()).to_account_metas(None),
+        data: factory_program::instruction::BuyTokens {
+            sol_amount: 1_000_000_000,
+            affiliate_key: None,
+            enable_vesting: false,
+            min_tokens_out: 0,
+            whitelist_proof: vec![],
+            recipient: None,
+        }.data(),
+    };
+    let set_paused_ix = |paused: bool| Instruction {
+        program_id: factory_program::id(),
+        accounts: factory_program::accounts::SetPaused {
+            launch_state: launch.launch_state,
+            authority,
+        }.to_account_metas(None),
+        data: factory_program::instruction::SetPaused { paused }.data(),
+    };
+
+    // --- WHEN: The authority pauses the launch ---
+    let tx = Transaction::new_signed_with_payer(
+        &[set_paused_ix(true)],
+        Some(&context.payer.pubkey()),
+        &[&context.payer],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(tx).await.unwrap();
+
+    // --- THEN: Purchases are rejected ---
+    let tx = Transaction::new_signed_with_payer(
+        &[buy_ix.clone()],
+        Some(&buyer.pubkey()),
+        &[&buyer],
+        context.last_blockhash,
+    );
+    assert!(context.banks_client.process_transaction(tx).await.is_err(), "Buying from a paused launch should fail");
+
+    // --- WHEN: The authority unpauses the launch ---
+    let tx = Transaction::new_signed_with_payer(
+        &[set_paused_ix(false)],
+        Some(&context.payer.pubkey()),
+        &[&context.payer],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(tx).await.unwrap();
+
+    // --- THEN: Purchases resume ---
+    let blockhash = context.get_new_latest_blockhash().await.unwrap();
+    let tx = Transaction::new_signed_with_payer(
+        &[buy_ix],
+        Some(&buyer.pubkey()),
+        &[&buyer],
+        blockhash,
+    );
+    context.banks_client.process_transaction(tx).await.unwrap();
+    let buyer_token_account = get_token_account(&mut context, &buyer_ata).await;
+    assert_eq!(buyer_token_account.amount, 10_000_000_000);
+}
+
+#[tokio::test]
+async fn test_set_and_read_launch_metadata() {
+    // --- SETUP: A standalone factory launch ---
+    let (mut context, launch) = setup_launch(false, |_| {}).await;
+    let authority = launch.authority;
+
+    // --- WHEN: The authority sets the launch's token metadata ---
+    let (launch_metadata_pda, _) = Pubkey::find_program_address(
+        &[b"launch_metadata", launch.launch_state.as_ref()],
+        &factory_program::id(),
+    );
+    let set_metadata_ix = Instruction {
+        program_id: factory_program::id(),
+        accounts: factory_program::accounts::SetLaunchMetadata {
+            launch_state: launch.launch_state,
+            launch_metadata: launch_metadata_pda,
+            authority,
+            system_program: system_program::id(),
+        }.to_account_metas(None),
+        data: factory_program::instruction::SetLaunchMetadata {
+            name: "Genesis Token".to_string(),
+            symbol: "GEN".to_string(),
+            uri: "https://example.com/gen.json".to_string(),
+        }.data(),
+    };
+    let tx = Transaction::new_signed_with_payer(
+        &[set_metadata_ix],
+        Some(&context.payer.pubkey()),
+        &[&context.payer],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(tx).await.unwrap();
+
+    // --- THEN: The metadata account holds the fields for the launch ---
+    let metadata_account = context.banks_client.get_account(launch_metadata_pda).await.unwrap().unwrap();
+    let metadata = factory_program::state::LaunchMetadata::try_deserialize(&mut metadata_account.data.as_slice()).unwrap();
+    assert_eq!(metadata.launch_state, launch.launch_state);
+    assert_eq!(metadata.name, "Genesis Token");
+    assert_eq!(metadata.symbol, "GEN");
+    assert_eq!(metadata.uri, "https://example.com/gen.json");
+}
+
+#[tokio::test]
+async fn test_platform_fee_split_three_ways() {
+    // --- SETUP: A standalone launch charging a 3% platform fee, split between three recipients ---
+    let (mut context, launch) = setup_launch(false, |args| args.platform_fee_bps = 300).await;
+    let authority = launch.authority;
+    let buyer = Keypair::new();
+    airdrop(&mut context, &buyer.pubkey(), 2_000_000_000).await;
+
+    let (fee_split_config_pda, _) = Pubkey::find_program_address(
+        &[b"fee_split_config", launch.launch_state.as_ref()],
+        &factory_program::id(),
+    );
+    let platform = Pubkey::new_unique();
+    let co_marketer = Pubkey::new_unique();
+    let treasury = Pubkey::new_unique();
+    let set_fee_split_ix = Instruction {
+        program_id: factory_program::id(),
+        accounts: factory_program::accounts::SetFeeSplit {
+            launch_state: launch.launch_state,
+            fee_split_config: fee_split_config_pda,
+            authority,
+            system_program: system_program::id(),
+        }.to_account_metas(None),
+        data: factory_program::instruction::SetFeeSplit {
+            recipients: vec![
+                factory_program::state::FeeSplitRecipient { recipient: platform, share_bps: 5_000 },
+                factory_program::state::FeeSplitRecipient { recipient: co_marketer, share_bps: 3_000 },
+                factory_program::state::FeeSplitRecipient { recipient: treasury, share_bps: 2_000 },
+            ],
+        }.data(),
+    };
+    let tx = Transaction::new_signed_with_payer(
+        &[set_fee_split_ix],
+        Some(&context.payer.pubkey()),
+        &[&context.payer],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(tx).await.unwrap();
+
+    // --- WHEN: A 1 SOL purchase passes the recipients as remaining accounts, in split order ---
+    let mut accounts = factory_program::accounts::BuyTokens {
+        fee_split_config: Some(fee_split_config_pda),
+        ..launch.buy_accounts(&buyer.pubkey(), &buyer.pubkey())
+    }.to_account_metas(None);
+    accounts.extend([platform, co_marketer, treasury].iter().map(|recipient| AccountMeta::new(*recipient, false)));
+    let buy_ix = Instruction {
+        program_id: factory_program::id(),
+        accounts,
+        data: factory_program::instruction::BuyTokens {
+            sol_amount: 1_000_000_000,
+            affiliate_key: None,
+            enable_vesting: false,
+            min_tokens_out: 0,
+            whitelist_proof: vec![],
+            recipient: None,
+        }.data(),
+    };
+    let tx = Transaction::new_signed_with_payer(
+        &[buy_ix],
+        Some(&buyer.pubkey()),
+        &[&buyer],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(tx).await.unwrap();
+
+    // --- THEN: The 0.03 SOL platform fee arrives 50/30/20 and the vault holds the rest ---
+    assert_eq!(context.banks_client.get_balance(platform).await.unwrap(), 15_000_000);
+    assert_eq!(context.banks_client.get_balance(co_marketer).await.unwrap(), 9_000_000);
+    assert_eq!(context.banks_client.get_balance(treasury).await.unwrap(), 6_000_000);
+    let launch_account = context.banks_client.get_account(launch.launch_state).await.unwrap().unwrap();
+    let launch_data = factory_program::state::LaunchState::try_deserialize(&mut launch_account.data.as_slice()).unwrap();
+    assert_eq!(launch_data.total_sol_collected, 970_000_000);
+}
+
+#[tokio::test]
+async fn test_close_settled_launch_returns_rent() {
+    // --- GIVEN: A factory-only launch with no purchases, which ends and is finalized below ---
+    let (mut context, launch) = setup_launch(false, |args| args.launch_end_time = args.launch_start_time + 100).await;
+    let authority = launch.authority;
+
+    let close_launch_ix = Instruction {
+        program_id: factory_program::id(),
+        accounts: factory_program::accounts::CloseLaunch {
+            launch_state: launch.launch_state,
+            sol_vault: launch.sol_vault,
+            authority,
+        }.to_account_metas(None),
+        data: factory_program::instruction::CloseLaunch {}.data(),
+    };
+
+    // Closing before finalization is rejected.
+    let tx = Transaction::new_signed_with_payer(
+        &[close_launch_ix.clone()],
+        Some(&context.payer.pubkey()),
+        &[&context.payer],
+        context.last_blockhash,
+    );
+    assert!(context.banks_client.process_transaction(tx).await.is_err(), "An unfinalized launch should not close");
+
+    context.set_sysvar(&Clock { unix_timestamp: launch.launch_end_time + 1, ..launch.clock.clone() });
+    let finalize_ix = Instruction {
+        program_id: factory_program::id(),
+        accounts: factory_program::accounts::FinalizeLaunch {
+            launch_state: launch.launch_state,
+            token_mint: launch.token_mint,
+            authority,
+            token_program: spl_token::id(),
+        }.to_account_metas(None),
+        data: factory_program::instruction::FinalizeLaunch {}.data(),
+    };
+    let tx = Transaction::new_signed_with_payer(
+        &[finalize_ix],
+        Some(&context.payer.pubkey()),
+        &[&context.payer],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(tx).await.unwrap();
+
+    let launch_rent = context.banks_client.get_account(launch.launch_state).await.unwrap().unwrap().lamports;
+    let authority_before = context.banks_client.get_balance(authority).await.unwrap();
+
+    // --- WHEN: The authority closes the settled launch ---
+    let blockhash = context.get_new_latest_blockhash().await.unwrap();
+    let tx = Transaction::new_signed_with_payer(
+        &[close_launch_ix],
+        Some(&context.payer.pubkey()),
+        &[&context.payer],
+        blockhash,
+    );
+    context.banks_client.process_transaction(tx).await.unwrap();
+
+    // --- THEN: The launch state is gone and its rent went back to the authority, less the tx fee ---
+    assert!(context.banks_client.get_account(launch.launch_state).await.unwrap().is_none());
+    let authority_after = context.banks_client.get_balance(authority).await.unwrap();
+    let fee = 5_000; // one signature at the default lamports_per_signature
+    assert_eq!(authority_after, authority_before + launch_rent - fee);
+}
+
+#[tokio::test]
+async fn test_register_affiliate_enforces_descendant_cap() {
+    use anchor_lang::{AccountDeserialize, AccountSerialize};
+    use genesis_common::constants::MAX_AFFILIATE_DESCENDANTS;
+    use solana_sdk::account::AccountSharedData;
+
+    // --- SETUP: Only the affiliate program is needed ---
+    let pt = ProgramTest::new(
+        "affiliate_program",
+        affiliate_program::id(),
+        processor!(affiliate_program::entry),
+    );
+    let mut context = pt.start_with_context().await;
+
+    let parent = Keypair::new();
+    let children = [Keypair::new(), Keypair::new()];
+    airdrop(&mut context, &parent.pubkey(), 1_000_000_000).await;
+    for child in &children {
+        airdrop(&mut context, &child.pubkey(), 1_000_000_000).await;
+    }
+
+    let affiliate_info_pda = |affiliate: &Pubkey| {
+        Pubkey::find_program_address(&[b"affiliate_info", affiliate.as_ref()], &affiliate_program::id()).0
+    };
+    let (ecosystem_stats_pda, _) = Pubkey::find_program_address(&[b"ecosystem_stats"], &affiliate_program::id());
+    let parent_info_pda = affiliate_info_pda(&parent.pubkey());
+
+    let register_ix = |affiliate: &Pubkey, parent_affiliate: Option<Pubkey>| Instruction {
+        program_id: affiliate_program::id(),
+        accounts: affiliate_program::accounts::RegisterAffiliate {
+            affiliate_info: affiliate_info_pda(affiliate),
+            ecosystem_stats: ecosystem_stats_pda,
+            parent_affiliate_info: parent_affiliate.map(|key| affiliate_info_pda(&key)),
+            affiliate: *affiliate,
+            system_program: system_program::id(),
+        }.to_account_metas(None),
+        data: affiliate_program::instruction::RegisterAffiliate {
+            args: affiliate_program::RegisterAffiliateArgs {
+                parent_affiliate,
+                referral_level: if parent_affiliate.is_some() { 2 } else { 1 },
+                rate_caps_enabled: false,
+                max_commission_rate_bps: 2000,
+                min_commission_rate_bps: 50,
+            },
+        }.data(),
+    };
+
+    // --- GIVEN: A parent affiliate one descendant short of the cap ---
+    let tx = Transaction::new_signed_with_payer(
+        &[register_ix(&parent.pubkey(), None)],
+        Some(&parent.pubkey()),
+        &[&parent],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(tx).await.unwrap();
+
+    let mut parent_account = context.banks_client.get_account(parent_info_pda).await.unwrap().unwrap();
+    let mut parent_info = affiliate_program::state::AffiliateInfo::try_deserialize(&mut parent_account.data.as_slice()).unwrap();
+    parent_info.total_descendants = MAX_AFFILIATE_DESCENDANTS - 1;
+    let mut data = Vec::with_capacity(parent_account.data.len());
+    parent_info.try_serialize(&mut data).unwrap();
+    parent_account.data[..data.len()].copy_from_slice(&data);
+    context.set_account(&parent_info_pda, &AccountSharedData::from(parent_account));
+
+    // --- WHEN: Two sub-affiliates register under it ---
+    let tx = Transaction::new_signed_with_payer(
+        &[register_ix(&children[0].pubkey(), Some(parent.pubkey()))],
+        Some(&children[0].pubkey()),
+        &[&children[0]],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(tx).await.unwrap();
+
+    let tx = Transaction::new_signed_with_payer(
+        &[register_ix(&children[1].pubkey(), Some(parent.pubkey()))],
+        Some(&children[1].pubkey()),
+        &[&children[1]],
+        context.last_blockhash,
+    );
+    let result = context.banks_client.process_transaction(tx).await;
+
+    // --- THEN: The first reaches the cap and the second is rejected ---
+    assert!(result.is_err(), "Registering beyond the descendant cap should fail");
+    let parent_account = context.banks_client.get_account(parent_info_pda).await.unwrap().unwrap();
+    let parent_info = affiliate_program::state::AffiliateInfo::try_deserialize(&mut parent_account.data.as_slice()).unwrap();
+    assert_eq!(parent_info.total_descendants, MAX_AFFILIATE_DESCENDANTS);
+    assert!(context.banks_client.get_account(affiliate_info_pda(&children[1].pubkey())).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_register_affiliates_batch_for_campaign() {
+    use anchor_lang::AccountDeserialize;
+
+    // --- SETUP: Only the affiliate program is needed ---
+    let pt = ProgramTest::new(
+        "affiliate_program",
+        affiliate_program::id(),
+        processor!(affiliate_program::entry),
+    );
+    let mut context = pt.start_with_context().await;
+
+    let campaign_lead = Keypair::new();
+    let payer = Keypair::new();
+    let affiliates = [Keypair::new(), Keypair::new(), Keypair::new()];
+    airdrop(&mut context, &campaign_lead.pubkey(), 1_000_000_000).await;
+    airdrop(&mut context, &payer.pubkey(), 1_000_000_000).await;
+
+    let affiliate_info_pda = |affiliate: &Pubkey| {
+        Pubkey::find_program_address(&[b"affiliate_info", affiliate.as_ref()], &affiliate_program::id()).0
+    };
+    let (ecosystem_stats_pda, _) = Pubkey::find_program_address(&[b"ecosystem_stats"], &affiliate_program::id());
+    let lead_info_pda = affiliate_info_pda(&campaign_lead.pubkey());
+    let args = |parent_affiliate: Option<Pubkey>| affiliate_program::RegisterAffiliateArgs {
+        parent_affiliate,
+        referral_level: if parent_affiliate.is_some() { 2 } else { 1 },
+        rate_caps_enabled: false,
+        max_commission_rate_bps: 2000,
+        min_commission_rate_bps: 50,
+    };
+
+    // --- GIVEN: A registered campaign lead ---
+    let register_lead_ix = Instruction {
+        program_id: affiliate_program::id(),
+        accounts: affiliate_program::accounts::RegisterAffiliate {
+            affiliate_info: lead_info_pda,
+            ecosystem_stats: ecosystem_stats_pda,
+            parent_affiliate_info: None,
+            affiliate: campaign_lead.pubkey(),
+            system_program: system_program::id(),
+        }.to_account_metas(None),
+        data: affiliate_program::instruction::RegisterAffiliate { args: args(None) }.data(),
+    };
+    let tx = Transaction::new_signed_with_payer(
+        &[register_lead_ix],
+        Some(&campaign_lead.pubkey()),
+        &[&campaign_lead],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(tx).await.unwrap();
+
+    // ...and a third affiliate's account address that someone already sent lamports to.
+    airdrop(&mut context, &affiliate_info_pda(&affiliates[2].pubkey()), 1_000).await;
+
+    // --- WHEN: A campaign payer registers three affiliates in one transaction, two under the lead ---
+    let mut accounts = affiliate_program::accounts::RegisterAffiliatesBatch {
+        ecosystem_stats: ecosystem_stats_pda,
+        parent_affiliate_info: Some(lead_info_pda),
+        payer: payer.pubkey(),
+        system_program: system_program::id(),
+    }.to_account_metas(None);
+    // Each affiliate's account is followed by the affiliate's wallet, signing to consent.
+    for affiliate in &affiliates {
+        accounts.push(AccountMeta::new(affiliate_info_pda(&affiliate.pubkey()), false));
+        accounts.push(AccountMeta::new_readonly(affiliate.pubkey(), true));
+    }
+    let batch_ix = Instruction {
+        program_id: affiliate_program::id(),
+        accounts,
+        data: affiliate_program::instruction::RegisterAffiliatesBatch {
+            registrations: vec![
+                affiliate_program::AffiliateRegistration { affiliate: affiliates[0].pubkey(), args: args(Some(campaign_lead.pubkey())) },
+                affiliate_program::AffiliateRegistration { affiliate: affiliates[1].pubkey(), args: args(Some(campaign_lead.pubkey())) },
+                affiliate_program::AffiliateRegistration { affiliate: affiliates[2].pubkey(), args: args(None) },
+            ],
+        }.data(),
+    };
+    let tx = Transaction::new_signed_with_payer(
+        &[batch_ix],
+        Some(&payer.pubkey()),
+        &[&payer, &affiliates[0], &affiliates[1], &affiliates[2]],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(tx).await.unwrap();
+
+    // --- THEN: Each affiliate owns its own account, the lead counts two descendants, and all four are in the stats ---
+    for (affiliate, parent) in affiliates.iter().zip([Some(campaign_lead.pubkey()), Some(campaign_lead.pubkey()), None]) {
+        let account = context.banks_client.get_account(affiliate_info_pda(&affiliate.pubkey())).await.unwrap().unwrap();
+        assert_eq!(account.owner, affiliate_program::id());
+        let info = affiliate_program::state::AffiliateInfo::try_deserialize(&mut account.data.as_slice()).unwrap();
+        assert_eq!(info.affiliate_key, affiliate.pubkey());
+        assert_eq!(info.parent_affiliate, parent);
+        assert_eq!(info.commission_rate_bps, 1000);
+    }
+
+    let lead_account = context.banks_client.get_account(lead_info_pda).await.unwrap().unwrap();
+    let lead_info = affiliate_program::state::AffiliateInfo::try_deserialize(&mut lead_account.data.as_slice()).unwrap();
+    assert_eq!(lead_info.total_descendants, 2);
+
+    let stats_account = context.banks_client.get_account(ecosystem_stats_pda).await.unwrap().unwrap();
+    let stats = affiliate_program::state::EcosystemStats::try_deserialize(&mut stats_account.data.as_slice()).unwrap();
+    assert_eq!(stats.total_affiliates, 4);
+}
+
+#[tokio::test]
+async fn test_dex_swap_both_directions_and_stale_oracle() {
+    use anchor_spl::associated_token::{get_associated_token_address, spl_associated_token_account};
+
+    // --- SETUP: An AI-oracle pool over two mints the test can mint from ---
+    let mut pt = ProgramTest::new(
+        "barter_dex_program",
+        barter_dex_program::id(),
+        processor!(barter_dex_program::entry),
+    );
+
+    let mint_authority = Keypair::new();
+    let mint_a = Pubkey::new_unique();
+    let mint_b = Pubkey::new_unique();
+    for mint in [mint_a, mint_b] {
+        let mut data = vec![0u8; spl_token::state::Mint::LEN];
+        spl_token::state::Mint {
+            mint_authority: anchor_lang::solana_program::program_option::COption::Some(mint_authority.pubkey()),
+            supply: 0,
+            decimals: 9,
+            is_initialized: true,
+            freeze_authority: anchor_lang::solana_program::program_option::COption::None,
+        }.pack_into_slice(&mut data);
+        pt.add_account(mint, solana_sdk::account::Account {
+            lamports: 1_000_000_000,
+            data,
+            owner: spl_token::id(),
+            executable: false,
+            rent_epoch: 0,
+        });
+    }
+
+    let mut context = pt.start_with_context().await;
+    let authority = context.payer.pubkey();
+    let trader = Keypair::new();
+    airdrop(&mut context, &trader.pubkey(), 1_000_000_000).await;
+    let clock: Clock = context.banks_client.get_sysvar().await.unwrap();
+    context.set_sysvar(&Clock { unix_timestamp: 1_000, ..clock.clone() });
+
+    let (pool_pda, _) = Pubkey::find_program_address(
+        &[b"liquidity_pool", mint_a.as_ref(), mint_b.as_ref()],
+        &barter_dex_program::id(),
+    );
+    let (vault_a, _) = Pubkey::find_program_address(
+        &[b"pool_vault", mint_a.as_ref(), mint_b.as_ref(), b"a"],
+        &barter_dex_program::id(),
+    );
+    let (vault_b, _) = Pubkey::find_program_address(
+        &[b"pool_vault", mint_a.as_ref(), mint_b.as_ref(), b"b"],
+        &barter_dex_program::id(),
+    );
+    let (lp_mint, _) = Pubkey::find_program_address(
+        &[b"lp_mint", mint_a.as_ref(), mint_b.as_ref()],
+        &barter_dex_program::id(),
+    );
+
+    let create_pool_ix = Instruction {
+        program_id: barter_dex_program::id(),
+        accounts: barter_dex_program::accounts::CreatePool {
+            pool: pool_pda,
+            vault_a,
+            vault_b,
+            lp_mint,
+            mint_a,
+            mint_b,
+            authority,
+            system_program: system_program::id(),
+            token_program: spl_token::id(),
+            rent: sysvar::rent::id(),
+        }.to_account_metas(None),
+        data: barter_dex_program::instruction::CreatePool {
+            args: barter_dex_program::CreatePoolArgs {
+                oracle_authority: authority,
+                oracle_provider: barter_dex_program::state::OracleProvider::AIOracle,
+                pyth_price_feed_a: None,
+                pyth_price_feed_b: None,
+                switchboard_feed: None,
+                ai_oracle_program: None,
+                fee_bps: 30,
+                dynamic_fee_enabled: false,
+                volatility_threshold: 0,
+                max_staleness_seconds: 300,
+                hard_stale_seconds: 900,
+                stale_fee_bps: 50,
+                use_twap: false,
+                twap_window: 0,
+                smoothing_enabled: false,
+                ema_alpha_bps: 2_000,
+                max_price_move_bps: 0,
+                price_confirm_delay_seconds: 60,
+                max_price_deviation_bps: 0,
+                config_update_cooldown_seconds: 3_600,
+                price_tick: 0,
+                max_confidence_bps: 0,
+                amm_blend_enabled: false,
+                oracle_weight_bps: 10_000,
+                pyth_weight: DEFAULT_PYTH_WEIGHT,
+                switchboard_weight: DEFAULT_SWITCHBOARD_WEIGHT,
+                ai_weight: DEFAULT_AI_WEIGHT,
+                min_price_sources: 1,
+                require_ratio: false,
+            },
+        }.data(),
+    };
+
+    // Token accounts for the liquidity provider (the authority) and the trader, funded by minting.
+    let mut setup_ixs = vec![
+        create_pool_ix,
+        spl_associated_token_account::instruction::create_associated_token_account(
+            &authority,
+            &authority,
+            &lp_mint,
+            &spl_token::id(),
+        ),
+    ];
+    for (owner, amount) in [(authority, 5_000_000_000_000), (trader.pubkey(), 10_000_000_000)] {
+        for mint in [mint_a, mint_b] {
+            setup_ixs.push(spl_associated_token_account::instruction::create_associated_token_account(
+                &authority,
+                &owner,
+                &mint,
+                &spl_token::id(),
+            ));
+            setup_ixs.push(spl_token::instruction::mint_to(
+                &spl_token::id(),
+                &mint,
+                &get_associated_token_address(&owner, &mint),
+                &mint_authority.pubkey(),
+                &[],
+                amount,
+            ).unwrap());
+        }
+    }
+    let tx = Transaction::new_signed_with_payer(
+        &setup_ixs,
+        Some(&authority),
+        &[&context.payer, &mint_authority],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(tx).await.unwrap();
+
+    let lp_a = get_associated_token_address(&authority, &mint_a);
+    let lp_b = get_associated_token_address(&authority, &mint_b);
+    let lp_tokens = get_associated_token_address(&authority, &lp_mint);
+    let trader_a = get_associated_token_address(&trader.pubkey(), &mint_a);
+    let trader_b = get_associated_token_address(&trader.pubkey(), &mint_b);
+
+    // --- GIVEN: 1,000 A and 2,000 B of liquidity and an oracle price of 2 B per A ---
+    let add_liquidity_ix = Instruction {
+        program_id: barter_dex_program::id(),
+        accounts: barter_dex_program::accounts::AddLiquidity {
+            pool: pool_pda,
+            vault_a,
+            vault_b,
+            lp_mint,
+            user_lp_token_account: lp_tokens,
+            user_token_account_a: lp_a,
+            user_token_account_b: lp_b,
+            user: authority,
+            token_program: spl_token::id(),
+        }.to_account_metas(None),
+        data: barter_dex_program::instruction::AddLiquidity {
+            amount_a: 1_000_000_000_000,
+            amount_b: 2_000_000_000_000,
+            min_lp_out: 0,
+        }.data(),
+    };
+    let update_price_ix = Instruction {
+        program_id: barter_dex_program::id(),
+        accounts: barter_dex_program::accounts::UpdateOraclePrice {
+            pool: pool_pda,
+            oracle_authority: authority,
+        }.to_account_metas(None),
+        data: barter_dex_program::instruction::UpdateOraclePrice {
+            args: barter_dex_program::UpdatePriceArgs {
+                pyth_price: None,
+                switchboard_price: None,
+                ai_price: Some(2_000_000_000),
+                price_confidence: None,
+                override_deviation: false,
+            },
+        }.data(),
+    };
+    let tx = Transaction::new_signed_with_payer(
+        &[update_price_ix, add_liquidity_ix],
+        Some(&authority),
+        &[&context.payer],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(tx).await.unwrap();
+
+    let pool_account = context.banks_client.get_account(pool_pda).await.unwrap().unwrap();
+    let pool = barter_dex_program::state::LiquidityPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+    assert_eq!(pool.oracle_price, 2_000_000_000);
+    assert_eq!(pool.total_liquidity_a, 1_000_000_000_000);
+    assert_eq!(pool.total_liquidity_b, 2_000_000_000_000);
+    // The deposit is worth 4,000 B; all of it but MINIMUM_LIQUIDITY is minted as LP tokens.
+    assert_eq!(pool.lp_supply, 4_000_000_000_000);
+    assert_eq!(get_token_account(&mut context, &lp_tokens).await.amount, 3_999_999_000_000);
+
+    let swap_ix = |source: Pubkey, dest: Pubkey, amount_in: u64| Instruction {
+        program_id: barter_dex_program::id(),
+        accounts: barter_dex_program::accounts::Swap {
+            pool: pool_pda,
+            vault_a,
+            vault_b,
+            user_source_token_account: source,
+            user_dest_token_account: dest,
+            user: trader.pubkey(),
+            token_program: spl_token::id(),
+        }.to_account_metas(None),
+        data: barter_dex_program::instruction::Swap {
+            amount_in,
+            min_amount_out: 0,
+        }.data(),
+    };
+
+    // --- WHEN: The trader swaps 1 A for B, then 2 B for A ---
+    let tx = Transaction::new_signed_with_payer(
+        &[swap_ix(trader_a, trader_b, 1_000_000_000)],
+        Some(&trader.pubkey()),
+        &[&trader],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(tx).await.unwrap();
+
+    // --- THEN: A->B pays 2 B less the 0.3% fee (0.006 B) ---
+    assert_eq!(get_token_account(&mut context, &trader_a).await.amount, 9_000_000_000);
+    assert_eq!(get_token_account(&mut context, &trader_b).await.amount, 11_994_000_000);
+    assert_eq!(get_token_account(&mut context, &vault_a).await.amount, 1_001_000_000_000);
+    assert_eq!(get_token_account(&mut context, &vault_b).await.amount, 1_998_006_000_000);
+
+    let tx = Transaction::new_signed_with_payer(
+        &[swap_ix(trader_b, trader_a, 2_000_000_000)],
+        Some(&trader.pubkey()),
+        &[&trader],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(tx).await.unwrap();
+
+    // ...and B->A pays 1 A less the 0.3% fee (0.003 A).
+    assert_eq!(get_token_account(&mut context, &trader_a).await.amount, 9_997_000_000);
+    assert_eq!(get_token_account(&mut context, &trader_b).await.amount, 9_994_000_000);
+    assert_eq!(get_token_account(&mut context, &vault_a).await.amount, 1_000_003_000_000);
+    assert_eq!(get_token_account(&mut context, &vault_b).await.amount, 2_000_006_000_000);
+
+    let pool_account = context.banks_client.get_account(pool_pda).await.unwrap().unwrap();
+    let pool = barter_dex_program::state::LiquidityPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+    // The vaults hold the 0.003 A and 0.006 B of fees outside the LP reserves.
+    assert_eq!(pool.total_liquidity_a, 1_000_000_000_000);
+    assert_eq!(pool.total_liquidity_b, 2_000_000_000_000);
+    assert_eq!((pool.fees_a, pool.fees_b), (3_000_000, 6_000_000));
+    assert_eq!(pool.swap_count, 2);
+    assert_eq!(pool.cumulative_volume_a, 1_000_000_000 + 997_000_000);
+    assert_eq!(pool.cumulative_volume_b, 1_994_000_000 + 2_000_000_000);
+
+    // --- THEN: Once the price is older than `hard_stale_seconds`, swaps are rejected ---
+    context.set_sysvar(&Clock { unix_timestamp: 1_000 + 901, ..clock });
+    let blockhash = context.get_new_latest_blockhash().await.unwrap();
+    let tx = Transaction::new_signed_with_payer(
+        &[swap_ix(trader_a, trader_b, 1_000_000_000)],
+        Some(&trader.pubkey()),
+        &[&trader],
+        blockhash,
+    );
+    assert!(context.banks_client.process_transaction(tx).await.is_err(), "Swapping against an expired oracle price should fail");
+    assert_eq!(get_token_account(&mut context, &trader_a).await.amount, 9_997_000_000);
+
+    // --- THEN: Burning every LP token withdraws the reserves, less the locked minimum's share ---
+    let remove_liquidity_ix = Instruction {
+        program_id: barter_dex_program::id(),
+        accounts: barter_dex_program::accounts::RemoveLiquidity {
+            pool: pool_pda,
+            vault_a,
+            vault_b,
+            lp_mint,
+            user_lp_token_account: lp_tokens,
+            user_token_account_a: lp_a,
+            user_token_account_b: lp_b,
+            user: authority,
+            token_program: spl_token::id(),
+        }.to_account_metas(None),
+        data: barter_dex_program::instruction::RemoveLiquidity {
+            lp_amount: 3_999_999_000_000,
+        }.data(),
+    };
+    let tx = Transaction::new_signed_with_payer(
+        &[remove_liquidity_ix],
+        Some(&authority),
+        &[&context.payer],
+        blockhash,
+    );
+    context.banks_client.process_transaction(tx).await.unwrap();
+
+    assert_eq!(get_token_account(&mut context, &lp_tokens).await.amount, 0);
+    assert_eq!(get_token_account(&mut context, &lp_a).await.amount, 4_999_999_750_000);
+    assert_eq!(get_token_account(&mut context, &lp_b).await.amount, 4_999_999_500_000);
+    assert_eq!(get_token_account(&mut context, &vault_a).await.amount, 3_250_000);
+    assert_eq!(get_token_account(&mut context, &vault_b).await.amount, 6_500_000);
+
+    // --- THEN: The oracle authority sweeps the accrued fees, leaving only the locked reserves ---
+    let collect_fees_ix = Instruction {
+        program_id: barter_dex_program::id(),
+        accounts: barter_dex_program::accounts::CollectFees {
+            pool: pool_pda,
+            vault_a,
+            vault_b,
+            fee_destination_a: lp_a,
+            fee_destination_b: lp_b,
+            oracle_authority: authority,
+            token_program: spl_token::id(),
+        }.to_account_metas(None),
+        data: barter_dex_program::instruction::CollectFees {}.data(),
+    };
+    let tx = Transaction::new_signed_with_payer(
+        &[collect_fees_ix],
+        Some(&authority),
+        &[&context.payer],
+        blockhash,
+    );
+    context.banks_client.process_transaction(tx).await.unwrap();
+
+    assert_eq!(get_token_account(&mut context, &vault_a).await.amount, 250_000);
+    assert_eq!(get_token_account(&mut context, &vault_b).await.amount, 500_000);
+    let pool_account = context.banks_client.get_account(pool_pda).await.unwrap().unwrap();
+    let pool = barter_dex_program::state::LiquidityPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+    assert_eq!((pool.fees_a, pool.fees_b), (0, 0));
+
+    // --- WHEN: The oracle authority is rotated to a new key in two steps ---
+    let new_oracle_authority = Keypair::new();
+    let transfer_ix = Instruction {
+        program_id: barter_dex_program::id(),
+        accounts: barter_dex_program::accounts::TransferOracleAuthority {
+            pool: pool_pda,
+            oracle_authority: authority,
+        }.to_account_metas(None),
+        data: barter_dex_program::instruction::TransferOracleAuthority {
+            new_authority: new_oracle_authority.pubkey(),
+        }.data(),
+    };
+    let accept_ix = Instruction {
+        program_id: barter_dex_program::id(),
+        accounts: barter_dex_program::accounts::AcceptOracleAuthority {
+            pool: pool_pda,
+            new_oracle_authority: new_oracle_authority.pubkey(),
+        }.to_account_metas(None),
+        data: barter_dex_program::instruction::AcceptOracleAuthority {}.data(),
+    };
+    let tx = Transaction::new_signed_with_payer(
+        &[transfer_ix, accept_ix],
+        Some(&authority),
+        &[&context.payer, &new_oracle_authority],
+        blockhash,
+    );
+    context.banks_client.process_transaction(tx).await.unwrap();
+
+    // --- THEN: The old key can no longer push prices, and the new one can ---
+    let push_price_ix = |oracle_authority: Pubkey, ai_price: u64| Instruction {
+        program_id: barter_dex_program::id(),
+        accounts: barter_dex_program::accounts::UpdateOraclePrice {
+            pool: pool_pda,
+            oracle_authority,
+        }.to_account_metas(None),
+        data: barter_dex_program::instruction::UpdateOraclePrice {
+            args: barter_dex_program::UpdatePriceArgs {
+                pyth_price: None,
+                switchboard_price: None,
+                ai_price: Some(ai_price),
+                price_confidence: None,
+                override_deviation: false,
+            },
+        }.data(),
+    };
+    let tx = Transaction::new_signed_with_payer(
+        &[push_price_ix(authority, 2_100_000_000)],
+        Some(&authority),
+        &[&context.payer],
+        blockhash,
+    );
+    assert!(context.banks_client.process_transaction(tx).await.is_err(), "The rotated-out key should not push prices");
+
+    let tx = Transaction::new_signed_with_payer(
+        &[push_price_ix(new_oracle_authority.pubkey(), 2_200_000_000)],
+        Some(&authority),
+        &[&context.payer, &new_oracle_authority],
+        blockhash,
+    );
+    context.banks_client.process_transaction(tx).await.unwrap();
+    let pool_account = context.banks_client.get_account(pool_pda).await.unwrap().unwrap();
+    let pool = barter_dex_program::state::LiquidityPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+    assert_eq!(pool.oracle_authority, new_oracle_authority.pubkey());
+    assert_eq!(pool.ai_price, Some(2_200_000_000));
+
+    // --- WHEN: The new authority adds two redundant price keepers ---
+    let keepers = [Keypair::new(), Keypair::new()];
+    let add_signer_ixs: Vec<Instruction> = keepers.iter().map(|keeper| Instruction {
+        program_id: barter_dex_program::id(),
+        accounts: barter_dex_program::accounts::ManageOracleSigners {
+            pool: pool_pda,
+            oracle_authority: new_oracle_authority.pubkey(),
+        }.to_account_metas(None),
+        data: barter_dex_program::instruction::AddOracleSigner { signer: keeper.pubkey() }.data(),
+    }).collect();
+    let tx = Transaction::new_signed_with_payer(
+        &add_signer_ixs,
+        Some(&authority),
+        &[&context.payer, &new_oracle_authority],
+        blockhash,
+    );
+    context.banks_client.process_transaction(tx).await.unwrap();
+
+    // --- THEN: Both keepers can push prices, and an unauthorized key can't ---
+    for (keeper, ai_price) in keepers.iter().zip([2_300_000_000, 2_400_000_000]) {
+        let tx = Transaction::new_signed_with_payer(
+            &[push_price_ix(keeper.pubkey(), ai_price)],
+            Some(&authority),
+            &[&context.payer, keeper],
+            blockhash,
+        );
+        context.banks_client.process_transaction(tx).await.unwrap();
+    }
+    let pool_account = context.banks_client.get_account(pool_pda).await.unwrap().unwrap();
+    let pool = barter_dex_program::state::LiquidityPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+    assert_eq!(pool.ai_price, Some(2_400_000_000));
+
+    let outsider = Keypair::new();
+    let tx = Transaction::new_signed_with_payer(
+        &[push_price_ix(outsider.pubkey(), 2_500_000_000)],
+        Some(&authority),
+        &[&context.payer, &outsider],
+        blockhash,
+    );
+    assert!(context.banks_client.process_transaction(tx).await.is_err(), "Keys that aren't oracle signers should not push prices");
+}
+
+#[tokio::test]
+async fn test_affiliate_allowlist_pays_only_approved_affiliates() {
+    // --- GIVEN: A launch paying a 5% affiliate fee whose allowlist holds only `affiliate` ---
+    let (mut context, launch) = setup_launch(true, |args| {
+        args.launch_end_time = args.launch_start_time + 3_600;
+        args.affiliate_fee_bps = 500;
+    }).await;
+    let authority = launch.authority;
+    let affiliate = Keypair::new();
+    let outsider = Keypair::new();
+    let buyer = Keypair::new();
+    airdrop(&mut context, &affiliate.pubkey(), 1_000_000_000).await;
+    airdrop(&mut context, &outsider.pubkey(), 1_000_000_000).await;
+    airdrop(&mut context, &buyer.pubkey(), 2_000_000_000).await;
+    let affiliate_info_pda = |affiliate: &Pubkey| {
+        Pubkey::find_program_address(&[b"affiliate_info", affiliate.as_ref()], &affiliate_program::id()).0
+    };
+    let ecosystem_stats_pda = launch.ecosystem_stats.unwrap();
+
+    // Both affiliates register and hold a token account for the launch's commission.
+    for registrant in [&affiliate, &outsider] {
+        let register_ix = Instruction {
+            program_id: affiliate_program::id(),
+            accounts: affiliate_program::accounts::RegisterAffiliate {
+                affiliate_info: affiliate_info_pda(&registrant.pubkey()),
+                ecosystem_stats: ecosystem_stats_pda,
+                parent_affiliate_info: None,
+                affiliate: registrant.pubkey(),
+                system_program: system_program::id(),
+            }.to_account_metas(None),
+            data: affiliate_program::instruction::RegisterAffiliate {
+                args: affiliate_program::RegisterAffiliateArgs {
+                    parent_affiliate: None,
+                    referral_level: 1,
+                    rate_caps_enabled: false,
+                    max_commission_rate_bps: 2000,
+                    min_commission_rate_bps: 50,
+                },
+            }.data(),
+        };
+        let create_ata_ix = anchor_spl::associated_token::spl_associated_token_account::instruction::create_associated_token_account(
+            &registrant.pubkey(),
+            &registrant.pubkey(),
+            &launch.token_mint,
+            &spl_token::id(),
+        );
+        let tx = Transaction::new_signed_with_payer(
+            &[register_ix, create_ata_ix],
+            Some(&registrant.pubkey()),
+            &[registrant],
+            context.last_blockhash,
+        );
+        context.banks_client.process_transaction(tx).await.unwrap();
+    }
+
+    let allowlist_ix = Instruction {
+        program_id: factory_program::id(),
+        accounts: factory_program::accounts::SetApprovedAffiliates {
+            launch_state: launch.launch_state,
+            authority,
+        }.to_account_metas(None),
+        data: factory_program::instruction::SetApprovedAffiliates {
+            affiliates: vec![affiliate.pubkey()],
+        }.data(),
+    };
+    let tx = Transaction::new_signed_with_payer(
+        &[allowlist_ix],
+        Some(&context.payer.pubkey()),
+        &[&context.payer],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(tx).await.unwrap();
+
+    let buy_ix = |referrer: &Pubkey, sol_amount: u64| Instruction {
+        program_id: factory_program::id(),
+        accounts: factory_program::accounts::BuyTokens {
+            affiliate: Some(*referrer),
+            affiliate_info: Some(affiliate_info_pda(referrer)),
+            affiliate_token_account: Some(launch.ata(referrer)),
+            ..launch.buy_accounts(&buyer.pubkey(), &buyer.pubkey())
+        }.to_account_metas(None),
+        data: factory_program::instruction::BuyTokens {
+            sol_amount,
+            affiliate_key: Some(*referrer),
+            enable_vesting: false,
+            min_tokens_out: 0,
+            whitelist_proof: vec![],
+            recipient: None,
+        }.data(),
+    };
+
+    // --- WHEN: The buyer purchases once through each affiliate ---
+    let tx = Transaction::new_signed_with_payer(
+        &[buy_ix(&affiliate.pubkey(), 1_000_000_000)],
+        Some(&buyer.pubkey()),
+        &[&buyer],
+        context.last_blockhash,
+    );
+    let result = context.banks_client.process_transaction_with_metadata(tx).await.unwrap();
+    assert!(result.result.is_ok());
+    let approved_purchase = decode_events::<factory_program::PurchaseEvent>(&result.metadata.unwrap().log_messages).remove(0);
+
+    let tx = Transaction::new_signed_with_payer(
+        &[buy_ix(&outsider.pubkey(), 500_000_000)],
+        Some(&buyer.pubkey()),
+        &[&buyer],
+        context.last_blockhash,
+    );
+    let result = context.banks_client.process_transaction_with_metadata(tx).await.unwrap();
+    assert!(result.result.is_ok(), "A purchase through an unapproved affiliate should still succeed");
+    let outsider_purchase = decode_events::<factory_program::PurchaseEvent>(&result.metadata.unwrap().log_messages).remove(0);
+
+    // --- THEN: Only the approved affiliate is charged for and paid commission ---
+    assert_eq!(approved_purchase.affiliate_fee, 50_000_000);
+    let affiliate_ata = launch.ata(&affiliate.pubkey());
+    assert!(get_token_account(&mut context, &affiliate_ata).await.amount > 0);
+
+    assert_eq!(outsider_purchase.affiliate_fee, 0);
+    assert_eq!(outsider_purchase.net_sol_amount, 500_000_000);
+    assert!(outsider_purchase.tokens_minted > 0);
+    let outsider_ata = launch.ata(&outsider.pubkey());
+    assert_eq!(get_token_account(&mut context, &outsider_ata).await.amount, 0);
+
+    // --- THEN: A commission account for another mint is rejected, even for an approved affiliate ---
+    let other_mint = Keypair::new();
+    let rent = context.banks_client.get_rent().await.unwrap();
+    let wrong_mint_account = anchor_spl::associated_token::get_associated_token_address(&affiliate.pubkey(), &other_mint.pubkey());
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            system_instruction::create_account(
+                &authority,
+                &other_mint.pubkey(),
+                rent.minimum_balance(spl_token::state::Mint::LEN),
+                spl_token::state::Mint::LEN as u64,
+                &spl_token::id(),
+            ),
+            spl_token::instruction::initialize_mint2(&spl_token::id(), &other_mint.pubkey(), &authority, None, 9).unwrap(),
+            anchor_spl::associated_token::spl_associated_token_account::instruction::create_associated_token_account(
+                &authority,
+                &affiliate.pubkey(),
+                &other_mint.pubkey(),
+                &spl_token::id(),
+            ),
+        ],
+        Some(&authority),
+        &[&context.payer, &other_mint],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(tx).await.unwrap();
+
+    let mut wrong_mint_buy_ix = buy_ix(&affiliate.pubkey(), 500_000_000);
+    let commission_account_meta = wrong_mint_buy_ix.accounts.iter_mut()
+        .find(|meta| meta.pubkey == affiliate_ata)
+        .unwrap();
+    commission_account_meta.pubkey = wrong_mint_account;
+    let tx = Transaction::new_signed_with_payer(
+        &[wrong_mint_buy_ix],
+        Some(&buyer.pubkey()),
+        &[&buyer],
+        context.last_blockhash,
+    );
+    assert!(context.banks_client.process_transaction(tx).await.is_err(), "A wrong-mint commission account should be rejected");
+    assert_eq!(get_token_account(&mut context, &wrong_mint_account).await.amount, 0);
+}
+
+#[tokio::test]
+async fn test_commission_is_shared_with_parent_affiliate() {
+    // --- GIVEN: A launch paying a 5% affiliate fee ---
+    let (mut context, launch) = setup_launch(true, |args| {
+        args.launch_end_time = args.launch_start_time + 3_600;
+        args.affiliate_fee_bps = 500;
+    }).await;
+    let parent = Keypair::new();
+    let child = Keypair::new();
+    let buyer = Keypair::new();
+    airdrop(&mut context, &parent.pubkey(), 1_000_000_000).await;
+    airdrop(&mut context, &child.pubkey(), 1_000_000_000).await;
+    airdrop(&mut context, &buyer.pubkey(), 2_000_000_000).await;
+    let affiliate_info_pda = |affiliate: &Pubkey| {
+        Pubkey::find_program_address(&[b"affiliate_info", affiliate.as_ref()], &affiliate_program::id()).0
+    };
+    let ecosystem_stats_pda = launch.ecosystem_stats.unwrap();
+
+    // ...a level-1 parent affiliate and a level-2 child registered under it, each holding a
+    // token account for the launch's commission.
+    for (registrant, parent_affiliate) in [(&parent, None), (&child, Some(parent.pubkey()))] {
+        let register_ix = Instruction {
+            program_id: affiliate_program::id(),
+            accounts: affiliate_program::accounts::RegisterAffiliate {
+                affiliate_info: affiliate_info_pda(&registrant.pubkey()),
+                ecosystem_stats: ecosystem_stats_pda,
+                parent_affiliate_info: parent_affiliate.map(|key| affiliate_info_pda(&key)),
+                affiliate: registrant.pubkey(),
+                system_program: system_program::id(),
+            }.to_account_metas(None),
+            data: affiliate_program::instruction::RegisterAffiliate {
+                args: affiliate_program::RegisterAffiliateArgs {
+                    parent_affiliate,
+                    referral_level: if parent_affiliate.is_some() { 2 } else { 1 },
+                    rate_caps_enabled: false,
+                    max_commission_rate_bps: 2000,
+                    min_commission_rate_bps: 50,
+                },
+            }.data(),
+        };
+        let create_ata_ix = anchor_spl::associated_token::spl_associated_token_account::instruction::create_associated_token_account(
+            &registrant.pubkey(),
+            &registrant.pubkey(),
+            &launch.token_mint,
+            &spl_token::id(),
+        );
+        let tx = Transaction::new_signed_with_payer(
+            &[register_ix, create_ata_ix],
+            Some(&registrant.pubkey()),
+            &[registrant],
+            context.last_blockhash,
+        );
+        context.banks_client.process_transaction(tx).await.unwrap();
+    }
+
+    // --- WHEN: The buyer purchases through the child, passing the parent's accounts ---
+    let mut buy_ix = Instruction {
+        program_id: factory_program::id(),
+        accounts: factory_program::accounts::BuyTokens {
+            affiliate: Some(child.pubkey()),
+            affiliate_info: Some(affiliate_info_pda(&child.pubkey())),
+            affiliate_token_account: Some(launch.ata(&child.pubkey())),
+            ..launch.buy_accounts(&buyer.pubkey(), &buyer.pubkey())
+        }.to_account_metas(None),
+        data: factory_program::instruction::BuyTokens {
+            sol_amount: 1_000_000_000,
+            affiliate_key: Some(child.pubkey()),
+            enable_vesting: false,
+            min_tokens_out: 0,
+            whitelist_proof: vec![],
+            recipient: None,
+        }.data(),
+    };
+    buy_ix.accounts.push(AccountMeta::new_readonly(affiliate_info_pda(&parent.pubkey()), false));
+    buy_ix.accounts.push(AccountMeta::new(launch.ata(&parent.pubkey()), false));
+    let tx = Transaction::new_signed_with_payer(
+        &[buy_ix],
+        Some(&buyer.pubkey()),
+        &[&buyer],
+        context.last_blockhash,
+    );
+    let result = context.banks_client.process_transaction_with_metadata(tx).await.unwrap();
+    assert!(result.result.is_ok());
+    let purchase = decode_events::<factory_program::PurchaseEvent>(&result.metadata.unwrap().log_messages).remove(0);
+
+    // --- THEN: The child is paid its 10% commission and the parent half of that on top ---
+    let child_commission = get_token_account(&mut context, &launch.ata(&child.pubkey())).await.amount;
+    let parent_commission = get_token_account(&mut context, &launch.ata(&parent.pubkey())).await.amount;
+    assert_eq!(child_commission, purchase.tokens_minted / 10);
+    assert!(child_commission > 0);
+    assert_eq!(parent_commission, child_commission / 2);
+
+    let launch_account = context.banks_client.get_account(launch.launch_state).await.unwrap().unwrap();
+    let launch_data = factory_program::state::LaunchState::try_deserialize(&mut launch_account.data.as_slice()).unwrap();
+    assert_eq!(launch_data.affiliate_commission_minted, child_commission + parent_commission);
+}
+
+#[tokio::test]
+async fn test_close_affiliate_reclaims_accounts() {
+    use anchor_lang::AccountDeserialize;
+
+    // --- SETUP: Only the affiliate program is needed ---
+    let pt = ProgramTest::new(
+        "affiliate_program",
+        affiliate_program::id(),
+        processor!(affiliate_program::entry),
+    );
+    let mut context = pt.start_with_context().await;
+
+    let parent = Keypair::new();
+    let child = Keypair::new();
+    airdrop(&mut context, &parent.pubkey(), 1_000_000_000).await;
+    airdrop(&mut context, &child.pubkey(), 1_000_000_000).await;
+
+    let affiliate_info_pda = |affiliate: &Pubkey| {
+        Pubkey::find_program_address(&[b"affiliate_info", affiliate.as_ref()], &affiliate_program::id()).0
+    };
+    let (ecosystem_stats_pda, _) = Pubkey::find_program_address(&[b"ecosystem_stats"], &affiliate_program::id());
+    let (analytics_pda, _) = Pubkey::find_program_address(
+        &[b"affiliate_analytics", child.pubkey().as_ref()],
+        &affiliate_program::id(),
+    );
+    let parent_info_pda = affiliate_info_pda(&parent.pubkey());
+    let child_info_pda = affiliate_info_pda(&child.pubkey());
+
+    // --- GIVEN: A child affiliate registered under a parent, with analytics recorded ---
+    for (registrant, parent_affiliate) in [(&parent, None), (&child, Some(parent.pubkey()))] {
+        let register_ix = Instruction {
+            program_id: affiliate_program::id(),
+            accounts: affiliate_program::accounts::RegisterAffiliate {
+                affiliate_info: affiliate_info_pda(&registrant.pubkey()),
+                ecosystem_stats: ecosystem_stats_pda,
+                parent_affiliate_info: parent_affiliate.map(|key| affiliate_info_pda(&key)),
+                affiliate: registrant.pubkey(),
+                system_program: system_program::id(),
+            }.to_account_metas(None),
+            data: affiliate_program::instruction::RegisterAffiliate {
+                args: affiliate_program::RegisterAffiliateArgs {
+                    parent_affiliate,
+                    referral_level: if parent_affiliate.is_some() { 2 } else { 1 },
+                    rate_caps_enabled: false,
+                    max_commission_rate_bps: 2000,
+                    min_commission_rate_bps: 50,
+                },
+            }.data(),
+        };
+        let tx = Transaction::new_signed_with_payer(
+            &[register_ix],
+            Some(&registrant.pubkey()),
+            &[registrant],
+            context.last_blockhash,
+        );
+        context.banks_client.process_transaction(tx).await.unwrap();
+    }
+
+    let analytics_ix = Instruction {
+        program_id: affiliate_program::id(),
+        accounts: affiliate_program::accounts::UpdateAnalytics {
+            affiliate_info: child_info_pda,
+            analytics: analytics_pda,
+            affiliate: child.pubkey(),
+            system_program: system_program::id(),
+        }.to_account_metas(None),
+        data: affiliate_program::instruction::UpdateAnalytics {
+            args: affiliate_program::UpdateAnalyticsArgs { volume: 1_000, clicks: 10 },
+        }.data(),
+    };
+    let tx = Transaction::new_signed_with_payer(
+        &[analytics_ix],
+        Some(&child.pubkey()),
+        &[&child],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(tx).await.unwrap();
+    let balance_before = context.banks_client.get_balance(child.pubkey()).await.unwrap();
+
+    // --- WHEN: The child closes its affiliate account ---
+    let close_ix = Instruction {
+        program_id: affiliate_program::id(),
+        accounts: affiliate_program::accounts::CloseAffiliate {
+            affiliate_info: child_info_pda,
+            analytics: Some(analytics_pda),
+            parent_affiliate_info: Some(parent_info_pda),
+            ecosystem_stats: ecosystem_stats_pda,
+            affiliate_key: child.pubkey(),
+        }.to_account_metas(None),
+        data: affiliate_program::instruction::CloseAffiliate {}.data(),
+    };
+    let tx = Transaction::new_signed_with_payer(
+        &[close_ix],
+        Some(&child.pubkey()),
+        &[&child],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(tx).await.unwrap();
+
+    // --- THEN: Both accounts are gone, their rent is back with the child, and neither the
+    // parent nor the ecosystem stats count it as active ---
+    assert!(context.banks_client.get_account(child_info_pda).await.unwrap().is_none());
+    assert!(context.banks_client.get_account(analytics_pda).await.unwrap().is_none());
+    assert!(context.banks_client.get_balance(child.pubkey()).await.unwrap() > balance_before);
+
+    let parent_account = context.banks_client.get_account(parent_info_pda).await.unwrap().unwrap();
+    let parent_info = affiliate_program::state::AffiliateInfo::try_deserialize(&mut parent_account.data.as_slice()).unwrap();
+    assert_eq!((parent_info.total_descendants, parent_info.active_descendants), (1, 0));
+    let stats_account = context.banks_client.get_account(ecosystem_stats_pda).await.unwrap().unwrap();
+    let stats = affiliate_program::state::EcosystemStats::try_deserialize(&mut stats_account.data.as_slice()).unwrap();
+    assert_eq!(stats.total_affiliates, 1);
+}
+
+#[tokio::test]
+async fn test_closing_affiliates_cannot_reset_the_descendant_cap() {
+    use genesis_common::constants::MAX_AFFILIATE_DESCENDANTS;
+    use solana_sdk::account::AccountSharedData;
+
+    // --- SETUP: Only the affiliate program is needed ---
+    let pt = ProgramTest::new(
+        "affiliate_program",
+        affiliate_program::id(),
+        processor!(affiliate_program::entry),
+    );
+    let mut context = pt.start_with_context().await;
+
+    let parent = Keypair::new();
+    let child = Keypair::new();
+    airdrop(&mut context, &parent.pubkey(), 1_000_000_000).await;
+    airdrop(&mut context, &child.pubkey(), 1_000_000_000).await;
+
+    let affiliate_info_pda = |affiliate: &Pubkey| {
+        Pubkey::find_program_address(&[b"affiliate_info", affiliate.as_ref()], &affiliate_program::id()).0
+    };
+    let (ecosystem_stats_pda, _) = Pubkey::find_program_address(&[b"ecosystem_stats"], &affiliate_program::id());
+    let parent_info_pda = affiliate_info_pda(&parent.pubkey());
+    let child_info_pda = affiliate_info_pda(&child.pubkey());
+
+    let register_ix = |affiliate: &Pubkey, parent_affiliate: Option<Pubkey>| Instruction {
+        program_id: affiliate_program::id(),
+        accounts: affiliate_program::accounts::RegisterAffiliate {
+            affiliate_info: affiliate_info_pda(affiliate),
+            ecosystem_stats: ecosystem_stats_pda,
+            parent_affiliate_info: parent_affiliate.map(|key| affiliate_info_pda(&key)),
+            affiliate: *affiliate,
+            system_program: system_program::id(),
+        }.to_account_metas(None),
+        data: affiliate_program::instruction::RegisterAffiliate {
+            args: affiliate_program::RegisterAffiliateArgs {
+                parent_affiliate,
+                referral_level: if parent_affiliate.is_some() { 2 } else { 1 },
+                rate_caps_enabled: false,
+                max_commission_rate_bps: 2000,
+                min_commission_rate_bps: 50,
+            },
+        }.data(),
+    };
+    let close_ix = |affiliate: &Pubkey, parent_affiliate: Option<Pubkey>| Instruction {
+        program_id: affiliate_program::id(),
+        accounts: affiliate_program::accounts::CloseAffiliate {
+            affiliate_info: affiliate_info_pda(affiliate),
+            analytics: None,
+            parent_affiliate_info: parent_affiliate.map(|key| affiliate_info_pda(&key)),
+            ecosystem_stats: ecosystem_stats_pda,
+            affiliate_key: *affiliate,
+        }.to_account_metas(None),
+        data: affiliate_program::instruction::CloseAffiliate {}.data(),
+    };
+
+    // --- GIVEN: A parent one descendant short of the cap, and a child that fills it ---
+    let tx = Transaction::new_signed_with_payer(
+        &[register_ix(&parent.pubkey(), None)],
+        Some(&parent.pubkey()),
+        &[&parent],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(tx).await.unwrap();
+
+    let mut parent_account = context.banks_client.get_account(parent_info_pda).await.unwrap().unwrap();
+    let mut parent_info = affiliate_program::state::AffiliateInfo::try_deserialize(&mut parent_account.data.as_slice()).unwrap();
+    parent_info.total_descendants = MAX_AFFILIATE_DESCENDANTS - 1;
+    let mut data = Vec::with_capacity(parent_account.data.len());
+    parent_info.try_serialize(&mut data).unwrap();
+    parent_account.data[..data.len()].copy_from_slice(&data);
+    context.set_account(&parent_info_pda, &AccountSharedData::from(parent_account));
+
+    let tx = Transaction::new_signed_with_payer(
+        &[register_ix(&child.pubkey(), Some(parent.pubkey()))],
+        Some(&child.pubkey()),
+        &[&child],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(tx).await.unwrap();
+
+    // --- WHEN: The parent tries to close and re-register with a fresh count ---
+    let tx = Transaction::new_signed_with_payer(
+        &[close_ix(&parent.pubkey(), None)],
+        Some(&parent.pubkey()),
+        &[&parent],
+        context.last_blockhash,
+    );
+    // --- THEN: A parent with descendants can't close ---
+    assert!(context.banks_client.process_transaction(tx).await.is_err(), "Closing an affiliate with descendants should fail");
+
+    // --- WHEN: The child closes and re-registers under the parent ---
+    let tx = Transaction::new_signed_with_payer(
+        &[close_ix(&child.pubkey(), Some(parent.pubkey()))],
+        Some(&child.pubkey()),
+        &[&child],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(tx).await.unwrap();
+    assert!(context.banks_client.get_account(child_info_pda).await.unwrap().is_none());
+
+    let blockhash = context.get_new_latest_blockhash().await.unwrap();
+    let tx = Transaction::new_signed_with_payer(
+        &[register_ix(&child.pubkey(), Some(parent.pubkey()))],
+        Some(&child.pubkey()),
+        &[&child],
+        blockhash,
+    );
+    // --- THEN: The closed child still counts against the cap, so it can't come back ---
+    assert!(context.banks_client.process_transaction(tx).await.is_err(), "Re-registering past the descendant cap should fail");
+    let parent_account = context.banks_client.get_account(parent_info_pda).await.unwrap().unwrap();
+    let parent_info = affiliate_program::state::AffiliateInfo::try_deserialize(&mut parent_account.data.as_slice()).unwrap();
+    assert_eq!((parent_info.total_descendants, parent_info.active_descendants), (MAX_AFFILIATE_DESCENDANTS, 0));
+
+    // ...and the parent still can't close to reset it.
+    let tx = Transaction::new_signed_with_payer(
+        &[close_ix(&parent.pubkey(), None)],
+        Some(&parent.pubkey()),
+        &[&parent],
+        blockhash,
+    );
+    assert!(context.banks_client.process_transaction(tx).await.is_err(), "Closing an affiliate with closed descendants should fail");
+    assert!(context.banks_client.get_account(parent_info_pda).await.unwrap().is_some());
+}
+}
+
+#[tokio::test]
+async fn test_affiliate_transfer_to_new_wallet() {
+    use anchor_lang::AccountDeserialize;
+
+    // --- SETUP: Only the affiliate program is needed ---
+    let pt = ProgramTest::new(
+        "affiliate_program",
+        affiliate_program::id(),
+        processor!(affiliate_program::entry),
+    );
+    let mut context = pt.start_with_context().await;
+
+    let affiliate = Keypair::new();
+    let new_wallet = Keypair::new();
+    let intruder = Keypair::new();
+    for wallet in [&affiliate, &new_wallet, &intruder] {
+        airdrop(&mut context, &wallet.pubkey(), 1_000_000_000).await;
+    }
+    let (affiliate_info_pda, _) = Pubkey::find_program_address(
+        &[b"affiliate_info", affiliate.pubkey().as_ref()],
+        &affiliate_program::id(),
+    );
+    let (ecosystem_stats_pda, _) = Pubkey::find_program_address(&[b"ecosystem_stats"], &affiliate_program::id());
+
+    // --- GIVEN: A registered affiliate that proposes moving to a new wallet ---
+    let register_ix = Instruction {
+        program_id: affiliate_program::id(),
+        accounts: affiliate_program::accounts::RegisterAffiliate {
+            affiliate_info: affiliate_info_pda,
+            ecosystem_stats: ecosystem_stats_pda,
+            parent_affiliate_info: None,
+            affiliate: affiliate.pubkey(),
+            system_program: system_program::id(),
+        }.to_account_metas(None),
+        data: affiliate_program::instruction::RegisterAffiliate {
+            args: affiliate_program::RegisterAffiliateArgs {
+                parent_affiliate: None,
+                referral_level: 1,
+                rate_caps_enabled: false,
+                max_commission_rate_bps: 2000,
+                min_commission_rate_bps: 50,
+            },
+        }.data(),
+    };
+    let propose_ix = Instruction {
+        program_id: affiliate_program::id(),
+        accounts: affiliate_program::accounts::ProposeAffiliateTransfer {
+            affiliate_info: affiliate_info_pda,
+            affiliate_key: affiliate.pubkey(),
+        }.to_account_metas(None),
+        data: affiliate_program::instruction::ProposeAffiliateTransfer { new_key: new_wallet.pubkey() }.data(),
+    };
+    let tx = Transaction::new_signed_with_payer(
+        &[register_ix, propose_ix],
+        Some(&affiliate.pubkey()),
+        &[&affiliate],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(tx).await.unwrap();
+
+    let accept_ix = |signer: &Pubkey| Instruction {
+        program_id: affiliate_program::id(),
+        accounts: affiliate_program::accounts::AcceptAffiliateTransfer {
+            affiliate_info: affiliate_info_pda,
+            new_affiliate_key: *signer,
+        }.to_account_metas(None),
+        data: affiliate_program::instruction::AcceptAffiliateTransfer {}.data(),
+    };
+
+    // --- WHEN: Someone other than the proposed wallet tries to accept ---
+    let tx = Transaction::new_signed_with_payer(
+        &[accept_ix(&intruder.pubkey())],
+        Some(&intruder.pubkey()),
+        &[&intruder],
+        context.last_blockhash,
+    );
+    assert!(context.banks_client.process_transaction(tx).await.is_err(), "Only the proposed wallet may accept");
+
+    // --- WHEN: The proposed wallet accepts ---
+    let tx = Transaction::new_signed_with_payer(
+        &[accept_ix(&new_wallet.pubkey())],
+        Some(&new_wallet.pubkey()),
+        &[&new_wallet],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(tx).await.unwrap();
+
+    // --- THEN: The account, still at its original address, now belongs to the new wallet ---
+    let info_account = context.banks_client.get_account(affiliate_info_pda).await.unwrap().unwrap();
+    let info = affiliate_program::state::AffiliateInfo::try_deserialize(&mut info_account.data.as_slice()).unwrap();
+    assert_eq!(info.affiliate_key, new_wallet.pubkey());
+    assert_eq!(info.seed_key, affiliate.pubkey());
+    assert_eq!(info.pending_authority, None);
+
+    // ...so the new wallet can set the rate and the old one no longer can.
+    let set_rate_ix = |wallet: &Pubkey| Instruction {
+        program_id: affiliate_program::id(),
+        accounts: affiliate_program::accounts::SetCommissionRate {
+            affiliate_info: affiliate_info_pda,
+            affiliate_key: *wallet,
+        }.to_account_metas(None),
+        data: affiliate_program::instruction::SetCommissionRate { new_rate_bps: 1_500 }.data(),
+    };
+    let tx = Transaction::new_signed_with_payer(
+        &[set_rate_ix(&affiliate.pubkey())],
+        Some(&affiliate.pubkey()),
+        &[&affiliate],
+        context.last_blockhash,
+    );
+    assert!(context.banks_client.process_transaction(tx).await.is_err(), "The old wallet should lose control");
+    let tx = Transaction::new_signed_with_payer(
+        &[set_rate_ix(&new_wallet.pubkey())],
+        Some(&new_wallet.pubkey()),
+        &[&new_wallet],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(tx).await.unwrap();
+}
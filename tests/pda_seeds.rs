@@ -0,0 +1,79 @@
+//! # PDA Signer Seed Tests
+//!
+//! Verifies that the signer seed helpers in `genesis_common::utils::pda_utils`
+//! re-derive the same PDAs as the corresponding `derive_*_address` functions,
+//! so programs signing CPIs with them sign as the expected accounts.
+//!
+//! ## Usage
+//!
+//! Run with:
+//! ```bash
+//! cargo test -p launchpad-tests --test pda_seeds
+//! ```
+
+use anchor_lang::prelude::Pubkey;
+use genesis_common::utils::pda_utils;
+
+#[test]
+fn launch_state_signer_seeds_rederive_pda() {
+    let program_id = factory_program::id();
+    let authority = Pubkey::new_unique();
+    let token_mint = Pubkey::new_unique();
+
+    let (expected, bump) = pda_utils::derive_launch_state_address(&authority, &token_mint, &program_id);
+    let seeds = pda_utils::launch_state_signer_seeds(&authority, &token_mint, &bump);
+    assert_eq!(Pubkey::create_program_address(&seeds, &program_id).unwrap(), expected);
+}
+
+#[test]
+fn sol_vault_signer_seeds_rederive_pda() {
+    let program_id = factory_program::id();
+    let authority = Pubkey::new_unique();
+    let token_mint = Pubkey::new_unique();
+
+    let (expected, bump) = pda_utils::derive_sol_vault_address(&authority, &token_mint, &program_id);
+    let seeds = pda_utils::sol_vault_signer_seeds(&authority, &token_mint, &bump);
+    assert_eq!(Pubkey::create_program_address(&seeds, &program_id).unwrap(), expected);
+}
+
+#[test]
+fn vesting_schedule_signer_seeds_rederive_pda() {
+    let program_id = factory_program::id();
+    let launch_state = Pubkey::new_unique();
+    let beneficiary = Pubkey::new_unique();
+
+    let (expected, bump) = Pubkey::find_program_address(
+        &[b"vesting_schedule", launch_state.as_ref(), beneficiary.as_ref()],
+        &program_id,
+    );
+    let seeds = pda_utils::vesting_schedule_signer_seeds(&launch_state, &beneficiary, &bump);
+    assert_eq!(Pubkey::create_program_address(&seeds, &program_id).unwrap(), expected);
+}
+
+#[test]
+fn liquidity_pool_signer_seeds_rederive_pda() {
+    let program_id = barter_dex_program::id();
+    let mint_a = Pubkey::new_unique();
+    let mint_b = Pubkey::new_unique();
+
+    let (expected, bump) = pda_utils::derive_liquidity_pool_address(&mint_a, &mint_b, &program_id);
+    let seeds = pda_utils::liquidity_pool_signer_seeds(&mint_a, &mint_b, &bump);
+    assert_eq!(Pubkey::create_program_address(&seeds, &program_id).unwrap(), expected);
+}
+
+#[test]
+fn shared_factory_program_id_matches_declared_id() {
+    assert_eq!(genesis_common::constants::FACTORY_PROGRAM_ID, factory_program::id());
+}
+
+#[test]
+fn mint_authority_is_the_factory_launch_state() {
+    let authority = Pubkey::new_unique();
+    let token_mint = Pubkey::new_unique();
+
+    let expected = Pubkey::find_program_address(
+        &[b"launch_state", authority.as_ref(), token_mint.as_ref()],
+        &factory_program::id(),
+    );
+    assert_eq!(pda_utils::derive_mint_authority(&authority, &token_mint), expected);
+}